@@ -3,10 +3,12 @@
 //! Generates per-character charts with box plot, distribution histogram, and statistics table.
 //! Styling is configurable via chart_config.json.
 
-use super::config::ChartConfig;
+use super::config::{ChartConfig, CombinedPlotScale};
 use super::csv_reader::DataSet;
-use super::statistics::ColumnStats;
+use super::format::{format_thousands, format_thousands_f64};
+use super::statistics::{column_code, ColumnStats};
 use anyhow::{Context, Result};
+use plotters::coord::types::RangedCoordf64;
 use plotters::prelude::*;
 use std::path::Path;
 
@@ -223,6 +225,59 @@ fn draw_box_plot(
         whisker_color.stroke_width(2),
     )))?;
 
+    if config.statistics.show_p10_p90_caps {
+        draw_p10_p90_caps(&mut chart, stats, x_center, cap_width * 0.6, ValueMap::IDENTITY)?;
+    }
+
+    Ok(())
+}
+
+/// Affine map `(v - offset) / divisor` from a column's scores to a box plot's
+/// y axis (`CombinedPlotScale`); the identity for raw scores.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ValueMap {
+    offset: f64,
+    divisor: f64,
+}
+
+impl ValueMap {
+    const IDENTITY: Self = Self { offset: 0.0, divisor: 1.0 };
+
+    /// Map for `col` under `scale`. A column without spread maps to 0.
+    fn for_column(col: &ColumnStats, scale: CombinedPlotScale) -> Self {
+        let (offset, spread) = match scale {
+            CombinedPlotScale::Raw => return Self::IDENTITY,
+            CombinedPlotScale::ZScore => (col.mean, col.std_dev),
+            CombinedPlotScale::MinMax => (col.min as f64, col.range as f64),
+        };
+        Self { offset, divisor: if spread > 0.0 { spread } else { 1.0 } }
+    }
+
+    fn apply(&self, value: f64) -> f64 {
+        (value - self.offset) / self.divisor
+    }
+}
+
+/// Draw short p10/p90 caps on a box plot's whiskers, if those percentiles were
+/// computed for the column (see `StatisticsConfig::show_p10_p90_caps`).
+fn draw_p10_p90_caps<DB: DrawingBackend>(
+    chart: &mut ChartContext<'_, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    stats: &ColumnStats,
+    x_center: f64,
+    cap_width: f64,
+    map: ValueMap,
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    let cap_color = RGBColor(60, 110, 200);
+    for value in [stats.percentile(10.0), stats.percentile(90.0)].into_iter().flatten() {
+        let value = map.apply(value);
+        chart.draw_series(std::iter::once(PathElement::new(
+            vec![(x_center - cap_width, value), (x_center + cap_width, value)],
+            cap_color.stroke_width(2),
+        )))?;
+    }
     Ok(())
 }
 
@@ -364,23 +419,22 @@ fn draw_stats_table_top(
     let value_height = height as i32 - header_height;
 
     let headers = ["Min", "Average", "Median", "Max"];
+    let sep = &config.number_format.thousands_separator;
     let values = [
-        format!("{}", stats.min),
-        format!("{:.0}", stats.mean),
-        format!("{:.0}", stats.median),
-        format!("{}", stats.max),
+        format_thousands(stats.min as i64, sep),
+        format_thousands_f64(stats.mean, sep),
+        format_thousands_f64(stats.median, sep),
+        format_thousands(stats.max as i64, sep),
     ];
 
-    let header_font = ("sans-serif", config.font.table_header_size)
+    let header_style = ("sans-serif", config.font.table_header_size)
         .into_font()
-        .style(FontStyle::Bold);
-    let value_font = ("sans-serif", config.font.table_value_size)
+        .style(FontStyle::Bold)
+        .color(&WHITE);
+    let value_style = ("sans-serif", config.font.table_value_size)
         .into_font()
-        .style(FontStyle::Bold);
-
-    // Estimate character width based on font size
-    let char_width = (config.font.table_header_size / 2) as i32;
-    let value_char_width = (config.font.table_value_size / 2) as i32;
+        .style(FontStyle::Bold)
+        .color(&BLACK);
 
     for (i, (header, value)) in headers.iter().zip(values.iter()).enumerate() {
         let x_start = i as i32 * col_width;
@@ -402,9 +456,10 @@ fn draw_stats_table_top(
             ))?;
         }
 
-        // Draw header text (white, centered)
-        let header_text_x = x_start + (col_width - header.len() as i32 * char_width) / 2;
-        area.draw_text(header, &header_font.color(&WHITE), (header_text_x, 5))?;
+        // Draw header text (white, centered on its measured width)
+        let (header_width, _) = area.estimate_text_size(header, &header_style)?;
+        let header_text_x = x_start + (col_width - header_width as i32) / 2;
+        area.draw_text(header, &header_style, (header_text_x, 5))?;
 
         // Draw value background (white with border)
         area.draw(&Rectangle::new(
@@ -418,16 +473,20 @@ fn draw_stats_table_top(
             grid_color.stroke_width(1),
         ))?;
 
-        // Draw value text (centered)
-        let value_text_x = x_start + (col_width - value.len() as i32 * value_char_width) / 2;
+        // Draw value text (centered on its measured width; digits and
+        // separators are narrower than the font size suggests)
+        let (value_width, _) = area.estimate_text_size(value, &value_style)?;
+        let value_text_x = x_start + (col_width - value_width as i32) / 2;
         let value_text_y = header_height + (value_height - config.font.table_value_size as i32) / 2;
-        area.draw_text(value, &value_font.color(&BLACK), (value_text_x, value_text_y))?;
+        area.draw_text(value, &value_style, (value_text_x, value_text_y))?;
     }
 
     Ok(())
 }
 
-/// Generate all column charts (9 charts total).
+/// Generate one chart per score column (stages × criteria; 9 for the standard
+/// 3×3 layout), named by `chart_file_name`: `chart_s<N>c<M>.png`, with the
+/// column's `column_labels` name appended when one is set.
 pub fn generate_all_charts(
     data: &DataSet,
     stats: &super::statistics::DataSetStats,
@@ -436,171 +495,417 @@ pub fn generate_all_charts(
 ) -> Result<Vec<std::path::PathBuf>> {
     let mut paths = Vec::new();
 
-    for stage in 0..3 {
-        for criterion in 0..3 {
-            let idx = stage * 3 + criterion;
-            let col_stats = &stats.columns[idx];
-            let values = data.column_values(stage, criterion);
-            let column_name = format!("S{}C{}", stage + 1, criterion + 1);
-            let filename = format!("chart_{}.png", column_name.to_lowercase());
-            let output_path = output_dir.join(&filename);
-
-            generate_column_chart(
-                &column_name,
-                &values,
-                col_stats,
-                stats.total_runs,
-                &output_path,
-                config,
-            )?;
-
-            paths.push(output_path);
-        }
+    for col_stats in &stats.columns {
+        let values = data.column_values(col_stats.stage - 1, col_stats.criterion - 1);
+        let column_name = &col_stats.label;
+        let output_path = output_dir.join(chart_file_name(col_stats));
+
+        generate_column_chart(
+            column_name,
+            &values,
+            col_stats,
+            stats.total_runs,
+            &output_path,
+            config,
+        )?;
+
+        paths.push(output_path);
     }
 
     Ok(paths)
 }
 
-/// Generate a combined box plot showing all 9 columns side by side.
+/// Per-column chart file name: `chart_s1c1.png`, with the label appended when
+/// `column_labels` renames the column (`chart_s1c1_vocal.png`). The grid code
+/// keeps names unique even when two columns are given the same label.
+fn chart_file_name(col_stats: &ColumnStats) -> String {
+    let code = column_code(col_stats.stage, col_stats.criterion);
+    if col_stats.label == code {
+        format!("chart_{}.png", file_stem(&code))
+    } else {
+        format!("chart_{}_{}.png", file_stem(&code), file_stem(&col_stats.label))
+    }
+}
+
+/// File-name form of a column name: lowercased, with characters Windows
+/// rejects in file names and whitespace replaced by `_` ("S1C1" -> "s1c1").
+fn file_stem(column_name: &str) -> String {
+    column_name
+        .chars()
+        .map(|c| {
+            if c.is_whitespace() || c.is_control() || r#"<>:"/\|?*"#.contains(c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Generate a combined box plot showing every column side by side, colored by stage.
 pub fn generate_combined_box_plot(
     stats: &super::statistics::DataSetStats,
     output_path: &Path,
-    _config: &ChartConfig, // Reserved for future use
+    config: &ChartConfig,
 ) -> Result<()> {
     let root = BitMapBackend::new(output_path, (1200, 700)).into_drawing_area();
     root.fill(&WHITE)
         .context("Failed to fill chart background")?;
 
-    // Find global min/max across all columns for Y-axis scaling
-    let global_min = stats
-        .columns
-        .iter()
-        .map(|c| c.min)
-        .min()
-        .unwrap_or(0) as f64;
-    let global_max = stats
+    let scale = config.normalize_combined_plot;
+    let maps: Vec<ValueMap> = stats
         .columns
         .iter()
-        .map(|c| c.max)
-        .max()
-        .unwrap_or(100) as f64;
+        .map(|c| ValueMap::for_column(c, scale))
+        .collect();
+
+    // Find global min/max across all columns (after scaling) for Y-axis scaling
+    let (global_min, global_max) = if stats.columns.is_empty() {
+        (0.0, 100.0)
+    } else {
+        stats.columns.iter().zip(&maps).fold(
+            (f64::INFINITY, f64::NEG_INFINITY),
+            |(lo, hi), (c, map)| {
+                (lo.min(map.apply(c.min as f64)), hi.max(map.apply(c.max as f64)))
+            },
+        )
+    };
 
     let range = global_max - global_min;
-    let y_min = (global_min - range * 0.05).max(0.0);
+    let y_min = global_min - range * 0.05;
+    // Raw scores are never negative; scaled values are
+    let y_min = if scale == CombinedPlotScale::Raw { y_min.max(0.0) } else { y_min };
     let y_max = global_max + range * 0.05;
+    let (y_desc, y_precision) = match scale {
+        CombinedPlotScale::Raw => ("Score", 0),
+        CombinedPlotScale::ZScore => ("Score (z-score per column)", 1),
+        CombinedPlotScale::MinMax => ("Score (min-max per column)", 1),
+    };
 
     let title = format!("Score Distribution ({} runs)", stats.total_runs);
 
     // Split into chart area and label area at bottom
     let (upper, lower) = root.split_vertically(650);
+    let column_count = stats.columns.len().max(1);
 
     let mut chart = ChartBuilder::on(&upper)
         .caption(&title, ("sans-serif", 24))
         .margin(20)
         .x_label_area_size(10)
         .y_label_area_size(80)
-        .build_cartesian_2d(0.0f64..9.0f64, y_min..y_max)
+        .build_cartesian_2d(0.0f64..column_count as f64, y_min..y_max)
         .context("Failed to build combined box plot")?;
 
     chart
         .configure_mesh()
         .disable_x_mesh()
         .disable_x_axis()
-        .y_desc("Score")
-        .y_label_formatter(&|y| format!("{:.0}", y))
+        .y_desc(y_desc)
+        .y_label_formatter(&|y| format!("{:.*}", y_precision, y))
         .draw()
         .context("Failed to draw mesh")?;
 
     // Draw X-axis labels manually
-    let labels = ["S1C1", "S1C2", "S1C3", "S2C1", "S2C2", "S2C3", "S3C1", "S3C2", "S3C3"];
     let label_font = ("sans-serif", 16).into_font();
     let chart_left = 80; // Match y_label_area_size
     let chart_width = 1200 - chart_left - 20; // Total width minus margins
-    let box_width = chart_width as f64 / 9.0;
+    let box_width = chart_width as f64 / column_count as f64;
 
-    for (idx, label) in labels.iter().enumerate() {
-        let x_pos = chart_left + (idx as i32 * chart_width / 9) + (box_width as i32 / 2) - 15;
-        lower.draw_text(label, &label_font.color(&BLACK), (x_pos, 5))?;
+    for (idx, col_stats) in stats.columns.iter().enumerate() {
+        // Centered under the box; named columns are wider than "S1C1"
+        let label_style = label_font.color(&BLACK);
+        let (label_width, _) = lower.estimate_text_size(&col_stats.label, &label_style)?;
+        let x_pos = chart_left + (idx as i32 * chart_width / column_count as i32)
+            + (box_width as i32 / 2)
+            - label_width as i32 / 2;
+        lower.draw_text(&col_stats.label, &label_style, (x_pos, 5))?;
     }
 
-    // Stage colors
-    let stage_colors = [
-        RGBColor(220, 80, 80),   // Stage 1: Red
-        RGBColor(80, 180, 80),   // Stage 2: Green
-        RGBColor(80, 120, 200),  // Stage 3: Blue
-    ];
+    // Stage colors from the configured palette
+    let stage_colors = config.stage_colors().map(|[r, g, b]| RGBColor(r, g, b));
 
     let box_width = 0.35;
-    let cap_width = 0.2;
 
-    for (idx, col_stats) in stats.columns.iter().enumerate() {
-        let stage = idx / 3;
-        let x_center = idx as f64 + 0.5;
-        let box_color = stage_colors[stage];
-        let whisker_color = RGBColor(80, 80, 80);
+    for (idx, (col_stats, map)) in stats.columns.iter().zip(&maps).enumerate() {
+        let box_color = stage_colors[(col_stats.stage - 1) % stage_colors.len()];
+        draw_summary_box(
+            &mut chart,
+            col_stats,
+            idx as f64 + 0.5,
+            box_width,
+            box_color,
+            config.statistics.show_p10_p90_caps,
+            *map,
+        )?;
+    }
 
-        let min_val = col_stats.min as f64;
-        let max_val = col_stats.max as f64;
+    root.present().context("Failed to save combined box plot")?;
+    Ok(())
+}
 
-        // Box fill (Q1 to Q3)
-        chart.draw_series(std::iter::once(Rectangle::new(
-            [
-                (x_center - box_width, col_stats.quartile_1),
-                (x_center + box_width, col_stats.quartile_3),
-            ],
-            box_color.mix(0.4).filled(),
-        )))?;
+/// Generate a box plot comparing two sessions: for each column, session A's
+/// box on the left and session B's on the right. `columns` pairs the same
+/// column of both sessions; `names` label the legend.
+pub fn generate_comparison_box_plot(
+    columns: &[(&ColumnStats, &ColumnStats)],
+    names: (&str, &str),
+    output_path: &Path,
+    config: &ChartConfig,
+) -> Result<()> {
+    let root = BitMapBackend::new(output_path, (1200, 700)).into_drawing_area();
+    root.fill(&WHITE)
+        .context("Failed to fill chart background")?;
 
-        // Box outline
-        chart.draw_series(std::iter::once(Rectangle::new(
-            [
-                (x_center - box_width, col_stats.quartile_1),
-                (x_center + box_width, col_stats.quartile_3),
-            ],
-            box_color.stroke_width(2),
-        )))?;
+    let all = || columns.iter().flat_map(|(a, b)| [*a, *b]);
+    let global_min = all().map(|c| c.min).min().unwrap_or(0) as f64;
+    let global_max = all().map(|c| c.max).max().unwrap_or(100) as f64;
 
-        // Median line
-        chart.draw_series(std::iter::once(PathElement::new(
-            vec![
-                (x_center - box_width, col_stats.median),
-                (x_center + box_width, col_stats.median),
-            ],
-            RGBColor(200, 50, 50).stroke_width(2),
-        )))?;
+    let range = global_max - global_min;
+    let y_min = (global_min - range * 0.05).max(0.0);
+    let y_max = global_max + range * 0.05;
 
-        // Lower whisker
-        chart.draw_series(std::iter::once(PathElement::new(
-            vec![(x_center, min_val), (x_center, col_stats.quartile_1)],
-            whisker_color.stroke_width(1),
-        )))?;
+    let title = match columns.first() {
+        Some((a, b)) => format!("Session Comparison ({} vs {} runs)", a.count, b.count),
+        None => "Session Comparison".to_string(),
+    };
 
-        // Upper whisker
-        chart.draw_series(std::iter::once(PathElement::new(
-            vec![(x_center, col_stats.quartile_3), (x_center, max_val)],
-            whisker_color.stroke_width(1),
-        )))?;
+    let (upper, lower) = root.split_vertically(650);
+    let column_count = columns.len().max(1);
 
-        // Min cap
-        chart.draw_series(std::iter::once(PathElement::new(
-            vec![
-                (x_center - cap_width, min_val),
-                (x_center + cap_width, min_val),
-            ],
-            whisker_color.stroke_width(1),
-        )))?;
+    let mut chart = ChartBuilder::on(&upper)
+        .caption(&title, ("sans-serif", 24))
+        .margin(20)
+        .x_label_area_size(10)
+        .y_label_area_size(80)
+        .build_cartesian_2d(0.0f64..column_count as f64, y_min..y_max)
+        .context("Failed to build comparison box plot")?;
 
-        // Max cap
-        chart.draw_series(std::iter::once(PathElement::new(
-            vec![
-                (x_center - cap_width, max_val),
-                (x_center + cap_width, max_val),
-            ],
-            whisker_color.stroke_width(1),
-        )))?;
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .disable_x_axis()
+        .y_desc("Score")
+        .y_label_formatter(&|y| format!("{:.0}", y))
+        .draw()
+        .context("Failed to draw mesh")?;
+
+    let session_colors = [RGBColor(150, 150, 150), RGBColor(230, 140, 40)];
+
+    // Column labels under each pair, legend in the bottom-right corner
+    let label_style = ("sans-serif", 16).into_font().color(&BLACK);
+    let chart_left = 80; // Match y_label_area_size
+    let chart_width = 1200 - chart_left - 20;
+    let slot_width = chart_width as f64 / column_count as f64;
+    for (idx, (a, _)) in columns.iter().enumerate() {
+        let (label_width, _) = lower.estimate_text_size(&a.label, &label_style)?;
+        let x_pos = chart_left + (idx as f64 * slot_width + slot_width / 2.0) as i32
+            - label_width as i32 / 2;
+        lower.draw_text(&a.label, &label_style, (x_pos, 5))?;
+    }
+    let mut legend_x = 1200 - 20;
+    for (name, color) in [names.1, names.0].into_iter().zip(session_colors.iter().rev()) {
+        let (name_width, _) = lower.estimate_text_size(name, &label_style)?;
+        legend_x -= name_width as i32;
+        lower.draw_text(name, &label_style, (legend_x, 28))?;
+        lower.draw(&Rectangle::new(
+            [(legend_x - 18, 29), (legend_x - 6, 41)],
+            color.mix(0.6).filled(),
+        ))?;
+        legend_x -= 36;
     }
 
-    root.present().context("Failed to save combined box plot")?;
+    let box_width = 0.15;
+    for (idx, (a, b)) in columns.iter().enumerate() {
+        for (stats, offset, color) in [(*a, 0.3, session_colors[0]), (*b, 0.7, session_colors[1])] {
+            draw_summary_box(
+                &mut chart,
+                stats,
+                idx as f64 + offset,
+                box_width,
+                color,
+                config.statistics.show_p10_p90_caps,
+                ValueMap::IDENTITY,
+            )?;
+        }
+    }
+
+    root.present().context("Failed to save comparison box plot")?;
+    Ok(())
+}
+
+/// Draw one min/max box plot (Q1–Q3 box, median line, whiskers and caps) at
+/// `x_center`, `box_width` wide on each side, with every value passed
+/// through `map`.
+fn draw_summary_box<DB: DrawingBackend>(
+    chart: &mut ChartContext<'_, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    col_stats: &ColumnStats,
+    x_center: f64,
+    box_width: f64,
+    box_color: RGBColor,
+    show_p10_p90_caps: bool,
+    map: ValueMap,
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    // Caps are 0.2 wide for the combined plot's 0.35 boxes
+    let cap_width = box_width * 4.0 / 7.0;
+    let whisker_color = RGBColor(80, 80, 80);
+
+    let min_val = map.apply(col_stats.min as f64);
+    let max_val = map.apply(col_stats.max as f64);
+    let quartile_1 = map.apply(col_stats.quartile_1);
+    let quartile_3 = map.apply(col_stats.quartile_3);
+    let median = map.apply(col_stats.median);
+
+    // Box fill (Q1 to Q3)
+    chart.draw_series(std::iter::once(Rectangle::new(
+        [
+            (x_center - box_width, quartile_1),
+            (x_center + box_width, quartile_3),
+        ],
+        box_color.mix(0.4).filled(),
+    )))?;
+
+    // Box outline
+    chart.draw_series(std::iter::once(Rectangle::new(
+        [
+            (x_center - box_width, quartile_1),
+            (x_center + box_width, quartile_3),
+        ],
+        box_color.stroke_width(2),
+    )))?;
+
+    // Median line
+    chart.draw_series(std::iter::once(PathElement::new(
+        vec![
+            (x_center - box_width, median),
+            (x_center + box_width, median),
+        ],
+        RGBColor(200, 50, 50).stroke_width(2),
+    )))?;
+
+    // Lower whisker
+    chart.draw_series(std::iter::once(PathElement::new(
+        vec![(x_center, min_val), (x_center, quartile_1)],
+        whisker_color.stroke_width(1),
+    )))?;
+
+    // Upper whisker
+    chart.draw_series(std::iter::once(PathElement::new(
+        vec![(x_center, quartile_3), (x_center, max_val)],
+        whisker_color.stroke_width(1),
+    )))?;
+
+    // Min cap
+    chart.draw_series(std::iter::once(PathElement::new(
+        vec![
+            (x_center - cap_width, min_val),
+            (x_center + cap_width, min_val),
+        ],
+        whisker_color.stroke_width(1),
+    )))?;
+
+    // Max cap
+    chart.draw_series(std::iter::once(PathElement::new(
+        vec![
+            (x_center - cap_width, max_val),
+            (x_center + cap_width, max_val),
+        ],
+        whisker_color.stroke_width(1),
+    )))?;
+
+    if show_p10_p90_caps {
+        draw_p10_p90_caps(chart, col_stats, x_center, cap_width * 0.6, map)?;
+    }
+    Ok(())
+}
+
+/// Mean over all runs of one stage's total (the sum of its criteria scores).
+fn mean_stage_total(data: &DataSet, stage: usize) -> f64 {
+    if data.runs.is_empty() {
+        return 0.0;
+    }
+    let sum: u64 = data
+        .runs
+        .iter()
+        .filter_map(|run| run.scores.get(stage))
+        .flat_map(|row| row.iter().map(|&v| v as u64))
+        .sum();
+    sum as f64 / data.runs.len() as f64
+}
+
+/// Height of the dashboard's header row (title + per-stage totals).
+const DASHBOARD_HEADER_H: u32 = 90;
+/// Height of the label strip above each dashboard cell.
+const DASHBOARD_CELL_TITLE_H: u32 = 30;
+
+/// Generate a one-image overview: a header row with the run count and each
+/// stage's mean total, then a stages × criteria grid of cells, each holding
+/// that column's box plot and histogram (the same drawings as the per-column
+/// charts, minus the stats table).
+pub fn generate_dashboard(
+    data: &DataSet,
+    stats: &super::statistics::DataSetStats,
+    output_path: &Path,
+    config: &ChartConfig,
+) -> Result<()> {
+    let (stages, criteria) = stats.grid();
+    let (stages, criteria) = (stages.max(1), criteria.max(1));
+    let cell_w = config.layout.dashboard_cell_width;
+    let cell_h = config.layout.dashboard_cell_height;
+    let width = cell_w * criteria as u32;
+    let height = DASHBOARD_HEADER_H + cell_h * stages as u32;
+
+    let root = BitMapBackend::new(output_path, (width, height)).into_drawing_area();
+    root.fill(&WHITE)
+        .context("Failed to fill dashboard background")?;
+    let (header, grid) = root.split_vertically(DASHBOARD_HEADER_H);
+
+    // Header: title + mean stage totals
+    let sep = &config.number_format.thousands_separator;
+    let title_font = ("sans-serif", config.font.title_size)
+        .into_font()
+        .style(FontStyle::Bold);
+    header.draw_text(
+        &format!("Overview (n = {})", stats.total_runs),
+        &title_font.color(&BLACK),
+        (20, 8),
+    )?;
+    let totals: Vec<String> = (0..stages)
+        .map(|stage| {
+            let mean = mean_stage_total(data, stage);
+            format!("S{} total avg: {}", stage + 1, format_thousands_f64(mean, sep))
+        })
+        .collect();
+    let totals_font = ("sans-serif", config.font.legend_size + 6).into_font();
+    header.draw_text(&totals.join("    "), &totals_font.color(&BLACK), (20, 54))?;
+
+    // Grid: one cell per column, row-major (stage rows, criterion columns)
+    let label_font = ("sans-serif", config.font.box_plot_caption_size)
+        .into_font()
+        .style(FontStyle::Bold);
+    let box_w = cell_w * config.layout.box_plot_width / config.layout.chart_width.max(1);
+    for (idx, cell) in grid.split_evenly((stages, criteria)).iter().enumerate() {
+        let (stage, criterion) = (idx / criteria + 1, idx % criteria + 1);
+        let Some(col_stats) = stats
+            .columns
+            .iter()
+            .find(|c| c.stage == stage && c.criterion == criterion)
+        else {
+            continue;
+        };
+        let values = data.column_values(stage - 1, criterion - 1);
+
+        let (title, body) = cell.split_vertically(DASHBOARD_CELL_TITLE_H);
+        title.draw_text(&col_stats.label, &label_font.color(&BLACK), (10, 6))?;
+        let (box_area, hist_area) = body.split_horizontally(box_w);
+        draw_box_plot(&box_area, &values, col_stats, config)?;
+        draw_histogram(&hist_area, &values, col_stats, stats.total_runs, config)?;
+    }
+
+    root.present().context("Failed to save dashboard")?;
     Ok(())
 }
 
@@ -616,11 +921,12 @@ const LIVE_PLOT_SPLIT_Y: u32 = 550;
 ///
 /// Returns `(width, height, rgba_bytes)` with `rgba_bytes.len() == width*height*4`,
 /// ready for `egui::ColorImage::from_rgba_unmultiplied`. No file is written. This
-/// mirrors `generate_combined_box_plot` (same colors and box geometry) but targets an
+/// mirrors `generate_combined_box_plot` (same `config` palette and box geometry) but targets an
 /// in-memory buffer for the live GUI view. Per-column statistics are NOT drawn here;
 /// the GUI shows them as a separate live-updating table (see `render_live_stats_table`).
 pub fn render_live_box_plot_rgba(
     stats: &super::statistics::DataSetStats,
+    config: &ChartConfig,
 ) -> Result<(u32, u32, Vec<u8>)> {
     // plotters BitMapBackend writes RGB (3 bytes/pixel) into this buffer.
     let mut rgb = vec![0u8; (LIVE_PLOT_W * LIVE_PLOT_H * 3) as usize];
@@ -639,12 +945,13 @@ pub fn render_live_box_plot_rgba(
         let y_max = global_max + range * 0.05;
 
         let (upper, lower) = root.split_vertically(LIVE_PLOT_SPLIT_Y);
+        let column_count = stats.columns.len().max(1);
 
         let mut chart = ChartBuilder::on(&upper)
             .margin(20)
             .x_label_area_size(10)
             .y_label_area_size(80)
-            .build_cartesian_2d(0.0f64..9.0f64, y_min..y_max)
+            .build_cartesian_2d(0.0f64..column_count as f64, y_min..y_max)
             .context("Failed to build live box plot")?;
 
         // Fonts are sized generously because the 1200px-wide image is scaled down to
@@ -667,28 +974,20 @@ pub fn render_live_box_plot_rgba(
             .draw()
             .context("Failed to draw live mesh")?;
 
-        let labels = [
-            "S1C1", "S1C2", "S1C3", "S2C1", "S2C2", "S2C3", "S3C1", "S3C2", "S3C3",
-        ];
         let label_font = ("sans-serif", 28, FontStyle::Bold).into_font();
         let chart_left = 80i32; // Match y_label_area_size
         let chart_width = LIVE_PLOT_W as i32 - chart_left - 20; // Total width minus margins
-        let box_width_px = chart_width as f64 / 9.0;
-
-        // Stage colors (Stage 1 red, Stage 2 green, Stage 3 blue).
-        let stage_colors = [
-            RGBColor(220, 80, 80),
-            RGBColor(80, 180, 80),
-            RGBColor(80, 120, 200),
-        ];
+        let box_width_px = chart_width as f64 / column_count as f64;
+
+        // Stage colors from the configured palette, as in the on-disk figure.
+        let stage_colors = config.stage_colors().map(|[r, g, b]| RGBColor(r, g, b));
         let box_width = 0.35;
         let cap_width = 0.2;
         let whisker_color = RGBColor(80, 80, 80);
 
         for (idx, col_stats) in stats.columns.iter().enumerate() {
-            let stage = idx / 3;
             let x_center = idx as f64 + 0.5;
-            let box_color = stage_colors[stage];
+            let box_color = stage_colors[(col_stats.stage - 1) % stage_colors.len()];
             let min_val = col_stats.min as f64;
             let max_val = col_stats.max as f64;
 
@@ -744,11 +1043,15 @@ pub fn render_live_box_plot_rgba(
 
         // Column labels (S1C1 ..) in the lower strip, centered under each box. The
         // lower area's local origin (0,0) is its top-left; the box area sits above it.
-        for (idx, label) in labels.iter().enumerate() {
-            // Offset left by ~half the (bold, 28px) label width to center under the box.
-            let label_x =
-                chart_left + (idx as i32 * chart_width / 9) + (box_width_px as i32 / 2) - 38;
-            lower.draw_text(label, &label_font.color(&BLACK), (label_x, 12))?;
+        for (idx, col_stats) in stats.columns.iter().enumerate() {
+            // Centered on the measured width; named columns are wider than "S1C1"
+            let label_style = label_font.color(&BLACK);
+            let (label_width, _) = lower.estimate_text_size(&col_stats.label, &label_style)?;
+            let label_x = chart_left
+                + (idx as i32 * chart_width / column_count as i32)
+                + (box_width_px as i32 / 2)
+                - label_width as i32 / 2;
+            lower.draw_text(&col_stats.label, &label_style, (label_x, 12))?;
         }
 
         root.present().context("Failed to render live box plot")?;
@@ -783,7 +1086,7 @@ mod tests {
     #[test]
     fn render_live_box_plot_returns_rgba_buffer() {
         let stats = DataSetStats::from_score_rows(&sample_rows());
-        let (w, h, rgba) = render_live_box_plot_rgba(&stats).unwrap();
+        let (w, h, rgba) = render_live_box_plot_rgba(&stats, &ChartConfig::default()).unwrap();
         assert_eq!(w, LIVE_PLOT_W);
         assert_eq!(h, LIVE_PLOT_H);
         assert_eq!(rgba.len(), (w * h * 4) as usize);
@@ -791,11 +1094,25 @@ mod tests {
         assert!(rgba.chunks_exact(4).all(|px| px[3] == 255));
     }
 
+    #[test]
+    fn render_live_box_plot_uses_config_palette() {
+        let stats = DataSetStats::from_score_rows(&sample_rows());
+        let config = ChartConfig {
+            palette: super::super::config::ChartPalette::ColorblindSafe,
+            ..ChartConfig::default()
+        };
+        let (_, _, default_rgba) = render_live_box_plot_rgba(&stats, &ChartConfig::default()).unwrap();
+        let (_, _, rgba) = render_live_box_plot_rgba(&stats, &config).unwrap();
+        let [r, g, b] = config.stage_colors()[0];
+        assert!(rgba.chunks_exact(4).any(|px| px[..3] == [r, g, b]));
+        assert_ne!(rgba, default_rgba);
+    }
+
     #[test]
     fn render_live_box_plot_handles_empty() {
         // Zero usable rows must not panic (early-run case).
         let stats = DataSetStats::from_score_rows(&[]);
-        let (w, h, rgba) = render_live_box_plot_rgba(&stats).unwrap();
+        let (w, h, rgba) = render_live_box_plot_rgba(&stats, &ChartConfig::default()).unwrap();
         assert_eq!(rgba.len(), (w * h * 4) as usize);
     }
 
@@ -805,13 +1122,33 @@ mod tests {
     #[ignore]
     fn live_box_plot_preview() {
         let stats = DataSetStats::from_score_rows(&sample_rows());
-        let (w, h, rgba) = render_live_box_plot_rgba(&stats).unwrap();
+        let (w, h, rgba) = render_live_box_plot_rgba(&stats, &ChartConfig::default()).unwrap();
         let img = image::RgbaImage::from_raw(w, h, rgba).expect("buffer size matches dimensions");
         std::fs::create_dir_all("temp").ok();
         img.save("temp/live_box_plot_preview.png")
             .expect("write preview png");
     }
 
+    #[test]
+    fn test_file_stem_is_safe_for_windows() {
+        assert_eq!(file_stem("S1C1"), "s1c1");
+        assert_eq!(file_stem("Kotone Vo/Da"), "kotone_vo_da");
+        assert_eq!(file_stem("咲季: ボーカル"), "咲季__ボーカル");
+    }
+
+    #[test]
+    fn test_chart_file_names_unique_with_duplicate_labels() {
+        let labels = std::collections::BTreeMap::from([
+            ("S1C1".to_string(), "Vocal".to_string()),
+            ("S2C1".to_string(), "Vocal".to_string()),
+        ]);
+        let stats = DataSetStats::from_score_rows(&[[[1, 2, 3]; 3]]).with_column_labels(&labels);
+        let names: Vec<String> = stats.columns.iter().map(chart_file_name).collect();
+        assert_eq!(names[0], "chart_s1c1_vocal.png");
+        assert_eq!(names[1], "chart_s1c2.png");
+        assert_eq!(names[3], "chart_s2c1_vocal.png");
+    }
+
     #[test]
     fn test_calculate_bucket_size() {
         // Small range: should use MIN_BUCKET_SIZE
@@ -841,4 +1178,51 @@ mod tests {
         assert!(starts.is_empty());
         assert!(counts.is_empty());
     }
+
+    #[test]
+    fn test_mean_stage_total() {
+        let run = |scores: Vec<Vec<u32>>| crate::analysis::csv_reader::RunData {
+            iteration: 1,
+            timestamp: String::new(),
+            screenshot_path: String::new(),
+            scores,
+            total: None,
+            stage_totals: vec![None; 2],
+            recovery: None,
+        };
+        let data = DataSet {
+            runs: vec![
+                run(vec![vec![100, 200, 300], vec![1, 2, 3]]),
+                run(vec![vec![300, 400, 500], vec![4, 5, 6]]),
+            ],
+            stages: 2,
+            criteria: 3,
+        };
+        assert_eq!(mean_stage_total(&data, 0), 900.0);
+        assert_eq!(mean_stage_total(&data, 1), 10.5);
+        assert_eq!(mean_stage_total(&DataSet { runs: vec![], stages: 3, criteria: 3 }, 0), 0.0);
+    }
+
+    #[test]
+    fn value_map_scales_each_column() {
+        let stats = DataSetStats::from_score_rows(&sample_rows());
+        let col = &stats.columns[0];
+
+        let raw = ValueMap::for_column(col, CombinedPlotScale::Raw);
+        assert_eq!(raw, ValueMap::IDENTITY);
+        assert_eq!(raw.apply(120.0), 120.0);
+
+        let min_max = ValueMap::for_column(col, CombinedPlotScale::MinMax);
+        assert_eq!(min_max.apply(col.min as f64), 0.0);
+        assert_eq!(min_max.apply(col.max as f64), 1.0);
+
+        let z = ValueMap::for_column(col, CombinedPlotScale::ZScore);
+        assert!(z.apply(col.mean).abs() < 1e-9);
+        assert!((z.apply(col.mean + col.std_dev) - 1.0).abs() < 1e-9);
+
+        // A column without spread must not divide by zero
+        let flat = DataSetStats::from_score_rows(&[[[50; 3]; 3], [[50; 3]; 3]]);
+        let flat_map = ValueMap::for_column(&flat.columns[0], CombinedPlotScale::MinMax);
+        assert_eq!(flat_map.apply(50.0), 0.0);
+    }
 }
@@ -0,0 +1,279 @@
+//! Side-by-side comparison of two sessions (e.g. before and after a deck or
+//! build change).
+//!
+//! For every score column present in both sessions this reports the mean and
+//! median of each and their difference (B − A), plus Welch's t-statistic for
+//! the difference of means. Writes `comparison.json` and an overlay box plot
+//! `comparison.png`.
+
+use super::charts;
+use super::config::ChartConfig;
+use super::csv_reader::DataSet;
+use super::statistics::{ColumnStats, DataSetStats};
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Comparison of one score column between session A and session B.
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnComparison {
+    /// Stage number (1-based)
+    pub stage: usize,
+    /// Criterion number (1-based)
+    pub criterion: usize,
+    /// Column name (`column_labels` entry or code)
+    pub label: String,
+    /// Runs in session A
+    pub count_a: usize,
+    /// Runs in session B
+    pub count_b: usize,
+    /// Mean of session A
+    pub mean_a: f64,
+    /// Mean of session B
+    pub mean_b: f64,
+    /// `mean_b - mean_a`
+    pub mean_delta: f64,
+    /// Median of session A
+    pub median_a: f64,
+    /// Median of session B
+    pub median_b: f64,
+    /// `median_b - median_a`
+    pub median_delta: f64,
+    /// Welch's t-statistic for `mean_b - mean_a`; positive when B scores
+    /// higher. None when either session has fewer than two runs or both
+    /// columns have zero variance.
+    pub welch_t: Option<f64>,
+}
+
+/// Result of comparing two sessions, as written to `comparison.json`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionComparison {
+    /// Name of session A (its folder name)
+    pub session_a: String,
+    /// Name of session B (its folder name)
+    pub session_b: String,
+    /// Runs in session A
+    pub runs_a: usize,
+    /// Runs in session B
+    pub runs_b: usize,
+    /// Columns present in both sessions, stage-major
+    pub columns: Vec<ColumnComparison>,
+}
+
+/// Compares the results.csv of session folders `a` and `b`, writing
+/// `comparison.json` and `comparison.png` to `output_dir`.
+///
+/// Column labels, percentiles and p10/p90 caps follow chart_config.json.
+/// Returns (json_path, chart_path).
+pub fn compare_sessions(a: &Path, b: &Path, output_dir: &Path) -> Result<(PathBuf, PathBuf)> {
+    let config = ChartConfig::load(&crate::paths::resolve_data_file("chart_config.json"));
+
+    let data_a = load_session(a)?;
+    let data_b = load_session(b)?;
+    crate::log(&format!(
+        "Comparing {} ({} runs) with {} ({} runs)",
+        crate::paths::relative_display(a),
+        data_a.len(),
+        crate::paths::relative_display(b),
+        data_b.len()
+    ));
+
+    compare_datasets(&data_a, &data_b, (&session_name(a), &session_name(b)), output_dir, &config)
+}
+
+/// Compares two already-loaded data sets; the part of [`compare_sessions`]
+/// after reading the CSVs, so tests can pass synthetic data.
+pub fn compare_datasets(
+    a: &DataSet,
+    b: &DataSet,
+    names: (&str, &str),
+    output_dir: &Path,
+    config: &ChartConfig,
+) -> Result<(PathBuf, PathBuf)> {
+    if a.is_empty() || b.is_empty() {
+        return Err(anyhow!("Both sessions need at least one run to compare"));
+    }
+
+    let percentiles = config.statistics.effective_percentiles();
+    let stats_a = DataSetStats::from_dataset(a, &percentiles).with_column_labels(&config.column_labels);
+    let stats_b = DataSetStats::from_dataset(b, &percentiles).with_column_labels(&config.column_labels);
+
+    let pairs = matching_columns(&stats_a, &stats_b);
+    if pairs.is_empty() {
+        return Err(anyhow!("The sessions have no score columns in common"));
+    }
+    if pairs.len() < stats_a.columns.len().max(stats_b.columns.len()) {
+        crate::log(&format!(
+            "Score grids differ; comparing the {} columns both sessions have",
+            pairs.len()
+        ));
+    }
+
+    let comparison = SessionComparison {
+        session_a: names.0.to_string(),
+        session_b: names.1.to_string(),
+        runs_a: stats_a.total_runs,
+        runs_b: stats_b.total_runs,
+        columns: pairs.iter().map(|(a, b)| compare_columns(a, b)).collect(),
+    };
+
+    fs::create_dir_all(output_dir)
+        .context(format!("Failed to create {}", output_dir.display()))?;
+
+    let json_path = output_dir.join("comparison.json");
+    let json = serde_json::to_string_pretty(&comparison)
+        .context("Failed to serialize comparison to JSON")?;
+    fs::write(&json_path, json)
+        .context(format!("Failed to write {}", json_path.display()))?;
+    crate::log(&format!("Comparison saved: {}", crate::paths::relative_display(&json_path)));
+
+    let chart_path = output_dir.join("comparison.png");
+    charts::generate_comparison_box_plot(&pairs, names, &chart_path, config)?;
+    crate::log(&format!(
+        "Comparison chart saved: {}",
+        crate::paths::relative_display(&chart_path)
+    ));
+
+    Ok((json_path, chart_path))
+}
+
+/// Reads `session_dir/results.csv`.
+fn load_session(session_dir: &Path) -> Result<DataSet> {
+    if !session_dir.is_dir() {
+        return Err(anyhow!("Session folder not found: {}", session_dir.display()));
+    }
+    DataSet::from_csv_nonempty(&session_dir.join("results.csv"))
+}
+
+/// Folder name of a session, for the JSON and chart legend.
+fn session_name(session_dir: &Path) -> String {
+    session_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| session_dir.display().to_string())
+}
+
+/// Pairs each column of `a` with the same stage/criterion column of `b`.
+fn matching_columns<'a>(
+    a: &'a DataSetStats,
+    b: &'a DataSetStats,
+) -> Vec<(&'a ColumnStats, &'a ColumnStats)> {
+    a.columns
+        .iter()
+        .filter_map(|ca| {
+            b.columns
+                .iter()
+                .find(|cb| cb.stage == ca.stage && cb.criterion == ca.criterion)
+                .map(|cb| (ca, cb))
+        })
+        .collect()
+}
+
+fn compare_columns(a: &ColumnStats, b: &ColumnStats) -> ColumnComparison {
+    ColumnComparison {
+        stage: a.stage,
+        criterion: a.criterion,
+        label: a.label.clone(),
+        count_a: a.count,
+        count_b: b.count,
+        mean_a: a.mean,
+        mean_b: b.mean,
+        mean_delta: b.mean - a.mean,
+        median_a: a.median,
+        median_b: b.median,
+        median_delta: b.median - a.median,
+        welch_t: welch_t(a, b),
+    }
+}
+
+/// Welch's t-statistic `(mean_b - mean_a) / sqrt(s_a²/n_a + s_b²/n_b)`, with
+/// sample variances recovered from the population `std_dev` in `ColumnStats`.
+fn welch_t(a: &ColumnStats, b: &ColumnStats) -> Option<f64> {
+    if a.count < 2 || b.count < 2 {
+        return None;
+    }
+    let sample_variance = |c: &ColumnStats| c.std_dev.powi(2) * c.count as f64 / (c.count - 1) as f64;
+    let standard_error =
+        (sample_variance(a) / a.count as f64 + sample_variance(b) / b.count as f64).sqrt();
+    if standard_error == 0.0 {
+        return None;
+    }
+    Some((b.mean - a.mean) / standard_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::csv_reader::RunData;
+    use tempfile::tempdir;
+
+    fn dataset(first_column: &[u32]) -> DataSet {
+        let runs = first_column
+            .iter()
+            .enumerate()
+            .map(|(i, &score)| RunData {
+                iteration: i as u32 + 1,
+                timestamp: String::new(),
+                screenshot_path: String::new(),
+                scores: vec![vec![score, 2000, 3000], vec![4000, 5000, 6000], vec![7000, 8000, 9000]],
+                total: None,
+                stage_totals: vec![None; 3],
+                recovery: None,
+            })
+            .collect();
+        DataSet { runs, stages: 3, criteria: 3 }
+    }
+
+    fn column_stats(values: &[u32]) -> ColumnStats {
+        DataSetStats::from_dataset(&dataset(values), &[]).columns.remove(0)
+    }
+
+    #[test]
+    fn test_welch_t_matches_reference() {
+        // Sample variances 2.5 and 10 over five runs each: SE = sqrt(0.5 + 2)
+        let a = column_stats(&[1, 2, 3, 4, 5]);
+        let b = column_stats(&[4, 6, 8, 10, 12]);
+        let t = welch_t(&a, &b).unwrap();
+        assert!((t - 5.0 / 2.5f64.sqrt()).abs() < 1e-9, "{}", t);
+        assert!(welch_t(&b, &a).unwrap() < 0.0);
+    }
+
+    #[test]
+    fn test_welch_t_undefined_cases() {
+        assert_eq!(welch_t(&column_stats(&[5]), &column_stats(&[1, 2, 3])), None);
+        assert_eq!(welch_t(&column_stats(&[7, 7]), &column_stats(&[7, 7, 7])), None);
+    }
+
+    #[test]
+    fn test_compare_datasets_writes_outputs() {
+        let dir = tempdir().unwrap();
+        let a = dataset(&[1000, 1100, 1200, 1300]);
+        let b = dataset(&[1500, 1600, 1700, 1800]);
+
+        let (json_path, chart_path) =
+            compare_datasets(&a, &b, ("before", "after"), dir.path(), &ChartConfig::default()).unwrap();
+        assert!(chart_path.exists());
+
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(json_path).unwrap()).unwrap();
+        assert_eq!(json["session_a"], "before");
+        assert_eq!(json["columns"].as_array().unwrap().len(), 9);
+        let first = &json["columns"][0];
+        assert_eq!(first["label"], "S1C1");
+        assert_eq!(first["mean_delta"], 500.0);
+        assert_eq!(first["median_delta"], 500.0);
+        assert!(first["welch_t"].as_f64().unwrap() > 0.0);
+        // Identical constant columns have no t-statistic
+        assert!(json["columns"][1]["welch_t"].is_null());
+    }
+
+    #[test]
+    fn test_compare_datasets_rejects_empty() {
+        let dir = tempdir().unwrap();
+        let empty = DataSet { runs: vec![], stages: 3, criteria: 3 };
+        let result =
+            compare_datasets(&empty, &dataset(&[1]), ("a", "b"), dir.path(), &ChartConfig::default());
+        assert!(result.is_err());
+    }
+}
@@ -5,6 +5,7 @@
 //! so changes take effect without rebuilding.
 
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
@@ -18,8 +19,56 @@ pub struct ChartConfig {
     pub colors: ColorConfig,
     /// Layout dimensions
     pub layout: LayoutConfig,
+    /// Extra statistics computed for statistics.json and the box plots
+    pub statistics: StatisticsConfig,
+    /// How large numbers are written in the stats table and statistics.json
+    pub number_format: NumberFormatConfig,
+    /// Stage color set for the combined box plot
+    pub palette: ChartPalette,
+    /// Y-axis scale of the combined box plot; per-column charts always show
+    /// raw scores
+    pub normalize_combined_plot: CombinedPlotScale,
+    /// Also write chart_dashboard.png (every column's box plot + histogram on
+    /// one canvas); off by default
+    pub generate_dashboard: bool,
+    /// Display names for score columns, keyed by column code
+    /// (`{"S1C1": "Vocal", ...}`), used in chart titles and file names, the
+    /// combined box plot, statistics.json and summary.txt. Columns without an
+    /// entry keep their code.
+    pub column_labels: BTreeMap<String, String>,
 }
 
+/// Stage color set used by the combined box plot.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, serde::Serialize)]
+pub enum ChartPalette {
+    /// `colors.stage_colors` (red/green/blue unless overridden)
+    #[default]
+    Default,
+    /// Okabe-Ito orange / blue / reddish purple, distinguishable with the
+    /// common forms of color blindness
+    ColorblindSafe,
+}
+
+/// How the combined box plot scales each column. Criteria score on different
+/// scales, so with raw scores one column can dwarf the others.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, serde::Serialize)]
+pub enum CombinedPlotScale {
+    /// Raw scores on a shared axis
+    #[default]
+    Raw,
+    /// Standard deviations from each column's own mean
+    ZScore,
+    /// Each column rescaled so its minimum is 0 and its maximum is 1
+    MinMax,
+}
+
+/// Okabe-Ito colors used by `ChartPalette::ColorblindSafe`, stage 1..3.
+const COLORBLIND_SAFE_STAGE_COLORS: [[u8; 3]; 3] = [
+    [230, 159, 0],   // Orange
+    [0, 114, 178],   // Blue
+    [204, 121, 167], // Reddish purple
+];
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
 pub struct FontConfig {
@@ -48,6 +97,8 @@ pub struct ColorConfig {
     pub light_gray_bg: [u8; 3],
     /// Grid line color [R, G, B]
     pub grid_color: [u8; 3],
+    /// Box colors for stages 1-3 in the combined box plot [[R, G, B]; 3]
+    pub stage_colors: [[u8; 3]; 3],
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -65,6 +116,27 @@ pub struct LayoutConfig {
     pub table_header_height: i32,
     /// Box plot area width
     pub box_plot_width: u32,
+    /// Width of one column cell in the dashboard image
+    pub dashboard_cell_width: u32,
+    /// Height of one column cell in the dashboard image
+    pub dashboard_cell_height: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct StatisticsConfig {
+    /// Percentiles (0-100) computed per column and written to statistics.json
+    pub percentiles: Vec<f64>,
+    /// Draw p10/p90 caps on the box plots (computed even if not listed above)
+    pub show_p10_p90_caps: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct NumberFormatConfig {
+    /// Thousands separator ("," by default; "." or " " for other locales,
+    /// "" to disable grouping)
+    pub thousands_separator: String,
 }
 
 impl Default for ChartConfig {
@@ -73,6 +145,12 @@ impl Default for ChartConfig {
             font: FontConfig::default(),
             colors: ColorConfig::default(),
             layout: LayoutConfig::default(),
+            statistics: StatisticsConfig::default(),
+            number_format: NumberFormatConfig::default(),
+            palette: ChartPalette::Default,
+            normalize_combined_plot: CombinedPlotScale::Raw,
+            generate_dashboard: false,
+            column_labels: BTreeMap::new(),
         }
     }
 }
@@ -97,6 +175,11 @@ impl Default for ColorConfig {
             orange_header: [230, 126, 34],     // #E67E22
             light_gray_bg: [245, 245, 245],
             grid_color: [220, 220, 220],
+            stage_colors: [
+                [220, 80, 80],  // Stage 1: Red
+                [80, 180, 80],  // Stage 2: Green
+                [80, 120, 200], // Stage 3: Blue
+            ],
         }
     }
 }
@@ -110,11 +193,59 @@ impl Default for LayoutConfig {
             table_height: 90,
             table_header_height: 40,
             box_plot_width: 300,
+            dashboard_cell_width: 600,
+            dashboard_cell_height: 400,
         }
     }
 }
 
+impl Default for StatisticsConfig {
+    fn default() -> Self {
+        Self {
+            percentiles: vec![25.0, 75.0],
+            show_p10_p90_caps: false,
+        }
+    }
+}
+
+impl Default for NumberFormatConfig {
+    fn default() -> Self {
+        Self {
+            thousands_separator: ",".to_string(),
+        }
+    }
+}
+
+impl StatisticsConfig {
+    /// Percentiles to compute: the configured list plus 10/90 when caps are
+    /// drawn, sorted and de-duplicated. Values outside 0-100 are dropped.
+    pub fn effective_percentiles(&self) -> Vec<f64> {
+        let mut out: Vec<f64> = self.percentiles.clone();
+        if self.show_p10_p90_caps {
+            out.extend([10.0, 90.0]);
+        }
+        out.retain(|p| {
+            let ok = (0.0..=100.0).contains(p);
+            if !ok {
+                crate::log(&format!("Ignoring percentile {} (must be 0-100)", p));
+            }
+            ok
+        });
+        out.sort_by(|a, b| a.total_cmp(b));
+        out.dedup();
+        out
+    }
+}
+
 impl ChartConfig {
+    /// Stage 1-3 box colors for the selected palette.
+    pub fn stage_colors(&self) -> [[u8; 3]; 3] {
+        match self.palette {
+            ChartPalette::Default => self.colors.stage_colors,
+            ChartPalette::ColorblindSafe => COLORBLIND_SAFE_STAGE_COLORS,
+        }
+    }
+
     /// Load config from file, or return defaults if file doesn't exist.
     pub fn load(config_path: &Path) -> Self {
         if config_path.exists() {
@@ -157,10 +288,41 @@ impl serde::Serialize for ChartConfig {
         S: serde::Serializer,
     {
         use serde::ser::SerializeStruct;
-        let mut state = serializer.serialize_struct("ChartConfig", 3)?;
+        let mut state = serializer.serialize_struct("ChartConfig", 9)?;
         state.serialize_field("font", &self.font)?;
         state.serialize_field("colors", &self.colors)?;
         state.serialize_field("layout", &self.layout)?;
+        state.serialize_field("statistics", &self.statistics)?;
+        state.serialize_field("number_format", &self.number_format)?;
+        state.serialize_field("palette", &self.palette)?;
+        state.serialize_field("normalize_combined_plot", &self.normalize_combined_plot)?;
+        state.serialize_field("generate_dashboard", &self.generate_dashboard)?;
+        state.serialize_field("column_labels", &self.column_labels)?;
+        state.end()
+    }
+}
+
+impl serde::Serialize for NumberFormatConfig {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("NumberFormatConfig", 1)?;
+        state.serialize_field("thousands_separator", &self.thousands_separator)?;
+        state.end()
+    }
+}
+
+impl serde::Serialize for StatisticsConfig {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("StatisticsConfig", 2)?;
+        state.serialize_field("percentiles", &self.percentiles)?;
+        state.serialize_field("show_p10_p90_caps", &self.show_p10_p90_caps)?;
         state.end()
     }
 }
@@ -188,11 +350,12 @@ impl serde::Serialize for ColorConfig {
         S: serde::Serializer,
     {
         use serde::ser::SerializeStruct;
-        let mut state = serializer.serialize_struct("ColorConfig", 4)?;
+        let mut state = serializer.serialize_struct("ColorConfig", 5)?;
         state.serialize_field("orange_primary", &self.orange_primary)?;
         state.serialize_field("orange_header", &self.orange_header)?;
         state.serialize_field("light_gray_bg", &self.light_gray_bg)?;
         state.serialize_field("grid_color", &self.grid_color)?;
+        state.serialize_field("stage_colors", &self.stage_colors)?;
         state.end()
     }
 }
@@ -203,13 +366,15 @@ impl serde::Serialize for LayoutConfig {
         S: serde::Serializer,
     {
         use serde::ser::SerializeStruct;
-        let mut state = serializer.serialize_struct("LayoutConfig", 6)?;
+        let mut state = serializer.serialize_struct("LayoutConfig", 8)?;
         state.serialize_field("chart_width", &self.chart_width)?;
         state.serialize_field("chart_height", &self.chart_height)?;
         state.serialize_field("title_height", &self.title_height)?;
         state.serialize_field("table_height", &self.table_height)?;
         state.serialize_field("table_header_height", &self.table_header_height)?;
         state.serialize_field("box_plot_width", &self.box_plot_width)?;
+        state.serialize_field("dashboard_cell_width", &self.dashboard_cell_width)?;
+        state.serialize_field("dashboard_cell_height", &self.dashboard_cell_height)?;
         state.end()
     }
 }
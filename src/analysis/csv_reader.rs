@@ -1,12 +1,33 @@
 //! CSV reader for automation results.
 //!
 //! Parses the CSV file produced by automation (Phase 3) into structured data.
+//! Columns are located by header name rather than position, and the score grid
+//! (stages × criteria) is read from the header's `sNcM` columns rather than
+//! assumed, so other contest layouts and renamed headers (`csv_header`) load
+//! the same way. Fields may be quoted (RFC 4180), as the writer does for
+//! screenshot paths containing commas.
 
 use anyhow::{anyhow, Context, Result};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use std::ops::RangeInclusive;
 use std::path::Path;
 
+use crate::automation::csv_writer::CSV_HEADER;
+use crate::ocr::reconcile::expected_stage_total;
+
+/// Number of leading non-score columns (iteration, timestamp, screenshot),
+/// assumed when the header does not name them.
+const META_COLUMNS: usize = 3;
+
+/// Header names recognized for the metadata columns (compared lowercased).
+const ITERATION_NAMES: &[&str] = &["iteration", "iter", "run", "#"];
+const TIMESTAMP_NAMES: &[&str] = &["timestamp", "time", "datetime", "date"];
+const SCREENSHOT_NAMES: &[&str] = &["screenshot", "image", "file", "path"];
+
+/// Grid assumed when the file has no header to derive it from.
+const DEFAULT_LAYOUT: (usize, usize) = (3, 3);
+
 /// Raw data from one CSV row (one rehearsal run).
 #[derive(Debug, Clone)]
 pub struct RunData {
@@ -16,8 +37,80 @@ pub struct RunData {
     pub timestamp: String,
     /// Path to screenshot file
     pub screenshot_path: String,
-    /// Scores: [stage][criterion], 3 stages with 3 criteria each
-    pub scores: [[u32; 3]; 3],
+    /// Scores: [stage][criterion], `DataSet::stages` × `DataSet::criteria`
+    pub scores: Vec<Vec<u32>>,
+    /// Grand total of the row's scores (`total` column), if the file has one
+    pub total: Option<u64>,
+    /// OCR'd stage totals (`sN_total` columns), one per stage; `None` where
+    /// the file lacks the column or the total was not read
+    pub stage_totals: Vec<Option<u32>>,
+    /// Overlap-reconstruction outcome (`recovery` column: `ok`, `repaired`,
+    /// `flagged`, `manual`, `verified`), if the file has one
+    pub recovery: Option<String>,
+}
+
+/// A total column that disagrees with the row's scores.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TotalMismatch {
+    /// Stage index (0-based), or `None` for the grand `total` column
+    pub stage: Option<usize>,
+    /// Value in the file
+    pub recorded: u64,
+    /// Value the row's scores give
+    pub expected: u64,
+}
+
+impl RunData {
+    /// Cross-checks the row's total columns against its scores: the grand
+    /// `total` must be the plain sum of every score, and each OCR'd stage total
+    /// the game's `c1 + c2 + c3 + floor(max / 5)` (see `ocr::reconcile`).
+    /// Totals the file lacks are skipped.
+    pub fn total_mismatches(&self) -> Vec<TotalMismatch> {
+        let mut mismatches = Vec::new();
+        if let Some(total) = self.total {
+            let expected = self.scores.iter().flatten().map(|&v| v as u64).sum();
+            if total != expected {
+                mismatches.push(TotalMismatch { stage: None, recorded: total, expected });
+            }
+        }
+        for (stage, (scores, total)) in self.scores.iter().zip(&self.stage_totals).enumerate() {
+            let Some(total) = total else { continue };
+            let expected = expected_stage_total(scores);
+            if *total as u64 != expected {
+                mismatches.push(TotalMismatch {
+                    stage: Some(stage),
+                    recorded: *total as u64,
+                    expected,
+                });
+            }
+        }
+        mismatches
+    }
+}
+
+/// Positions of the optional trailing columns, found by header name so files
+/// written before they existed still load.
+#[derive(Debug, Clone, Default)]
+struct OptionalColumns {
+    /// Index of `recovery`
+    recovery: Option<usize>,
+    /// Index of `total`
+    total: Option<usize>,
+    /// Index of `sN_total` for each stage
+    stage_totals: Vec<Option<usize>>,
+}
+
+impl OptionalColumns {
+    fn from_header(header: &str, stages: usize) -> Self {
+        let names: Vec<String> =
+            split_csv_line(header).iter().map(|name| name.trim().to_ascii_lowercase()).collect();
+        let find = |wanted: &str| names.iter().position(|name| name == wanted);
+        OptionalColumns {
+            recovery: find("recovery"),
+            total: find("total"),
+            stage_totals: (1..=stages).map(|s| find(&format!("s{}_total", s))).collect(),
+        }
+    }
 }
 
 /// All data loaded from CSV.
@@ -25,6 +118,10 @@ pub struct RunData {
 pub struct DataSet {
     /// All runs loaded from CSV
     pub runs: Vec<RunData>,
+    /// Number of stages (grid rows), from the header
+    pub stages: usize,
+    /// Number of criteria per stage (grid columns), from the header
+    pub criteria: usize,
 }
 
 impl DataSet {
@@ -33,17 +130,27 @@ impl DataSet {
     /// CSV format expected:
     /// iteration,timestamp,screenshot,s1c1,s1c2,s1c3,s2c1,s2c2,s2c3,s3c1,s3c2,s3c3
     ///
-    /// Skips the header row and any malformed rows (with warning log).
+    /// The score header columns define the grid (3×3 above) and may appear in
+    /// any order under any recognized spelling (`s1c1`, `S1_C1`,
+    /// `stage1_criterion1`, ...); a header naming no score columns falls back
+    /// to the default positions. The metadata columns are found by name too,
+    /// falling back to the first three. The optional `recovery`, `total` and
+    /// `sN_total` columns are read when the header names them; other columns
+    /// are ignored. Skips the header row and any malformed rows (with warning log).
     pub fn from_csv(path: &Path) -> Result<Self> {
         let file = File::open(path).context(format!("Failed to open CSV file: {}", path.display()))?;
         let reader = BufReader::new(file);
         let mut runs = Vec::new();
+        let mut layout = ColumnLayout::default();
+        let mut optional = OptionalColumns::default();
 
         for (line_num, line_result) in reader.lines().enumerate() {
             let line = line_result.context("Failed to read line from CSV")?;
 
-            // Skip header row
+            // Header row: locate the columns and derive the score grid
             if line_num == 0 {
+                layout = ColumnLayout::from_header(&line)?;
+                optional = OptionalColumns::from_header(&line, layout.stages);
                 continue;
             }
 
@@ -53,7 +160,7 @@ impl DataSet {
             }
 
             // Parse the line
-            match Self::parse_line(&line) {
+            match Self::parse_line(&line, &layout, &optional) {
                 Ok(run_data) => {
                     runs.push(run_data);
                 }
@@ -67,42 +174,90 @@ impl DataSet {
             }
         }
 
-        Ok(DataSet { runs })
+        Ok(DataSet { runs, stages: layout.stages, criteria: layout.criteria })
+    }
+
+    /// `from_csv` for callers that need at least one run (analysis and
+    /// session comparison). A file without a readable run is an error that
+    /// says why: it is empty, it has a header but no rows (the run produced no
+    /// OCR results), or every row is malformed (e.g. truncated mid-write).
+    /// A missing file is still the `from_csv` open error.
+    pub fn from_csv_nonempty(path: &Path) -> Result<Self> {
+        let data = Self::from_csv(path)?;
+        if !data.is_empty() {
+            return Ok(data);
+        }
+
+        let contents = std::fs::read_to_string(path)
+            .context(format!("Failed to read CSV file: {}", path.display()))?;
+        let name = crate::paths::relative_display(path);
+        if contents.trim().is_empty() {
+            return Err(anyhow!("{} is empty", name));
+        }
+        let rows = contents.lines().skip(1).filter(|line| !line.trim().is_empty()).count();
+        if rows == 0 {
+            Err(anyhow!(
+                "{} has a header but no result rows; the run produced no OCR results",
+                name
+            ))
+        } else {
+            Err(anyhow!(
+                "None of the {} rows in {} could be read (truncated or malformed); \
+                 see the log for details",
+                rows,
+                name
+            ))
+        }
     }
 
     /// Parse a single CSV line into RunData.
-    fn parse_line(line: &str) -> Result<RunData> {
-        let parts: Vec<&str> = line.split(',').collect();
+    fn parse_line(line: &str, layout: &ColumnLayout, optional: &OptionalColumns) -> Result<RunData> {
+        let fields = split_csv_line(line);
+        let parts: Vec<&str> = fields.iter().map(String::as_str).collect();
 
-        if parts.len() < 12 {
+        let expected = layout.min_columns();
+        if parts.len() < expected {
             return Err(anyhow!(
-                "Expected 12 columns, got {}",
+                "Expected {} columns, got {}",
+                expected,
                 parts.len()
             ));
         }
 
-        let iteration = parts[0]
+        let iteration = parts[layout.iteration]
             .parse::<u32>()
             .context("Invalid iteration number")?;
-        let timestamp = parts[1].to_string();
-        let screenshot_path = parts[2].to_string();
-
-        // Parse 9 score values
-        let mut scores = [[0u32; 3]; 3];
-        for stage in 0..3 {
-            for criterion in 0..3 {
-                let idx = 3 + stage * 3 + criterion;
-                scores[stage][criterion] = parts[idx]
+        let timestamp = parts[layout.timestamp].to_string();
+        let screenshot_path = parts[layout.screenshot].to_string();
+
+        // Parse stages × criteria score values
+        let stages = layout.stages;
+        let mut scores = vec![vec![0u32; layout.criteria]; stages];
+        for (stage, row) in scores.iter_mut().enumerate() {
+            for (criterion, score) in row.iter_mut().enumerate() {
+                let idx = layout.scores[stage][criterion];
+                *score = parts[idx]
                     .parse::<u32>()
                     .context(format!("Invalid score at column {}", idx + 1))?;
             }
         }
 
+        // Optional columns: blank or unparsable cells read as None
+        let total = optional_cell(&parts, optional.total);
+        let stage_totals = (0..stages)
+            .map(|s| optional_cell(&parts, optional.stage_totals.get(s).copied().flatten()))
+            .collect();
+        let recovery = optional_cell::<String>(&parts, optional.recovery)
+            .filter(|value| !value.is_empty());
+
         Ok(RunData {
             iteration,
             timestamp,
             screenshot_path,
             scores,
+            total,
+            stage_totals,
+            recovery,
         })
     }
 
@@ -125,6 +280,202 @@ impl DataSet {
     pub fn is_empty(&self) -> bool {
         self.runs.is_empty()
     }
+
+    /// A copy holding only the runs whose iteration number is in `range`,
+    /// with the same grid shape.
+    pub fn filter_iterations(&self, range: &RangeInclusive<u32>) -> DataSet {
+        DataSet {
+            runs: self
+                .runs
+                .iter()
+                .filter(|run| range.contains(&run.iteration))
+                .cloned()
+                .collect(),
+            stages: self.stages,
+            criteria: self.criteria,
+        }
+    }
+}
+
+/// Splits one CSV line into fields, honoring double-quoted fields: commas
+/// inside quotes do not split and `""` is a literal quote. Unquoted fields are
+/// taken verbatim. The inverse of `automation::csv_writer::csv_field`.
+pub fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if in_quotes => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            }
+            '"' if field.is_empty() => in_quotes = true,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            _ => field.push(ch),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Parses the cell at `idx`, or `None` when there is no such column or the
+/// cell is blank/unparsable.
+fn optional_cell<T: std::str::FromStr>(parts: &[&str], idx: Option<usize>) -> Option<T> {
+    idx.and_then(|i| parts.get(i)).and_then(|cell| cell.trim().parse().ok())
+}
+
+/// Where each required field sits in a row, found from the header.
+#[derive(Debug, Clone)]
+struct ColumnLayout {
+    iteration: usize,
+    timestamp: usize,
+    screenshot: usize,
+    /// Column index of each score: `[stage][criterion]`
+    scores: Vec<Vec<usize>>,
+    /// Number of stages (grid rows)
+    stages: usize,
+    /// Number of criteria per stage (grid columns)
+    criteria: usize,
+}
+
+impl Default for ColumnLayout {
+    /// The built-in layout: metadata first, then a 3×3 grid in `s1c1..` order.
+    fn default() -> Self {
+        let (stages, criteria) = DEFAULT_LAYOUT;
+        ColumnLayout {
+            iteration: 0,
+            timestamp: 1,
+            screenshot: 2,
+            scores: (0..stages)
+                .map(|s| (0..criteria).map(|c| META_COLUMNS + s * criteria + c).collect())
+                .collect(),
+            stages,
+            criteria,
+        }
+    }
+}
+
+impl ColumnLayout {
+    /// Locates the columns named in `header`.
+    ///
+    /// Score columns may come in any order but must form a complete grid;
+    /// a header with no recognizable score columns keeps the default layout.
+    fn from_header(header: &str) -> Result<Self> {
+        let names: Vec<String> =
+            split_csv_line(header).iter().map(|name| name.trim().to_ascii_lowercase()).collect();
+        let find = |aliases: &[&str], fallback: usize| {
+            names.iter().position(|name| aliases.contains(&name.as_str())).unwrap_or(fallback)
+        };
+        let mut layout = ColumnLayout {
+            iteration: find(ITERATION_NAMES, 0),
+            timestamp: find(TIMESTAMP_NAMES, 1),
+            screenshot: find(SCREENSHOT_NAMES, 2),
+            ..ColumnLayout::default()
+        };
+
+        let cells: Vec<((usize, usize), usize)> = names
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, name)| score_column(name).map(|cell| (cell, idx)))
+            .collect();
+        if cells.is_empty() {
+            return Ok(layout);
+        }
+
+        let stages = cells.iter().map(|&((s, _), _)| s).max().unwrap_or(0);
+        let criteria = cells.iter().map(|&((_, c), _)| c).max().unwrap_or(0);
+        let mut grid = vec![vec![None; criteria]; stages];
+        for ((s, c), idx) in cells {
+            if grid[s - 1][c - 1].replace(idx).is_some() {
+                return Err(anyhow!("Score column s{}c{} appears more than once", s, c));
+            }
+        }
+        layout.scores = grid
+            .into_iter()
+            .map(|row| row.into_iter().collect::<Option<Vec<usize>>>())
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| {
+                anyhow!("Score columns do not form a complete {}x{} grid", stages, criteria)
+            })?;
+        layout.stages = stages;
+        layout.criteria = criteria;
+        Ok(layout)
+    }
+
+    /// Fewest fields a row needs to reach every required column.
+    fn min_columns(&self) -> usize {
+        let last_score = self.scores.iter().flatten().copied().max().unwrap_or(0);
+        [self.iteration, self.timestamp, self.screenshot, last_score]
+            .into_iter()
+            .max()
+            .unwrap_or(0)
+            + 1
+    }
+}
+
+/// Checks a custom `csv_header` against the built-in column order. The writer
+/// fills columns by position but this reader finds them by name, so every name
+/// it recognizes must sit where `CSV_HEADER` puts that column. Returns a
+/// description of the first misplaced name.
+pub(crate) fn misplaced_column(header: &str) -> Option<String> {
+    let builtin: Vec<&str> = CSV_HEADER.split(',').collect();
+    split_csv_line(header).iter().enumerate().find_map(|(idx, name)| {
+        let name = name.trim().to_ascii_lowercase();
+        let column = recognized_column(&name)?;
+        match builtin.iter().position(|known| *known == column) {
+            Some(expected) if expected == idx => None,
+            Some(expected) => {
+                Some(format!("'{}' is column {}, expected {}", name, idx + 1, expected + 1))
+            }
+            None => Some(format!("'{}' is not a built-in column", name)),
+        }
+    })
+}
+
+/// Built-in name of the column this reader takes a (lowercased) header name
+/// to be, if it recognizes it at all.
+fn recognized_column(name: &str) -> Option<String> {
+    let builtin = |aliases: &[&str], column: &str| {
+        aliases.contains(&name).then(|| column.to_string())
+    };
+    let stage_total = name
+        .strip_prefix('s')
+        .and_then(|rest| rest.strip_suffix("_total"))
+        .is_some_and(|stage| !stage.is_empty() && stage.bytes().all(|b| b.is_ascii_digit()));
+    builtin(ITERATION_NAMES, "iteration")
+        .or_else(|| builtin(TIMESTAMP_NAMES, "timestamp"))
+        .or_else(|| builtin(SCREENSHOT_NAMES, "screenshot"))
+        .or_else(|| score_column(name).map(|(s, c)| format!("s{}c{}", s, c)))
+        .or_else(|| {
+            (matches!(name, "recovery" | "total") || stage_total).then(|| name.to_string())
+        })
+}
+
+/// Recognizes a score column name, returning its 1-based `(stage, criterion)`.
+///
+/// Accepts `s1c1` and common variants: any case, `_`/`-`/space separators, and
+/// the words spelled out (`S1_C1`, `stage1_criterion1`, `stage 1 c 1`).
+fn score_column(name: &str) -> Option<(usize, usize)> {
+    let name: String = name
+        .trim()
+        .to_ascii_lowercase()
+        .chars()
+        .filter(|ch| !matches!(ch, '_' | '-' | ' '))
+        .collect();
+    let rest = name.strip_prefix("stage").or_else(|| name.strip_prefix('s'))?;
+    let split = rest.find(|ch: char| !ch.is_ascii_digit())?;
+    let (stage, rest) = rest.split_at(split);
+    let criterion = ["criterion", "crit", "c"].iter().find_map(|prefix| rest.strip_prefix(prefix))?;
+    let stage: usize = stage.parse().ok()?;
+    let criterion: usize = criterion.parse().ok()?;
+    (stage >= 1 && criterion >= 1).then_some((stage, criterion))
 }
 
 #[cfg(test)]
@@ -156,6 +507,29 @@ mod tests {
         assert_eq!(dataset.runs[1].scores[0][0], 110);
     }
 
+    #[test]
+    fn test_split_csv_line_quoted_fields() {
+        assert_eq!(split_csv_line("1,a,,b"), ["1", "a", "", "b"]);
+        assert_eq!(split_csv_line(r#"1,"a,b","say ""hi""",2"#), ["1", "a,b", r#"say "hi""#, "2"]);
+        assert_eq!(split_csv_line(r#"1,x"y,2"#), ["1", r#"x"y"#, "2"]);
+    }
+
+    #[test]
+    fn test_parse_path_with_comma() {
+        // Unquoted, the comma in the path would shift every score column by one
+        let csv_content = r#"iteration,timestamp,screenshot,s1c1,s1c2,s1c3,s2c1,s2c2,s2c3,s3c1,s3c2,s3c3,recovery
+1,2026-01-15T10:00:00,"C:\Users\a,b\shot.png",100,200,300,400,500,600,700,800,900,ok"#;
+
+        let file = create_test_csv(csv_content);
+        let dataset = DataSet::from_csv(file.path()).unwrap();
+
+        assert_eq!(dataset.len(), 1);
+        assert_eq!(dataset.runs[0].screenshot_path, r"C:\Users\a,b\shot.png");
+        assert_eq!(dataset.runs[0].scores[0], vec![100, 200, 300]);
+        assert_eq!(dataset.runs[0].scores[2][2], 900);
+        assert_eq!(dataset.runs[0].recovery.as_deref(), Some("ok"));
+    }
+
     #[test]
     fn test_column_values() {
         let csv_content = "iteration,timestamp,screenshot,s1c1,s1c2,s1c3,s2c1,s2c2,s2c3,s3c1,s3c2,s3c3
@@ -172,6 +546,22 @@ mod tests {
         assert_eq!(s3c3_values, vec![900, 950]);
     }
 
+    #[test]
+    fn test_filter_iterations() {
+        let csv_content = "iteration,timestamp,screenshot,s1c1,s1c2,s1c3,s2c1,s2c2,s2c3,s3c1,s3c2,s3c3
+1,2026-01-15T10:00:00,test1.png,100,200,300,400,500,600,700,800,900
+2,2026-01-15T10:01:00,test2.png,150,250,350,450,550,650,750,850,950
+3,2026-01-15T10:02:00,test3.png,170,270,370,470,570,670,770,870,970";
+
+        let file = create_test_csv(csv_content);
+        let dataset = DataSet::from_csv(file.path()).unwrap();
+
+        let filtered = dataset.filter_iterations(&(2..=3));
+        assert_eq!(filtered.column_values(0, 0), vec![150, 170]);
+        assert_eq!((filtered.stages, filtered.criteria), (3, 3));
+        assert!(dataset.filter_iterations(&(4..=10)).is_empty());
+    }
+
     #[test]
     fn test_empty_csv_header_only() {
         let csv_content =
@@ -181,6 +571,176 @@ mod tests {
         let dataset = DataSet::from_csv(file.path()).unwrap();
 
         assert!(dataset.is_empty());
+        assert_eq!((dataset.stages, dataset.criteria), (3, 3));
+    }
+
+    #[test]
+    fn test_nonempty_rejects_header_only() {
+        let file = create_test_csv(
+            "iteration,timestamp,screenshot,s1c1,s1c2,s1c3,s2c1,s2c2,s2c3,s3c1,s3c2,s3c3\n",
+        );
+        let err = DataSet::from_csv_nonempty(file.path()).unwrap_err();
+        assert!(err.to_string().contains("no result rows"), "{}", err);
+
+        let empty = create_test_csv("");
+        let err = DataSet::from_csv_nonempty(empty.path()).unwrap_err();
+        assert!(err.to_string().contains("is empty"), "{}", err);
+    }
+
+    #[test]
+    fn test_nonempty_truncated_row() {
+        let header = "iteration,timestamp,screenshot,s1c1,s1c2,s1c3,s2c1,s2c2,s2c3,s3c1,s3c2,s3c3";
+        let truncated = "2,2026-01-15T10:01:00,test2.png,150,250";
+
+        let file = create_test_csv(&format!("{}\n{}", header, truncated));
+        let err = DataSet::from_csv_nonempty(file.path()).unwrap_err();
+        assert!(err.to_string().contains("None of the 1 rows"), "{}", err);
+
+        // A truncated last row is skipped when earlier rows are intact
+        let file = create_test_csv(&format!(
+            "{}\n1,2026-01-15T10:00:00,test1.png,100,200,300,400,500,600,700,800,900\n{}",
+            header, truncated
+        ));
+        let dataset = DataSet::from_csv_nonempty(file.path()).unwrap();
+        assert_eq!(dataset.len(), 1);
+    }
+
+    #[test]
+    fn test_layout_from_header() {
+        let csv_content = "iteration,timestamp,screenshot,s1c1,s1c2,s2c1,s2c2,recovery
+1,2026-01-15T10:00:00,test1.png,100,200,300,400,ok";
+
+        let file = create_test_csv(csv_content);
+        let dataset = DataSet::from_csv(file.path()).unwrap();
+
+        assert_eq!((dataset.stages, dataset.criteria), (2, 2));
+        assert_eq!(dataset.runs[0].scores, vec![vec![100, 200], vec![300, 400]]);
+    }
+
+    #[test]
+    fn test_quoted_header_name_with_comma() {
+        let csv_content = r#"iteration,timestamp,"shot, file",s1c1,s1c2,s2c1,s2c2,recovery
+1,2026-01-15T10:00:00,test1.png,100,200,300,400,flagged"#;
+
+        let file = create_test_csv(csv_content);
+        let dataset = DataSet::from_csv(file.path()).unwrap();
+
+        assert_eq!((dataset.stages, dataset.criteria), (2, 2));
+        assert_eq!(dataset.runs[0].scores, vec![vec![100, 200], vec![300, 400]]);
+        assert_eq!(dataset.runs[0].recovery.as_deref(), Some("flagged"));
+    }
+
+    #[test]
+    fn test_layout_rejects_incomplete_grid() {
+        let layout = |header| ColumnLayout::from_header(header).map(|l| (l.stages, l.criteria));
+        assert!(layout("iteration,timestamp,screenshot,s1c1,s1c2,s2c1").is_err());
+        assert_eq!(layout("iteration,timestamp,screenshot").unwrap(), (3, 3));
+    }
+
+    #[test]
+    fn test_renamed_and_reordered_header() {
+        let csv_content = "Run,S1_C2,S1_C1,stage2_criterion1,stage2_criterion2,Image,Time,recovery
+7,200,100,300,400,test1.png,2026-01-15T10:00:00,ok";
+
+        let file = create_test_csv(csv_content);
+        let dataset = DataSet::from_csv(file.path()).unwrap();
+
+        assert_eq!((dataset.stages, dataset.criteria), (2, 2));
+        let run = &dataset.runs[0];
+        assert_eq!(run.iteration, 7);
+        assert_eq!(run.timestamp, "2026-01-15T10:00:00");
+        assert_eq!(run.screenshot_path, "test1.png");
+        assert_eq!(run.scores, vec![vec![100, 200], vec![300, 400]]);
+        assert_eq!(run.recovery.as_deref(), Some("ok"));
+    }
+
+    #[test]
+    fn test_unrecognized_score_names_fall_back_to_positions() {
+        let csv_content = "n,when,shot,a,b,c,d,e,f,g,h,i
+1,2026-01-15T10:00:00,test1.png,100,200,300,400,500,600,700,800,900";
+
+        let file = create_test_csv(csv_content);
+        let dataset = DataSet::from_csv(file.path()).unwrap();
+
+        assert_eq!(dataset.runs[0].iteration, 1);
+        assert_eq!(dataset.runs[0].scores[2][2], 900);
+    }
+
+    #[test]
+    fn test_score_column_names() {
+        assert_eq!(score_column("s2c3"), Some((2, 3)));
+        assert_eq!(score_column("S1_C2"), Some((1, 2)));
+        assert_eq!(score_column("stage3_criterion1"), Some((3, 1)));
+        assert_eq!(score_column("s1_total"), None);
+        assert_eq!(score_column("screenshot"), None);
+        assert_eq!(score_column("s0c1"), None);
+    }
+
+    #[test]
+    fn test_total_columns() {
+        let csv_content = "iteration,timestamp,screenshot,s1c1,s1c2,s1c3,s2c1,s2c2,s2c3,s3c1,s3c2,s3c3,recovery,total,s1_total,s2_total,s3_total
+1,2026-01-15T10:00:00,test1.png,100,200,300,400,500,600,700,800,900,ok,4500,600,,2400";
+
+        let file = create_test_csv(csv_content);
+        let dataset = DataSet::from_csv(file.path()).unwrap();
+
+        assert_eq!((dataset.stages, dataset.criteria), (3, 3));
+        assert_eq!(dataset.runs[0].total, Some(4500));
+        assert_eq!(dataset.runs[0].stage_totals, vec![Some(600), None, Some(2400)]);
+        assert_eq!(dataset.runs[0].recovery.as_deref(), Some("ok"));
+    }
+
+    #[test]
+    fn test_misplaced_column() {
+        assert_eq!(misplaced_column(CSV_HEADER), None);
+        let renamed = "run,time,file,a1,a2,a3,b1,b2,b3,c1,c2,c3,flag,sum,t1,t2,t3";
+        assert_eq!(misplaced_column(renamed), None);
+
+        let swapped = CSV_HEADER.replace("s1c1,s1c2", "S1_C2,s1c1");
+        assert_eq!(misplaced_column(&swapped).as_deref(), Some("'s1_c2' is column 4, expected 5"));
+        let moved = renamed.replace("sum,t1", "t1,total");
+        assert_eq!(misplaced_column(&moved).as_deref(), Some("'total' is column 15, expected 14"));
+        let extra = renamed.replace("t3", "s4_total");
+        let problem = misplaced_column(&extra);
+        assert_eq!(problem.as_deref(), Some("'s4_total' is not a built-in column"));
+
+        // A quoted name holding a comma is one column, not two
+        let quoted = renamed.replace("flag", r#""flag, ok""#);
+        assert_eq!(misplaced_column(&quoted), None);
+        let quoted_moved = quoted.replace("sum,t1", "t1,total");
+        assert_eq!(misplaced_column(&quoted_moved).as_deref(), Some("'total' is column 15, expected 14"));
+    }
+
+    #[test]
+    fn test_total_mismatches() {
+        // S1: 100 + 200 + 300 + 300 / 5 = 660; S2: 1,500 + 120 = 1,620.
+        let csv_content = "iteration,timestamp,screenshot,s1c1,s1c2,s1c3,s2c1,s2c2,s2c3,total,s1_total,s2_total
+1,2026-01-15T10:00:00,test1.png,100,200,300,400,500,600,2100,660,1620
+2,2026-01-15T10:01:00,test2.png,100,200,300,400,500,600,2000,600,";
+
+        let file = create_test_csv(csv_content);
+        let dataset = DataSet::from_csv(file.path()).unwrap();
+
+        assert!(dataset.runs[0].total_mismatches().is_empty());
+        assert_eq!(
+            dataset.runs[1].total_mismatches(),
+            vec![
+                TotalMismatch { stage: None, recorded: 2000, expected: 2100 },
+                TotalMismatch { stage: Some(0), recorded: 600, expected: 660 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_total_columns_absent_in_older_files() {
+        let csv_content = "iteration,timestamp,screenshot,s1c1,s1c2,s1c3,s2c1,s2c2,s2c3,s3c1,s3c2,s3c3,recovery
+1,2026-01-15T10:00:00,test1.png,100,200,300,400,500,600,700,800,900,ok";
+
+        let file = create_test_csv(csv_content);
+        let dataset = DataSet::from_csv(file.path()).unwrap();
+
+        assert_eq!(dataset.runs[0].total, None);
+        assert_eq!(dataset.runs[0].stage_totals, vec![None; 3]);
     }
 
     #[test]
@@ -1,17 +1,33 @@
-//! JSON export for statistics data.
+//! JSON export for statistics data, and long-format CSV export of the scores.
 
-use super::statistics::DataSetStats;
+use super::csv_reader::DataSet;
+use super::format::{format_thousands, format_thousands_f64};
+use super::statistics::{ColumnStats, DataSetStats};
 use anyhow::{Context, Result};
+use serde_json::{json, Value};
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
 
 /// Export statistics to a JSON file.
 ///
-/// The output is pretty-printed for human readability.
-pub fn export_to_json(stats: &DataSetStats, output_path: &Path) -> Result<()> {
+/// The output is pretty-printed for human readability. Each column also gets a
+/// `formatted` object holding its score-valued stats as strings grouped with
+/// `thousands_separator` (e.g. `"168,009"`); the raw numbers are kept as-is.
+pub fn export_to_json(
+    stats: &DataSetStats,
+    output_path: &Path,
+    thousands_separator: &str,
+) -> Result<()> {
+    let mut value =
+        serde_json::to_value(stats).context("Failed to serialize statistics to JSON")?;
+    if let Some(columns) = value.get_mut("columns").and_then(Value::as_array_mut) {
+        for (column, column_stats) in columns.iter_mut().zip(&stats.columns) {
+            column["formatted"] = formatted_column(column_stats, thousands_separator);
+        }
+    }
     let json =
-        serde_json::to_string_pretty(stats).context("Failed to serialize statistics to JSON")?;
+        serde_json::to_string_pretty(&value).context("Failed to serialize statistics to JSON")?;
 
     let mut file = File::create(output_path)
         .context(format!("Failed to create JSON file: {}", output_path.display()))?;
@@ -22,6 +38,45 @@ pub fn export_to_json(stats: &DataSetStats, output_path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Export every score as one row of a tidy long-format CSV.
+///
+/// Columns are `iteration,stage,criterion,score` (stage and criterion
+/// 1-based), one row per run × grid cell, so tools like pandas or R can group
+/// by stage/criterion without pivoting the wide `sNcM` layout first.
+pub fn export_long_csv(data: &DataSet, output_path: &Path) -> Result<()> {
+    let mut out = String::from("iteration,stage,criterion,score\n");
+    for run in &data.runs {
+        for (stage, row) in run.scores.iter().enumerate() {
+            for (criterion, score) in row.iter().enumerate() {
+                out.push_str(&format!("{},{},{},{}\n", run.iteration, stage + 1, criterion + 1, score));
+            }
+        }
+    }
+
+    let mut file = File::create(output_path)
+        .context(format!("Failed to create CSV file: {}", output_path.display()))?;
+
+    file.write_all(out.as_bytes())
+        .context("Failed to write long-format CSV data")?;
+
+    Ok(())
+}
+
+/// Thousands-separated strings for one column's score-valued statistics.
+fn formatted_column(stats: &ColumnStats, sep: &str) -> Value {
+    json!({
+        "mean": format_thousands_f64(stats.mean, sep),
+        "median": format_thousands_f64(stats.median, sep),
+        "mode": format_thousands(stats.mode as i64, sep),
+        "min": format_thousands(stats.min as i64, sep),
+        "max": format_thousands(stats.max as i64, sep),
+        "std_dev": format_thousands_f64(stats.std_dev, sep),
+        "range": format_thousands(stats.range as i64, sep),
+        "quartile_1": format_thousands_f64(stats.quartile_1, sep),
+        "quartile_3": format_thousands_f64(stats.quartile_3, sep),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -35,6 +90,7 @@ mod tests {
             columns: vec![ColumnStats {
                 stage: 1,
                 criterion: 1,
+                label: "Vocal".to_string(),
                 count: 5,
                 mean: 100.0,
                 median: 100.0,
@@ -42,19 +98,83 @@ mod tests {
                 min: 90,
                 max: 110,
                 std_dev: 5.0,
+                cv: 0.05,
+                range: 20,
                 quartile_1: 95.0,
                 quartile_3: 105.0,
+                percentiles: vec![],
             }],
         };
 
         let dir = tempdir().unwrap();
         let path = dir.path().join("stats.json");
 
-        export_to_json(&stats, &path).unwrap();
+        export_to_json(&stats, &path, ",").unwrap();
 
         let content = std::fs::read_to_string(&path).unwrap();
         assert!(content.contains("\"total_runs\": 5"));
         assert!(content.contains("\"mean\": 100.0"));
         assert!(content.contains("\"stage\": 1"));
+        assert!(content.contains("\"label\": \"Vocal\""));
+        assert!(content.contains("\"mean\": \"100\""));
+    }
+
+    #[test]
+    fn test_export_long_csv() {
+        let dir = tempdir().unwrap();
+        let wide = dir.path().join("results.csv");
+        std::fs::write(
+            &wide,
+            "iteration,timestamp,screenshot,s1c1,s1c2,s2c1,s2c2\n\
+             1,2026-01-01T00:00:00,a.png,10,11,20,21\n\
+             2,2026-01-01T00:01:00,b.png,12,13,22,23\n",
+        )
+        .unwrap();
+        let data = DataSet::from_csv(&wide).unwrap();
+
+        let path = dir.path().join("results_long.csv");
+        export_long_csv(&data, &path).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines[0], "iteration,stage,criterion,score");
+        assert_eq!(lines.len(), 1 + 2 * 4);
+        assert_eq!(lines[1], "1,1,1,10");
+        assert_eq!(lines[4], "1,2,2,21");
+        assert_eq!(lines[7], "2,2,1,22");
+    }
+
+    #[test]
+    fn test_export_formatted_uses_separator() {
+        let column = ColumnStats {
+            stage: 2,
+            criterion: 3,
+            label: "S2C3".to_string(),
+            count: 2,
+            mean: 1_168_009.4,
+            median: 1_168_009.0,
+            mode: 1_168_009,
+            min: 168_009,
+            max: 2_168_009,
+            std_dev: 12_345.0,
+            cv: 0.01,
+            range: 2_000_000,
+            quartile_1: 668_009.0,
+            quartile_3: 1_668_009.0,
+            percentiles: vec![],
+        };
+        let stats = DataSetStats { total_runs: 2, columns: vec![column] };
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("stats.json");
+        export_to_json(&stats, &path, ".").unwrap();
+
+        let value: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let formatted = &value["columns"][0]["formatted"];
+        assert_eq!(formatted["min"], "168.009");
+        assert_eq!(formatted["mean"], "1.168.009");
+        assert_eq!(formatted["range"], "2.000.000");
+        // Raw numbers are kept alongside.
+        assert_eq!(value["columns"][0]["min"], 168_009);
     }
 }
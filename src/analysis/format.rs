@@ -0,0 +1,49 @@
+//! Human-readable number formatting for chart labels and statistics.json.
+
+/// Formats an integer with `separator` between groups of three digits
+/// (e.g. `168009` with `","` -> `"168,009"`). An empty separator disables
+/// grouping.
+pub fn format_thousands(value: i64, separator: &str) -> String {
+    let digits = value.unsigned_abs().to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3 * separator.len() + 1);
+    if value < 0 {
+        out.push('-');
+    }
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push_str(separator);
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// `format_thousands` for a float statistic, rounded to the nearest integer
+/// (scores are whole numbers, so a fractional part is just noise in a label).
+pub fn format_thousands_f64(value: f64, separator: &str) -> String {
+    format_thousands(value.round() as i64, separator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_thousands_edge_cases() {
+        assert_eq!(format_thousands(0, ","), "0");
+        assert_eq!(format_thousands(7, ","), "7");
+        assert_eq!(format_thousands(999, ","), "999");
+        assert_eq!(format_thousands(1000, ","), "1,000");
+        assert_eq!(format_thousands(168009, ","), "168,009");
+        assert_eq!(format_thousands(1_234_567, ","), "1,234,567");
+        assert_eq!(format_thousands(12_345_678, "."), "12.345.678");
+        assert_eq!(format_thousands(-1_500, ","), "-1,500");
+        assert_eq!(format_thousands(1_234_567, ""), "1234567");
+    }
+
+    #[test]
+    fn test_format_thousands_f64_rounds() {
+        assert_eq!(format_thousands_f64(168008.6, ","), "168,009");
+        assert_eq!(format_thousands_f64(0.4, ","), "0");
+    }
+}
@@ -5,39 +5,44 @@
 //! - Statistics calculation (mean, median, mode, std_dev, quartiles)
 //! - Per-character charts with box plot, histogram, and statistics table
 //! - JSON export of statistics
+//! - Long-format CSV export of the scores for external tools
+//! - Plain-text run summary (summary.txt)
+//! - Comparison of two sessions (comparison.json / comparison.png)
 //! - Configurable chart styling via chart_config.json
 
 pub mod charts;
+pub mod compare;
 pub mod config;
 pub mod csv_reader;
 pub mod export;
+pub mod format;
 pub mod statistics;
+pub mod summary;
 
 pub use config::ChartConfig;
 pub use csv_reader::DataSet;
 pub use statistics::DataSetStats;
 
 use anyhow::{anyhow, Result};
+use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
 
 /// Runs the full analysis pipeline for a session folder.
 ///
 /// Reads results.csv from the session folder, generates charts in a charts/ subfolder,
-/// and exports statistics.json to the session folder.
+/// and exports statistics.json, a plain-text summary.txt and a long-format
+/// results_long.csv (`iteration,stage,criterion,score`) to the session folder.
+/// With `iterations`, only runs in that range are analyzed and exported, into
+/// the session's `iterations_<first>-<last>/` subfolder (same layout), so the
+/// full-session outputs are left as they are.
 ///
 /// Returns (chart_paths, json_path) where chart_paths contains per-column PNGs plus combined box plot.
-pub fn generate_analysis_for_session(session_dir: &Path) -> Result<(Vec<PathBuf>, PathBuf)> {
+pub fn generate_analysis_for_session(
+    session_dir: &Path,
+    iterations: Option<&RangeInclusive<u32>>,
+) -> Result<(Vec<PathBuf>, PathBuf)> {
     let csv_path = session_dir.join("results.csv");
-    let charts_dir = session_dir.join("charts");
-    let json_path = session_dir.join("statistics.json");
-    let combined_chart_path = charts_dir.join("chart_combined.png");
-    let config_path = crate::paths::get_exe_dir().join("chart_config.json");
-
-    // Create charts directory if it doesn't exist
-    if !charts_dir.exists() {
-        std::fs::create_dir_all(&charts_dir)?;
-        crate::log(&format!("Created charts directory: {}", crate::paths::relative_display(&charts_dir)));
-    }
+    let config_path = crate::paths::resolve_data_file("chart_config.json");
 
     // Load chart config (creates default if not exists)
     let config = config::ChartConfig::load(&config_path);
@@ -55,43 +60,160 @@ pub fn generate_analysis_for_session(session_dir: &Path) -> Result<(Vec<PathBuf>
     }
 
     // Load data
-    let data = csv_reader::DataSet::from_csv(&csv_path)?;
+    let data = csv_reader::DataSet::from_csv_nonempty(&csv_path)?;
+
+    crate::log(&format!("Loaded {} runs from CSV", data.len()));
+    let data = match iterations {
+        Some(range) => select_iterations(&data, range)?,
+        None => data,
+    };
+
+    let output_dir = range_output_dir(session_dir, iterations);
+    std::fs::create_dir_all(&output_dir)?;
+
+    let long_csv_path = output_dir.join("results_long.csv");
+    export::export_long_csv(&data, &long_csv_path)?;
+    crate::log(&format!("Long-format CSV saved: {}", crate::paths::relative_display(&long_csv_path)));
+
+    generate_analysis_from_dataset(&data, &output_dir, &config, None)
+}
+
+/// Folder the analysis of `iterations` is written to: `dir` itself for the
+/// full data, else its `iterations_<first>-<last>` subfolder (`<last>` is
+/// `end` for an open range).
+fn range_output_dir(dir: &Path, iterations: Option<&RangeInclusive<u32>>) -> PathBuf {
+    match iterations {
+        Some(range) => {
+            let last = match *range.end() {
+                u32::MAX => "end".to_string(),
+                last => last.to_string(),
+            };
+            dir.join(format!("iterations_{}-{}", range.start(), last))
+        }
+        None => dir.to_path_buf(),
+    }
+}
+
+/// Runs statistics, charts and JSON export on an already-loaded data set.
+///
+/// Writes the same layout as [`generate_analysis_for_session`]: charts in an
+/// `output_dir/charts/` subfolder, `output_dir/statistics.json` and
+/// `output_dir/summary.txt`. No CSV or
+/// chart config is read, so callers (and tests) can pass synthetic data.
+/// With `iterations`, only runs in that range are analyzed.
+///
+/// Returns (chart_paths, json_path) like [`generate_analysis_for_session`].
+pub fn generate_analysis_from_dataset(
+    data: &DataSet,
+    output_dir: &Path,
+    config: &ChartConfig,
+    iterations: Option<&RangeInclusive<u32>>,
+) -> Result<(Vec<PathBuf>, PathBuf)> {
     if data.is_empty() {
-        return Err(anyhow!("No data in CSV file"));
+        return Err(anyhow!("No runs to analyze"));
     }
+    let filtered;
+    let data = match iterations {
+        Some(range) => {
+            filtered = select_iterations(data, range)?;
+            &filtered
+        }
+        None => data,
+    };
 
-    crate::log(&format!("Loaded {} runs from CSV", data.len()));
+    let charts_dir = output_dir.join("charts");
+    let json_path = output_dir.join("statistics.json");
+    let combined_chart_path = charts_dir.join("chart_combined.png");
+
+    // Create charts directory if it doesn't exist
+    if !charts_dir.exists() {
+        std::fs::create_dir_all(&charts_dir)?;
+        crate::log(&format!("Created charts directory: {}", crate::paths::relative_display(&charts_dir)));
+    }
 
     // Calculate statistics
-    let stats = statistics::DataSetStats::from_dataset(&data);
+    let percentiles = config.statistics.effective_percentiles();
+    let stats = statistics::DataSetStats::from_dataset(data, &percentiles)
+        .with_column_labels(&config.column_labels);
 
-    // Generate per-column charts (9 charts total)
-    let mut chart_paths = charts::generate_all_charts(&data, &stats, &charts_dir, &config)?;
+    // Generate per-column charts (one per stage/criterion column)
+    let mut chart_paths = charts::generate_all_charts(data, &stats, &charts_dir, config)?;
     crate::log(&format!("Generated {} per-column charts", chart_paths.len()));
 
     // Generate combined box plot
-    charts::generate_combined_box_plot(&stats, &combined_chart_path, &config)?;
+    charts::generate_combined_box_plot(&stats, &combined_chart_path, config)?;
     chart_paths.push(combined_chart_path.clone());
     crate::log(&format!(
         "Generated combined box plot: {}",
         crate::paths::relative_display(&combined_chart_path)
     ));
 
+    // Generate the one-image overview
+    if config.generate_dashboard {
+        let dashboard_path = charts_dir.join("chart_dashboard.png");
+        charts::generate_dashboard(data, &stats, &dashboard_path, config)?;
+        chart_paths.push(dashboard_path.clone());
+        crate::log(&format!(
+            "Generated dashboard: {}",
+            crate::paths::relative_display(&dashboard_path)
+        ));
+    }
+
     // Export JSON
-    export::export_to_json(&stats, &json_path)?;
+    export::export_to_json(&stats, &json_path, &config.number_format.thousands_separator)?;
     crate::log(&format!("Statistics JSON saved: {}", crate::paths::relative_display(&json_path)));
 
+    // Human-readable headline numbers and warnings
+    let summary_path = output_dir.join("summary.txt");
+    summary::write_summary(data, &stats, &summary_path, &config.number_format.thousands_separator)?;
+    crate::log(&format!("Summary saved: {}", crate::paths::relative_display(&summary_path)));
+
     Ok((chart_paths, json_path))
 }
 
+/// Keeps the runs whose iteration is in `range`; an error (rather than empty
+/// charts) when none are.
+fn select_iterations(data: &DataSet, range: &RangeInclusive<u32>) -> Result<DataSet> {
+    let filtered = data.filter_iterations(range);
+    if filtered.is_empty() {
+        return Err(anyhow!(
+            "No runs in iterations {}-{} ({} runs loaded, iterations {})",
+            range.start(),
+            range.end(),
+            data.len(),
+            iteration_span(data)
+        ));
+    }
+    crate::log(&format!(
+        "Analyzing iterations {}-{}: {} of {} runs",
+        range.start(),
+        range.end(),
+        filtered.len(),
+        data.len()
+    ));
+    Ok(filtered)
+}
+
+/// "first-last" iteration numbers present in `data`, for messages.
+fn iteration_span(data: &DataSet) -> String {
+    let first = data.runs.iter().map(|r| r.iteration).min();
+    let last = data.runs.iter().map(|r| r.iteration).max();
+    match (first, last) {
+        (Some(first), Some(last)) => format!("{}-{}", first, last),
+        _ => "none".to_string(),
+    }
+}
+
 /// Runs the full analysis pipeline using the current session folder.
 ///
 /// This is a convenience function that gets the session path from the runner.
 /// Falls back to legacy behavior if no session is active.
-pub fn generate_analysis() -> Result<(Vec<PathBuf>, PathBuf)> {
+pub fn generate_analysis(
+    iterations: Option<&RangeInclusive<u32>>,
+) -> Result<(Vec<PathBuf>, PathBuf)> {
     // Try to use current session path from runner
     if let Some(session_dir) = crate::automation::runner::get_current_session_path() {
-        return generate_analysis_for_session(&session_dir);
+        return generate_analysis_for_session(&session_dir, iterations);
     }
 
     // Fallback: legacy behavior for backward compatibility
@@ -103,7 +225,7 @@ pub fn generate_analysis() -> Result<(Vec<PathBuf>, PathBuf)> {
     }
 
     // Use output/ directory for legacy mode
-    let output_dir = exe_dir.join("output");
+    let output_dir = range_output_dir(&exe_dir.join("output"), iterations);
     let json_path = output_dir.join("statistics.json");
     let combined_chart_path = output_dir.join("chart_combined.png");
     let config_path = exe_dir.join("chart_config.json");
@@ -124,15 +246,18 @@ pub fn generate_analysis() -> Result<(Vec<PathBuf>, PathBuf)> {
     }
 
     // Load data
-    let data = csv_reader::DataSet::from_csv(&csv_path)?;
-    if data.is_empty() {
-        return Err(anyhow!("No data in CSV file"));
-    }
+    let data = csv_reader::DataSet::from_csv_nonempty(&csv_path)?;
 
     crate::log(&format!("Loaded {} runs from CSV (legacy mode)", data.len()));
+    let data = match iterations {
+        Some(range) => select_iterations(&data, range)?,
+        None => data,
+    };
 
     // Calculate statistics
-    let stats = statistics::DataSetStats::from_dataset(&data);
+    let percentiles = config.statistics.effective_percentiles();
+    let stats = statistics::DataSetStats::from_dataset(&data, &percentiles)
+        .with_column_labels(&config.column_labels);
 
     // Generate charts
     let mut chart_paths = charts::generate_all_charts(&data, &stats, &output_dir, &config)?;
@@ -140,7 +265,90 @@ pub fn generate_analysis() -> Result<(Vec<PathBuf>, PathBuf)> {
     chart_paths.push(combined_chart_path.clone());
 
     // Export JSON
-    export::export_to_json(&stats, &json_path)?;
+    export::export_to_json(&stats, &json_path, &config.number_format.thousands_separator)?;
 
     Ok((chart_paths, json_path))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use csv_reader::RunData;
+    use tempfile::tempdir;
+
+    fn synthetic_dataset(n: u32) -> DataSet {
+        let runs = (1..=n)
+            .map(|i| RunData {
+                iteration: i,
+                timestamp: String::new(),
+                screenshot_path: String::new(),
+                scores: (0..3)
+                    .map(|stage| (0..3).map(|c| 10_000 * (stage + 1) + 100 * c + 37 * i).collect())
+                    .collect(),
+                total: None,
+                stage_totals: vec![None; 3],
+                recovery: None,
+            })
+            .collect();
+        DataSet { runs, stages: 3, criteria: 3 }
+    }
+
+    #[test]
+    fn test_generate_analysis_from_dataset_writes_outputs() {
+        let dir = tempdir().unwrap();
+        let config = ChartConfig { generate_dashboard: true, ..Default::default() };
+
+        let (chart_paths, json_path) =
+            generate_analysis_from_dataset(&synthetic_dataset(20), dir.path(), &config, None).unwrap();
+
+        // 9 per-column charts + combined box plot + dashboard
+        assert_eq!(chart_paths.len(), 11);
+        assert!(chart_paths.iter().all(|p| p.exists() && p.starts_with(dir.path().join("charts"))));
+        assert_eq!(json_path, dir.path().join("statistics.json"));
+        assert!(json_path.exists());
+        assert!(dir.path().join("summary.txt").exists());
+    }
+
+    #[test]
+    fn test_generate_analysis_from_dataset_skips_dashboard_by_default() {
+        let dir = tempdir().unwrap();
+
+        let (chart_paths, _) =
+            generate_analysis_from_dataset(&synthetic_dataset(20), dir.path(), &ChartConfig::default(), None)
+                .unwrap();
+
+        // 9 per-column charts + combined box plot
+        assert_eq!(chart_paths.len(), 10);
+        assert!(chart_paths.iter().all(|p| !p.to_string_lossy().contains("dashboard")));
+        assert!(!dir.path().join("charts").join("chart_dashboard.png").exists());
+    }
+
+    #[test]
+    fn test_range_output_dir() {
+        let dir = Path::new("session");
+        assert_eq!(range_output_dir(dir, None), dir);
+        assert_eq!(range_output_dir(dir, Some(&(100..=500))), dir.join("iterations_100-500"));
+        assert_eq!(range_output_dir(dir, Some(&(100..=u32::MAX))), dir.join("iterations_100-end"));
+    }
+
+    #[test]
+    fn test_generate_analysis_from_dataset_rejects_empty() {
+        let dir = tempdir().unwrap();
+        let empty = DataSet { runs: vec![], stages: 3, criteria: 3 };
+        assert!(generate_analysis_from_dataset(&empty, dir.path(), &ChartConfig::default(), None).is_err());
+    }
+
+    #[test]
+    fn test_generate_analysis_from_dataset_rejects_empty_range() {
+        let dir = tempdir().unwrap();
+        let err = generate_analysis_from_dataset(
+            &synthetic_dataset(20),
+            dir.path(),
+            &ChartConfig::default(),
+            Some(&(100..=500)),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("iterations 1-20"), "{}", err);
+        assert!(!dir.path().join("statistics.json").exists());
+    }
+}
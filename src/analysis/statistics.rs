@@ -4,15 +4,18 @@
 
 use super::csv_reader::DataSet;
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Statistics for one score column (one stage/criterion combination).
 #[derive(Debug, Clone, Serialize)]
 pub struct ColumnStats {
-    /// Stage number (1, 2, or 3)
+    /// Stage number (1-based)
     pub stage: usize,
-    /// Criterion number (1, 2, or 3)
+    /// Criterion number (1-based)
     pub criterion: usize,
+    /// Name used for chart titles, file names and axis labels: the
+    /// `column_labels` entry for this column, else its code ("S1C1")
+    pub label: String,
     /// Number of values
     pub count: usize,
     /// Arithmetic mean (average)
@@ -27,10 +30,40 @@ pub struct ColumnStats {
     pub max: u32,
     /// Standard deviation (population)
     pub std_dev: f64,
+    /// Coefficient of variation (std_dev / mean); 0 when the mean is 0
+    pub cv: f64,
+    /// Range (max - min)
+    pub range: u32,
     /// First quartile (25th percentile)
     pub quartile_1: f64,
     /// Third quartile (75th percentile)
     pub quartile_3: f64,
+    /// Configured percentiles (`chart_config.json` → statistics.percentiles)
+    pub percentiles: Vec<Percentile>,
+}
+
+/// One computed percentile of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Percentile {
+    /// Percentile rank, 0-100
+    pub p: f64,
+    /// Interpolated value at that rank
+    pub value: f64,
+}
+
+/// Percentiles computed when no configuration is given (Q1/Q3).
+const DEFAULT_PERCENTILES: [f64; 2] = [25.0, 75.0];
+
+impl ColumnStats {
+    /// Value of percentile `p`, if it was computed for this column.
+    pub fn percentile(&self, p: f64) -> Option<f64> {
+        self.percentiles.iter().find(|x| x.p == p).map(|x| x.value)
+    }
+}
+
+/// Grid code of a column ("S1C1"), the key `column_labels` is looked up by.
+pub fn column_code(stage: usize, criterion: usize) -> String {
+    format!("S{}C{}", stage, criterion)
 }
 
 /// Statistics for the entire dataset.
@@ -38,19 +71,21 @@ pub struct ColumnStats {
 pub struct DataSetStats {
     /// Total number of runs
     pub total_runs: usize,
-    /// Statistics for each of the 9 columns
+    /// Statistics for each column, stage-major (S1C1, S1C2, …)
     pub columns: Vec<ColumnStats>,
 }
 
 impl DataSetStats {
-    /// Calculate statistics for all columns in the dataset.
-    pub fn from_dataset(data: &DataSet) -> Self {
-        let mut columns = Vec::with_capacity(9);
+    /// Calculate statistics for all columns in the dataset, including
+    /// `percentiles` (0-100) for every column.
+    pub fn from_dataset(data: &DataSet, percentiles: &[f64]) -> Self {
+        let mut columns = Vec::with_capacity(data.stages * data.criteria);
 
-        for stage in 0..3 {
-            for criterion in 0..3 {
+        for stage in 0..data.stages {
+            for criterion in 0..data.criteria {
                 let values = data.column_values(stage, criterion);
-                let stats = calculate_column_stats(&values, stage + 1, criterion + 1);
+                let stats =
+                    calculate_column_stats_with(&values, stage + 1, criterion + 1, percentiles);
                 columns.push(stats);
             }
         }
@@ -63,8 +98,9 @@ impl DataSetStats {
 
     /// Calculate statistics from raw score rows (`[stage][slot]`), e.g. the live
     /// in-run buffer. Mirrors `from_dataset` but takes owned rows instead of a
-    /// `DataSet`/CSV. An empty `rows` slice yields nine zeroed columns (so an
-    /// early-run figure renders flat boxes at 0 rather than panicking).
+    /// `DataSet`/CSV. Rows come from the OCR readout, which is always 3×3. An
+    /// empty `rows` slice yields nine zeroed columns (so an early-run figure
+    /// renders flat boxes at 0 rather than panicking).
     pub fn from_score_rows(rows: &[[[u32; 3]; 3]]) -> Self {
         let mut columns = Vec::with_capacity(9);
 
@@ -81,14 +117,50 @@ impl DataSetStats {
             columns,
         }
     }
+
+    /// Grid dimensions `(stages, criteria)` spanned by `columns`.
+    pub fn grid(&self) -> (usize, usize) {
+        let stages = self.columns.iter().map(|c| c.stage).max().unwrap_or(0);
+        let criteria = self.columns.iter().map(|c| c.criterion).max().unwrap_or(0);
+        (stages, criteria)
+    }
+
+    /// Replaces column codes with the names given in `labels` (keyed by code,
+    /// e.g. `"S1C1": "Vocal 1"`); columns without an entry keep their code.
+    /// Keys that match no column are logged and ignored.
+    pub fn with_column_labels(mut self, labels: &BTreeMap<String, String>) -> Self {
+        for (code, name) in labels {
+            let column = self
+                .columns
+                .iter_mut()
+                .find(|c| column_code(c.stage, c.criterion).eq_ignore_ascii_case(code));
+            match column {
+                Some(column) if !name.trim().is_empty() => column.label = name.trim().to_string(),
+                Some(_) => {}
+                None => crate::log(&format!("Ignoring column label for unknown column {}", code)),
+            }
+        }
+        self
+    }
 }
 
 /// Calculate statistics for a single column of values.
 fn calculate_column_stats(values: &[u32], stage: usize, criterion: usize) -> ColumnStats {
+    calculate_column_stats_with(values, stage, criterion, &DEFAULT_PERCENTILES)
+}
+
+/// Calculate statistics for a single column, including the given percentiles.
+fn calculate_column_stats_with(
+    values: &[u32],
+    stage: usize,
+    criterion: usize,
+    percentiles: &[f64],
+) -> ColumnStats {
     if values.is_empty() {
         return ColumnStats {
             stage,
             criterion,
+            label: column_code(stage, criterion),
             count: 0,
             mean: 0.0,
             median: 0.0,
@@ -96,8 +168,11 @@ fn calculate_column_stats(values: &[u32], stage: usize, criterion: usize) -> Col
             min: 0,
             max: 0,
             std_dev: 0.0,
+            cv: 0.0,
+            range: 0,
             quartile_1: 0.0,
             quartile_3: 0.0,
+            percentiles: percentiles.iter().map(|&p| Percentile { p, value: 0.0 }).collect(),
         };
     }
 
@@ -135,9 +210,13 @@ fn calculate_column_stats(values: &[u32], stage: usize, criterion: usize) -> Col
         / count as f64;
     let std_dev = variance.sqrt();
 
+    // Relative spread, comparable across idols with different score scales
+    let cv = if mean > 0.0 { std_dev / mean } else { 0.0 };
+
     ColumnStats {
         stage,
         criterion,
+        label: column_code(stage, criterion),
         count,
         mean,
         median,
@@ -145,8 +224,14 @@ fn calculate_column_stats(values: &[u32], stage: usize, criterion: usize) -> Col
         min,
         max,
         std_dev,
+        cv,
+        range: max - min,
         quartile_1,
         quartile_3,
+        percentiles: percentiles
+            .iter()
+            .map(|&p| Percentile { p, value: calculate_percentile(&sorted, p) })
+            .collect(),
     }
 }
 
@@ -250,6 +335,33 @@ mod tests {
         assert_eq!(c22.max, 940);
     }
 
+    #[test]
+    fn grid_and_labels_follow_columns() {
+        let stats = DataSetStats {
+            total_runs: 0,
+            columns: vec![
+                calculate_column_stats(&[], 1, 1),
+                calculate_column_stats(&[], 1, 2),
+                calculate_column_stats(&[], 2, 1),
+                calculate_column_stats(&[], 2, 2),
+            ],
+        };
+        assert_eq!(stats.grid(), (2, 2));
+        assert_eq!(stats.columns[2].label, "S2C1");
+    }
+
+    #[test]
+    fn column_labels_replace_codes_and_fall_back() {
+        let labels = BTreeMap::from([
+            ("S1C1".to_string(), "Vocal".to_string()),
+            ("s1c3".to_string(), " Visual ".to_string()),
+            ("S9C9".to_string(), "Nowhere".to_string()),
+        ]);
+        let stats = DataSetStats::from_score_rows(&[]).with_column_labels(&labels);
+        let names: Vec<&str> = stats.columns.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(names[..4], ["Vocal", "S1C2", "Visual", "S2C1"]);
+    }
+
     #[test]
     fn from_score_rows_empty_is_zeroed() {
         let stats = DataSetStats::from_score_rows(&[]);
@@ -258,6 +370,34 @@ mod tests {
         assert!(stats.columns.iter().all(|c| c.count == 0 && c.max == 0));
     }
 
+    #[test]
+    fn test_cv_and_range() {
+        let values = vec![1, 2, 3, 4, 5];
+        let stats = calculate_column_stats(&values, 1, 1);
+        // Population std_dev of 1..=5 is sqrt(2); mean is 3.
+        assert!((stats.cv - 2f64.sqrt() / 3.0).abs() < 0.0001);
+        assert_eq!(stats.range, 4);
+
+        let zeros = calculate_column_stats(&[0, 0, 0], 1, 1);
+        assert_eq!(zeros.cv, 0.0, "Zero mean must not divide by zero");
+        assert_eq!(zeros.range, 0);
+    }
+
+    #[test]
+    fn test_configured_percentiles() {
+        let values: Vec<u32> = (0..=100).collect();
+        let stats = calculate_column_stats_with(&values, 1, 1, &[10.0, 90.0, 95.0]);
+        assert_eq!(stats.percentile(10.0), Some(10.0));
+        assert_eq!(stats.percentile(90.0), Some(90.0));
+        assert_eq!(stats.percentile(95.0), Some(95.0));
+        assert_eq!(stats.percentile(50.0), None);
+
+        // Default set mirrors the quartile fields.
+        let stats = calculate_column_stats(&values, 1, 1);
+        assert_eq!(stats.percentile(25.0), Some(stats.quartile_1));
+        assert_eq!(stats.percentile(75.0), Some(stats.quartile_3));
+    }
+
     #[test]
     fn test_median_odd() {
         let values = vec![1, 2, 3, 4, 5];
@@ -0,0 +1,235 @@
+//! Plain-text run summary (`summary.txt`).
+//!
+//! The headline numbers of a session (run count, elapsed time, per-column
+//! mean/median/min/max) plus the rows worth a second look, so an overnight
+//! session can be checked without opening statistics.json.
+
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
+use std::path::Path;
+
+use super::csv_reader::DataSet;
+use super::format::{format_thousands, format_thousands_f64};
+use super::statistics::DataSetStats;
+
+/// Timestamp format of the `timestamp` column in results.csv.
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// Writes the summary of `data` / `stats` to `path`. Numbers are grouped with
+/// `separator` like statistics.json.
+pub fn write_summary(
+    data: &DataSet,
+    stats: &DataSetStats,
+    path: &Path,
+    separator: &str,
+) -> Result<()> {
+    std::fs::write(path, format_summary(data, stats, separator))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Builds the text of `summary.txt`.
+fn format_summary(data: &DataSet, stats: &DataSetStats, separator: &str) -> String {
+    let mut out = String::new();
+    out.push_str("Rehearsal summary\n=================\n\n");
+
+    out.push_str(&format!("Runs:     {}\n", stats.total_runs));
+    out.push_str(&format!("Elapsed:  {}\n\n", elapsed_line(data)));
+
+    // Wide enough for configured column names, at least "Column" + gap
+    let name_width = stats
+        .columns
+        .iter()
+        .map(|c| c.label.chars().count() + 2)
+        .max()
+        .unwrap_or(0)
+        .max(8);
+    out.push_str(&format!(
+        "{:<name_width$}{:>14}{:>14}{:>14}{:>14}\n",
+        "Column", "Mean", "Median", "Min", "Max"
+    ));
+    for col in &stats.columns {
+        out.push_str(&format!(
+            "{:<name_width$}{:>14}{:>14}{:>14}{:>14}\n",
+            col.label,
+            format_thousands_f64(col.mean, separator),
+            format_thousands_f64(col.median, separator),
+            format_thousands(col.min as i64, separator),
+            format_thousands(col.max as i64, separator),
+        ));
+    }
+
+    out.push_str("\nWarnings\n--------\n");
+    let warnings = warnings(data, separator);
+    if warnings.is_empty() {
+        out.push_str("None\n");
+    }
+    for warning in warnings {
+        out.push_str(&format!("- {}\n", warning));
+    }
+    out
+}
+
+/// Time between the first and last result timestamps, or "unknown" when no
+/// two rows have distinct readable timestamps.
+fn elapsed_line(data: &DataSet) -> String {
+    let times: Vec<NaiveDateTime> = data
+        .runs
+        .iter()
+        .filter_map(|run| NaiveDateTime::parse_from_str(&run.timestamp, TIMESTAMP_FORMAT).ok())
+        .collect();
+    let (Some(first), Some(last)) = (times.iter().min(), times.iter().max()) else {
+        return "unknown".to_string();
+    };
+    if first == last {
+        return "unknown".to_string();
+    }
+    format!(
+        "{} ({} to {})",
+        format_elapsed((*last - *first).num_seconds()),
+        first.format("%Y-%m-%d %H:%M:%S"),
+        last.format("%Y-%m-%d %H:%M:%S")
+    )
+}
+
+/// Formats a duration in seconds as e.g. `2h 03m 15s` (hours only when nonzero).
+fn format_elapsed(seconds: i64) -> String {
+    let seconds = seconds.max(0);
+    let (h, m, s) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if h > 0 {
+        format!("{}h {:02}m {:02}s", h, m, s)
+    } else {
+        format!("{}m {:02}s", m, s)
+    }
+}
+
+/// One line per kind of problem found: rows flagged for review, total columns
+/// that disagree with their scores (`RunData::total_mismatches`), and runs whose scores repeat the
+/// previous run exactly (a result screen captured twice).
+fn warnings(data: &DataSet, separator: &str) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    let flagged: Vec<u32> = data
+        .runs
+        .iter()
+        .filter(|run| run.recovery.as_deref() == Some("flagged"))
+        .map(|run| run.iteration)
+        .collect();
+    if !flagged.is_empty() {
+        warnings.push(format!(
+            "{} row(s) flagged for review (low-confidence OCR): iterations {}",
+            flagged.len(),
+            join_iterations(&flagged)
+        ));
+    }
+
+    let mut mismatches = Vec::new();
+    for run in &data.runs {
+        for mismatch in run.total_mismatches() {
+            let column = match mismatch.stage {
+                Some(stage) => format!("S{}", stage + 1),
+                None => "grand".to_string(),
+            };
+            mismatches.push(format!(
+                "{} {} (total {}, scores give {})",
+                run.iteration,
+                column,
+                format_thousands(mismatch.recorded as i64, separator),
+                format_thousands(mismatch.expected as i64, separator)
+            ));
+        }
+    }
+    if !mismatches.is_empty() {
+        warnings.push(format!(
+            "{} total mismatch(es): iterations {}",
+            mismatches.len(),
+            mismatches.join(", ")
+        ));
+    }
+
+    let repeated: Vec<u32> = data
+        .runs
+        .windows(2)
+        .filter(|pair| pair[0].scores == pair[1].scores)
+        .map(|pair| pair[1].iteration)
+        .collect();
+    if !repeated.is_empty() {
+        warnings.push(format!(
+            "{} run(s) repeat the previous run's scores exactly (possible duplicate capture): \
+             iterations {}",
+            repeated.len(),
+            join_iterations(&repeated)
+        ));
+    }
+
+    warnings
+}
+
+fn join_iterations(iterations: &[u32]) -> String {
+    iterations.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::csv_reader::RunData;
+    use crate::ocr::reconcile::expected_stage_total;
+
+    fn run(iteration: u32, timestamp: &str, scores: [u32; 3], recovery: &str) -> RunData {
+        RunData {
+            iteration,
+            timestamp: timestamp.to_string(),
+            screenshot_path: String::new(),
+            scores: vec![scores.to_vec()],
+            total: None,
+            stage_totals: vec![Some(expected_stage_total(&scores) as u32)],
+            recovery: Some(recovery.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_format_summary() {
+        let mut mismatch = run(3, "2026-06-29T02:03:15", [300, 400, 500], "repaired");
+        // 300 + 400 + 500 + 500 / 5 = 1,300 on screen; OCR read 1,350
+        mismatch.stage_totals = vec![Some(1_350)];
+        let data = DataSet {
+            runs: vec![
+                run(1, "2026-06-29T01:00:00", [100, 200, 300], "ok"),
+                run(2, "2026-06-29T01:30:00", [100, 200, 300], "flagged"),
+                mismatch,
+            ],
+            stages: 1,
+            criteria: 3,
+        };
+        let stats = DataSetStats::from_dataset(&data, &[]);
+        let text = format_summary(&data, &stats, ",");
+
+        assert!(text.contains("Runs:     3\n"));
+        assert!(text.contains("Elapsed:  1h 03m 15s (2026-06-29 01:00:00 to 2026-06-29 02:03:15)"));
+        let s1c3 = format!("{:<8}{:>14}{:>14}{:>14}{:>14}\n", "S1C3", "367", "300", "300", "500");
+        assert!(text.contains(&s1c3));
+        assert!(text.contains("1 row(s) flagged for review (low-confidence OCR): iterations 2\n"));
+        assert!(text.contains("iterations 3 S1 (total 1,350, scores give 1,300)\n"));
+        assert!(text.contains("possible duplicate capture): iterations 2\n"));
+    }
+
+    #[test]
+    fn test_format_summary_clean_run() {
+        let data = DataSet {
+            runs: vec![run(1, "", [100, 200, 300], "ok")],
+            stages: 1,
+            criteria: 3,
+        };
+        let stats = DataSetStats::from_dataset(&data, &[]);
+        let text = format_summary(&data, &stats, ",");
+
+        assert!(text.contains("Elapsed:  unknown\n"));
+        assert!(text.ends_with("Warnings\n--------\nNone\n"));
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(0), "0m 00s");
+        assert_eq!(format_elapsed(75), "1m 15s");
+        assert_eq!(format_elapsed(7_395), "2h 03m 15s");
+    }
+}
@@ -1,19 +1,23 @@
 //! Configuration types for automation.
 //!
-//! Loads settings from config.json at startup. Provides button positions,
-//! detection thresholds, and timing parameters.
+//! Loads settings from config.json at startup (and again on `reload_config`).
+//! Provides button positions, detection thresholds, and timing parameters.
 
+use anyhow::Result;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::Path;
-use std::sync::OnceLock;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
 
-/// Global configuration instance, initialized once at startup.
-static CONFIG: OnceLock<AutomationConfig> = OnceLock::new();
+/// Global configuration instance. Set at startup and replaced wholesale by
+/// `reload_config`; readers get a cheap `Arc` clone, so a run that already
+/// holds a config keeps it while new readers see the reloaded one.
+static CONFIG: RwLock<Option<Arc<AutomationConfig>>> = RwLock::new(None);
 
 /// A rectangle in relative coordinates (0.0 to 1.0).
 /// Used for defining screen regions that scale with window size.
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct RelativeRect {
     /// X position of top-left corner (0.0 = left edge, 1.0 = right edge)
     pub x: f32,
@@ -36,6 +40,28 @@ impl Default for RelativeRect {
     }
 }
 
+impl RelativeRect {
+    /// Smallest rect containing both `self` and `other`.
+    pub fn union(&self, other: &RelativeRect) -> RelativeRect {
+        let x0 = self.x.min(other.x);
+        let y0 = self.y.min(other.y);
+        let x1 = (self.x + self.width).max(other.x + other.width);
+        let y1 = (self.y + self.height).max(other.y + other.height);
+        RelativeRect { x: x0, y: y0, width: x1 - x0, height: y1 - y0 }
+    }
+
+    /// `self` re-expressed relative to `outer`, for use on an image cropped to
+    /// `outer`. Both rects are relative to the same full window.
+    pub fn relative_to(&self, outer: &RelativeRect) -> RelativeRect {
+        RelativeRect {
+            x: (self.x - outer.x) / outer.width,
+            y: (self.y - outer.y) / outer.height,
+            width: self.width / outer.width,
+            height: self.height / outer.height,
+        }
+    }
+}
+
 /// Adjustment applied on top of each `score_regions[stage]` to produce the
 /// human-review crop shown inline in the review window. All values are window
 /// fractions (0..1). One shared instance covers all three stages because the
@@ -44,7 +70,7 @@ impl Default for RelativeRect {
 /// character portraits above the digits so the user can see who/what they are
 /// correcting. Reusing `score_regions`' x/width single-sources the horizontal
 /// layout, which the game may change in a future update.
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ReviewCropAdjust {
     /// Extend the crop upward (decreasing y) to include the character portraits
     /// that sit above the printed scores.
@@ -80,13 +106,31 @@ fn default_review_crop_adjust() -> ReviewCropAdjust {
 /// and `review_crop_adjust`, clamped into `[0,1]` so the result is always a
 /// valid UV rect (an over-extension never samples outside the image; an inset
 /// wider than the region yields a zero — not negative — dimension).
+///
+/// For `ScoreRegionsOnly` captures the crop is re-expressed relative to the
+/// saved score area (and clamped to it: portraits above the area are gone).
 pub fn review_crop_rect(config: &AutomationConfig, stage: usize) -> RelativeRect {
     let s = config.score_regions[stage];
     let a = config.review_crop_adjust;
-    let x0 = (s.x + a.left_inset).clamp(0.0, 1.0);
-    let y0 = (s.y - a.top_extend).clamp(0.0, 1.0);
-    let x1 = (s.x + s.width - a.right_inset).clamp(0.0, 1.0);
-    let y1 = (s.y + s.height + a.bottom_extend).clamp(0.0, 1.0);
+    let crop = clamp_unit(RelativeRect {
+        x: s.x + a.left_inset,
+        y: s.y - a.top_extend,
+        width: s.width - a.left_inset - a.right_inset,
+        height: s.height + a.top_extend + a.bottom_extend,
+    });
+    match config.capture_mode {
+        CaptureMode::FullWindow => crop,
+        CaptureMode::ScoreRegionsOnly => clamp_unit(crop.relative_to(&config.score_area())),
+    }
+}
+
+/// Clamps `r` into `[0,1]`, shrinking it to a zero (never negative) size when
+/// it lies entirely outside.
+fn clamp_unit(r: RelativeRect) -> RelativeRect {
+    let x0 = r.x.clamp(0.0, 1.0);
+    let y0 = r.y.clamp(0.0, 1.0);
+    let x1 = (r.x + r.width).clamp(0.0, 1.0);
+    let y1 = (r.y + r.height).clamp(0.0, 1.0);
     RelativeRect {
         x: x0,
         y: y0,
@@ -95,24 +139,151 @@ pub fn review_crop_rect(config: &AutomationConfig, stage: usize) -> RelativeRect
     }
 }
 
-/// A point in relative coordinates for button centers.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// How score-row and stage-total crops are binarized before OCR.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum OcrPreprocessMode {
+    /// Fixed cutoff (`ocr_threshold` / `total_threshold`) on every RGB channel.
+    #[default]
+    Global,
+    /// Otsu's method: cutoff chosen per crop from its luminance histogram.
+    Otsu,
+    /// Local mean: each pixel is compared against the mean of its neighbourhood,
+    /// which tolerates gradients and uneven lighting behind the digits.
+    AdaptiveMean,
+    /// Sobel gradient magnitude with an Otsu cutoff: keeps the outlines of
+    /// thin anti-aliased strokes that a brightness cutoff drops (8 read as 3).
+    Edge,
+}
+
+/// Histogram metric used to match a button region against its reference image.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum DetectionMetric {
+    /// Bhattacharyya coefficient: tolerant of lighting changes, but a histogram
+    /// shifted a little along the intensity axis still scores high.
+    #[default]
+    Bhattacharyya,
+    /// Pearson correlation of the bins (negative values clamp to 0); drops
+    /// quickly when the histogram's shape is shifted.
+    Correlation,
+    /// Both metrics must reach `histogram_threshold` (the lower one is used).
+    Both,
+}
+
+/// How Tesseract is run for each OCR crop.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum OcrBackend {
+    /// A `tesseract.exe` process per crop (the bundled executable).
+    #[default]
+    Process,
+    /// One engine per OCR thread, reused across crops. Needs a build with the
+    /// `tesseract-inprocess` feature; otherwise `Process` is used.
+    InProcess,
+}
+
+/// Key that can stand in for a button click (see `AutomationConfig::end_key`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum NavigationKey {
+    Enter,
+    Escape,
+    Space,
+}
+
+/// When automation brings the game window to the foreground for its input.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum ForegroundMode {
+    /// Before every click and key press (most reliable, but focus snaps back
+    /// to the game whenever another app is used during a run).
+    #[default]
+    EachClick,
+    /// Once when the run starts. Input pauses while another app is in front
+    /// and resumes when the game is brought back, so clicks never land on
+    /// another app.
+    Once,
+    /// Never, not even at run start: the user keeps the game in front, and
+    /// input pauses as with `Once` while it isn't. (The game ignores posted
+    /// window messages, so there is no way to click it from behind.)
+    Never,
+}
+
+/// How much of each result screen automation screenshots keep.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum CaptureMode {
+    /// The whole captured frame.
+    #[default]
+    FullWindow,
+    /// Only the bounding box of the score, total and bonus regions
+    /// (`AutomationConfig::score_area`). The frame is still copied off the GPU
+    /// whole, but only this box is converted to RGBA and saved; OCR regions
+    /// are remapped into it.
+    ScoreRegionsOnly,
+}
+
+/// Pixel format capture frame pools are created with. Frames are converted
+/// to 8-bit RGBA either way; saved screenshots and OCR see the same images.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum CapturePixelFormat {
+    /// 8-bit BGRA, what every capture path has always used.
+    #[default]
+    Bgra8,
+    /// 16-bit float RGBA (linear scRGB), for HDR displays where 8-bit frames
+    /// come out washed out; tone-mapped to sRGB by clamping.
+    Rgba16Float,
+}
+
+/// Where automation frames are captured from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum CaptureSource {
+    /// The game window itself (Windows Graphics Capture on its HWND).
+    #[default]
+    Window,
+    /// A rectangle of a whole monitor, for setups where window capture of the
+    /// game is blocked but display capture still works.
+    Monitor,
+}
+
+/// A point in relative coordinates for button centers, plus how to click it.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ButtonConfig {
     /// X position (0.0 = left edge, 1.0 = right edge)
     pub x: f32,
     /// Y position (0.0 = top edge, 1.0 = bottom edge)
     pub y: f32,
+    /// How long the mouse button is held down per click, in ms (default 50).
+    /// Raise it for buttons that ignore a quick tap.
+    #[serde(default = "default_click_hold_ms")]
+    pub click_hold_ms: u64,
+    /// Send a double click instead of a single one (default false).
+    #[serde(default)]
+    pub double_click: bool,
 }
 
 impl Default for ButtonConfig {
     fn default() -> Self {
-        Self { x: 0.5, y: 0.5 }
+        Self {
+            x: 0.5,
+            y: 0.5,
+            click_hold_ms: default_click_hold_ms(),
+            double_click: false,
+        }
     }
 }
 
+fn default_click_hold_ms() -> u64 {
+    50
+}
+
 /// Complete automation configuration.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct AutomationConfig {
+    /// Path of the JSON Schema editors validate this file against (written
+    /// next to config.json at startup); not used by the app itself. Empty in
+    /// copies saved elsewhere (`without_schema_ref`), where it is left out.
+    #[serde(
+        rename = "$schema",
+        default = "default_schema_ref",
+        skip_serializing_if = "String::is_empty"
+    )]
+    pub schema: String,
     /// Position of the "開始する" (Start) button
     pub start_button: ButtonConfig,
     /// Region around start button for histogram comparison (detecting rehearsal page)
@@ -121,15 +292,35 @@ pub struct AutomationConfig {
     /// Path to Start button reference image for histogram comparison
     #[serde(default = "default_start_button_reference")]
     pub start_button_reference: String,
+    /// Watch for a blocked screen (maintenance or update notice) while waiting
+    /// for the rehearsal page, and abort the run as soon as it is confirmed
+    /// instead of waiting out `loading_timeout_ms` (default off).
+    #[serde(default)]
+    pub blocked_screen_detection: bool,
+    /// Path to a full-window reference image of the blocked screen, compared
+    /// by histogram like the button references (captured with 参照画像 →
+    /// メンテナンス画面を撮影)
+    #[serde(default = "default_blocked_screen_reference")]
+    pub blocked_screen_reference: String,
     /// Position of the "スキップ" (Skip) button
     pub skip_button: ButtonConfig,
     /// Region around skip button for brightness detection
     pub skip_button_region: RelativeRect,
+    /// Part of `skip_button_region` (relative to that region, not the window)
+    /// that brightness is measured over, so background around the button does
+    /// not dilute the enabled/dimmed jump. `None` (default) uses the whole region.
+    #[serde(default)]
+    pub skip_button_active_subrect: Option<RelativeRect>,
     /// Brightness threshold: above this = Skip button enabled, below = disabled/dimmed
     pub brightness_threshold: f32,
     /// Histogram similarity threshold: above this = Skip button detected (0.0-1.0)
     #[serde(default = "default_histogram_threshold")]
     pub histogram_threshold: f32,
+    /// Metric compared against `histogram_threshold` (default Bhattacharyya).
+    /// `Both` rejects look-alikes such as a loading spinner whose histogram is
+    /// similar to the button's but shifted.
+    #[serde(default)]
+    pub detection_metric: DetectionMetric,
     /// Path to Skip button reference image for histogram comparison
     #[serde(default = "default_skip_button_reference")]
     pub skip_button_reference: String,
@@ -142,6 +333,15 @@ pub struct AutomationConfig {
     /// Path to End button reference image for histogram comparison
     #[serde(default = "default_end_button_reference")]
     pub end_button_reference: String,
+    /// Press this key to leave the result page instead of clicking
+    /// `end_button` (default none = click). Keys don't depend on the button
+    /// position, so they survive layout changes that break calibrated clicks.
+    #[serde(default)]
+    pub end_key: Option<NavigationKey>,
+    /// When the game is brought to the foreground for clicks and key presses
+    /// (default before each one).
+    #[serde(default)]
+    pub foreground_mode: ForegroundMode,
     /// Maximum time to wait for loading (milliseconds)
     pub loading_timeout_ms: u64,
     /// Maximum time to wait for result page (milliseconds)
@@ -170,6 +370,13 @@ pub struct AutomationConfig {
     /// badge sits under whichever column has the largest score.
     #[serde(default = "default_bonus_regions")]
     pub bonus_regions: [RelativeRect; 3],
+    /// Binarization used for the score rows and the stage totals. `Global`
+    /// (default) uses `ocr_threshold` / `total_threshold` as-is.
+    #[serde(default)]
+    pub ocr_preprocess_mode: OcrPreprocessMode,
+    /// How Tesseract is run: `Process` (default, most portable) or `InProcess`.
+    #[serde(default)]
+    pub ocr_backend: OcrBackend,
     /// Brightness threshold for binarizing the stage-total crop (white text).
     #[serde(default = "default_total_threshold")]
     pub total_threshold: u8,
@@ -181,17 +388,216 @@ pub struct AutomationConfig {
     /// gold crown icon while keeping the light-blue digits.
     #[serde(default = "default_bonus_br_margin")]
     pub bonus_br_margin: u8,
-    /// Number of consecutive histogram matches required to confirm detection (default 3)
-    #[serde(default = "default_detection_confirm_count")]
+    /// Smallest per-character score accepted from OCR; lower readings are noise.
+    #[serde(default = "default_min_valid_score")]
+    pub min_valid_score: u32,
+    /// Largest per-character score accepted from OCR. Defaults to the 7-digit
+    /// ceiling rather than the game's real maximum so that over-range misreads
+    /// still reach the checksum reconstruction, which can repair them.
+    #[serde(default = "default_max_valid_score")]
+    pub max_valid_score: u32,
+    /// Tesseract word confidence (0-100) below which a per-stage score word is
+    /// discarded as noise (default 60).
+    #[serde(default = "default_ocr_min_confidence")]
+    pub ocr_min_confidence: f32,
+    /// Tesseract page segmentation mode (`--psm`) for the score-row OCR
+    /// (default 6). Common values: 6 = single uniform block of text, 7 = single
+    /// text line, 8 = single word, 11 = sparse text, 13 = raw line. Accepted
+    /// range is 3-13; 0-2 (orientation detection only / unsupported) never
+    /// return text. The stage total and bonus always use 7.
+    #[serde(default = "default_ocr_psm")]
+    pub ocr_psm: u8,
+    /// Number of idols per stage in the contest formation (1-3, default 3).
+    /// Fewer idols leave the trailing score slots empty (recorded as 0).
+    #[serde(default = "default_characters_per_stage")]
+    pub characters_per_stage: usize,
+    /// Integer upscale applied to every OCR crop before binarization (1-4,
+    /// default 1 = off). Helps small windows where digits are only ~12px tall.
+    #[serde(default = "default_ocr_upscale_factor")]
+    pub ocr_upscale_factor: u32,
+    /// Frames combined (per-pixel median) for each result screenshot, to wash
+    /// out the result screen's animations (1-9, default 1 = single frame).
+    #[serde(default = "default_stable_frames")]
+    pub stable_frames: u32,
+    /// Mean Tesseract confidence (0-100) a stage's score row must reach on a
+    /// quick pre-check of the result capture; below it the screen is captured
+    /// again, up to `max_recaptures` times (default 0 = no pre-check). Only
+    /// applies to runs with OCR.
+    #[serde(default)]
+    pub recapture_confidence: f32,
+    /// Recaptures allowed per iteration by `recapture_confidence` (0-5,
+    /// default 2). The last capture is kept either way.
+    #[serde(default = "default_max_recaptures")]
+    pub max_recaptures: u32,
+    /// Capture from the game window (default) or from a monitor region.
+    #[serde(default)]
+    pub capture_source: CaptureSource,
+    /// Extra attempts, each with a fresh capture session, when a window capture
+    /// times out waiting for its first frame (default 1).
+    #[serde(default = "default_capture_retries")]
+    pub capture_retries: u32,
+    /// Save whole result screens (default) or just the score area, which is
+    /// far smaller on disk. A resumed session keeps the mode it was started
+    /// with (recorded in its run-meta.json).
+    #[serde(default)]
+    pub capture_mode: CaptureMode,
+    /// Include the mouse cursor in captures (default false: always excluded).
+    #[serde(default)]
+    pub capture_cursor: bool,
+    /// Turn off the yellow border Windows draws around a captured window
+    /// (default true). Older Windows versions don't support this; the log
+    /// says so on the first capture.
+    #[serde(default = "default_suppress_capture_border")]
+    pub suppress_capture_border: bool,
+    /// Store captures fully opaque (alpha 255). Graphics Capture often reports
+    /// alpha 0 for opaque content; turn off to keep the captured alpha.
+    #[serde(default = "default_force_opaque")]
+    pub force_opaque: bool,
+    /// Pixel format requested from Windows Graphics Capture (default `Bgra8`).
+    #[serde(default)]
+    pub capture_pixel_format: CapturePixelFormat,
+    /// Wake the capture thread from the `FrameArrived` handler instead of
+    /// polling for a frame every 10ms. Saves up to 10ms per capture; off by
+    /// default. Either way a capture gives up after 5 seconds.
+    #[serde(default)]
+    pub event_driven_frame_wait: bool,
+    /// Monitor to capture when `capture_source` is `Monitor` (0-based, in
+    /// display enumeration order).
+    #[serde(default)]
+    pub capture_monitor_index: usize,
+    /// Part of that monitor showing the game's client area, relative to the
+    /// monitor (default: the whole monitor, i.e. a fullscreen game).
+    #[serde(default = "default_capture_monitor_rect")]
+    pub capture_monitor_rect: RelativeRect,
+    /// Number of consecutive histogram matches required to confirm detection (default 3).
+    /// The Start, Skip (phase 1) and End waits only return once the similarity
+    /// has stayed above the threshold for this many polls, so a screen still
+    /// animating in is not captured early. Also read as `detection_stable_count`.
+    #[serde(default = "default_detection_confirm_count", alias = "detection_stable_count")]
     pub detection_confirm_count: u32,
     /// Maximum number of click retry attempts if button is still visible (default 3)
     #[serde(default = "default_max_click_retries")]
     pub max_click_retries: u32,
+    /// Hard cap on the whole run's wall-clock time in milliseconds (0 = unlimited).
+    /// A guardrail for unattended runs: if detection is mis-tuned and iterations
+    /// drag on, automation stops with an error instead of looping all night.
+    #[serde(default)]
+    pub max_total_runtime_ms: u64,
+    /// How long starting automation keeps polling for the game window (ms)
+    /// before giving up, so the tool can be started while the game is still
+    /// launching (0 = fail immediately, default).
+    #[serde(default)]
+    pub window_find_timeout_ms: u64,
+    /// Extra pause (ms) between finishing one iteration and waiting for the
+    /// next start page, on top of the fixed settle after clicking End (default 0).
+    #[serde(default)]
+    pub inter_iteration_delay_ms: u64,
+    /// Interval (ms) between screenshots in timer capture mode, which captures
+    /// without clicking (at least 100, default 5000).
+    #[serde(default = "default_timed_capture_interval_ms")]
+    pub timed_capture_interval_ms: u64,
+    /// OCR each timer capture into the session's results.csv (default true).
+    #[serde(default = "default_timed_capture_ocr")]
+    pub timed_capture_ocr: bool,
+    /// Stamp the iteration number and capture time into the top-left corner of
+    /// each saved automation screenshot (off by default: clean captures).
+    #[serde(default)]
+    pub annotate_screenshots: bool,
+    /// Keep at most this many screenshots per session, deleting the oldest
+    /// once their OCR row is written (default 0 = keep all).
+    #[serde(default)]
+    pub max_screenshots: u32,
+    /// Encode and write automation screenshots on a background thread, so the
+    /// next click doesn't wait on disk I/O (off by default). OCR is queued once
+    /// the file is written; save errors stop the run at the next iteration.
+    #[serde(default)]
+    pub async_screenshot_save: bool,
+    /// Save a full-window screenshot to the session folder when a detection
+    /// wait times out (`timeout_<state>_<iteration>.png`), for threshold tuning.
+    #[serde(default = "default_dump_on_timeout")]
+    pub dump_on_timeout: bool,
+    /// Copy the effective config (as `config.json`) and the Start/Skip/End and
+    /// blocked-screen reference images (into `references/`, which the copied
+    /// config points at) into each new session folder, so old sessions can be
+    /// reproduced after the settings change (default true).
+    #[serde(default = "default_archive_config")]
+    pub archive_config: bool,
+    /// Save every OCR crop before and after binarization to the session's
+    /// `ocr_debug/` folder, for tuning thresholds (off by default: slow, bulky).
+    #[serde(default)]
+    pub debug_ocr: bool,
+    /// Result screens whose score-row fingerprints differ in at most this many
+    /// bits count as a repeat of the previous iteration (a stuck game); each
+    /// repeat is logged as a warning.
+    #[serde(default = "default_duplicate_max_distance")]
+    pub duplicate_max_distance: u32,
+    /// Stop the run with an error on a repeated result screen instead of only
+    /// warning and recording the duplicate row.
+    #[serde(default)]
+    pub abort_on_duplicate: bool,
+    /// When the game window is found minimized (captures would be black or
+    /// stale), restore it and carry on (default true) or stop with an error.
+    #[serde(default = "default_restore_minimized")]
+    pub restore_minimized: bool,
+    /// Fsync the result CSVs every this many rows so a power loss or OS crash
+    /// loses at most that many rows (each row is always flushed; 0 = only sync
+    /// when the run ends).
+    #[serde(default = "default_csv_sync_interval")]
+    pub csv_sync_interval: u32,
+    /// At the end of a run, wait at most this long (milliseconds) for the OCR
+    /// worker to finish its queue; past that the worker is stopped, the
+    /// screenshots it did not read are left without a CSV row (a resume
+    /// re-queues them) and the run completes (0 = wait indefinitely).
+    #[serde(default = "default_ocr_drain_timeout_ms")]
+    pub ocr_drain_timeout_ms: u64,
+    /// Folder new session folders are created in, absolute or relative to the
+    /// data directory (`paths::get_data_dir`). Empty (default) keeps
+    /// `<data_dir>/output/`.
+    #[serde(default)]
+    pub output_dir: String,
+    /// Replacement header line for `results.csv`, for downstream tools that
+    /// expect other column names. Columns keep their built-in order, so it must
+    /// name the same number of columns as the default header. Empty (default)
+    /// writes the default `iteration,timestamp,screenshot,s1c1,...` header.
+    #[serde(default)]
+    pub csv_header: String,
+    /// File name (without `.png`) for saved screenshots, with placeholders
+    /// `{iter}` (3-digit iteration, `gakumas` for manual captures), `{ts}`
+    /// (`YYYYMMDD_HHMMSS`), `{profile}` (`profile_name`), `{w}` and `{h}`
+    /// (capture size in pixels). It must start with `{iter}_` (or be just
+    /// `{iter}`), since resume and rotation read the iteration back from the
+    /// name. Empty (default) keeps `{iter}_{ts}`. Manual and timer captures
+    /// whose name already exists get `_2`, `_3`, ... appended
+    /// (`paths::unique_file_path`), so a template without `{ts}` never
+    /// overwrites an earlier screenshot.
+    #[serde(default)]
+    pub screenshot_name_template: String,
+    /// Label for this config, for the `{profile}` placeholder of
+    /// `screenshot_name_template` (empty = `default`).
+    #[serde(default)]
+    pub profile_name: String,
+    /// GUI: the window's close button hides the window to the tray instead of
+    /// exiting (exit from the tray menu's 終了). Off by default.
+    #[serde(default)]
+    pub minimize_to_tray: bool,
+    /// GUI: open hidden in the tray (show it from the tray menu), or minimized
+    /// to the taskbar if the tray icon could not be created. Off by default.
+    #[serde(default)]
+    pub start_minimized: bool,
+    /// Launch at Windows login via the per-user `Run` registry key. The key is
+    /// written/removed when this changes and the config is saved from the GUI;
+    /// the editor's checkbox shows whether the key actually exists.
+    #[serde(default)]
+    pub autostart: bool,
     /// Developer mode: when enabled, runs as tray app with advanced features
     #[serde(default)]
     pub developer_mode: bool,
 }
 
+fn default_schema_ref() -> String {
+    format!("./{}", CONFIG_SCHEMA_FILE)
+}
+
 fn default_score_regions() -> [RelativeRect; 3] {
     [
         RelativeRect { x: 0.0, y: 0.179, width: 1.0, height: 0.022 },  // Stage 1
@@ -236,6 +642,86 @@ fn default_bonus_br_margin() -> u8 {
     30
 }
 
+fn default_min_valid_score() -> u32 {
+    100
+}
+
+fn default_max_valid_score() -> u32 {
+    9_999_999
+}
+
+fn default_ocr_min_confidence() -> f32 {
+    60.0
+}
+
+fn default_ocr_psm() -> u8 {
+    6
+}
+
+fn default_characters_per_stage() -> usize {
+    3
+}
+
+fn default_ocr_upscale_factor() -> u32 {
+    1
+}
+
+fn default_suppress_capture_border() -> bool {
+    true
+}
+
+fn default_stable_frames() -> u32 {
+    1
+}
+
+fn default_max_recaptures() -> u32 {
+    2
+}
+
+fn default_timed_capture_interval_ms() -> u64 {
+    5000
+}
+
+fn default_timed_capture_ocr() -> bool {
+    true
+}
+
+fn default_capture_monitor_rect() -> RelativeRect {
+    RelativeRect { x: 0.0, y: 0.0, width: 1.0, height: 1.0 }
+}
+
+fn default_dump_on_timeout() -> bool {
+    true
+}
+
+fn default_archive_config() -> bool {
+    true
+}
+
+fn default_restore_minimized() -> bool {
+    true
+}
+
+fn default_capture_retries() -> u32 {
+    1
+}
+
+fn default_force_opaque() -> bool {
+    true
+}
+
+fn default_duplicate_max_distance() -> u32 {
+    8
+}
+
+fn default_csv_sync_interval() -> u32 {
+    1
+}
+
+fn default_ocr_drain_timeout_ms() -> u64 {
+    120_000
+}
+
 fn default_histogram_threshold() -> f32 {
     0.85 // 85% similarity required to detect buttons
 }
@@ -254,13 +740,17 @@ fn default_start_button_reference() -> String {
     "resources/template/rehearsal/start_button_ref.png".to_string()
 }
 
+fn default_blocked_screen_reference() -> String {
+    "resources/template/rehearsal/blocked_screen_ref.png".to_string()
+}
+
 fn default_skip_button_reference() -> String {
     "resources/template/rehearsal/skip_button_ref.png".to_string()
 }
 
 fn default_end_button() -> ButtonConfig {
     // Default position for "終了" button (bottom center of result page)
-    ButtonConfig { x: 0.5, y: 0.9 }
+    ButtonConfig { x: 0.5, y: 0.9, ..Default::default() }
 }
 
 fn default_end_button_region() -> RelativeRect {
@@ -292,29 +782,38 @@ fn default_result_timeout_ms() -> u64 {
 impl Default for AutomationConfig {
     fn default() -> Self {
         Self {
-            start_button: ButtonConfig { x: 0.5, y: 0.85 },
+            schema: default_schema_ref(),
+            start_button: ButtonConfig { x: 0.5, y: 0.85, ..Default::default() },
             start_button_region: default_start_button_region(),
             start_button_reference: default_start_button_reference(),
-            skip_button: ButtonConfig { x: 0.82, y: 0.82 },
+            blocked_screen_detection: false,
+            blocked_screen_reference: default_blocked_screen_reference(),
+            skip_button: ButtonConfig { x: 0.82, y: 0.82, ..Default::default() },
             skip_button_region: RelativeRect {
                 x: 0.7,
                 y: 0.8,
                 width: 0.22,
                 height: 0.04,
             },
+            skip_button_active_subrect: None,
             // Brightness threshold: Skip button dimmed ~92, enabled ~97
             // Set to 94 to detect when Skip button becomes enabled
             brightness_threshold: 94.0,
             histogram_threshold: default_histogram_threshold(),
+            detection_metric: DetectionMetric::Bhattacharyya,
             skip_button_reference: default_skip_button_reference(),
             end_button: default_end_button(),
             end_button_region: default_end_button_region(),
             end_button_reference: default_end_button_reference(),
+            end_key: None,
+            foreground_mode: ForegroundMode::EachClick,
             loading_timeout_ms: 30000,
             result_timeout_ms: default_result_timeout_ms(),
             capture_delay_ms: 500,
-            test_click_position: ButtonConfig { x: 0.5, y: 0.5 },
+            test_click_position: ButtonConfig::default(),
             ocr_threshold: default_ocr_threshold(),
+            ocr_preprocess_mode: OcrPreprocessMode::Global,
+            ocr_backend: OcrBackend::Process,
             score_regions: default_score_regions(),
             review_crop_adjust: default_review_crop_adjust(),
             total_regions: default_total_regions(),
@@ -322,26 +821,616 @@ impl Default for AutomationConfig {
             total_threshold: default_total_threshold(),
             bonus_blue_min: default_bonus_blue_min(),
             bonus_br_margin: default_bonus_br_margin(),
+            min_valid_score: default_min_valid_score(),
+            max_valid_score: default_max_valid_score(),
+            ocr_min_confidence: default_ocr_min_confidence(),
+            ocr_psm: default_ocr_psm(),
+            characters_per_stage: default_characters_per_stage(),
+            ocr_upscale_factor: default_ocr_upscale_factor(),
+            stable_frames: default_stable_frames(),
+            recapture_confidence: 0.0,
+            max_recaptures: default_max_recaptures(),
+            capture_source: CaptureSource::Window,
+            capture_retries: default_capture_retries(),
+            capture_mode: CaptureMode::FullWindow,
+            capture_cursor: false,
+            suppress_capture_border: default_suppress_capture_border(),
+            force_opaque: default_force_opaque(),
+            capture_pixel_format: CapturePixelFormat::Bgra8,
+            event_driven_frame_wait: false,
+            capture_monitor_index: 0,
+            capture_monitor_rect: default_capture_monitor_rect(),
             detection_confirm_count: default_detection_confirm_count(),
             max_click_retries: default_max_click_retries(),
+            max_total_runtime_ms: 0,
+            window_find_timeout_ms: 0,
+            inter_iteration_delay_ms: 0,
+            timed_capture_interval_ms: default_timed_capture_interval_ms(),
+            timed_capture_ocr: default_timed_capture_ocr(),
+            annotate_screenshots: false,
+            max_screenshots: 0,
+            async_screenshot_save: false,
+            dump_on_timeout: default_dump_on_timeout(),
+            archive_config: default_archive_config(),
+            debug_ocr: false,
+            duplicate_max_distance: default_duplicate_max_distance(),
+            abort_on_duplicate: false,
+            restore_minimized: default_restore_minimized(),
+            csv_sync_interval: default_csv_sync_interval(),
+            ocr_drain_timeout_ms: default_ocr_drain_timeout_ms(),
+            output_dir: String::new(),
+            csv_header: String::new(),
+            screenshot_name_template: String::new(),
+            profile_name: String::new(),
+            minimize_to_tray: false,
+            start_minimized: false,
+            autostart: false,
             developer_mode: false,
         }
     }
 }
 
+/// Slack for floating-point edges in hand-edited configs (e.g. x + width = 1.0001).
+const BOUNDS_EPSILON: f32 = 1e-4;
+
+/// Largest accepted `ocr_upscale_factor`; beyond 4x Tesseract gains nothing and
+/// the crops just get slower to process.
+pub const MAX_OCR_UPSCALE_FACTOR: u32 = 4;
+
+/// Largest accepted `stable_frames`; each frame is a full capture (~tens of ms).
+pub const MAX_STABLE_FRAMES: u32 = 9;
+
+/// Largest accepted `max_recaptures`; each one is a capture plus a score-row OCR.
+pub const MAX_RECAPTURES: u32 = 5;
+
+/// Shortest accepted `timed_capture_interval_ms`; a capture alone takes tens of ms.
+pub const MIN_TIMED_CAPTURE_INTERVAL_MS: u64 = 100;
+
+/// Longest accepted `click_hold_ms`; anything longer reads as a long-press.
+pub const MAX_CLICK_HOLD_MS: u64 = 2000;
+
+/// Placeholders accepted by `screenshot_name_template`.
+const NAME_PLACEHOLDERS: [&str; 5] = ["{iter}", "{ts}", "{profile}", "{w}", "{h}"];
+
+/// `template` with its placeholders replaced (see `screenshot_name_template`).
+fn expand_name_template(
+    template: &str,
+    iter: &str,
+    timestamp: &str,
+    profile: &str,
+    (width, height): (u32, u32),
+) -> String {
+    let profile = match profile.trim() {
+        "" => "default",
+        profile => profile,
+    };
+    // {profile} last, so placeholder-like text in the profile name stays as is
+    template
+        .replace("{iter}", iter)
+        .replace("{ts}", timestamp)
+        .replace("{w}", &width.to_string())
+        .replace("{h}", &height.to_string())
+        .replace("{profile}", profile)
+}
+
+/// Describes why `stem` (a file name without extension) is not a legal
+/// Windows file name, or None if it is.
+fn file_name_problem(stem: &str) -> Option<String> {
+    if let Some(c) = stem.chars().find(|c| r#"<>:"/\|?*"#.contains(*c) || c.is_control()) {
+        return Some(format!("contains '{}', which file names cannot", c.escape_default()));
+    }
+    if stem.is_empty() || stem.ends_with([' ', '.']) {
+        return Some("is empty or ends with a space or dot".to_string());
+    }
+    // 255 characters per path component, including ".png"
+    if stem.chars().count() > 251 {
+        return Some(format!("is {} characters long (at most 251)", stem.chars().count()));
+    }
+    // CON, COM1, ... are reserved whatever the extension
+    let device = stem.split('.').next().unwrap_or(stem).trim_end().to_ascii_uppercase();
+    let reserved = match device.as_str() {
+        "CON" | "PRN" | "AUX" | "NUL" => true,
+        d => ["COM", "LPT"].iter().any(|prefix| {
+            d.strip_prefix(prefix).is_some_and(|n| matches!(n.as_bytes(), [b'1'..=b'9']))
+        }),
+    };
+    reserved.then(|| format!("is the reserved device name {}", device))
+}
+
+/// Describes why a `screenshot_name_template` is invalid, or None if it is
+/// valid or empty. The expansion is checked with sample values and the
+/// configured `profile`.
+fn name_template_problem(template: &str, profile: &str) -> Option<String> {
+    let template = template.trim();
+    if template.is_empty() {
+        return None;
+    }
+    let rest = template.strip_prefix("{iter}");
+    if !rest.is_some_and(|rest| rest.is_empty() || rest.starts_with('_')) {
+        return Some("must start with {iter}_ (resume reads the iteration from the name)".into());
+    }
+    let unknown = NAME_PLACEHOLDERS.iter().fold(template.to_string(), |t, p| t.replace(p, ""));
+    if unknown.contains(['{', '}']) {
+        return Some(format!("\"{}\" has an unknown placeholder", template));
+    }
+    let sample = expand_name_template(template, "001", "20260101_120000", profile, (1920, 1080));
+    file_name_problem(&sample)
+        .map(|problem| format!("expands to \"{}\", which {}", sample, problem))
+}
+
+/// Describes why a relative point is invalid, or None if it is inside the window.
+fn point_problem(p: &ButtonConfig) -> Option<String> {
+    (!(0.0..=1.0).contains(&p.x) || !(0.0..=1.0).contains(&p.y))
+        .then(|| format!("({}, {}) is outside 0.0-1.0", p.x, p.y))
+}
+
+/// Describes why a click hold time is invalid, or None if it is a sane tap.
+fn click_hold_problem(ms: u64) -> Option<String> {
+    (ms == 0 || ms > MAX_CLICK_HOLD_MS)
+        .then(|| format!("{} is outside 1-{}", ms, MAX_CLICK_HOLD_MS))
+}
+
+/// Describes why a relative rectangle is invalid, or None if it is a non-empty
+/// region fully inside the window.
+fn rect_problem(r: &RelativeRect) -> Option<String> {
+    if !(0.0..=1.0).contains(&r.x) || !(0.0..=1.0).contains(&r.y) {
+        return Some(format!("origin ({}, {}) is outside 0.0-1.0", r.x, r.y));
+    }
+    if r.width.is_nan() || r.height.is_nan() || r.width <= 0.0 || r.height <= 0.0 {
+        return Some(format!("size {} x {} must be positive", r.width, r.height));
+    }
+    if r.x + r.width > 1.0 + BOUNDS_EPSILON || r.y + r.height > 1.0 + BOUNDS_EPSILON {
+        return Some(format!(
+            "extends past the window edge (right {:.4}, bottom {:.4})",
+            r.x + r.width,
+            r.y + r.height
+        ));
+    }
+    None
+}
+
+/// One config value `AutomationConfig::validate` found invalid and reset.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// Field name as written in config.json, with an index for array entries
+    /// (e.g. `"score_regions[1]"`)
+    pub field: String,
+    /// What was wrong and what replaced it
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Resets `value` to `default` when `problem` is Some, recording the violation.
+fn reset_if_invalid<T: Clone>(
+    name: &str,
+    value: &mut T,
+    default: &T,
+    problem: Option<String>,
+    issues: &mut Vec<ConfigIssue>,
+) {
+    if let Some(problem) = problem {
+        issues.push(ConfigIssue {
+            field: name.to_string(),
+            message: format!("{}; using default", problem),
+        });
+        *value = default.clone();
+    }
+}
+
+impl AutomationConfig {
+    /// Copy without the `$schema` reference, for config copies written outside
+    /// the config folder (session manifest and archive), where the relative
+    /// schema path does not resolve.
+    pub fn without_schema_ref(&self) -> Self {
+        Self { schema: String::new(), ..self.clone() }
+    }
+
+    /// Bounding box of every score, total and bonus region: the part of the
+    /// result screen a `ScoreRegionsOnly` screenshot keeps.
+    pub fn score_area(&self) -> RelativeRect {
+        self.score_regions
+            .iter()
+            .chain(&self.total_regions)
+            .chain(&self.bonus_regions)
+            .fold(self.score_regions[0], |area, region| area.union(region))
+    }
+
+    /// The score, total and bonus regions in the coordinates of a saved
+    /// automation screenshot: as configured for `FullWindow`, relative to
+    /// `score_area` for `ScoreRegionsOnly`.
+    pub fn screenshot_regions(&self) -> [[RelativeRect; 3]; 3] {
+        let regions = [self.score_regions, self.total_regions, self.bonus_regions];
+        match self.capture_mode {
+            CaptureMode::FullWindow => regions,
+            CaptureMode::ScoreRegionsOnly => {
+                let area = self.score_area();
+                regions.map(|group| group.map(|r| r.relative_to(&area)))
+            }
+        }
+    }
+
+    /// File name for a screenshot captured at `timestamp` (`%Y%m%d_%H%M%S`)
+    /// with `size` in pixels: `screenshot_name_template` expanded, or the
+    /// built-in `NNN_<ts>.png` (`gakumas_<ts>.png` for manual captures, where
+    /// `iteration` is None) when the template is empty or the expanded name is
+    /// not a legal Windows file name.
+    pub fn screenshot_file_name(
+        &self,
+        iteration: Option<u32>,
+        size: (u32, u32),
+        timestamp: &str,
+    ) -> String {
+        let iter = match iteration {
+            Some(iteration) => format!("{:03}", iteration),
+            None => "gakumas".to_string(),
+        };
+        let default = format!("{}_{}.png", iter, timestamp);
+        let template = self.screenshot_name_template.trim();
+        if template.is_empty() {
+            return default;
+        }
+        let stem = expand_name_template(template, &iter, timestamp, &self.profile_name, size);
+        match file_name_problem(&stem) {
+            None => format!("{}.png", stem),
+            Some(problem) => {
+                crate::log(&format!(
+                    "screenshot_name_template: \"{}\" {}; saving as {}",
+                    stem, problem, default
+                ));
+                default
+            }
+        }
+    }
+
+    /// Checks every field for values that would misbehave later (out-of-window
+    /// coordinates, empty regions, impossible thresholds, zero timeouts).
+    ///
+    /// Each invalid field is reset to its default so one typo does not discard
+    /// the rest of a hand-tuned config. Returns one issue per violation; an
+    /// empty list means the config was already valid.
+    pub fn validate(&mut self) -> Vec<ConfigIssue> {
+        let d = AutomationConfig::default();
+        let mut issues = Vec::new();
+
+        let points = [
+            ("start_button", &mut self.start_button, &d.start_button),
+            ("skip_button", &mut self.skip_button, &d.skip_button),
+            ("end_button", &mut self.end_button, &d.end_button),
+            ("test_click_position", &mut self.test_click_position, &d.test_click_position),
+        ];
+        for (name, value, default) in points {
+            // A bad hold time alone keeps the calibrated position
+            let problem = click_hold_problem(value.click_hold_ms);
+            let field = format!("{}.click_hold_ms", name);
+            let (hold, default_hold) = (&mut value.click_hold_ms, &default.click_hold_ms);
+            reset_if_invalid(&field, hold, default_hold, problem, &mut issues);
+            let problem = point_problem(value);
+            reset_if_invalid(name, value, default, problem, &mut issues);
+        }
+
+        let rects = [
+            ("start_button_region", &mut self.start_button_region, &d.start_button_region),
+            ("skip_button_region", &mut self.skip_button_region, &d.skip_button_region),
+            ("end_button_region", &mut self.end_button_region, &d.end_button_region),
+            ("capture_monitor_rect", &mut self.capture_monitor_rect, &d.capture_monitor_rect),
+        ];
+        for (name, value, default) in rects {
+            let problem = rect_problem(value);
+            reset_if_invalid(name, value, default, problem, &mut issues);
+        }
+        let problem = self.skip_button_active_subrect.as_ref().and_then(rect_problem);
+        reset_if_invalid(
+            "skip_button_active_subrect",
+            &mut self.skip_button_active_subrect,
+            &d.skip_button_active_subrect,
+            problem,
+            &mut issues,
+        );
+        let region_sets = [
+            ("score_regions", &mut self.score_regions, &d.score_regions),
+            ("total_regions", &mut self.total_regions, &d.total_regions),
+            ("bonus_regions", &mut self.bonus_regions, &d.bonus_regions),
+        ];
+        for (name, values, defaults) in region_sets {
+            for (i, value) in values.iter_mut().enumerate() {
+                let problem = rect_problem(value);
+                let name = format!("{}[{}]", name, i);
+                reset_if_invalid(&name, value, &defaults[i], problem, &mut issues);
+            }
+        }
+
+        let a = &self.review_crop_adjust;
+        let problem = [a.top_extend, a.bottom_extend, a.left_inset, a.right_inset]
+            .iter()
+            .any(|v| !(0.0..=1.0).contains(v))
+            .then(|| "values must be within 0.0-1.0".to_string());
+        reset_if_invalid(
+            "review_crop_adjust",
+            &mut self.review_crop_adjust,
+            &d.review_crop_adjust,
+            problem,
+            &mut issues,
+        );
+
+        let problem = (!(0.0..=1.0).contains(&self.histogram_threshold))
+            .then(|| format!("{} is outside 0.0-1.0", self.histogram_threshold));
+        reset_if_invalid(
+            "histogram_threshold",
+            &mut self.histogram_threshold,
+            &d.histogram_threshold,
+            problem,
+            &mut issues,
+        );
+        let problem = (!(0.0..=255.0).contains(&self.brightness_threshold))
+            .then(|| format!("{} is outside 0-255", self.brightness_threshold));
+        reset_if_invalid(
+            "brightness_threshold",
+            &mut self.brightness_threshold,
+            &d.brightness_threshold,
+            problem,
+            &mut issues,
+        );
+
+        let timeouts = [
+            ("loading_timeout_ms", &mut self.loading_timeout_ms, &d.loading_timeout_ms),
+            ("result_timeout_ms", &mut self.result_timeout_ms, &d.result_timeout_ms),
+        ];
+        for (name, value, default) in timeouts {
+            let problem = (*value == 0).then(|| "must be greater than 0".to_string());
+            reset_if_invalid(name, value, default, problem, &mut issues);
+        }
+
+        let problem = (self.timed_capture_interval_ms < MIN_TIMED_CAPTURE_INTERVAL_MS).then(|| {
+            format!("{} is below {}", self.timed_capture_interval_ms, MIN_TIMED_CAPTURE_INTERVAL_MS)
+        });
+        reset_if_invalid(
+            "timed_capture_interval_ms",
+            &mut self.timed_capture_interval_ms,
+            &d.timed_capture_interval_ms,
+            problem,
+            &mut issues,
+        );
+
+        let problem = (self.detection_confirm_count == 0).then(|| "must be at least 1".to_string());
+        reset_if_invalid(
+            "detection_confirm_count",
+            &mut self.detection_confirm_count,
+            &d.detection_confirm_count,
+            problem,
+            &mut issues,
+        );
+        let problem = (!(0.0..=100.0).contains(&self.ocr_min_confidence))
+            .then(|| format!("{} is outside 0-100", self.ocr_min_confidence));
+        reset_if_invalid(
+            "ocr_min_confidence",
+            &mut self.ocr_min_confidence,
+            &d.ocr_min_confidence,
+            problem,
+            &mut issues,
+        );
+        let expected = crate::automation::csv_writer::CSV_HEADER.split(',').count();
+        let columns = crate::analysis::csv_reader::split_csv_line(&self.csv_header).len();
+        let problem = if self.csv_header.trim().is_empty() {
+            None
+        } else if columns != expected {
+            Some(format!("names {} columns, expected {}", columns, expected))
+        } else {
+            crate::analysis::csv_reader::misplaced_column(&self.csv_header)
+        };
+        reset_if_invalid("csv_header", &mut self.csv_header, &d.csv_header, problem, &mut issues);
+        let problem = name_template_problem(&self.screenshot_name_template, &self.profile_name);
+        reset_if_invalid(
+            "screenshot_name_template",
+            &mut self.screenshot_name_template,
+            &d.screenshot_name_template,
+            problem,
+            &mut issues,
+        );
+        let problem = (!(3..=13).contains(&self.ocr_psm)).then(|| format!("{} is outside 3-13", self.ocr_psm));
+        reset_if_invalid(
+            "ocr_psm",
+            &mut self.ocr_psm,
+            &d.ocr_psm,
+            problem,
+            &mut issues,
+        );
+        let problem = !(1..=3).contains(&self.characters_per_stage);
+        let problem = problem.then(|| format!("{} is outside 1-3", self.characters_per_stage));
+        reset_if_invalid(
+            "characters_per_stage",
+            &mut self.characters_per_stage,
+            &d.characters_per_stage,
+            problem,
+            &mut issues,
+        );
+        let problem = !(1..=MAX_OCR_UPSCALE_FACTOR).contains(&self.ocr_upscale_factor);
+        let problem = problem.then(|| {
+            format!("{} is outside 1-{}", self.ocr_upscale_factor, MAX_OCR_UPSCALE_FACTOR)
+        });
+        reset_if_invalid(
+            "ocr_upscale_factor",
+            &mut self.ocr_upscale_factor,
+            &d.ocr_upscale_factor,
+            problem,
+            &mut issues,
+        );
+        let problem = !(1..=MAX_STABLE_FRAMES).contains(&self.stable_frames);
+        let problem = problem
+            .then(|| format!("{} is outside 1-{}", self.stable_frames, MAX_STABLE_FRAMES));
+        reset_if_invalid(
+            "stable_frames",
+            &mut self.stable_frames,
+            &d.stable_frames,
+            problem,
+            &mut issues,
+        );
+        let problem = (!(0.0..=100.0).contains(&self.recapture_confidence))
+            .then(|| format!("{} is outside 0-100", self.recapture_confidence));
+        reset_if_invalid(
+            "recapture_confidence",
+            &mut self.recapture_confidence,
+            &d.recapture_confidence,
+            problem,
+            &mut issues,
+        );
+        let problem = (self.max_recaptures > MAX_RECAPTURES)
+            .then(|| format!("{} is outside 0-{}", self.max_recaptures, MAX_RECAPTURES));
+        reset_if_invalid(
+            "max_recaptures",
+            &mut self.max_recaptures,
+            &d.max_recaptures,
+            problem,
+            &mut issues,
+        );
+        if self.min_valid_score > self.max_valid_score {
+            issues.push(ConfigIssue {
+                field: "min_valid_score".to_string(),
+                message: format!(
+                    "{} exceeds max_valid_score {}; using defaults",
+                    self.min_valid_score, self.max_valid_score
+                ),
+            });
+            self.min_valid_score = d.min_valid_score;
+            self.max_valid_score = d.max_valid_score;
+        }
+
+        issues
+    }
+
+    /// Names of the fields `validate` would reset (e.g. `"score_regions[1]"`),
+    /// without modifying `self`. Used by the GUI editor to highlight them.
+    pub fn invalid_fields(&self) -> Vec<String> {
+        self.clone().validate().into_iter().map(|issue| issue.field).collect()
+    }
+}
+
+/// `<user_dir>/config.json` (see `paths::get_user_dir`), if the OS has a
+/// config dir.
+fn user_config_path() -> Option<PathBuf> {
+    crate::paths::get_user_dir().map(|dir| dir.join("config.json"))
+}
+
+/// Config files to look for, in order: `config.json` next to the executable,
+/// then the user config. When the exe folder is read-only `save_config`
+/// writes the user config, so that one comes first and saved calibration
+/// wins over a config.json shipped with the install.
+fn config_candidates(
+    exe_config: PathBuf,
+    exe_writable: bool,
+    user_config: Option<PathBuf>,
+) -> Vec<PathBuf> {
+    let mut candidates = vec![exe_config];
+    if let Some(user_config) = user_config {
+        if exe_writable {
+            candidates.push(user_config);
+        } else {
+            candidates.insert(0, user_config);
+        }
+    }
+    candidates
+}
+
+/// Where `save_config` writes: next to the executable, or the user config
+/// when the exe folder can't be written.
+fn config_save_path(exe_writable: bool) -> PathBuf {
+    let exe_config = crate::paths::get_exe_dir().join("config.json");
+    if exe_writable {
+        return exe_config;
+    }
+    user_config_path().unwrap_or(exe_config)
+}
+
+/// Path of the active config file: the first existing file in
+/// `config_candidates`, or where `save_config` would create one.
+pub fn config_path() -> PathBuf {
+    let exe_writable = crate::paths::exe_dir_writable();
+    let exe_config = crate::paths::get_exe_dir().join("config.json");
+    config_candidates(exe_config, exe_writable, user_config_path())
+        .into_iter()
+        .find(|path| path.exists())
+        .unwrap_or_else(|| config_save_path(exe_writable))
+}
+
+/// Writes `config` as pretty-printed JSON next to the executable, or to the
+/// user config dir when the exe folder is read-only. Returns the path written.
+///
+/// Does not touch the in-memory config; call `reload_config` afterwards to
+/// make the saved values active.
+pub fn save_config(config: &AutomationConfig) -> Result<PathBuf> {
+    let path = config_save_path(crate::paths::exe_dir_writable());
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(config)?;
+    fs::write(&path, json)?;
+    Ok(path)
+}
+
+/// File name of the JSON Schema written next to config.json.
+pub const CONFIG_SCHEMA_FILE: &str = "config.schema.json";
+
+/// JSON Schema for config.json, generated from `AutomationConfig`: field docs
+/// become descriptions and serde defaults become `default` values.
+pub fn config_schema_json() -> Result<String> {
+    let schema = schemars::schema_for!(AutomationConfig);
+    Ok(serde_json::to_string_pretty(&schema)?)
+}
+
+/// Writes config.schema.json next to the saved config.json, so editors such as
+/// VS Code can validate and autocomplete config.json (which points at it
+/// through `$schema`). An existing schema is rewritten when it differs from the
+/// current one, so it follows config fields added or changed by an update.
+pub fn write_config_schema() {
+    let path =
+        config_save_path(crate::paths::exe_dir_writable()).with_file_name(CONFIG_SCHEMA_FILE);
+    match config_schema_json().and_then(|json| write_if_changed(&path, &json)) {
+        Ok(false) => {}
+        Ok(true) => crate::log(&format!(
+            "Wrote {} to {}",
+            CONFIG_SCHEMA_FILE,
+            crate::paths::relative_display(&path)
+        )),
+        Err(e) => crate::log(&format!("Failed to write {}: {}", CONFIG_SCHEMA_FILE, e)),
+    }
+}
+
+/// Writes `content` to `path` unless the file already holds exactly that.
+/// Returns whether it wrote.
+fn write_if_changed(path: &Path, content: &str) -> Result<bool> {
+    if fs::read_to_string(path).is_ok_and(|existing| existing == content) {
+        return Ok(false);
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)?;
+    Ok(true)
+}
+
 /// Loads configuration from config.json or returns defaults.
-/// Looks for config.json in the same directory as the executable.
+/// Looks next to the executable, then in the user config dir (see
+/// `config_candidates`).
 fn load_config() -> AutomationConfig {
-    // Try to find config.json next to the executable
-    let config_path = crate::paths::get_exe_dir().join("config.json");
+    let config_path = config_path();
 
     crate::log(&format!("Looking for config at: {}", crate::paths::relative_display(&config_path)));
 
     if config_path.exists() {
-        match fs::read_to_string(config_path) {
-            Ok(contents) => match serde_json::from_str(&contents) {
-                Ok(config) => {
-                    crate::log("Config loaded from config.json");
+        match fs::read_to_string(&config_path) {
+            Ok(contents) => match serde_json::from_str::<AutomationConfig>(&contents) {
+                Ok(mut config) => {
+                    crate::log(&format!(
+                        "Config loaded from {}",
+                        crate::paths::relative_display(&config_path)
+                    ));
+                    for issue in config.validate() {
+                        crate::log(&format!("Invalid config value: {}", issue));
+                    }
                     return config;
                 }
                 Err(e) => {
@@ -367,14 +1456,29 @@ fn load_config() -> AutomationConfig {
 
 /// Initializes the global configuration. Call once at startup.
 pub fn init_config() {
-    let _ = CONFIG.set(load_config());
+    let mut guard = CONFIG.write().unwrap();
+    if guard.is_none() {
+        *guard = Some(Arc::new(load_config()));
+    }
+}
+
+/// Re-reads config.json and replaces the global configuration.
+///
+/// Automation already in progress keeps the config it cloned at start; the
+/// next run (and every later `get_config` call) sees the reloaded values.
+pub fn reload_config() {
+    crate::log("Reloading config.json...");
+    let config = Arc::new(load_config());
+    *CONFIG.write().unwrap() = Some(config);
 }
 
-/// Returns a reference to the global configuration.
+/// Returns the current global configuration.
 /// Panics if called before init_config().
-pub fn get_config() -> &'static AutomationConfig {
+pub fn get_config() -> Arc<AutomationConfig> {
     CONFIG
-        .get()
+        .read()
+        .unwrap()
+        .clone()
         .expect("Config not initialized. Call init_config() first.")
 }
 
@@ -386,6 +1490,21 @@ mod tests {
         (0.0..=1.0).contains(&v)
     }
 
+    #[test]
+    fn config_candidates_prefer_user_config_when_exe_dir_is_read_only() {
+        let exe = PathBuf::from("exe/config.json");
+        let user = PathBuf::from("user/config.json");
+        assert_eq!(
+            config_candidates(exe.clone(), true, Some(user.clone())),
+            [exe.clone(), user.clone()]
+        );
+        assert_eq!(
+            config_candidates(exe.clone(), false, Some(user.clone())),
+            [user, exe.clone()]
+        );
+        assert_eq!(config_candidates(exe.clone(), false, None), [exe]);
+    }
+
     #[test]
     fn review_crop_default_frames_portrait_column() {
         let cfg = AutomationConfig::default();
@@ -436,4 +1555,275 @@ mod tests {
         assert_eq!(crop.width, 0.0);
         assert!(crop.height > 0.0);
     }
+
+    #[test]
+    fn validate_default_config_is_clean() {
+        let mut cfg = AutomationConfig::default();
+        assert!(cfg.validate().is_empty());
+    }
+
+    #[test]
+    fn validate_resets_out_of_bounds_region() {
+        let mut cfg = AutomationConfig {
+            skip_button_region: RelativeRect { x: 1.5, y: 0.8, width: 0.2, height: 0.04 },
+            ..Default::default()
+        };
+        cfg.score_regions[1] = RelativeRect { x: 0.5, y: 0.4, width: 0.8, height: 0.02 };
+        cfg.end_button_region.height = 0.0;
+
+        let issues = cfg.validate();
+        assert_eq!(issues.len(), 3, "{:?}", issues);
+        let d = AutomationConfig::default();
+        assert_eq!(cfg.skip_button_region.x, d.skip_button_region.x);
+        assert_eq!(cfg.score_regions[1].width, d.score_regions[1].width);
+        assert_eq!(cfg.end_button_region.height, d.end_button_region.height);
+        // Untouched neighbours keep their values.
+        assert_eq!(cfg.score_regions[0].y, d.score_regions[0].y);
+    }
+
+    #[test]
+    fn invalid_fields_lists_field_names() {
+        let mut cfg = AutomationConfig::default();
+        cfg.score_regions[1].height = 0.0;
+        cfg.min_valid_score = cfg.max_valid_score + 1;
+        assert_eq!(cfg.invalid_fields(), ["score_regions[1]", "min_valid_score"]);
+        // Only a copy is validated
+        assert_eq!(cfg.score_regions[1].height, 0.0);
+    }
+
+    #[test]
+    fn validate_rejects_csv_header_with_wrong_column_count() {
+        let mut cfg = AutomationConfig {
+            csv_header: crate::automation::csv_writer::CSV_HEADER.replace("iteration", "run"),
+            ..Default::default()
+        };
+        assert!(cfg.validate().is_empty());
+
+        cfg.csv_header = "run,time,file".to_string();
+        assert_eq!(cfg.validate().len(), 1);
+        assert!(cfg.csv_header.is_empty());
+
+        // A quoted name containing a comma counts as a single column
+        cfg.csv_header = "run,time,file,a1,a2,a3,b1,b2,b3,c1,c2,c3,\"flag, ok\",sum,t1,t2,t3".to_string();
+        assert!(cfg.validate().is_empty());
+        assert!(!cfg.csv_header.is_empty());
+    }
+
+    #[test]
+    fn validate_rejects_csv_header_with_known_columns_out_of_place() {
+        let mut cfg = AutomationConfig::default();
+        let renamed = "run,time,file,a1,a2,a3,b1,b2,b3,c1,c2,c3,flag,sum,t1,t2,t3";
+        cfg.csv_header = renamed.to_string();
+        assert!(cfg.validate().is_empty());
+
+        let swapped = crate::automation::csv_writer::CSV_HEADER.replace("s1c1,s1c2", "s1c2,s1c1");
+        cfg.csv_header = swapped;
+        assert_eq!(cfg.validate().len(), 1);
+        assert!(cfg.csv_header.is_empty());
+
+        cfg.csv_header = renamed.replace("sum,t1", "t1,total");
+        assert_eq!(cfg.validate().len(), 1);
+        assert!(cfg.csv_header.is_empty());
+    }
+
+    #[test]
+    fn validate_resets_invalid_active_subrect_to_full_region() {
+        let mut cfg = AutomationConfig::default();
+        let inner = RelativeRect { x: 0.2, y: 0.25, width: 0.6, height: 0.5 };
+        cfg.skip_button_active_subrect = Some(inner);
+        assert!(cfg.validate().is_empty());
+
+        cfg.skip_button_active_subrect = Some(RelativeRect { x: 0.5, y: 0.0, width: 0.8, height: 1.0 });
+        assert_eq!(cfg.validate().len(), 1);
+        assert!(cfg.skip_button_active_subrect.is_none());
+    }
+
+    #[test]
+    fn validate_resets_thresholds_and_timeouts() {
+        let mut cfg = AutomationConfig {
+            histogram_threshold: 85.0,
+            brightness_threshold: 300.0,
+            loading_timeout_ms: 0,
+            start_button: ButtonConfig { x: -0.1, y: 0.5, ..Default::default() },
+            ..Default::default()
+        };
+
+        let issues = cfg.validate();
+        assert_eq!(issues.len(), 4, "{:?}", issues);
+        assert_eq!(cfg.histogram_threshold, default_histogram_threshold());
+        assert_eq!(cfg.brightness_threshold, 94.0);
+        assert_eq!(cfg.loading_timeout_ms, 30000);
+        assert_eq!(cfg.start_button.x, 0.5);
+    }
+
+    #[test]
+    fn validate_resets_too_short_timed_capture_interval() {
+        let mut cfg = AutomationConfig { timed_capture_interval_ms: 0, ..Default::default() };
+
+        let issues = cfg.validate();
+        assert_eq!(issues.len(), 1, "{:?}", issues);
+        assert_eq!(cfg.timed_capture_interval_ms, 5000);
+    }
+
+    #[test]
+    fn screenshot_file_name_expands_template() {
+        let mut cfg = AutomationConfig::default();
+        let ts = "20260101_120000";
+        assert_eq!(cfg.screenshot_file_name(Some(7), (1920, 1080), ts), "007_20260101_120000.png");
+        assert_eq!(cfg.screenshot_file_name(None, (1920, 1080), ts), "gakumas_20260101_120000.png");
+
+        cfg.screenshot_name_template = "{iter}_{profile}_{w}x{h}_{ts}".to_string();
+        assert_eq!(
+            cfg.screenshot_file_name(Some(7), (1920, 1080), ts),
+            "007_default_1920x1080_20260101_120000.png"
+        );
+        cfg.profile_name = "vocal".to_string();
+        assert_eq!(
+            cfg.screenshot_file_name(None, (800, 600), ts),
+            "gakumas_vocal_800x600_20260101_120000.png"
+        );
+
+        // A profile name that makes the file name illegal falls back to the default
+        cfg.profile_name = "a:b".to_string();
+        assert_eq!(cfg.screenshot_file_name(Some(7), (1920, 1080), ts), "007_20260101_120000.png");
+    }
+
+    #[test]
+    fn validate_rejects_unusable_name_templates() {
+        for template in ["{ts}_{iter}", "{iter}{ts}", "{iter}_{date}", "{iter}_a?b", "{iter}_x."] {
+            let mut cfg = AutomationConfig {
+                screenshot_name_template: template.to_string(),
+                ..Default::default()
+            };
+            assert_eq!(cfg.validate().len(), 1, "{}", template);
+            assert_eq!(cfg.screenshot_name_template, "");
+        }
+        let mut cfg = AutomationConfig {
+            screenshot_name_template: "{iter}".to_string(),
+            ..Default::default()
+        };
+        assert!(cfg.validate().is_empty());
+        assert_eq!(file_name_problem("com1").unwrap(), "is the reserved device name COM1");
+        assert!(file_name_problem("com10").is_none());
+    }
+
+    #[test]
+    fn validate_resets_out_of_range_click_hold() {
+        let mut cfg = AutomationConfig {
+            skip_button: ButtonConfig { x: 0.7, y: 0.7, click_hold_ms: 0, double_click: true },
+            ..Default::default()
+        };
+
+        let issues = cfg.validate();
+        assert_eq!(issues.len(), 1, "{:?}", issues);
+        assert_eq!(issues[0].field, "skip_button.click_hold_ms");
+        assert_eq!(cfg.skip_button.click_hold_ms, 50);
+        assert_eq!((cfg.skip_button.x, cfg.skip_button.y), (0.7, 0.7));
+        assert!(cfg.skip_button.double_click);
+
+        // Configs written before these fields existed load with the old behaviour.
+        let b: ButtonConfig = serde_json::from_str(r#"{"x": 0.1, "y": 0.2}"#).unwrap();
+        assert_eq!(b.click_hold_ms, 50);
+        assert!(!b.double_click);
+    }
+
+    #[test]
+    fn detection_stable_count_is_read_as_confirm_count() {
+        let mut json = serde_json::to_value(AutomationConfig::default()).unwrap();
+        let obj = json.as_object_mut().unwrap();
+        obj.remove("detection_confirm_count");
+        obj.insert("detection_stable_count".to_string(), serde_json::json!(5));
+        let cfg: AutomationConfig = serde_json::from_value(json).unwrap();
+        assert_eq!(cfg.detection_confirm_count, 5);
+    }
+
+    #[test]
+    fn end_key_is_optional() {
+        let mut json = serde_json::to_value(AutomationConfig::default()).unwrap();
+        json.as_object_mut().unwrap().remove("end_key");
+        let cfg: AutomationConfig = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(cfg.end_key, None);
+
+        json["end_key"] = serde_json::json!("Escape");
+        let cfg: AutomationConfig = serde_json::from_value(json).unwrap();
+        assert_eq!(cfg.end_key, Some(NavigationKey::Escape));
+    }
+
+    #[test]
+    fn schema_describes_config_fields() {
+        let schema: serde_json::Value =
+            serde_json::from_str(&config_schema_json().unwrap()).unwrap();
+        let properties = &schema["properties"];
+        assert!(properties["$schema"].is_object());
+        assert!(properties["start_button"].is_object());
+        assert!(properties["ocr_psm"].is_object());
+
+        // Every serialized field is described
+        let config = serde_json::to_value(AutomationConfig::default()).unwrap();
+        for key in config.as_object().unwrap().keys() {
+            assert!(properties.get(key).is_some(), "{} missing from schema", key);
+        }
+    }
+
+    #[test]
+    fn schema_ref_left_out_of_copies() {
+        let config = AutomationConfig::default();
+        let saved = serde_json::to_value(&config).unwrap();
+        assert_eq!(saved["$schema"], "./config.schema.json");
+        let copy = serde_json::to_value(config.without_schema_ref()).unwrap();
+        assert!(copy.get("$schema").is_none());
+        // A copy without it still loads
+        let loaded: AutomationConfig = serde_json::from_value(copy).unwrap();
+        assert_eq!(loaded.schema, "./config.schema.json");
+    }
+
+    #[test]
+    fn write_if_changed_rewrites_only_different_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join(CONFIG_SCHEMA_FILE);
+        assert!(write_if_changed(&path, "{}").unwrap());
+        assert!(!write_if_changed(&path, "{}").unwrap());
+        assert!(write_if_changed(&path, "{\"type\": \"object\"}").unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"type\": \"object\"}");
+    }
+
+    #[test]
+    fn foreground_mode_defaults_to_each_click() {
+        let mut json = serde_json::to_value(AutomationConfig::default()).unwrap();
+        json.as_object_mut().unwrap().remove("foreground_mode");
+        let cfg: AutomationConfig = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(cfg.foreground_mode, ForegroundMode::EachClick);
+
+        json["foreground_mode"] = serde_json::json!("Never");
+        let cfg: AutomationConfig = serde_json::from_value(json).unwrap();
+        assert_eq!(cfg.foreground_mode, ForegroundMode::Never);
+    }
+
+    #[test]
+    fn score_regions_only_remaps_into_score_area() {
+        let mut cfg = AutomationConfig {
+            score_regions: [
+                RelativeRect { x: 0.1, y: 0.2, width: 0.8, height: 0.05 },
+                RelativeRect { x: 0.1, y: 0.4, width: 0.8, height: 0.05 },
+                RelativeRect { x: 0.1, y: 0.6, width: 0.8, height: 0.05 },
+            ],
+            total_regions: [RelativeRect { x: 0.5, y: 0.15, width: 0.2, height: 0.05 }; 3],
+            bonus_regions: [RelativeRect { x: 0.7, y: 0.6, width: 0.25, height: 0.1 }; 3],
+            ..Default::default()
+        };
+
+        let area = cfg.score_area();
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-5;
+        assert!(close(area.x, 0.1) && close(area.y, 0.15));
+        assert!(close(area.width, 0.85) && close(area.height, 0.55));
+
+        // FullWindow screenshots use the regions as configured.
+        assert!(close(cfg.screenshot_regions()[0][1].y, 0.4));
+
+        cfg.capture_mode = CaptureMode::ScoreRegionsOnly;
+        let [scores, totals, _] = cfg.screenshot_regions();
+        assert!(close(scores[0].x, 0.0) && close(scores[0].width, 0.8 / 0.85));
+        assert!(close(scores[1].y, 0.25 / 0.55));
+        assert!(close(totals[0].y, 0.0));
+    }
 }
@@ -1,10 +1,15 @@
 //! CSV writer for automation results.
 //!
-//! Writes OCR results to a CSV file in append-only mode for crash safety.
+//! Writes OCR results to a CSV file in append-only mode for crash safety: each
+//! row is flushed as it is written and the file is fsynced periodically.
 //! Each row contains: iteration, timestamp, screenshot path, and 9 score values.
+//! Text fields are quoted per RFC 4180 when needed (see `csv_field`), so a
+//! screenshot path containing a comma stays one field.
 
+use crate::automation::config::AutomationConfig;
 use crate::automation::queue::OcrWorkItem;
 use anyhow::{Context, Result};
+use std::borrow::Cow;
 use std::fs::{File, OpenOptions};
 use std::io::{BufRead, BufReader, Write};
 use std::path::Path;
@@ -12,18 +17,58 @@ use std::path::Path;
 /// CSV header row.
 /// Columns: iteration, timestamp, screenshot, then 9 scores (3 stages × 3
 /// criteria each), then a `recovery` flag (`ok`/`repaired`/`flagged`, the worst
-/// of the three stages' overlap-reconstruction outcomes).
+/// of the three stages' overlap-reconstruction outcomes), then `total` (the sum
+/// of the 9 scores) and the OCR'd stage totals `s1_total..s3_total` (empty when
+/// that total could not be read), for cross-checking without the screenshots
+/// (`analysis::csv_reader::RunData::total_mismatches`).
 ///
-/// `recovery` is the 13th column, appended after the original 12. Readers index
-/// the first 12 by position (see `analysis::csv_reader`), so older `results.csv`
-/// files that lack it remain readable; when resuming such a file the header is
-/// preserved (it simply won't name the trailing column).
-const CSV_HEADER: &str = "iteration,timestamp,screenshot,s1c1,s1c2,s1c3,s2c1,s2c2,s2c3,s3c1,s3c2,s3c3,recovery";
+/// `recovery` is the 13th column, appended after the original 12, and the total
+/// columns follow it. `analysis::csv_reader` finds columns by header name, and
+/// the review loader indexes the first 12 by position, so older `results.csv`
+/// files that lack them remain readable; when resuming such a file the header
+/// is preserved (it simply won't name the trailing columns). The `csv_header`
+/// config option renames the columns but never reorders them.
+pub const CSV_HEADER: &str = "iteration,timestamp,screenshot,s1c1,s1c2,s1c3,s2c1,s2c2,s2c3,s3c1,s3c2,s3c3,recovery,total,s1_total,s2_total,s3_total";
 
-/// Initializes CSV file with header if it doesn't exist or is empty.
+/// Header line written to `results.csv`: the configured `csv_header`, or
+/// `CSV_HEADER` when none is set.
+pub fn results_header(config: &AutomationConfig) -> &str {
+    if config.csv_header.trim().is_empty() {
+        CSV_HEADER
+    } else {
+        config.csv_header.trim()
+    }
+}
+
+/// The trailing `total,s1_total,s2_total,s3_total` fields of a result row:
+/// the sum of `scores`, then each OCR'd stage total (empty when unread).
+pub fn total_fields(scores: &[[u32; 3]; 3], stage_totals: &[Option<u32>; 3]) -> String {
+    let total: u64 = scores.iter().flatten().map(|&s| s as u64).sum();
+    let stage = |t: Option<u32>| t.map(|t| t.to_string()).unwrap_or_default();
+    format!(
+        "{},{},{},{}",
+        total,
+        stage(stage_totals[0]),
+        stage(stage_totals[1]),
+        stage(stage_totals[2])
+    )
+}
+
+/// `value` as a CSV field: wrapped in double quotes (with embedded quotes
+/// doubled) when it contains a comma, quote or line break, else unchanged.
+/// `analysis::csv_reader::split_csv_line` reads it back.
+pub fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// Initializes CSV file with `header` if it doesn't exist or is empty.
 ///
 /// If the file exists and has content, this does nothing (preserves existing data).
-pub fn init_csv(path: &Path) -> Result<()> {
+pub fn init_csv(path: &Path, header: &str) -> Result<()> {
     if path.exists() {
         // Check if file has content
         let file = File::open(path).context("Failed to open existing CSV")?;
@@ -36,77 +81,110 @@ pub fn init_csv(path: &Path) -> Result<()> {
 
     // Create new file with header
     let mut file = File::create(path).context("Failed to create CSV file")?;
-    writeln!(file, "{}", CSV_HEADER).context("Failed to write CSV header")?;
+    writeln!(file, "{}", header).context("Failed to write CSV header")?;
     Ok(())
 }
 
-/// Appends just the 9 scores (comma-separated, no header) to rehearsal_data.csv.
+/// Append-mode writer for one result CSV, held open for a whole run.
 ///
-/// This file contains only raw score data for easy external processing.
-pub fn append_to_raw_csv(path: &Path, scores: &[[u32; 3]; 3]) -> Result<()> {
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(path)
-        .context("Failed to open raw CSV for append")?;
-
-    // Format: s1c1,s1c2,s1c3,s2c1,s2c2,s2c3,s3c1,s3c2,s3c3 (no header, just scores)
-    let line = format!(
-        "{},{},{},{},{},{},{},{},{}",
-        scores[0][0],
-        scores[0][1],
-        scores[0][2],
-        scores[1][0],
-        scores[1][1],
-        scores[1][2],
-        scores[2][0],
-        scores[2][1],
-        scores[2][2],
-    );
-
-    writeln!(file, "{}", line).context("Failed to write raw CSV row")?;
-    Ok(())
+/// Every row is flushed to the OS as soon as it is written, so a crash of this
+/// process loses at most the in-flight row. Every `sync_interval` rows the file
+/// is also fsynced (`sync_all`), bounding what a power loss or OS crash can
+/// lose; any unsynced tail is synced when the writer is dropped.
+pub struct CsvWriter {
+    file: File,
+    sync_interval: u32,
+    unsynced_rows: u32,
 }
 
-/// Appends one result row to the CSV file.
-///
-/// Opens the file in append mode for each write, ensuring crash safety.
-/// If OCR fails partway through automation, completed results are already saved.
-///
-/// `recovery` is the overlap-reconstruction outcome for this row
-/// (`ok`/`repaired`/`flagged`), written as the trailing column.
-pub fn append_to_csv(
-    path: &Path,
-    work_item: &OcrWorkItem,
-    scores: &[[u32; 3]; 3],
-    recovery: &str,
-) -> Result<()> {
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(path)
-        .context("Failed to open CSV for append")?;
-
-    // Format: iteration,timestamp,screenshot,s1c1..s3c3,recovery
-    let line = format!(
-        "{},{},{},{},{},{},{},{},{},{},{},{},{}",
-        work_item.iteration,
-        work_item.captured_at.format("%Y-%m-%dT%H:%M:%S"),
-        work_item.screenshot_path.display(),
-        scores[0][0],
-        scores[0][1],
-        scores[0][2],
-        scores[1][0],
-        scores[1][1],
-        scores[1][2],
-        scores[2][0],
-        scores[2][1],
-        scores[2][2],
-        recovery,
-    );
-
-    writeln!(file, "{}", line).context("Failed to write CSV row")?;
-    Ok(())
+impl CsvWriter {
+    /// Opens `path` for appending, creating it if needed. `sync_interval` is
+    /// the number of rows between fsyncs (0 = only on drop).
+    pub fn open(path: &Path, sync_interval: u32) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .context("Failed to open CSV for append")?;
+        Ok(Self { file, sync_interval, unsynced_rows: 0 })
+    }
+
+    /// Appends one result row to `results.csv`.
+    ///
+    /// `recovery` is the overlap-reconstruction outcome for this row
+    /// (`ok`/`repaired`/`flagged`); `stage_totals` are the OCR'd stage totals,
+    /// written after the computed grand total.
+    pub fn append_row(
+        &mut self,
+        work_item: &OcrWorkItem,
+        scores: &[[u32; 3]; 3],
+        recovery: &str,
+        stage_totals: &[Option<u32>; 3],
+    ) -> Result<()> {
+        // Format: iteration,timestamp,screenshot,s1c1..s3c3,recovery,total,s1_total..s3_total
+        let line = format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            work_item.iteration,
+            work_item.captured_at.format("%Y-%m-%dT%H:%M:%S"),
+            csv_field(&work_item.screenshot_path.display().to_string()),
+            scores[0][0],
+            scores[0][1],
+            scores[0][2],
+            scores[1][0],
+            scores[1][1],
+            scores[1][2],
+            scores[2][0],
+            scores[2][1],
+            scores[2][2],
+            recovery,
+            total_fields(scores, stage_totals),
+        );
+        self.write_line(&line).context("Failed to write CSV row")
+    }
+
+    /// Appends just the 9 scores (comma-separated, no header) to
+    /// `rehearsal_data.csv`.
+    ///
+    /// This file contains only raw score data for easy external processing.
+    pub fn append_raw_row(&mut self, scores: &[[u32; 3]; 3]) -> Result<()> {
+        // Format: s1c1,s1c2,s1c3,s2c1,s2c2,s2c3,s3c1,s3c2,s3c3 (no header, just scores)
+        let line = format!(
+            "{},{},{},{},{},{},{},{},{}",
+            scores[0][0],
+            scores[0][1],
+            scores[0][2],
+            scores[1][0],
+            scores[1][1],
+            scores[1][2],
+            scores[2][0],
+            scores[2][1],
+            scores[2][2],
+        );
+        self.write_line(&line).context("Failed to write raw CSV row")
+    }
+
+    /// Writes and flushes one line, fsyncing once `sync_interval` rows have
+    /// accumulated since the last sync.
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        writeln!(self.file, "{}", line)?;
+        self.file.flush()?;
+        self.unsynced_rows += 1;
+        if self.sync_interval > 0 && self.unsynced_rows >= self.sync_interval {
+            self.file.sync_all()?;
+            self.unsynced_rows = 0;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for CsvWriter {
+    fn drop(&mut self) {
+        if self.unsynced_rows > 0
+            && let Err(e) = self.file.sync_all()
+        {
+            crate::log(&format!("CSV writer: final sync failed: {}", e));
+        }
+    }
 }
 
 #[cfg(test)]
@@ -120,7 +198,7 @@ mod tests {
         let dir = tempdir().unwrap();
         let csv_path = dir.path().join("test.csv");
 
-        init_csv(&csv_path).unwrap();
+        init_csv(&csv_path, CSV_HEADER).unwrap();
 
         let content = std::fs::read_to_string(&csv_path).unwrap();
         assert!(content.starts_with(CSV_HEADER));
@@ -134,7 +212,7 @@ mod tests {
         // Write some existing content
         std::fs::write(&csv_path, "existing,data\n1,2,3\n").unwrap();
 
-        init_csv(&csv_path).unwrap();
+        init_csv(&csv_path, CSV_HEADER).unwrap();
 
         let content = std::fs::read_to_string(&csv_path).unwrap();
         assert!(content.starts_with("existing,data"));
@@ -145,12 +223,15 @@ mod tests {
         let dir = tempdir().unwrap();
         let csv_path = dir.path().join("test.csv");
 
-        init_csv(&csv_path).unwrap();
+        init_csv(&csv_path, CSV_HEADER).unwrap();
 
         let work_item = OcrWorkItem::new(PathBuf::from("screenshots/001.png"), 1);
         let scores = [[100, 200, 300], [400, 500, 600], [700, 800, 900]];
 
-        append_to_csv(&csv_path, &work_item, &scores, "ok").unwrap();
+        CsvWriter::open(&csv_path, 1)
+            .unwrap()
+            .append_row(&work_item, &scores, "ok", &[Some(600), None, Some(2400)])
+            .unwrap();
 
         let content = std::fs::read_to_string(&csv_path).unwrap();
         let lines: Vec<&str> = content.lines().collect();
@@ -158,7 +239,35 @@ mod tests {
         assert_eq!(lines.len(), 2); // header + 1 data row
         assert!(lines[1].contains("screenshots/001.png"));
         assert!(lines[1].contains("100,200,300,400,500,600,700,800,900"));
-        assert!(lines[1].ends_with(",ok"));
+        assert!(lines[1].ends_with(",ok,4500,600,,2400"));
+    }
+
+    #[test]
+    fn test_csv_field_quotes_when_needed() {
+        assert_eq!(csv_field("screenshots/001.png"), "screenshots/001.png");
+        assert_eq!(csv_field(r"C:\Users\a,b\shot.png"), r#""C:\Users\a,b\shot.png""#);
+        assert_eq!(csv_field(r#"say "hi""#), r#""say ""hi""""#);
+    }
+
+    #[test]
+    fn test_append_row_path_with_comma_reads_back() {
+        let dir = tempdir().unwrap();
+        let csv_path = dir.path().join("results.csv");
+        init_csv(&csv_path, CSV_HEADER).unwrap();
+
+        let path = r"C:\Users\a,b\shot.png";
+        let work_item = OcrWorkItem::new(PathBuf::from(path), 1);
+        let scores = [[100, 200, 300], [400, 500, 600], [700, 800, 900]];
+        CsvWriter::open(&csv_path, 1)
+            .unwrap()
+            .append_row(&work_item, &scores, "ok", &[None; 3])
+            .unwrap();
+
+        let dataset = crate::analysis::DataSet::from_csv(&csv_path).unwrap();
+        assert_eq!(dataset.len(), 1);
+        assert_eq!(dataset.runs[0].screenshot_path, path);
+        assert_eq!(dataset.runs[0].scores[0], vec![100, 200, 300]);
+        assert_eq!(dataset.runs[0].recovery.as_deref(), Some("ok"));
     }
 
     #[test]
@@ -166,12 +275,15 @@ mod tests {
         let dir = tempdir().unwrap();
         let csv_path = dir.path().join("test.csv");
 
-        init_csv(&csv_path).unwrap();
+        init_csv(&csv_path, CSV_HEADER).unwrap();
 
         for i in 1..=3 {
             let work_item = OcrWorkItem::new(PathBuf::from(format!("screenshots/{:03}.png", i)), i);
             let scores = [[i * 100, i * 100, i * 100]; 3];
-            append_to_csv(&csv_path, &work_item, &scores, "ok").unwrap();
+            CsvWriter::open(&csv_path, 1)
+                .unwrap()
+                .append_row(&work_item, &scores, "ok", &[None; 3])
+                .unwrap();
         }
 
         let content = std::fs::read_to_string(&csv_path).unwrap();
@@ -179,4 +291,40 @@ mod tests {
 
         assert_eq!(lines.len(), 4); // header + 3 data rows
     }
+
+    #[test]
+    fn test_rows_on_disk_after_writer_dropped() {
+        let dir = tempdir().unwrap();
+        let csv_path = dir.path().join("test.csv");
+
+        init_csv(&csv_path, CSV_HEADER).unwrap();
+
+        // Interval larger than the row count: the tail is only synced on drop.
+        let mut writer = CsvWriter::open(&csv_path, 4).unwrap();
+        for i in 1..=5 {
+            let work_item = OcrWorkItem::new(PathBuf::from(format!("screenshots/{:03}.png", i)), i);
+            writer.append_row(&work_item, &[[i; 3]; 3], "ok", &[None; 3]).unwrap();
+        }
+        drop(writer);
+
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+
+        assert_eq!(lines.len(), 6); // header + 5 data rows
+        assert_eq!(lines[0], CSV_HEADER);
+        assert!(lines[5].starts_with("5,"));
+    }
+
+    #[test]
+    fn test_append_raw_row() {
+        let dir = tempdir().unwrap();
+        let raw_path = dir.path().join("rehearsal_data.csv");
+
+        let mut writer = CsvWriter::open(&raw_path, 0).unwrap();
+        writer.append_raw_row(&[[1, 2, 3], [4, 5, 6], [7, 8, 9]]).unwrap();
+        drop(writer);
+
+        let content = std::fs::read_to_string(&raw_path).unwrap();
+        assert_eq!(content.trim_end(), "1,2,3,4,5,6,7,8,9");
+    }
 }
@@ -4,6 +4,7 @@
 //! by monitoring screen regions using:
 //! - Histogram comparison: Detect when Skip button appears (matches reference image)
 //! - Brightness analysis: Detect when Skip button becomes enabled (not dimmed)
+//! - Difference hashing: Detect a result screen captured twice in a row
 //!
 //! The histogram comparison is resolution-independent: captured regions are resized
 //! to match the reference image dimensions before comparison.
@@ -15,10 +16,14 @@ use std::sync::atomic::Ordering;
 use std::time::{Duration, Instant};
 use windows::Win32::Foundation::HWND;
 
-use crate::automation::config::{AutomationConfig, RelativeRect};
-use crate::automation::input::click_at_relative;
+use crate::automation::config::{
+    AutomationConfig, ButtonConfig, DetectionMetric, ForegroundMode, NavigationKey, RelativeRect,
+};
+use crate::automation::input::{click_button, press_navigation_key};
 use crate::automation::state::ABORT_REQUESTED;
 use crate::capture::region::capture_region;
+use crate::error::GakumasError;
+use crate::ocr::preprocess::crop_region;
 
 /// Calculates the average brightness (luminance) of an image.
 ///
@@ -43,6 +48,15 @@ pub fn calculate_brightness(img: &ImageBuffer<Rgba<u8>, Vec<u8>>) -> f32 {
     (total / pixel_count) as f32
 }
 
+/// Average brightness of `subrect` (relative to `img`), or of the whole image
+/// when `subrect` is `None`.
+fn subrect_brightness(img: &ImageBuffer<Rgba<u8>, Vec<u8>>, subrect: Option<&RelativeRect>) -> f32 {
+    match subrect {
+        Some(subrect) => calculate_brightness(&crop_region(img, subrect)),
+        None => calculate_brightness(img),
+    }
+}
+
 /// Calculates a grayscale histogram for an image.
 ///
 /// Returns an array of 256 bins representing the distribution of pixel intensities.
@@ -71,11 +85,26 @@ fn calculate_histogram(img: &ImageBuffer<Rgba<u8>, Vec<u8>>) -> [f32; 256] {
     normalized
 }
 
-/// Calculates histogram similarity using Bhattacharyya coefficient.
+/// Calculates histogram similarity using the configured `metric`.
+///
+/// Returns a value from 0.0 (completely different) to 1.0 (identical). With
+/// `Both`, the lower of the two scores is returned, so a single threshold check
+/// requires both metrics to pass.
+fn histogram_similarity(hist1: &[f32; 256], hist2: &[f32; 256], metric: DetectionMetric) -> f32 {
+    match metric {
+        DetectionMetric::Bhattacharyya => bhattacharyya_coefficient(hist1, hist2),
+        DetectionMetric::Correlation => histogram_correlation(hist1, hist2),
+        DetectionMetric::Both => {
+            bhattacharyya_coefficient(hist1, hist2).min(histogram_correlation(hist1, hist2))
+        }
+    }
+}
+
+/// Bhattacharyya coefficient of two normalized histograms.
 ///
-/// Returns a value from 0.0 (completely different) to 1.0 (identical).
-/// This metric is robust to lighting changes and works well for template matching.
-fn histogram_similarity(hist1: &[f32; 256], hist2: &[f32; 256]) -> f32 {
+/// Robust to lighting changes and works well for template matching, but a
+/// histogram shifted by a few intensity levels still overlaps heavily.
+fn bhattacharyya_coefficient(hist1: &[f32; 256], hist2: &[f32; 256]) -> f32 {
     let mut bc = 0.0f32;
     for i in 0..256 {
         bc += (hist1[i] * hist2[i]).sqrt();
@@ -83,6 +112,28 @@ fn histogram_similarity(hist1: &[f32; 256], hist2: &[f32; 256]) -> f32 {
     bc
 }
 
+/// Pearson correlation of two histograms, clamped to [0.0, 1.0].
+///
+/// Compares the shape bin by bin, so it falls off much faster than the
+/// Bhattacharyya coefficient when one histogram is shifted. A flat histogram
+/// has no variance; it only correlates with another flat one.
+fn histogram_correlation(hist1: &[f32; 256], hist2: &[f32; 256]) -> f32 {
+    let mean1 = hist1.iter().sum::<f32>() / 256.0;
+    let mean2 = hist2.iter().sum::<f32>() / 256.0;
+    let (mut cov, mut var1, mut var2) = (0.0f32, 0.0f32, 0.0f32);
+    for i in 0..256 {
+        let a = hist1[i] - mean1;
+        let b = hist2[i] - mean2;
+        cov += a * b;
+        var1 += a * a;
+        var2 += b * b;
+    }
+    if var1 == 0.0 || var2 == 0.0 {
+        return if var1 == var2 { 1.0 } else { 0.0 };
+    }
+    (cov / (var1 * var2).sqrt()).clamp(0.0, 1.0)
+}
+
 /// Reference image data including histogram and dimensions for resolution-independent matching.
 pub struct ReferenceImage {
     /// Normalized grayscale histogram (256 bins)
@@ -99,16 +150,20 @@ pub struct ReferenceImage {
 pub struct ClickRetryInfo<'a> {
     /// Window handle for clicking and capturing
     pub hwnd: HWND,
-    /// Relative X position of the button to retry
-    pub button_x: f32,
-    /// Relative Y position of the button to retry
-    pub button_y: f32,
+    /// Button to retry (position and click style)
+    pub button: &'a ButtonConfig,
+    /// Key to press instead of clicking `button`, if one is configured
+    pub key: Option<NavigationKey>,
     /// Region to capture for similarity check
     pub button_region: &'a RelativeRect,
     /// Reference image to compare against
     pub ref_img: &'a ReferenceImage,
     /// Similarity threshold above which the button is considered still visible
     pub histogram_threshold: f32,
+    /// Metric the similarity is computed with
+    pub metric: DetectionMetric,
+    /// Whether the retry brings the game to the foreground first
+    pub foreground: ForegroundMode,
     /// Maximum number of retry clicks allowed
     pub max_retries: u32,
 }
@@ -132,7 +187,7 @@ fn maybe_retry_click(
     }
 
     // Check if previous button is still visible
-    match check_button_similarity(info.hwnd, info.button_region, info.ref_img) {
+    match check_button_similarity(info.hwnd, info.button_region, info.ref_img, info.metric) {
         Ok(similarity) => {
             if similarity >= info.histogram_threshold {
                 *retries_used += 1;
@@ -140,7 +195,11 @@ fn maybe_retry_click(
                     "Previous button still visible (similarity = {:.3}), retry click {}/{}",
                     similarity, *retries_used, info.max_retries
                 ));
-                if let Err(e) = click_at_relative(info.hwnd, info.button_x, info.button_y) {
+                let retry = match info.key {
+                    Some(key) => press_navigation_key(info.hwnd, key, info.foreground),
+                    None => click_button(info.hwnd, info.button, info.foreground),
+                };
+                if let Err(e) = retry {
                     crate::log(&format!("Warning: Retry click failed: {}", e));
                 }
                 true
@@ -176,6 +235,30 @@ pub fn load_reference_histogram(path: &Path) -> Result<ReferenceImage> {
     })
 }
 
+/// Relative width/height difference between a reference image and the live
+/// region above which the reference is treated as captured at another window size.
+const REFERENCE_SIZE_TOLERANCE: f32 = 0.15;
+
+/// Logs a warning when the live region is a significantly different size from
+/// the reference image. Comparison still works (the capture is resized to the
+/// reference first), but a template taken at another window size has different
+/// anti-aliasing and scaling, so similarity scores quietly drop.
+fn warn_if_reference_size_mismatch(name: &str, ref_img: &ReferenceImage, captured: (u32, u32)) {
+    let differs = |reference: u32, live: u32| {
+        let reference = reference.max(1) as f32;
+        (live as f32 - reference).abs() / reference > REFERENCE_SIZE_TOLERANCE
+    };
+    let (ref_w, ref_h) = ref_img.dimensions;
+    if differs(ref_w, captured.0) || differs(ref_h, captured.1) {
+        crate::log(&format!(
+            "WARNING: {} button reference is {}x{} but the live region is {}x{}. \
+             The game window was probably resized since the reference was captured; \
+             detection may be unreliable. Recapture the {} reference at the current window size.",
+            name, ref_w, ref_h, captured.0, captured.1, name
+        ));
+    }
+}
+
 /// Resizes an image to target dimensions for resolution-independent comparison.
 fn resize_to_match(img: &ImageBuffer<Rgba<u8>, Vec<u8>>, target_width: u32, target_height: u32) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
     if img.width() == target_width && img.height() == target_height {
@@ -236,7 +319,7 @@ pub fn wait_for_loading(
     let last_click_time = Instant::now();
 
     // Try to load reference histogram
-    let ref_path = crate::paths::get_exe_dir().join(&config.skip_button_reference);
+    let ref_path = crate::paths::resolve_data_file(&config.skip_button_reference);
 
     let reference = if ref_path.exists() {
         match load_reference_histogram(&ref_path) {
@@ -262,7 +345,7 @@ pub fn wait_for_loading(
             "Warning: Reference image {} not found. Using brightness-only detection.",
             crate::paths::relative_display(&ref_path)
         ));
-        crate::log("Hint: Use 'Capture Skip Reference' from tray menu to create it.");
+        crate::log("Hint: Use 参照画像 → スキップボタンを撮影 in the GUI (or 'Capture Skip Reference' from the tray menu) to create it.");
         None
     };
 
@@ -271,25 +354,34 @@ pub fn wait_for_loading(
         crate::log("Phase 1: Waiting for Skip button to appear...");
         let confirm_needed = config.detection_confirm_count.max(1);
         let mut consecutive_matches: u32 = 0;
+        let mut size_checked = false;
         loop {
             if ABORT_REQUESTED.load(Ordering::SeqCst) {
                 return Err(anyhow!("Abort requested"));
             }
 
             if start.elapsed() > timeout {
-                return Err(anyhow!(
-                    "Timeout waiting for Skip button (phase 1) after {}ms",
-                    config.loading_timeout_ms
-                ));
+                return Err(GakumasError::DetectionTimeout {
+                    what: "Skip button (phase 1)",
+                    timeout_ms: config.loading_timeout_ms,
+                }
+                .into());
             }
 
-            let region_img = capture_region(hwnd, &config.skip_button_region)?;
-            // Resize to match reference dimensions for resolution-independent comparison
-            let resized = resize_to_match(&region_img, ref_img.dimensions.0, ref_img.dimensions.1);
-            let current_hist = calculate_histogram(&resized);
-            let similarity = histogram_similarity(&ref_img.histogram, &current_hist);
+            let check = check_page(
+                hwnd,
+                &config.skip_button_region,
+                ref_img,
+                config.histogram_threshold,
+                config.detection_metric,
+            )?;
+            if !size_checked {
+                warn_if_reference_size_mismatch("Skip", ref_img, check.captured_size);
+                size_checked = true;
+            }
+            let similarity = check.similarity;
 
-            if similarity >= config.histogram_threshold {
+            if check.matched {
                 consecutive_matches += 1;
                 crate::log(&format!(
                     "Phase 1: similarity = {:.3} - match {}/{} (threshold = {:.3})",
@@ -331,14 +423,16 @@ pub fn wait_for_loading(
         }
 
         if start.elapsed() > timeout {
-            return Err(anyhow!(
-                "Timeout waiting for Skip enabled (phase 2) after {}ms",
-                config.loading_timeout_ms
-            ));
+            return Err(GakumasError::DetectionTimeout {
+                what: "Skip enabled (phase 2)",
+                timeout_ms: config.loading_timeout_ms,
+            }
+            .into());
         }
 
         let region_img = capture_region(hwnd, &config.skip_button_region)?;
-        let brightness = calculate_brightness(&region_img);
+        let brightness =
+            subrect_brightness(&region_img, config.skip_button_active_subrect.as_ref());
 
         crate::log(&format!(
             "Phase 2: brightness = {:.2} (threshold = {:.2})",
@@ -356,11 +450,12 @@ pub fn wait_for_loading(
 
 /// Captures a region and returns its brightness value.
 ///
-/// This is a convenience function for calibration - it captures the specified
-/// region and calculates its brightness without any threshold checking.
+/// This is a convenience function for calibration - it captures the skip button
+/// region and calculates its brightness (over `skip_button_active_subrect`, if
+/// set) without any threshold checking.
 pub fn measure_region_brightness(hwnd: HWND, config: &AutomationConfig) -> Result<f32> {
     let region_img = capture_region(hwnd, &config.skip_button_region)?;
-    Ok(calculate_brightness(&region_img))
+    Ok(subrect_brightness(&region_img, config.skip_button_active_subrect.as_ref()))
 }
 
 /// Waits for the result page to appear by detecting the "終了" (End) button.
@@ -380,7 +475,7 @@ pub fn wait_for_result(
     let last_click_time = Instant::now();
 
     // Try to load reference histogram
-    let ref_path = crate::paths::get_exe_dir().join(&config.end_button_reference);
+    let ref_path = crate::paths::resolve_data_file(&config.end_button_reference);
 
     let reference = if ref_path.exists() {
         match load_reference_histogram(&ref_path) {
@@ -406,7 +501,7 @@ pub fn wait_for_result(
             "Warning: End button reference {} not found. Using fixed delay.",
             crate::paths::relative_display(&ref_path)
         ));
-        crate::log("Hint: Use 'Capture End Reference' from tray menu to create it.");
+        crate::log("Hint: Use 参照画像 → 終了ボタンを撮影 in the GUI (or 'Capture End Reference' from the tray menu) to create it.");
         None
     };
 
@@ -426,25 +521,34 @@ pub fn wait_for_result(
     crate::log("Waiting for End button to appear (result page)...");
     let confirm_needed = config.detection_confirm_count.max(1);
     let mut consecutive_matches: u32 = 0;
+    let mut size_checked = false;
     loop {
         if ABORT_REQUESTED.load(Ordering::SeqCst) {
             return Err(anyhow!("Abort requested"));
         }
 
         if start.elapsed() > timeout {
-            return Err(anyhow!(
-                "Timeout waiting for result page after {}ms",
-                config.result_timeout_ms
-            ));
+            return Err(GakumasError::DetectionTimeout {
+                what: "result page",
+                timeout_ms: config.result_timeout_ms,
+            }
+            .into());
         }
 
-        let region_img = capture_region(hwnd, &config.end_button_region)?;
-        // Resize to match reference dimensions for resolution-independent comparison
-        let resized = resize_to_match(&region_img, ref_img.dimensions.0, ref_img.dimensions.1);
-        let current_hist = calculate_histogram(&resized);
-        let similarity = histogram_similarity(&ref_img.histogram, &current_hist);
+        let check = check_page(
+            hwnd,
+            &config.end_button_region,
+            &ref_img,
+            config.histogram_threshold,
+            config.detection_metric,
+        )?;
+        if !size_checked {
+            warn_if_reference_size_mismatch("End", &ref_img, check.captured_size);
+            size_checked = true;
+        }
+        let similarity = check.similarity;
 
-        if similarity >= config.histogram_threshold {
+        if check.matched {
             consecutive_matches += 1;
             crate::log(&format!(
                 "Result page detection: similarity = {:.3} - match {}/{} (threshold = {:.3})",
@@ -478,10 +582,46 @@ pub fn wait_for_result(
     }
 }
 
+/// The whole client area, compared against `blocked_screen_reference`.
+const FULL_WINDOW: RelativeRect = RelativeRect { x: 0.0, y: 0.0, width: 1.0, height: 1.0 };
+
+/// Saves the whole game window as the blocked screen (maintenance or update
+/// notice) reference.
+pub fn save_blocked_screen_reference(hwnd: HWND, path: &Path) -> Result<()> {
+    let window_img = capture_region(hwnd, &FULL_WINDOW)?;
+    window_img.save(path)
+        .map_err(|e| anyhow!("Failed to save reference image: {}", e))?;
+    crate::log(&format!(
+        "Saved blocked screen reference to {}",
+        crate::paths::relative_display(path)
+    ));
+    Ok(())
+}
+
+/// Loads `blocked_screen_reference` when `blocked_screen_detection` is on. A
+/// missing or unreadable image only disables the check.
+fn load_blocked_screen_reference(config: &AutomationConfig) -> Option<ReferenceImage> {
+    if !config.blocked_screen_detection {
+        return None;
+    }
+    let path = crate::paths::resolve_data_file(&config.blocked_screen_reference);
+    match load_reference_histogram(&path) {
+        Ok(ref_img) => Some(ref_img),
+        Err(e) => {
+            crate::log(&format!("Warning: {}. Blocked screen detection disabled.", e));
+            None
+        }
+    }
+}
+
 /// Waits for the rehearsal start page to appear by detecting the "開始する" (Start) button.
 ///
 /// Uses histogram comparison against a reference image of the Start button region.
 /// Returns Ok(()) when the Start button is detected, or Err on timeout or abort.
+/// With `blocked_screen_detection` and a readable `blocked_screen_reference`,
+/// each poll captures the whole window instead of just the Start region, and a
+/// confirmed match of it against the reference fails at once with
+/// `GakumasError::GameNotReady`.
 ///
 /// If no reference image exists, returns immediately (assumes page is ready).
 pub fn wait_for_start_page(
@@ -495,7 +635,7 @@ pub fn wait_for_start_page(
     let last_click_time = Instant::now();
 
     // Try to load reference histogram
-    let ref_path = crate::paths::get_exe_dir().join(&config.start_button_reference);
+    let ref_path = crate::paths::resolve_data_file(&config.start_button_reference);
 
     let reference = if ref_path.exists() {
         match load_reference_histogram(&ref_path) {
@@ -521,7 +661,7 @@ pub fn wait_for_start_page(
             "Warning: Start button reference {} not found. Skipping page detection.",
             crate::paths::relative_display(&ref_path)
         ));
-        crate::log("Hint: Use 'Capture Start Reference' from tray menu to create it.");
+        crate::log("Hint: Use 参照画像 → 開始ボタンを撮影 in the GUI (or 'Capture Start Reference' from the tray menu) to create it.");
         None
     };
 
@@ -531,31 +671,56 @@ pub fn wait_for_start_page(
     }
 
     let ref_img = reference.unwrap();
+    let blocked_ref = load_blocked_screen_reference(config);
 
     // Wait for Start button to appear (histogram comparison)
     crate::log("Waiting for Start button to appear (rehearsal page)...");
     let confirm_needed = config.detection_confirm_count.max(1);
     let mut consecutive_matches: u32 = 0;
+    let mut blocked_matches: u32 = 0;
+    let mut size_checked = false;
     loop {
         if ABORT_REQUESTED.load(Ordering::SeqCst) {
             return Err(anyhow!("Abort requested"));
         }
 
         if start.elapsed() > timeout {
-            return Err(anyhow!(
-                "Timeout waiting for rehearsal page after {}ms",
-                config.loading_timeout_ms
-            ));
+            return Err(GakumasError::DetectionTimeout {
+                what: "rehearsal page",
+                timeout_ms: config.loading_timeout_ms,
+            }
+            .into());
         }
 
-        let region_img = capture_region(hwnd, &config.start_button_region)?;
-        // Resize to match reference dimensions for resolution-independent comparison
-        let resized = resize_to_match(&region_img, ref_img.dimensions.0, ref_img.dimensions.1);
-        let current_hist = calculate_histogram(&resized);
-        let similarity = histogram_similarity(&ref_img.histogram, &current_hist);
+        // Only a configured blocked screen check needs the whole window
+        let window_img = match &blocked_ref {
+            Some(_) => Some(capture_region(hwnd, &FULL_WINDOW)?),
+            None => None,
+        };
+        let check = match &window_img {
+            Some(img) => compare_to_reference(
+                &crop_region(img, &config.start_button_region),
+                &ref_img,
+                config.histogram_threshold,
+                config.detection_metric,
+            ),
+            None => check_page(
+                hwnd,
+                &config.start_button_region,
+                &ref_img,
+                config.histogram_threshold,
+                config.detection_metric,
+            )?,
+        };
+        if !size_checked {
+            warn_if_reference_size_mismatch("Start", &ref_img, check.captured_size);
+            size_checked = true;
+        }
+        let similarity = check.similarity;
 
-        if similarity >= config.histogram_threshold {
+        if check.matched {
             consecutive_matches += 1;
+            blocked_matches = 0;
             crate::log(&format!(
                 "Rehearsal page detection: similarity = {:.3} - match {}/{} (threshold = {:.3})",
                 similarity, consecutive_matches, confirm_needed, config.histogram_threshold
@@ -578,6 +743,27 @@ pub fn wait_for_start_page(
             }
             consecutive_matches = 0;
 
+            if let (Some(blocked), Some(img)) = (&blocked_ref, &window_img) {
+                let blocked_check = compare_to_reference(
+                    img,
+                    blocked,
+                    config.histogram_threshold,
+                    config.detection_metric,
+                );
+                if blocked_check.matched {
+                    blocked_matches += 1;
+                    crate::log(&format!(
+                        "Blocked screen detection: similarity = {:.3} - match {}/{}",
+                        blocked_check.similarity, blocked_matches, confirm_needed
+                    ));
+                    if blocked_matches >= confirm_needed {
+                        return Err(GakumasError::GameNotReady.into());
+                    }
+                } else {
+                    blocked_matches = 0;
+                }
+            }
+
             // Retry previous button click if needed
             if let Some(ref retry_info) = click_retry {
                 maybe_retry_click(retry_info, last_click_time.elapsed(), &mut retries_used);
@@ -588,6 +774,86 @@ pub fn wait_for_start_page(
     }
 }
 
+/// Result of a single capture + histogram comparison of a button region.
+#[derive(Clone, Copy, Debug)]
+pub struct PageCheck {
+    /// Histogram similarity to the reference (0.0 to 1.0)
+    pub similarity: f32,
+    /// Whether `similarity` reached the threshold
+    pub matched: bool,
+    /// Size of the captured region before resizing to the reference
+    pub captured_size: (u32, u32),
+}
+
+/// Captures `region` once and compares it against `ref_img`.
+///
+/// This is the single page-match rule shared by the `wait_for_*` loops and the
+/// `is_*_page` predicates: the region is resized to the reference dimensions
+/// (resolution-independent) and matches when its `metric` similarity >= `threshold`.
+pub fn check_page(
+    hwnd: HWND,
+    region: &RelativeRect,
+    ref_img: &ReferenceImage,
+    threshold: f32,
+    metric: DetectionMetric,
+) -> Result<PageCheck> {
+    let region_img = capture_region(hwnd, region)?;
+    Ok(compare_to_reference(&region_img, ref_img, threshold, metric))
+}
+
+/// The comparison behind `check_page`, on an already captured image.
+fn compare_to_reference(
+    img: &ImageBuffer<Rgba<u8>, Vec<u8>>,
+    ref_img: &ReferenceImage,
+    threshold: f32,
+    metric: DetectionMetric,
+) -> PageCheck {
+    let resized = resize_to_match(img, ref_img.dimensions.0, ref_img.dimensions.1);
+    let current_hist = calculate_histogram(&resized);
+    let similarity = histogram_similarity(&ref_img.histogram, &current_hist, metric);
+    PageCheck {
+        similarity,
+        matched: similarity >= threshold,
+        captured_size: img.dimensions(),
+    }
+}
+
+/// Whether the result page (End button) is on screen right now.
+///
+/// One capture and comparison against `end_button_reference`, no waiting or
+/// confirmation streak. Errors if the reference image cannot be loaded.
+pub fn is_result_page(hwnd: HWND, config: &AutomationConfig) -> Result<bool> {
+    let ref_img = load_reference_histogram(
+        &crate::paths::resolve_data_file(&config.end_button_reference),
+    )?;
+    let check = check_page(
+        hwnd,
+        &config.end_button_region,
+        &ref_img,
+        config.histogram_threshold,
+        config.detection_metric,
+    )?;
+    Ok(check.matched)
+}
+
+/// Whether the rehearsal start page (Start button) is on screen right now.
+///
+/// One capture and comparison against `start_button_reference`, no waiting or
+/// confirmation streak. Errors if the reference image cannot be loaded.
+pub fn is_rehearsal_page(hwnd: HWND, config: &AutomationConfig) -> Result<bool> {
+    let ref_img = load_reference_histogram(
+        &crate::paths::resolve_data_file(&config.start_button_reference),
+    )?;
+    let check = check_page(
+        hwnd,
+        &config.start_button_region,
+        &ref_img,
+        config.histogram_threshold,
+        config.detection_metric,
+    )?;
+    Ok(check.matched)
+}
+
 /// Checks the current histogram similarity of a button region against a reference image.
 ///
 /// Returns the similarity score (0.0 to 1.0). Useful for post-click verification
@@ -596,9 +862,246 @@ pub fn check_button_similarity(
     hwnd: HWND,
     region: &RelativeRect,
     ref_img: &ReferenceImage,
+    metric: DetectionMetric,
 ) -> Result<f32> {
+    // Only the similarity is reported, so the threshold does not matter here
+    Ok(check_page(hwnd, region, ref_img, 0.0, metric)?.similarity)
+}
+
+/// One live measurement of a button region (GUI 検出モニター).
+#[derive(Clone, Copy, Debug)]
+pub struct RegionReading {
+    /// Average luminance of the region (compare with `brightness_threshold`).
+    pub brightness: f32,
+    /// Histogram similarity against the reference image, if one is loaded
+    /// (compare with `histogram_threshold`).
+    pub similarity: Option<f32>,
+}
+
+/// Captures `region` once and measures it the same way the wait functions do:
+/// brightness of the capture (or of `brightness_subrect` within it), and
+/// histogram similarity after resizing to the reference's dimensions.
+pub fn measure_region(
+    hwnd: HWND,
+    region: &RelativeRect,
+    brightness_subrect: Option<&RelativeRect>,
+    ref_img: Option<&ReferenceImage>,
+    metric: DetectionMetric,
+) -> Result<RegionReading> {
     let region_img = capture_region(hwnd, region)?;
-    let resized = resize_to_match(&region_img, ref_img.dimensions.0, ref_img.dimensions.1);
-    let current_hist = calculate_histogram(&resized);
-    Ok(histogram_similarity(&ref_img.histogram, &current_hist))
+    let similarity =
+        ref_img.map(|ref_img| compare_to_reference(&region_img, ref_img, 0.0, metric).similarity);
+    Ok(RegionReading {
+        brightness: subrect_brightness(&region_img, brightness_subrect),
+        similarity,
+    })
+}
+
+/// Difference-hash columns per sampled row (one bit per neighbouring pair).
+const FINGERPRINT_COLS: u32 = 128;
+
+/// Rows sampled from each score region.
+const FINGERPRINT_ROWS: usize = 4;
+
+/// Smallest luma step (0-255) between neighbouring samples that counts as an
+/// edge; smaller steps are capture noise on the flat background.
+const FINGERPRINT_EDGE_MIN: i16 = 12;
+
+/// Difference hash of the three score rows of a result screen.
+///
+/// Two captures of the same result screen hash to (nearly) the same bits, while
+/// any change in a score moves digit edges and flips many of them. Used to spot
+/// an iteration that captured the previous iteration's screen again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResultFingerprint([[u128; FINGERPRINT_ROWS]; 3]);
+
+impl ResultFingerprint {
+    /// Hashes the `score_regions` of a captured result screen.
+    pub fn of(img: &ImageBuffer<Rgba<u8>, Vec<u8>>, score_regions: &[RelativeRect; 3]) -> Self {
+        let mut hashes = [[0u128; FINGERPRINT_ROWS]; 3];
+        for (stage, region) in score_regions.iter().enumerate() {
+            let crop = crop_region(img, region);
+            if crop.width() == 0 || crop.height() == 0 {
+                continue;
+            }
+            let gray = image::imageops::grayscale(&crop);
+            let small = image::imageops::resize(
+                &gray,
+                FINGERPRINT_COLS + 1,
+                FINGERPRINT_ROWS as u32,
+                image::imageops::FilterType::Triangle,
+            );
+            for (row, bits) in hashes[stage].iter_mut().enumerate() {
+                for col in 0..FINGERPRINT_COLS {
+                    let left = small.get_pixel(col, row as u32)[0] as i16;
+                    let right = small.get_pixel(col + 1, row as u32)[0] as i16;
+                    if (left - right).abs() >= FINGERPRINT_EDGE_MIN {
+                        *bits |= 1u128 << col;
+                    }
+                }
+            }
+        }
+        Self(hashes)
+    }
+
+    /// Number of differing bits (0 = identical score rows).
+    pub fn distance(&self, other: &Self) -> u32 {
+        self.0
+            .iter()
+            .flatten()
+            .zip(other.0.iter().flatten())
+            .map(|(a, b)| (a ^ b).count_ones())
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        calculate_histogram, compare_to_reference, histogram_similarity, subrect_brightness,
+        DetectionMetric, ReferenceImage, RelativeRect, ResultFingerprint,
+    };
+    use crate::automation::config::AutomationConfig;
+    use image::{ImageBuffer, Rgba};
+
+    /// A `width`x`height` screen: light background with a dark band of text
+    /// through the middle when `notice`, else a dark page with a bright button.
+    fn screen(width: u32, height: u32, notice: bool) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+        ImageBuffer::from_fn(width, height, |x, y| {
+            let band = (height * 2 / 5..height * 3 / 5).contains(&y);
+            let button = band && (width / 3..width * 2 / 3).contains(&x);
+            match (notice, band, button) {
+                (true, true, _) => Rgba([40, 40, 40, 255]),
+                (true, false, _) => Rgba([235, 235, 235, 255]),
+                (false, _, true) => Rgba([250, 200, 80, 255]),
+                (false, _, false) => Rgba([30, 20, 60, 255]),
+            }
+        })
+    }
+
+    /// Normalized Gaussian-shaped histogram centred on `center`.
+    fn gaussian_histogram(center: f32, sigma: f32) -> [f32; 256] {
+        let mut hist = [0.0f32; 256];
+        for (i, bin) in hist.iter_mut().enumerate() {
+            let d = i as f32 - center;
+            *bin = (-(d * d) / (2.0 * sigma * sigma)).exp();
+        }
+        let total: f32 = hist.iter().sum();
+        hist.iter_mut().for_each(|bin| *bin /= total);
+        hist
+    }
+
+    #[test]
+    fn active_subrect_ignores_dark_background() {
+        // 20x10 dark region with a bright 10x4 button in the middle.
+        let img = ImageBuffer::from_fn(20, 10, |x, y| {
+            if (5..15).contains(&x) && (3..7).contains(&y) {
+                Rgba([200, 200, 200, 255])
+            } else {
+                Rgba([20, 20, 20, 255])
+            }
+        });
+        let inner = RelativeRect { x: 0.25, y: 0.3, width: 0.5, height: 0.4 };
+
+        let full = subrect_brightness(&img, None);
+        let active = subrect_brightness(&img, Some(&inner));
+        assert!((active - 200.0).abs() < 0.5, "active {}", active);
+        assert!(full < 60.0, "full {}", full);
+    }
+
+    #[test]
+    fn blocked_screen_reference_matches_only_the_blocked_screen() {
+        let reference_img = screen(160, 90, true);
+        let reference = ReferenceImage {
+            histogram: calculate_histogram(&reference_img),
+            dimensions: reference_img.dimensions(),
+        };
+        for metric in [DetectionMetric::Bhattacharyya, DetectionMetric::Correlation, DetectionMetric::Both] {
+            // Same screen at another window size: resized before comparing
+            let blocked = compare_to_reference(&screen(320, 180, true), &reference, 0.85, metric);
+            assert!(blocked.matched, "{:?}: {}", metric, blocked.similarity);
+            assert_eq!(blocked.captured_size, (320, 180));
+
+            let page = compare_to_reference(&screen(320, 180, false), &reference, 0.85, metric);
+            assert!(!page.matched, "{:?}: {}", metric, page.similarity);
+        }
+    }
+
+    #[test]
+    fn identical_histograms_match_with_every_metric() {
+        let hist = gaussian_histogram(100.0, 20.0);
+        for metric in [DetectionMetric::Bhattacharyya, DetectionMetric::Correlation, DetectionMetric::Both] {
+            let similarity = histogram_similarity(&hist, &hist, metric);
+            assert!((similarity - 1.0).abs() < 1e-3, "{:?}: {}", metric, similarity);
+        }
+    }
+
+    #[test]
+    fn shifted_histogram_only_fools_bhattacharyya() {
+        let threshold = 0.85;
+        let reference = gaussian_histogram(100.0, 20.0);
+        let shifted = gaussian_histogram(120.0, 20.0);
+
+        let bc = histogram_similarity(&reference, &shifted, DetectionMetric::Bhattacharyya);
+        let corr = histogram_similarity(&reference, &shifted, DetectionMetric::Correlation);
+        let both = histogram_similarity(&reference, &shifted, DetectionMetric::Both);
+
+        assert!(bc >= threshold, "bhattacharyya {}", bc);
+        assert!(corr < threshold, "correlation {}", corr);
+        assert_eq!(both, bc.min(corr));
+        assert!(both < threshold);
+    }
+
+    /// Score row regions of a `result_screen`, one per stage.
+    fn score_rows() -> [RelativeRect; 3] {
+        [0, 1, 2].map(|stage| RelativeRect {
+            x: 0.1,
+            y: (300 + 100 * stage) as f32 / 960.0,
+            width: 0.8,
+            height: 40.0 / 960.0,
+        })
+    }
+
+    /// A 540x960 result screen showing the six-digit `scores` in its score
+    /// rows: light background, each digit a dark bar placed by its value.
+    fn result_screen(scores: [u32; 3]) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+        ImageBuffer::from_fn(540, 960, |x, y| {
+            let stage = (y / 100).wrapping_sub(3) as usize;
+            let on_bar = stage < 3 && y % 100 < 40 && (54..486).contains(&x) && {
+                let cell = (x - 54) / 72;
+                let digit = scores[stage] / 10u32.pow(5 - cell) % 10;
+                (digit * 6..digit * 6 + 8).contains(&((x - 54) % 72))
+            };
+            if on_bar {
+                Rgba([50, 40, 60, 255])
+            } else {
+                Rgba([240, 235, 225, 255])
+            }
+        })
+    }
+
+    #[test]
+    fn identical_result_screens_have_zero_fingerprint_distance() {
+        let scores = [123456, 234567, 345678];
+        let first = ResultFingerprint::of(&result_screen(scores), &score_rows());
+        let again = ResultFingerprint::of(&result_screen(scores), &score_rows());
+        assert_eq!(first.distance(&again), 0);
+
+        // Capture noise on the flat background stays below the edge threshold
+        let mut noisy = result_screen(scores);
+        noisy.pixels_mut().enumerate().for_each(|(i, px)| px[1] ^= (i % 4) as u8);
+        assert_eq!(first.distance(&ResultFingerprint::of(&noisy, &score_rows())), 0);
+    }
+
+    #[test]
+    fn changed_score_exceeds_duplicate_threshold() {
+        let max_distance = AutomationConfig::default().duplicate_max_distance;
+        let scores = [123456, 234567, 345678];
+        let before = ResultFingerprint::of(&result_screen(scores), &score_rows());
+        for scores in [[123457, 234567, 345678], [123456, 234567, 345687], [0, 0, 0]] {
+            let after = ResultFingerprint::of(&result_screen(scores), &score_rows());
+            let distance = before.distance(&after);
+            assert!(distance > max_distance, "{:?}: distance {}", scores, distance);
+        }
+    }
 }
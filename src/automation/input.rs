@@ -1,23 +1,33 @@
-//! Mouse input simulation for UI automation.
+//! Mouse and keyboard input simulation for UI automation.
 //!
 //! This module provides functions for simulating mouse clicks on the game window.
 //! Two methods are implemented:
 //! - PostMessage: Sends window messages directly (does not work with the game)
 //! - SendInput: Simulates hardware-level input (works, but moves the actual cursor)
+//!
+//! Key presses (`send_key`) also go through SendInput, to the foreground window.
+//! `ForegroundMode` decides whether the game is brought forward first; when it
+//! isn't, input waits until the game is in front again (`wait_for_foreground`)
+//! so it never lands on another app.
 
 use anyhow::{anyhow, Result};
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
 
 use windows::Win32::Foundation::{HWND, LPARAM, POINT, RECT, WPARAM};
 use windows::Win32::Graphics::Gdi::ClientToScreen;
 use windows::Win32::UI::Input::KeyboardAndMouse::{
-    SendInput, INPUT, INPUT_0, INPUT_MOUSE, MOUSEEVENTF_ABSOLUTE, MOUSEEVENTF_LEFTDOWN,
-    MOUSEEVENTF_LEFTUP, MOUSEEVENTF_MOVE, MOUSEINPUT,
+    SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, INPUT_MOUSE, KEYBDINPUT, KEYBD_EVENT_FLAGS,
+    KEYEVENTF_KEYUP, MOUSEEVENTF_ABSOLUTE, MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP,
+    MOUSEEVENTF_MOVE, MOUSEINPUT, VIRTUAL_KEY, VK_ESCAPE, VK_RETURN, VK_SPACE,
 };
 use windows::Win32::UI::WindowsAndMessaging::{
-    GetClientRect, GetSystemMetrics, PostMessageW, SetForegroundWindow, SM_CXSCREEN, SM_CYSCREEN,
-    WM_LBUTTONDOWN, WM_LBUTTONUP, WM_MOUSEMOVE,
+    GetClientRect, GetForegroundWindow, GetSystemMetrics, IsWindow, PostMessageW,
+    SetForegroundWindow, SM_CXSCREEN, SM_CYSCREEN, WM_LBUTTONDOWN, WM_LBUTTONUP, WM_MOUSEMOVE,
 };
 
+use crate::automation::config::{ButtonConfig, ForegroundMode, NavigationKey};
+use crate::automation::state::ABORT_REQUESTED;
 use crate::capture::find_gakumas_window;
 
 /// Tests if PostMessage-based clicking works with the game.
@@ -200,18 +210,39 @@ pub fn test_sendinput_click() -> Result<()> {
     Ok(())
 }
 
+/// Default mouse-down duration for a click, matching `ButtonConfig::click_hold_ms`.
+const DEFAULT_CLICK_HOLD_MS: u64 = 50;
+
+/// Pause between the two clicks of a double click; well inside the Windows
+/// default double-click time (500ms).
+const DOUBLE_CLICK_GAP_MS: u64 = 80;
+
 /// Clicks at a position specified in client coordinates.
 ///
 /// This is an internal helper that:
-/// 1. Brings the window to foreground
+/// 1. Brings the window to foreground (`ForegroundMode::EachClick` only)
 /// 2. Converts client coordinates to screen coordinates
-/// 3. Sends the click via SendInput
-fn click_at_client(hwnd: HWND, client_x: i32, client_y: i32) -> Result<()> {
+/// 3. Sends the click via SendInput, holding the button for `hold_ms` and
+///    repeating once when `double_click` is set
+///
+/// With `ForegroundMode::Once` or `Never`, step 1 is instead waiting for the
+/// game to be in front (`wait_for_foreground`).
+fn click_at_client(
+    hwnd: HWND,
+    client_x: i32,
+    client_y: i32,
+    hold_ms: u64,
+    double_click: bool,
+    foreground: ForegroundMode,
+) -> Result<()> {
     // Bring window to foreground
-    unsafe {
-        let _ = SetForegroundWindow(hwnd);
+    if foreground == ForegroundMode::EachClick {
+        unsafe {
+            let _ = SetForegroundWindow(hwnd);
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
     }
-    std::thread::sleep(std::time::Duration::from_millis(100));
+    wait_for_foreground(hwnd, foreground)?;
 
     // Convert client coordinates to screen coordinates
     let mut screen_point = POINT {
@@ -249,48 +280,117 @@ fn click_at_client(hwnd: HWND, client_x: i32, client_y: i32) -> Result<()> {
 
         std::thread::sleep(std::time::Duration::from_millis(50));
 
-        // Mouse down
-        let down_input = INPUT {
-            r#type: INPUT_MOUSE,
-            Anonymous: INPUT_0 {
-                mi: MOUSEINPUT {
-                    dx: norm_x,
-                    dy: norm_y,
-                    dwFlags: MOUSEEVENTF_LEFTDOWN | MOUSEEVENTF_ABSOLUTE | MOUSEEVENTF_MOVE,
-                    ..Default::default()
+        let clicks = if double_click { 2 } else { 1 };
+        for i in 0..clicks {
+            if i > 0 {
+                std::thread::sleep(std::time::Duration::from_millis(DOUBLE_CLICK_GAP_MS));
+            }
+
+            // Mouse down
+            let down_input = INPUT {
+                r#type: INPUT_MOUSE,
+                Anonymous: INPUT_0 {
+                    mi: MOUSEINPUT {
+                        dx: norm_x,
+                        dy: norm_y,
+                        dwFlags: MOUSEEVENTF_LEFTDOWN | MOUSEEVENTF_ABSOLUTE | MOUSEEVENTF_MOVE,
+                        ..Default::default()
+                    },
                 },
-            },
-        };
-        SendInput(&[down_input], std::mem::size_of::<INPUT>() as i32);
-
-        std::thread::sleep(std::time::Duration::from_millis(50));
-
-        // Mouse up
-        let up_input = INPUT {
-            r#type: INPUT_MOUSE,
-            Anonymous: INPUT_0 {
-                mi: MOUSEINPUT {
-                    dx: norm_x,
-                    dy: norm_y,
-                    dwFlags: MOUSEEVENTF_LEFTUP | MOUSEEVENTF_ABSOLUTE | MOUSEEVENTF_MOVE,
-                    ..Default::default()
+            };
+            SendInput(&[down_input], std::mem::size_of::<INPUT>() as i32);
+
+            std::thread::sleep(std::time::Duration::from_millis(hold_ms));
+
+            // Mouse up
+            let up_input = INPUT {
+                r#type: INPUT_MOUSE,
+                Anonymous: INPUT_0 {
+                    mi: MOUSEINPUT {
+                        dx: norm_x,
+                        dy: norm_y,
+                        dwFlags: MOUSEEVENTF_LEFTUP | MOUSEEVENTF_ABSOLUTE | MOUSEEVENTF_MOVE,
+                        ..Default::default()
+                    },
                 },
-            },
-        };
-        SendInput(&[up_input], std::mem::size_of::<INPUT>() as i32);
+            };
+            SendInput(&[up_input], std::mem::size_of::<INPUT>() as i32);
+        }
     }
 
     Ok(())
 }
 
-/// Clicks at a position specified in relative coordinates (0.0 to 1.0).
+/// How often `wait_for_foreground` checks whether the game is back in front.
+const FOCUS_POLL_MS: u64 = 250;
+
+/// Unless `ForegroundMode::EachClick` just focused it, blocks until the game
+/// window is in front.
+/// SendInput goes to whichever window has focus and the game ignores posted
+/// mouse messages, so input sent while the user is in another app would land
+/// there or do nothing; the run pauses instead, logging once when it starts
+/// waiting.
+///
+/// # Errors
+/// Returns an error if the run is aborted or the window closes while waiting.
+fn wait_for_foreground(hwnd: HWND, foreground: ForegroundMode) -> Result<()> {
+    if foreground == ForegroundMode::EachClick || unsafe { GetForegroundWindow() } == hwnd {
+        return Ok(());
+    }
+    crate::log(&format!(
+        "Game window is not in the foreground; pausing input until it is brought back \
+         (foreground_mode = {:?})",
+        foreground
+    ));
+    let started = Instant::now();
+    loop {
+        std::thread::sleep(Duration::from_millis(FOCUS_POLL_MS));
+        if ABORT_REQUESTED.load(Ordering::SeqCst) {
+            return Err(anyhow!("Aborted while waiting for the game window to be in front"));
+        }
+        if !unsafe { IsWindow(hwnd) }.as_bool() {
+            return Err(anyhow!("Game window no longer exists"));
+        }
+        if unsafe { GetForegroundWindow() } == hwnd {
+            crate::log(&format!(
+                "Game window is in front again after {:.1}s; resuming input",
+                started.elapsed().as_secs_f64()
+            ));
+            return Ok(());
+        }
+    }
+}
+
+/// Clicks a configured button at its relative position (0.0 to 1.0), holding
+/// the mouse button for `click_hold_ms` and clicking twice when `double_click`
+/// is set.
 ///
 /// Relative coordinates are converted to client area pixel coordinates:
-/// - rel_x: 0.0 = left edge, 1.0 = right edge
-/// - rel_y: 0.0 = top edge, 1.0 = bottom edge
+/// - x: 0.0 = left edge, 1.0 = right edge
+/// - y: 0.0 = top edge, 1.0 = bottom edge
 ///
 /// WARNING: This WILL move your actual cursor to the target position.
-pub fn click_at_relative(hwnd: HWND, rel_x: f32, rel_y: f32) -> Result<()> {
+pub fn click_button(hwnd: HWND, button: &ButtonConfig, foreground: ForegroundMode) -> Result<()> {
+    let (client_x, client_y) = relative_to_client(hwnd, button.x, button.y)?;
+    if button.click_hold_ms != DEFAULT_CLICK_HOLD_MS || button.double_click {
+        crate::log(&format!(
+            "Click style: hold {}ms{}",
+            button.click_hold_ms,
+            if button.double_click { ", double click" } else { "" }
+        ));
+    }
+    click_at_client(
+        hwnd,
+        client_x,
+        client_y,
+        button.click_hold_ms,
+        button.double_click,
+        foreground,
+    )
+}
+
+/// Converts a relative position to client-area pixels, logging the mapping.
+fn relative_to_client(hwnd: HWND, rel_x: f32, rel_y: f32) -> Result<(i32, i32)> {
     // Get client area size
     let mut client_rect = RECT::default();
     unsafe { GetClientRect(hwnd, &mut client_rect)? };
@@ -298,14 +398,99 @@ pub fn click_at_relative(hwnd: HWND, rel_x: f32, rel_y: f32) -> Result<()> {
     let client_width = client_rect.right - client_rect.left;
     let client_height = client_rect.bottom - client_rect.top;
 
-    // Convert relative to client coordinates
-    let client_x = (rel_x * client_width as f32) as i32;
-    let client_y = (rel_y * client_height as f32) as i32;
+    // Convert relative to client coordinates, never leaving the client area
+    let raw_x = (rel_x * client_width as f32) as i32;
+    let raw_y = (rel_y * client_height as f32) as i32;
+    let (client_x, client_y) = clamp_to_client(raw_x, raw_y, client_width, client_height);
+    if (client_x, client_y) != (raw_x, raw_y) {
+        crate::log(&format!(
+            "Warning: Click at relative ({:.3}, {:.3}) = client ({}, {}) is outside the \
+             {}x{} client area; clamped to ({}, {})",
+            rel_x, rel_y, raw_x, raw_y, client_width, client_height, client_x, client_y
+        ));
+    }
 
     crate::log(&format!(
         "Clicking at relative ({:.3}, {:.3}) = client ({}, {}) in {}x{} window",
         rel_x, rel_y, client_x, client_y, client_width, client_height
     ));
 
-    click_at_client(hwnd, client_x, client_y)
+    Ok((client_x, client_y))
+}
+
+/// Clamps client coordinates to `[0, width-1] x [0, height-1]`, so a
+/// misconfigured position can never click outside the game window (onto the
+/// desktop or another app).
+fn clamp_to_client(x: i32, y: i32, width: i32, height: i32) -> (i32, i32) {
+    (x.clamp(0, (width - 1).max(0)), y.clamp(0, (height - 1).max(0)))
+}
+
+/// How long a simulated key is held down, like a click's default hold.
+const KEY_HOLD_MS: u64 = 50;
+
+/// Presses and releases a key in the game window via SendInput.
+///
+/// Like a click, the window is brought to the foreground first, since
+/// SendInput delivers keystrokes to whichever window has focus; with
+/// `ForegroundMode::Once` or `Never` the key instead waits for the game to be
+/// in front.
+pub fn send_key(hwnd: HWND, vk: VIRTUAL_KEY, foreground: ForegroundMode) -> Result<()> {
+    if foreground == ForegroundMode::EachClick {
+        unsafe {
+            let _ = SetForegroundWindow(hwnd);
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+
+    wait_for_foreground(hwnd, foreground)?;
+
+    crate::log(&format!("Sending key VK 0x{:02X}", vk.0));
+
+    let key_input = |flags: KEYBD_EVENT_FLAGS| INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: vk,
+                dwFlags: flags,
+                ..Default::default()
+            },
+        },
+    };
+
+    unsafe {
+        if SendInput(&[key_input(KEYBD_EVENT_FLAGS(0))], std::mem::size_of::<INPUT>() as i32) != 1 {
+            return Err(anyhow!("SendInput key down failed"));
+        }
+        std::thread::sleep(std::time::Duration::from_millis(KEY_HOLD_MS));
+        if SendInput(&[key_input(KEYEVENTF_KEYUP)], std::mem::size_of::<INPUT>() as i32) != 1 {
+            return Err(anyhow!("SendInput key up failed"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Presses a configured navigation key (see `AutomationConfig::end_key`).
+pub fn press_navigation_key(hwnd: HWND, key: NavigationKey, foreground: ForegroundMode) -> Result<()> {
+    let vk = match key {
+        NavigationKey::Enter => VK_RETURN,
+        NavigationKey::Escape => VK_ESCAPE,
+        NavigationKey::Space => VK_SPACE,
+    };
+    send_key(hwnd, vk, foreground)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::clamp_to_client;
+
+    #[test]
+    fn clamp_to_client_keeps_clicks_inside_window() {
+        assert_eq!(clamp_to_client(640, 360, 1280, 720), (640, 360));
+        // x > 1.0 or exactly 1.0 lands on the last pixel, not past it.
+        assert_eq!(clamp_to_client(1920, 720, 1280, 720), (1279, 719));
+        assert_eq!(clamp_to_client(-5, -1, 1280, 720), (0, 0));
+        // Zero-sized (minimized) client area still yields a valid point.
+        assert_eq!(clamp_to_client(10, 10, 0, 0), (0, 0));
+    }
 }
@@ -14,20 +14,26 @@ pub mod ocr_worker;
 pub mod queue;
 pub mod results_edit;
 pub mod runner;
+pub mod screenshot_writer;
 pub mod session_meta;
 pub mod state;
+pub mod timed_capture;
 
 pub use config::{
-    get_config, init_config, review_crop_rect, AutomationConfig, ButtonConfig, RelativeRect,
-    ReviewCropAdjust,
+    get_config, init_config, reload_config, review_crop_rect, save_config, AutomationConfig,
+    ButtonConfig, CaptureMode, CapturePixelFormat, CaptureSource, DetectionMetric, ForegroundMode,
+    NavigationKey, OcrBackend, OcrPreprocessMode, RelativeRect, ReviewCropAdjust,
 };
 pub use detection::{
-    calculate_brightness, check_button_similarity, load_reference_histogram,
-    measure_region_brightness, save_end_button_reference, save_skip_button_reference,
-    save_start_button_reference, wait_for_loading, wait_for_result, wait_for_start_page,
-    ClickRetryInfo,
+    calculate_brightness, check_button_similarity, is_rehearsal_page, is_result_page,
+    load_reference_histogram, measure_region, measure_region_brightness,
+    save_blocked_screen_reference, save_end_button_reference, save_skip_button_reference,
+    save_start_button_reference,
+    wait_for_loading, wait_for_result, wait_for_start_page, ClickRetryInfo, RegionReading,
 };
-pub use input::{click_at_relative, test_postmessage_click, test_sendinput_click};
+pub use input::{click_button, test_postmessage_click, test_sendinput_click};
 pub use runner::{
     extend_automation, is_automation_running, request_abort, resume_automation, start_automation,
+    AutomationEvent,
 };
+pub use timed_capture::TimedCapture;
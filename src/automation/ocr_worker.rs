@@ -3,13 +3,15 @@
 //! Runs in a separate thread, receiving screenshot paths from the work queue
 //! and processing them with OCR. Results are written to a CSV file.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
 
-use crate::automation::config::RelativeRect;
-use crate::automation::csv_writer::{append_to_csv, append_to_raw_csv};
+use crate::automation::config::{AutomationConfig, RelativeRect};
+use crate::automation::csv_writer::CsvWriter;
 use crate::automation::queue::OcrWorkItem;
-use crate::ocr::{ocr_screenshot, Recovery};
+use crate::automation::runner::{AutomationEvent, RunEvents};
+use crate::ocr::{ocr_screenshot_with_debug, OcrDebugDump, Recovery};
 
 /// Worst recovery outcome across the three stages (Flagged > Repaired > Ok).
 fn worst_recovery(flags: &[Recovery; 3]) -> Recovery {
@@ -22,25 +24,81 @@ fn worst_recovery(flags: &[Recovery; 3]) -> Recovery {
     }
 }
 
+/// Lets the runner stop an OCR worker it has given up waiting for.
+///
+/// The worker writes each row while holding the lock `stop` takes, so once
+/// `stop` returns no further rows are written; the worker exits after the
+/// screenshot in progress.
+#[derive(Clone, Debug, Default)]
+pub struct OcrWorkerStop(Arc<Mutex<bool>>);
+
+impl OcrWorkerStop {
+    /// Stops the worker, waiting for a row being written to finish.
+    pub fn stop(&self) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = true;
+    }
+
+    /// Whether `stop` was called.
+    pub fn is_stopped(&self) -> bool {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Runs `write` unless stopped, holding the lock throughout. Returns None
+    /// when stopped.
+    pub fn unless_stopped<R>(&self, write: impl FnOnce() -> R) -> Option<R> {
+        let stopped = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        (!*stopped).then(write)
+    }
+}
+
+/// Returns the open writer in `slot`, opening `path` first if needed (a failed
+/// open is retried on the next row rather than giving up for the whole run).
+fn writer_for<'a>(
+    slot: &'a mut Option<CsvWriter>,
+    path: &Path,
+    sync_interval: u32,
+) -> anyhow::Result<&'a mut CsvWriter> {
+    if slot.is_none() {
+        *slot = Some(CsvWriter::open(path, sync_interval)?);
+    }
+    Ok(slot.as_mut().expect("writer was just opened"))
+}
+
 /// Runs the OCR worker loop.
 ///
-/// Processes items from the queue until the channel is closed (sender dropped).
-/// Each screenshot is loaded, processed with OCR, and results appended to CSV.
+/// Processes items from the queue until the channel is closed (sender dropped)
+/// or `stop` is used. Each screenshot is loaded, processed with OCR over the
+/// `[score, total, bonus]` regions with the run's `config`, its scores
+/// published to `events` (if any), and results appended to CSV. Both CSVs stay
+/// open for the run and are synced when the worker exits. The OCR debug crops are written with `debug_ocr` or
+/// `force_debug_crops` (a test run).
 ///
 /// This function blocks until the channel closes, so it should be run in a
 /// dedicated thread.
 pub fn run_ocr_worker(
     receiver: Receiver<OcrWorkItem>,
     csv_path: PathBuf,
-    score_regions: [RelativeRect; 3],
-    total_regions: [RelativeRect; 3],
-    bonus_regions: [RelativeRect; 3],
+    regions: [[RelativeRect; 3]; 3],
+    config: AutomationConfig,
+    events: Option<RunEvents>,
+    force_debug_crops: bool,
+    stop: OcrWorkerStop,
 ) {
     crate::log("OCR worker started");
+    let [score_regions, total_regions, bonus_regions] = regions;
+
+    let sync_interval = config.csv_sync_interval;
+    let debug_ocr = config.debug_ocr || force_debug_crops;
+    let raw_csv_path = csv_path.with_file_name("rehearsal_data.csv");
+    let mut csv_writer: Option<CsvWriter> = None;
+    let mut raw_writer: Option<CsvWriter> = None;
 
     loop {
         match receiver.recv() {
             Ok(work_item) => {
+                if stop.is_stopped() {
+                    break;
+                }
                 crate::log(&format!(
                     "OCR worker: processing iteration {} ({})",
                     work_item.iteration,
@@ -60,8 +118,19 @@ pub fn run_ocr_worker(
                     }
                 };
 
-                // Run OCR
-                let readout = match ocr_screenshot(&img, &score_regions, &total_regions, &bonus_regions) {
+                // Run OCR (optionally dumping the intermediate crops next to the CSV)
+                let debug = debug_ocr.then(|| OcrDebugDump {
+                    dir: csv_path.with_file_name("ocr_debug"),
+                    iteration: work_item.iteration,
+                });
+                let readout = match ocr_screenshot_with_debug(
+                    &img,
+                    &config,
+                    &score_regions,
+                    &total_regions,
+                    &bonus_regions,
+                    debug.as_ref(),
+                ) {
                     Ok(readout) => readout,
                     Err(e) => {
                         crate::log(&format!(
@@ -101,31 +170,49 @@ pub fn run_ocr_worker(
                     Recovery::Ok => {}
                 }
 
-                // Feed the live distribution buffer (read by the GUI to render the
-                // in-run box plot). Done before the CSV write so the live view does
-                // not depend on disk success. Flagged rows are kept but excluded
-                // from live stats until verified.
-                crate::automation::runner::record_live_score(
-                    scores,
-                    matches!(recovery, Recovery::Flagged),
-                );
-
-                // Append to CSV
-                if let Err(e) = append_to_csv(&csv_path, &work_item, &scores, recovery_str) {
-                    crate::log(&format!(
-                        "OCR worker: failed to write CSV for iteration {}: {}",
-                        work_item.iteration, e
-                    ));
-                    // Continue anyway - the screenshot is saved for manual retry
-                }
+                // Publish and write the row unless the runner stopped waiting
+                let written = stop.unless_stopped(|| {
+                    // Publish the scores; this also feeds the live distribution
+                    // buffer (read by the GUI to render the in-run box plot). Done
+                    // before the CSV write so the live view does not depend on disk
+                    // success. Flagged rows are kept but excluded from live stats
+                    // until verified.
+                    if let Some(events) = &events {
+                        events.publish(AutomationEvent::Scores {
+                            iteration: work_item.iteration,
+                            scores,
+                            flagged: matches!(recovery, Recovery::Flagged),
+                        });
+                    }
 
-                // Append to raw CSV (just scores, no header)
-                let raw_csv_path = csv_path.with_file_name("rehearsal_data.csv");
-                if let Err(e) = append_to_raw_csv(&raw_csv_path, &scores) {
+                    // Append to CSV
+                    let written = writer_for(&mut csv_writer, &csv_path, sync_interval).and_then(
+                        |w| w.append_row(&work_item, &scores, recovery_str, &readout.totals),
+                    );
+                    if let Err(e) = written {
+                        crate::log(&format!(
+                            "OCR worker: failed to write CSV for iteration {}: {}",
+                            work_item.iteration, e
+                        ));
+                        // Continue anyway - the screenshot is saved for manual retry
+                    }
+
+                    // Append to raw CSV (just scores, no header)
+                    let written = writer_for(&mut raw_writer, &raw_csv_path, sync_interval)
+                        .and_then(|w| w.append_raw_row(&scores));
+                    if let Err(e) = written {
+                        crate::log(&format!(
+                            "OCR worker: failed to write raw CSV for iteration {}: {}",
+                            work_item.iteration, e
+                        ));
+                    }
+                });
+                if written.is_none() {
                     crate::log(&format!(
-                        "OCR worker: failed to write raw CSV for iteration {}: {}",
-                        work_item.iteration, e
+                        "OCR worker: stopped; iteration {} not written",
+                        work_item.iteration
                     ));
+                    break;
                 }
             }
             Err(_) => {
@@ -153,7 +240,7 @@ mod tests {
 
         let dir = tempdir().unwrap();
         let csv_path = dir.path().join("test.csv");
-        init_csv(&csv_path).unwrap();
+        init_csv(&csv_path, crate::automation::csv_writer::CSV_HEADER).unwrap();
 
         let (sender, receiver) = create_work_queue();
 
@@ -168,7 +255,15 @@ mod tests {
         // Spawn worker
         let csv_path_clone = csv_path.clone();
         let handle = thread::spawn(move || {
-            run_ocr_worker(receiver, csv_path_clone, score_regions, total_regions, bonus_regions);
+            run_ocr_worker(
+                receiver,
+                csv_path_clone,
+                [score_regions, total_regions, bonus_regions],
+                AutomationConfig::default(),
+                None,
+                false,
+                OcrWorkerStop::default(),
+            );
         });
 
         // Drop sender to close channel
@@ -9,6 +9,9 @@
 //! files consistent and marking each hand-edited row `recovery=manual` so the
 //! correction is auditable.
 //!
+//! The same rewrite backs the `--review <DIR>` CLI prompt, which asks for the
+//! nine values of each flagged row in turn.
+//!
 //! Rewriting (not appending) is deliberate: editing an existing row is the whole
 //! point. Saves go through a temp-file-then-rename so an interrupted write can
 //! never truncate the originals.
@@ -16,6 +19,10 @@
 use anyhow::{Context, Result};
 use std::path::{Path, PathBuf};
 
+use crate::analysis::csv_reader::split_csv_line;
+use crate::automation::csv_writer::{csv_field, total_fields, CSV_HEADER};
+use crate::ocr::reconcile::expected_stage_total;
+
 /// One reviewable/editable result row, mirroring a `results.csv` line.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ReviewRow {
@@ -31,8 +38,16 @@ pub struct ReviewRow {
     /// (flagged/repaired) row, confirms it is correct, and clears it without
     /// changing any value.
     pub recovery: String,
+    /// OCR'd stage totals (`s1_total..s3_total`), kept verbatim on rewrite;
+    /// `None` when unread or in files written before these columns existed.
+    /// The `total` column is not stored: it is recomputed from `scores`.
+    pub stage_totals: [Option<u32>; 3],
 }
 
+/// The recovery marker the OCR worker writes for a row whose checksum could
+/// not be reconciled; such rows are left for the user to review.
+pub const RECOVERY_FLAGGED: &str = "flagged";
+
 /// The recovery marker written for a row the user corrected by hand.
 pub const RECOVERY_MANUAL: &str = "manual";
 
@@ -40,8 +55,16 @@ pub const RECOVERY_MANUAL: &str = "manual";
 /// correct without editing it (resolves the flag while preserving the data).
 pub const RECOVERY_VERIFIED: &str = "verified";
 
-const CSV_HEADER: &str =
-    "iteration,timestamp,screenshot,s1c1,s1c2,s1c3,s2c1,s2c2,s2c3,s3c1,s3c2,s3c3,recovery";
+impl ReviewRow {
+    /// Replaces the scores with hand-typed ones and marks the row manual. The
+    /// stage totals become the totals the game shows for the typed scores
+    /// (`expected_stage_total`): the OCR'd totals belonged to the misread scores.
+    pub fn set_manual_scores(&mut self, scores: [[u32; 3]; 3]) {
+        self.scores = scores;
+        self.stage_totals = scores.map(|stage| u32::try_from(expected_stage_total(&stage)).ok());
+        self.recovery = RECOVERY_MANUAL.to_string();
+    }
+}
 
 fn results_path(session_dir: &Path) -> PathBuf {
     session_dir.join("results.csv")
@@ -54,7 +77,8 @@ fn raw_path(session_dir: &Path) -> PathBuf {
 /// Parses `results.csv` in `session_dir` into rows, in file order.
 ///
 /// Tolerates the legacy 12-column form (no `recovery`) by defaulting the flag to
-/// `ok`. A row that cannot be parsed (too few columns, non-numeric scores) is
+/// `ok`, and the 13-column form (no total columns) by leaving `stage_totals`
+/// empty. A row that cannot be parsed (too few columns, non-numeric scores) is
 /// skipped rather than aborting the whole load, so a partially-corrupt file is
 /// still reviewable.
 pub fn load_review_rows(session_dir: &Path) -> Result<Vec<ReviewRow>> {
@@ -70,9 +94,9 @@ pub fn load_review_rows(session_dir: &Path) -> Result<Vec<ReviewRow>> {
         if line.trim().is_empty() {
             continue;
         }
-        // Screenshot paths and ISO timestamps contain no commas, so a plain split
-        // yields exactly 12 (legacy) or 13 (current) fields.
-        let f: Vec<&str> = line.split(',').collect();
+        // 12 or 13 (legacy) or 17 (current) fields; a screenshot path with a
+        // comma is quoted
+        let f = split_csv_line(line);
         if f.len() < 12 {
             continue;
         }
@@ -98,12 +122,15 @@ pub fn load_review_rows(session_dir: &Path) -> Result<Vec<ReviewRow>> {
         }
         let recovery = f.get(12).map(|s| s.trim().to_string()).unwrap_or_default();
         let recovery = if recovery.is_empty() { "ok".to_string() } else { recovery };
+        // f[13] is the grand total (recomputed on save); stage totals follow it.
+        let stage_totals = [14, 15, 16].map(|i| f.get(i).and_then(|s| s.trim().parse().ok()));
         rows.push(ReviewRow {
             iteration,
             timestamp: f[1].trim().to_string(),
             screenshot: f[2].trim().to_string(),
             scores,
             recovery,
+            stage_totals,
         });
     }
     Ok(rows)
@@ -112,24 +139,31 @@ pub fn load_review_rows(session_dir: &Path) -> Result<Vec<ReviewRow>> {
 /// Rewrites `results.csv` (header + every row) and patches `rehearsal_data.csv`
 /// line-for-line from the same `rows`, in the given order.
 ///
+/// The file's own header is kept when it names every column, so a session
+/// written under another `csv_header` keeps its column names; `header` replaces
+/// a missing or legacy (shorter) one.
+///
 /// The caller is responsible for having set `recovery = RECOVERY_MANUAL` on rows
 /// it changed. Both files are written via a temp file + rename so a crash mid-
 /// write cannot leave a truncated CSV.
-pub fn save_review_rows(session_dir: &Path, rows: &[ReviewRow]) -> Result<()> {
+pub fn save_review_rows(session_dir: &Path, rows: &[ReviewRow], header: &str) -> Result<()> {
     // results.csv
-    let mut out = String::with_capacity(rows.len() * 96 + CSV_HEADER.len() + 1);
-    out.push_str(CSV_HEADER);
+    let existing = existing_header(session_dir);
+    let header = existing.as_deref().unwrap_or(header);
+    let mut out = String::with_capacity(rows.len() * 96 + header.len() + 1);
+    out.push_str(header);
     out.push('\n');
     for r in rows {
         out.push_str(&format!(
-            "{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
             r.iteration,
-            r.timestamp,
-            r.screenshot,
+            csv_field(&r.timestamp),
+            csv_field(&r.screenshot),
             r.scores[0][0], r.scores[0][1], r.scores[0][2],
             r.scores[1][0], r.scores[1][1], r.scores[1][2],
             r.scores[2][0], r.scores[2][1], r.scores[2][2],
             r.recovery,
+            total_fields(&r.scores, &r.stage_totals),
         ));
     }
     write_atomic(&results_path(session_dir), &out)?;
@@ -148,6 +182,43 @@ pub fn save_review_rows(session_dir: &Path, rows: &[ReviewRow]) -> Result<()> {
     Ok(())
 }
 
+/// The header line of `session_dir`'s `results.csv`, if it has one naming as
+/// many columns as `CSV_HEADER` (a first line that starts with an iteration
+/// number is a data row, not a header).
+fn existing_header(session_dir: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(results_path(session_dir)).ok()?;
+    let first = content.lines().next()?.trim();
+    let fields = split_csv_line(first);
+    let is_data = fields.first().is_some_and(|f| f.trim().parse::<u32>().is_ok());
+    (!is_data && fields.len() == CSV_HEADER.split(',').count()).then(|| first.to_string())
+}
+
+/// Parses nine hand-typed scores (stage by stage, `s1c1..s3c3`) for the CLI
+/// manual-entry prompt.
+///
+/// Values are separated by whitespace or commas; blank input returns `None`
+/// (keep the row as it is). Anything other than exactly nine integers is an
+/// error so a typo is re-prompted rather than written.
+pub fn parse_manual_scores(input: &str) -> Result<Option<[[u32; 3]; 3]>> {
+    let values: Vec<&str> = input
+        .split(|ch: char| ch == ',' || ch.is_whitespace())
+        .filter(|v| !v.is_empty())
+        .collect();
+    if values.is_empty() {
+        return Ok(None);
+    }
+    if values.len() != 9 {
+        anyhow::bail!("Expected 9 scores, got {}", values.len());
+    }
+    let mut scores = [[0u32; 3]; 3];
+    for (i, value) in values.iter().enumerate() {
+        scores[i / 3][i % 3] = value
+            .parse()
+            .with_context(|| format!("Invalid score: {}", value))?;
+    }
+    Ok(Some(scores))
+}
+
 /// Writes `content` to `path` via a sibling temp file and an atomic rename, so an
 /// interrupted write never truncates the existing file.
 fn write_atomic(path: &Path, content: &str) -> Result<()> {
@@ -190,11 +261,26 @@ mod tests {
         let dir = tempdir().unwrap();
         std::fs::write(results_path(dir.path()), sample_results()).unwrap();
         let rows = load_review_rows(dir.path()).unwrap();
-        save_review_rows(dir.path(), &rows).unwrap();
+        save_review_rows(dir.path(), &rows, CSV_HEADER).unwrap();
         let again = load_review_rows(dir.path()).unwrap();
         assert_eq!(rows, again);
     }
 
+    #[test]
+    fn test_roundtrip_quoted_path_with_comma() {
+        let dir = tempdir().unwrap();
+        let csv = "iteration,timestamp,screenshot,s1c1,s1c2,s1c3,s2c1,s2c2,s2c3,s3c1,s3c2,s3c3,recovery\n\
+                   1,2026-06-24T22:28:09,\"C:\\Users\\a,b\\shot.png\",1,2,3,4,5,6,7,8,9,flagged\n";
+        std::fs::write(results_path(dir.path()), csv).unwrap();
+        let rows = load_review_rows(dir.path()).unwrap();
+        assert_eq!(rows[0].screenshot, "C:\\Users\\a,b\\shot.png");
+        assert_eq!(rows[0].scores[0], [1, 2, 3]);
+        assert_eq!(rows[0].recovery, "flagged");
+
+        save_review_rows(dir.path(), &rows, CSV_HEADER).unwrap();
+        assert_eq!(load_review_rows(dir.path()).unwrap(), rows);
+    }
+
     #[test]
     fn test_edit_updates_both_files_and_marks_manual() {
         let dir = tempdir().unwrap();
@@ -203,7 +289,7 @@ mod tests {
         // Correct iter 2's stage 2 to a real value and mark manual.
         rows[1].scores[1] = [206174, 1032249, 1048189];
         rows[1].recovery = RECOVERY_MANUAL.to_string();
-        save_review_rows(dir.path(), &rows).unwrap();
+        save_review_rows(dir.path(), &rows, CSV_HEADER).unwrap();
 
         // results.csv reflects the edit + manual marker.
         let reloaded = load_review_rows(dir.path()).unwrap();
@@ -226,14 +312,14 @@ mod tests {
         std::fs::write(results_path(dir.path()), csv).unwrap();
         let rows = load_review_rows(dir.path()).unwrap();
         assert_eq!(rows[0].recovery, RECOVERY_VERIFIED);
-        save_review_rows(dir.path(), &rows).unwrap();
+        save_review_rows(dir.path(), &rows, CSV_HEADER).unwrap();
         let again = load_review_rows(dir.path()).unwrap();
         assert_eq!(again[0].recovery, "verified");
         assert_eq!(again[0].scores[0], [848392, 1340813, 1026578]);
     }
 
     #[test]
-    fn test_legacy_12_column_loads_and_saves_13() {
+    fn test_legacy_12_column_loads_and_saves_current() {
         let dir = tempdir().unwrap();
         let legacy = "iteration,timestamp,screenshot,s1c1,s1c2,s1c3,s2c1,s2c2,s2c3,s3c1,s3c2,s3c3\n\
                       1,2026-06-24T00:00:00,C:\\out\\001.png,1,2,3,4,5,6,7,8,9\n";
@@ -241,9 +327,78 @@ mod tests {
         let rows = load_review_rows(dir.path()).unwrap();
         assert_eq!(rows.len(), 1);
         assert_eq!(rows[0].recovery, "ok"); // defaulted
-        save_review_rows(dir.path(), &rows).unwrap();
+        save_review_rows(dir.path(), &rows, CSV_HEADER).unwrap();
+        let content = std::fs::read_to_string(results_path(dir.path())).unwrap();
+        assert_eq!(content.lines().next().unwrap(), CSV_HEADER);
+        // recovery defaulted to ok, grand total recomputed, stage totals unknown
+        assert!(content.lines().nth(1).unwrap().ends_with(",ok,45,,,"));
+    }
+
+    #[test]
+    fn test_save_keeps_custom_header() {
+        let dir = tempdir().unwrap();
+        let custom = "run,time,file,a1,a2,a3,b1,b2,b3,c1,c2,c3,flag,sum,t1,t2,t3";
+        let csv = format!(
+            "{}\n1,2026-06-24T00:00:00,C:\\out\\001.png,1,2,3,4,5,6,7,8,9,ok,45,6,,24\n",
+            custom
+        );
+        std::fs::write(results_path(dir.path()), csv).unwrap();
+        let rows = load_review_rows(dir.path()).unwrap();
+        assert_eq!(rows.len(), 1);
+        save_review_rows(dir.path(), &rows, CSV_HEADER).unwrap();
+        let content = std::fs::read_to_string(results_path(dir.path())).unwrap();
+        assert_eq!(content.lines().next().unwrap(), custom);
+        assert_eq!(load_review_rows(dir.path()).unwrap(), rows);
+    }
+
+    #[test]
+    fn test_manual_scores_update_stage_totals() {
+        let dir = tempdir().unwrap();
+        let csv = format!(
+            "{}\n1,t,C:\\out\\001.png,9,9,9,4,5,6,7,8,9,flagged,57,27,15,24\n",
+            CSV_HEADER
+        );
+        std::fs::write(results_path(dir.path()), csv).unwrap();
+        let mut rows = load_review_rows(dir.path()).unwrap();
+        rows[0].set_manual_scores([[1, 2, 3], [4, 5, 6], [7, 8, 9]]);
+        save_review_rows(dir.path(), &rows, CSV_HEADER).unwrap();
+
+        let rows = load_review_rows(dir.path()).unwrap();
+        assert_eq!(rows[0].scores, [[1, 2, 3], [4, 5, 6], [7, 8, 9]]);
+        // Sum plus a fifth of the best score, as the game totals a stage
+        assert_eq!(rows[0].stage_totals, [Some(6), Some(16), Some(25)]);
+        assert_eq!(rows[0].recovery, RECOVERY_MANUAL);
+    }
+
+    #[test]
+    fn test_parse_manual_scores() {
+        assert_eq!(parse_manual_scores("  ").unwrap(), None);
+        assert_eq!(
+            parse_manual_scores("1 2 3 4 5 6 7 8 9").unwrap(),
+            Some([[1, 2, 3], [4, 5, 6], [7, 8, 9]])
+        );
+        assert_eq!(
+            parse_manual_scores("10,20,30, 40,50,60,70 80 90\r\n").unwrap(),
+            Some([[10, 20, 30], [40, 50, 60], [70, 80, 90]])
+        );
+        assert!(parse_manual_scores("1 2 3").is_err());
+        assert!(parse_manual_scores("1 2 3 4 5 6 7 8 x").is_err());
+    }
+
+    #[test]
+    fn test_stage_totals_roundtrip_and_total_recomputed() {
+        let dir = tempdir().unwrap();
+        let csv = format!(
+            "{}\n1,2026-06-24T00:00:00,C:\\out\\001.png,1,2,3,4,5,6,7,8,9,ok,45,6,,24\n",
+            CSV_HEADER
+        );
+        std::fs::write(results_path(dir.path()), csv).unwrap();
+        let mut rows = load_review_rows(dir.path()).unwrap();
+        assert_eq!(rows[0].stage_totals, [Some(6), None, Some(24)]);
+
+        rows[0].scores[0][0] = 11;
+        save_review_rows(dir.path(), &rows, CSV_HEADER).unwrap();
         let content = std::fs::read_to_string(results_path(dir.path())).unwrap();
-        assert!(content.lines().next().unwrap().ends_with(",recovery"));
-        assert!(content.lines().nth(1).unwrap().ends_with(",ok"));
+        assert!(content.lines().nth(1).unwrap().ends_with(",ok,55,6,,24"));
     }
 }
@@ -2,21 +2,30 @@
 //!
 //! Coordinates the automation thread and OCR worker thread.
 //! Spawns threads, runs the state machine, and handles completion.
+//!
+//! Progress is published as [`AutomationEvent`]s through the run's
+//! [`RunEvents`]: each one updates the globals the GUI polls
+//! (`get_current_iteration`, the live score buffer, ...) and is forwarded to
+//! the channel an embedder passed to `start_automation`.
 
 use anyhow::{anyhow, Result};
 use chrono::Local;
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
-use std::sync::Mutex;
-use std::thread;
-
-use crate::automation::config::get_config;
-use crate::automation::csv_writer::init_csv;
-use crate::automation::ocr_worker::run_ocr_worker;
-use crate::automation::queue::create_work_queue;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::automation::config::{get_config, AutomationConfig};
+use crate::automation::csv_writer::{init_csv, results_header};
+use crate::automation::ocr_worker::{run_ocr_worker, OcrWorkerStop};
+use crate::automation::queue::{create_work_queue, OcrWorkItem};
 use crate::automation::state::{reset_abort_flag, AutomationContext, AutomationState};
-use crate::capture::find_gakumas_window;
+use crate::capture::timing::take_capture_timing_stats;
+use crate::capture::{find_gakumas_window_with_retry, get_client_area_info};
 
 /// Global flag indicating if automation is currently running.
 static AUTOMATION_RUNNING: AtomicBool = AtomicBool::new(false);
@@ -27,6 +36,24 @@ static CURRENT_ITERATION: AtomicU32 = AtomicU32::new(0);
 /// Total iterations for current run (for GUI progress display).
 static TOTAL_ITERATIONS: AtomicU32 = AtomicU32::new(0);
 
+/// Whether the current (or most recent) run queues screenshots for OCR. False
+/// for capture-only runs, which the GUI uses to skip chart generation.
+static RUN_OCR: AtomicBool = AtomicBool::new(true);
+
+/// Returns true if the current/most recent run performed OCR.
+pub fn is_ocr_enabled() -> bool {
+    RUN_OCR.load(Ordering::SeqCst)
+}
+
+/// Whether the current (or most recent) run is a `start_test_run` check, which
+/// dumps the OCR debug crops whatever `debug_ocr` says.
+static TEST_RUN: AtomicBool = AtomicBool::new(false);
+
+/// Returns true if the current/most recent run is a test run.
+pub fn is_test_run() -> bool {
+    TEST_RUN.load(Ordering::SeqCst)
+}
+
 /// One row of live OCR scores for the in-progress run's distribution view.
 ///
 /// `flagged` is true when overlap-recovery could not confidently reconstruct the
@@ -46,9 +73,9 @@ pub struct LiveScoreRow {
 /// CSV on resume/extend. Mirrors the `CURRENT_STATE_DESC` mutex pattern.
 static LIVE_SCORES: Mutex<Vec<LiveScoreRow>> = Mutex::new(Vec::new());
 
-/// Records one completed iteration's scores into the live buffer (called from the
-/// OCR worker thread). `flagged` rows are kept but excluded from live statistics.
-pub fn record_live_score(scores: [[u32; 3]; 3], flagged: bool) {
+/// Records one completed iteration's scores into the live buffer (on a
+/// `Scores` event). `flagged` rows are kept but excluded from live statistics.
+fn record_live_score(scores: [[u32; 3]; 3], flagged: bool) {
     if let Ok(mut v) = LIVE_SCORES.lock() {
         v.push(LiveScoreRow { scores, flagged });
     }
@@ -94,7 +121,7 @@ fn seed_live_scores_from_csv(session_dir: &Path) {
                 for r in rows {
                     v.push(LiveScoreRow {
                         scores: r.scores,
-                        flagged: r.recovery == "flagged",
+                        flagged: r.recovery == crate::automation::results_edit::RECOVERY_FLAGGED,
                     });
                 }
             }
@@ -108,6 +135,89 @@ fn seed_live_scores_from_csv(session_dir: &Path) {
     }
 }
 
+/// Iterations that already have a row in the session's results CSV (empty if
+/// it can't be read).
+pub(super) fn processed_iterations(csv_path: &Path) -> HashSet<u32> {
+    csv_path
+        .parent()
+        .and_then(|dir| crate::automation::results_edit::load_review_rows(dir).ok())
+        .unwrap_or_default()
+        .iter()
+        .map(|r| r.iteration)
+        .collect()
+}
+
+/// Screenshots in `screenshot_dir` (named `NNN_<timestamp>.png`) whose
+/// iteration is not in `processed`, sorted by iteration.
+///
+/// After a crash the OCR worker may have died with captures still queued; on
+/// resume these are re-queued so every screenshot ends up with a CSV row. Only
+/// iterations missing from the CSV are returned, so nothing is processed twice.
+fn screenshots_missing_results(
+    screenshot_dir: &Path,
+    processed: &HashSet<u32>,
+) -> Vec<(u32, PathBuf)> {
+    session_screenshots(screenshot_dir)
+        .into_iter()
+        .filter(|(iteration, _)| !processed.contains(iteration))
+        .collect()
+}
+
+/// Deletes the oldest screenshots in `screenshot_dir` so that at most `keep`
+/// remain, skipping any iteration for which `pending` is true (queued for OCR
+/// without a CSV row yet). Returns the deleted paths.
+///
+/// Skipped files still count toward `keep`, so the folder can briefly hold
+/// more than `keep` screenshots until the OCR worker catches up.
+pub(super) fn rotate_screenshots(
+    screenshot_dir: &Path,
+    keep: usize,
+    pending: impl Fn(u32) -> bool,
+) -> Vec<PathBuf> {
+    let screenshots = session_screenshots(screenshot_dir);
+    let excess = screenshots.len().saturating_sub(keep);
+    screenshots
+        .into_iter()
+        .take(excess)
+        .filter(|(iteration, _)| !pending(*iteration))
+        .filter_map(|(_, path)| match fs::remove_file(&path) {
+            Ok(()) => Some(path),
+            Err(e) => {
+                crate::log(&format!(
+                    "Warning: Failed to delete {}: {}",
+                    crate::paths::relative_display(&path),
+                    e
+                ));
+                None
+            }
+        })
+        .collect()
+}
+
+/// Screenshots in `screenshot_dir` (named `NNN_<timestamp>.png`) with their
+/// iteration, sorted by iteration (oldest first).
+fn session_screenshots(screenshot_dir: &Path) -> Vec<(u32, PathBuf)> {
+    let Ok(entries) = fs::read_dir(screenshot_dir) else {
+        return Vec::new();
+    };
+    let mut screenshots: Vec<(u32, PathBuf)> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.extension()
+                .map(|x| x.eq_ignore_ascii_case("png"))
+                .unwrap_or(false)
+        })
+        .filter_map(|p| {
+            let stem = p.file_stem()?.to_str()?;
+            let iteration: u32 = stem.split('_').next()?.parse().ok()?;
+            Some((iteration, p))
+        })
+        .collect();
+    screenshots.sort();
+    screenshots
+}
+
 /// Current state description (for GUI progress display).
 static CURRENT_STATE_DESC: Mutex<String> = Mutex::new(String::new());
 
@@ -131,6 +241,83 @@ pub enum AutomationOutcome {
     },
 }
 
+/// Progress of an automation run, in the order it happens.
+///
+/// A run publishes `StateChanged` and `IterationStarted`/`IterationCompleted`
+/// from the automation thread and `Scores` from the OCR worker, and ends with
+/// exactly one `Finished`, sent once the OCR worker has drained or been
+/// stopped and its `RunEvents` closed.
+#[derive(Clone, Debug)]
+pub enum AutomationEvent {
+    /// The state machine entered `state`; `description` is its GUI label.
+    StateChanged { state: AutomationState, description: String },
+    /// Iteration `iteration` (1-based) of `total` started.
+    IterationStarted { iteration: u32, total: u32 },
+    /// Iteration `iteration` captured its result; `completed` runs of `total`
+    /// have now finished.
+    IterationCompleted { iteration: u32, completed: u32, total: u32 },
+    /// OCR read the scores of `iteration` (`[stage][slot]`); `flagged` when
+    /// the overlap recovery could not confidently reconstruct them.
+    Scores { iteration: u32, scores: [[u32; 3]; 3], flagged: bool },
+    /// The run ended; the last event of a run.
+    Finished(AutomationOutcome),
+}
+
+/// Where one run's events go: the progress globals, then the embedder's
+/// channel (the `events` argument of `start_automation`), if any.
+///
+/// The automation thread and the run's OCR worker each hold their own handle
+/// on the channel; the worker's is closed before `Finished` is sent, so a
+/// worker that outlives its run can never send after it.
+#[derive(Clone, Debug, Default)]
+pub struct RunEvents(Arc<Mutex<Option<Sender<AutomationEvent>>>>);
+
+impl RunEvents {
+    /// Events forwarded to `sender` (None: the progress globals only).
+    pub(super) fn new(sender: Option<Sender<AutomationEvent>>) -> Self {
+        Self(Arc::new(Mutex::new(sender)))
+    }
+
+    /// A separate handle on the same channel, closed independently.
+    pub(super) fn handle(&self) -> Self {
+        Self::new(self.0.lock().ok().and_then(|sender| sender.clone()))
+    }
+
+    /// Applies `event` to the progress globals, then forwards it to the
+    /// channel, if any. A disconnected receiver is dropped quietly; the run
+    /// carries on.
+    pub(super) fn publish(&self, event: AutomationEvent) {
+        match &event {
+            AutomationEvent::StateChanged { description, .. } => {
+                update_state_description(description)
+            }
+            AutomationEvent::IterationStarted { iteration, total } => {
+                CURRENT_ITERATION.store(*iteration, Ordering::SeqCst);
+                TOTAL_ITERATIONS.store(*total, Ordering::SeqCst);
+            }
+            AutomationEvent::IterationCompleted { .. } => {}
+            AutomationEvent::Scores { scores, flagged, .. } => {
+                record_live_score(*scores, *flagged)
+            }
+            AutomationEvent::Finished(outcome) => set_last_outcome(outcome.clone()),
+        }
+
+        if let Ok(mut sender) = self.0.lock()
+            && sender.as_ref().is_some_and(|s| s.send(event).is_err())
+        {
+            crate::log("Automation event receiver disconnected; no longer forwarding events");
+            *sender = None;
+        }
+    }
+
+    /// Drops this handle's sender; later events only update the globals.
+    pub(super) fn close(&self) {
+        if let Ok(mut sender) = self.0.lock() {
+            *sender = None;
+        }
+    }
+}
+
 /// Outcome of the most recently finished automation run.
 static LAST_OUTCOME: Mutex<Option<AutomationOutcome>> = Mutex::new(None);
 
@@ -190,8 +377,8 @@ pub fn get_last_outcome() -> Option<AutomationOutcome> {
     LAST_OUTCOME.lock().ok().and_then(|o| o.clone())
 }
 
-/// Sets the outcome of the just-finished automation run (called from the
-/// automation thread before it clears the running flag).
+/// Sets the outcome of the just-finished automation run (on the `Finished`
+/// event, before the automation thread clears the running flag).
 fn set_last_outcome(outcome: AutomationOutcome) {
     if let Ok(mut o) = LAST_OUTCOME.lock() {
         *o = Some(outcome);
@@ -213,14 +400,30 @@ fn clear_last_outcome() {
 ///
 /// # Arguments
 /// * `max_iterations` - Number of iterations to run (uses config default if None)
+/// * `run_ocr` - When false, only screenshots are saved (no OCR worker, no CSV rows)
+/// * `events` - Channel that receives the run's [`AutomationEvent`]s, ending
+///   with `Finished`; the sender is dropped after it
 ///
 /// # Errors
 /// Returns an error if:
 /// - Automation is already running
 /// - Game window cannot be found
-pub fn start_automation(max_iterations: Option<u32>) -> Result<()> {
+pub fn start_automation(
+    max_iterations: Option<u32>,
+    run_ocr: bool,
+    events: Option<Sender<AutomationEvent>>,
+) -> Result<()> {
     let iterations = max_iterations.unwrap_or(DEFAULT_ITERATIONS);
-    start_automation_inner(iterations, 1, None)
+    start_automation_inner(iterations, 1, None, run_ocr, events, false)
+}
+
+/// Starts a fresh one-iteration run with OCR as a check before a long session.
+///
+/// Identical to `start_automation(Some(1), true, None)` except that the OCR
+/// worker writes its debug crops (`ocr_debug/`) as with `debug_ocr`, so a bad
+/// read can be traced to its crop.
+pub fn start_test_run() -> Result<()> {
+    start_automation_inner(1, 1, None, true, None, true)
 }
 
 /// Resumes a previously interrupted run, appending into its existing folder.
@@ -237,7 +440,7 @@ pub fn resume_automation(session_dir: PathBuf, completed: u32, total: u32) -> Re
             session_dir.display()
         ));
     }
-    start_automation_inner(total, completed + 1, Some(session_dir))
+    start_automation_inner(total, completed + 1, Some(session_dir), true, None, false)
 }
 
 /// Extends a finished run with `additional` brand-new iterations, appending
@@ -261,7 +464,7 @@ pub fn extend_automation(session_dir: PathBuf, additional: u32) -> Result<()> {
     }
     let completed = crate::automation::session_meta::count_captured(&session_dir);
     let new_total = completed + additional;
-    start_automation_inner(new_total, completed + 1, Some(session_dir))
+    start_automation_inner(new_total, completed + 1, Some(session_dir), true, None, false)
 }
 
 /// Shared setup for fresh and resumed runs.
@@ -269,10 +472,16 @@ pub fn extend_automation(session_dir: PathBuf, additional: u32) -> Result<()> {
 /// * `iterations`     - total runs; the loop stops once this is reached
 /// * `start_iteration`- 1-based iteration to begin from (1 fresh; completed+1 resume)
 /// * `existing_session` - reuse this folder if Some (resume); else create new (fresh)
+/// * `run_ocr`        - spawn the OCR worker and queue captures (false = capture-only)
+/// * `events`         - embedder channel for this run's events, if any
+/// * `test_run`       - dump OCR debug crops regardless of `debug_ocr`
 fn start_automation_inner(
     iterations: u32,
     start_iteration: u32,
     existing_session: Option<PathBuf>,
+    run_ocr: bool,
+    events: Option<Sender<AutomationEvent>>,
+    test_run: bool,
 ) -> Result<()> {
     if AUTOMATION_RUNNING.swap(true, Ordering::SeqCst) {
         return Err(anyhow!("Automation is already running"));
@@ -280,24 +489,39 @@ fn start_automation_inner(
 
     reset_abort_flag();
     clear_last_outcome();
+    RUN_OCR.store(run_ocr, Ordering::SeqCst);
+    TEST_RUN.store(test_run, Ordering::SeqCst);
     clear_live_scores();
 
-    let hwnd = match find_gakumas_window() {
+    let mut config = AutomationConfig::clone(&get_config());
+
+    let hwnd = match find_gakumas_window_with_retry(config.window_find_timeout_ms) {
         Ok(hwnd) => hwnd,
         Err(e) => {
             AUTOMATION_RUNNING.store(false, Ordering::SeqCst);
-            return Err(anyhow!("Failed to find game window: {}", e));
+            // Keep the variant so the GUI can say the game isn't running
+            let message = format!("Failed to find game window: {}", e);
+            return Err(anyhow::Error::new(e).context(message));
         }
     };
-
-    let config = get_config().clone();
     let is_resume = existing_session.is_some();
 
     let session_dir = match existing_session {
         Some(dir) => dir,
         None => {
+            // Checked here rather than at startup so a bad output_dir (e.g. an
+            // unplugged drive) only fails the run, not the GUI or the CLI tools
+            let output_dir = crate::paths::get_session_output_dir();
+            if let Err(e) = crate::paths::ensure_writable_dir(&output_dir) {
+                AUTOMATION_RUNNING.store(false, Ordering::SeqCst);
+                return Err(anyhow!(
+                    "Output directory {} is not writable: {}",
+                    output_dir.display(),
+                    e
+                ));
+            }
             let timestamp = Local::now().format("%Y%m%d_%H%M%S").to_string();
-            crate::paths::get_output_dir().join(&timestamp)
+            output_dir.join(&timestamp)
         }
     };
 
@@ -308,6 +532,19 @@ fn start_automation_inner(
 
     set_current_session_path(session_dir.clone());
 
+    // A resumed session keeps the capture mode it was started with, so the
+    // re-queued and new screenshots share the layout the OCR regions assume.
+    if is_resume {
+        let recorded = crate::automation::session_meta::session_capture_mode(&session_dir);
+        if recorded != config.capture_mode {
+            crate::log(&format!(
+                "Resume: using the session's capture_mode {:?} instead of {:?}",
+                recorded, config.capture_mode
+            ));
+            config.capture_mode = recorded;
+        }
+    }
+
     let session_log_path = session_dir.join("session.log");
     crate::set_session_log(Some(session_log_path.clone()));
 
@@ -319,7 +556,7 @@ fn start_automation_inner(
         return Err(anyhow!("Failed to create screenshot directory: {}", e));
     }
 
-    if let Err(e) = init_csv(&csv_path) {
+    if let Err(e) = init_csv(&csv_path, results_header(&config)) {
         AUTOMATION_RUNNING.store(false, Ordering::SeqCst);
         return Err(anyhow!("Failed to initialize CSV file: {}", e));
     }
@@ -344,9 +581,29 @@ fn start_automation_inner(
             status: "running".to_string(),
             message: None,
             dismissed: false,
+            capture_mode: config.capture_mode,
         },
     );
 
+    let manifest = crate::automation::session_meta::RunManifest {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        started_at: Local::now().to_rfc3339(),
+        iterations,
+        start_iteration,
+        run_ocr,
+        client_size: get_client_area_info(hwnd).ok().map(|(rect, _)| {
+            [(rect.right - rect.left) as u32, (rect.bottom - rect.top) as u32]
+        }),
+        config: config.without_schema_ref(),
+        capture_timings: None,
+    };
+    crate::automation::session_meta::write_manifest(&session_dir, &manifest);
+    if config.archive_config && !is_resume {
+        crate::automation::session_meta::archive_config(&session_dir, &config, |rel| {
+            crate::paths::resolve_data_file(rel)
+        });
+    }
+
     if is_resume {
         crate::log(&format!(
             "Resuming automation from iteration {}/{} (Ctrl+Shift+Q to abort)",
@@ -361,17 +618,27 @@ fn start_automation_inner(
     crate::log(&format!("Session folder: {}", crate::paths::relative_display(&session_dir)));
     crate::log(&format!("Screenshots: {}", crate::paths::relative_display(&screenshot_dir)));
     crate::log(&format!("Results CSV: {}", crate::paths::relative_display(&csv_path)));
+    if !run_ocr {
+        crate::log("Capture-only run: OCR and chart generation are skipped");
+    }
+    if test_run {
+        crate::log("Test run: OCR debug crops are written to ocr_debug/");
+    }
 
     // Extract raw pointer value to pass across thread boundary
     // SAFETY: HWND is just a pointer wrapper, and Windows handles are valid
     // across threads. We reconstruct it in the spawned thread.
     let hwnd_raw = hwnd.0 as usize;
 
+    let events = RunEvents::new(events);
+
     // Spawn automation thread
     thread::spawn(move || {
         // Reconstruct HWND from raw pointer value
         let hwnd = windows::Win32::Foundation::HWND(hwnd_raw as *mut std::ffi::c_void);
-        run_automation_loop(hwnd, config, iterations, start_iteration, screenshot_dir, csv_path);
+        run_automation_loop(
+            hwnd, config, iterations, start_iteration, session_dir, manifest, events,
+        );
         AUTOMATION_RUNNING.store(false, Ordering::SeqCst);
         crate::log("Automation thread finished");
     });
@@ -379,39 +646,111 @@ fn start_automation_inner(
     Ok(())
 }
 
-/// Runs the automation loop (called from the automation thread).
+/// Runs the automation loop in `session_dir` (called from the automation
+/// thread).
+///
+/// `manifest` is the one written when the run started (its `run_ocr` decides
+/// whether the OCR worker runs); it is rewritten with the capture timings
+/// when the loop ends. The run's events go to `events`; the OCR worker gets
+/// its own handle on them.
 fn run_automation_loop(
     hwnd: windows::Win32::Foundation::HWND,
-    config: crate::automation::config::AutomationConfig,
+    config: AutomationConfig,
     max_iterations: u32,
     start_iteration: u32,
-    screenshot_dir: PathBuf,
-    csv_path: PathBuf,
+    session_dir: PathBuf,
+    mut manifest: crate::automation::session_meta::RunManifest,
+    events: RunEvents,
 ) {
-    // Create work queue
-    let (sender, receiver) = create_work_queue();
-
-    // Spawn OCR worker thread
-    let score_regions = config.score_regions;
-    let total_regions = config.total_regions;
-    let bonus_regions = config.bonus_regions;
-    let csv_path_clone = csv_path.clone();
-    let ocr_handle = thread::spawn(move || {
-        run_ocr_worker(receiver, csv_path_clone, score_regions, total_regions, bonus_regions);
-    });
+    let screenshot_dir = session_dir.join("screenshots");
+    let csv_path = session_dir.join("results.csv");
+
+    // Only this run's captures count towards the manifest's timings
+    let _ = take_capture_timing_stats();
+
+    // Create work queue and spawn the OCR worker thread, unless this is a
+    // capture-only run (then no sender is handed to the state machine at all).
+    let ocr_stop = OcrWorkerStop::default();
+    let ocr_events = events.handle();
+    let (sender, ocr_handle) = if manifest.run_ocr {
+        let (sender, receiver) = create_work_queue();
+        // Regions as they fall on the saved screenshots (cropped to the
+        // score area in ScoreRegionsOnly mode).
+        let regions = config.screenshot_regions();
+        let worker_config = config.clone();
+        let csv_path_clone = csv_path.clone();
+        let (stop, worker_events) = (ocr_stop.clone(), ocr_events.clone());
+        let test_run = is_test_run();
+        let handle = thread::spawn(move || {
+            run_ocr_worker(
+                receiver,
+                csv_path_clone,
+                regions,
+                worker_config,
+                Some(worker_events),
+                test_run,
+                stop,
+            );
+        });
+        (Some(sender), Some(handle))
+    } else {
+        (None, None)
+    };
+
+    // On resume/extend, finish OCR for captures from the interrupted run that
+    // never made it into results.csv (queued ahead of this run's captures).
+    if let Some(sender) = sender.as_ref().filter(|_| start_iteration > 1) {
+        let processed = processed_iterations(&csv_path);
+        let missing = screenshots_missing_results(&screenshot_dir, &processed);
+        if !missing.is_empty() {
+            crate::log(&format!(
+                "Re-queuing {} screenshot(s) without OCR results: {:?}",
+                missing.len(),
+                missing.iter().map(|(i, _)| *i).collect::<Vec<_>>()
+            ));
+        }
+        for (iteration, path) in missing {
+            if let Err(e) = sender.send(OcrWorkItem::new(path, iteration)) {
+                crate::log(&format!("Warning: Failed to re-queue iteration {}: {}", iteration, e));
+            }
+        }
+    }
 
     // Create and run state machine
     let mut ctx = AutomationContext::new(
         hwnd, config, max_iterations, start_iteration, sender, screenshot_dir,
     );
 
-    // Run state machine until complete
+    // Run state machine until complete, publishing what changed each step
+    let mut last_state = None;
+    let mut last_iteration = 0;
     loop {
-        // Update progress counters for GUI
-        CURRENT_ITERATION.store(ctx.current_iteration, Ordering::SeqCst);
-        update_state_description(&ctx.state.description_ja());
+        if last_state.as_ref() != Some(&ctx.state) {
+            last_state = Some(ctx.state.clone());
+            events.publish(AutomationEvent::StateChanged {
+                state: ctx.state.clone(),
+                description: ctx.state.description_ja(),
+            });
+        }
+        if ctx.current_iteration != last_iteration {
+            last_iteration = ctx.current_iteration;
+            events.publish(AutomationEvent::IterationStarted {
+                iteration: ctx.current_iteration,
+                total: max_iterations,
+            });
+        }
+        let completed_before = ctx.completed_iterations;
+
+        let step = ctx.step();
+        if ctx.completed_iterations > completed_before {
+            events.publish(AutomationEvent::IterationCompleted {
+                iteration: ctx.current_iteration,
+                completed: ctx.completed_iterations,
+                total: max_iterations,
+            });
+        }
 
-        match ctx.step() {
+        match step {
             Ok(true) => {
                 // Continue running
             }
@@ -426,6 +765,9 @@ fn run_automation_loop(
         }
     }
 
+    // Screenshots still being written belong to this run's result
+    ctx.finish_screenshot_writer();
+
     // Log final state and record the outcome for the GUI. `completed_iterations`
     // counts runs that actually captured a result, so the GUI can report exactly
     // how far the automation got when it stops early.
@@ -476,42 +818,122 @@ fn run_automation_loop(
         }
     };
 
+    // Where capture time went, for tuning
+    let timings = take_capture_timing_stats();
+    if timings.captures > 0 {
+        crate::log(&format!("Capture timings: {}", timings.summary()));
+        manifest.capture_timings = Some(timings);
+        crate::automation::session_meta::write_manifest(&session_dir, &manifest);
+    }
+
+    // Drop the sender to signal OCR worker to finish
+    drop(ctx.work_sender);
+
+    // Wait for OCR worker to finish processing remaining items, bounded so a
+    // wedged Tesseract cannot hold the run open forever
+    let mut ocr_pending = None;
+    if let Some(ocr_handle) = ocr_handle {
+        let drain_timeout_ms = ctx.config.ocr_drain_timeout_ms;
+        crate::log("Waiting for OCR worker to finish...");
+        let timeout = (drain_timeout_ms > 0).then(|| Duration::from_millis(drain_timeout_ms));
+        if !drain_ocr_worker(ocr_handle, &ocr_stop, timeout) {
+            let processed = processed_iterations(&csv_path);
+            let pending: Vec<u32> = screenshots_missing_results(&ctx.screenshot_dir, &processed)
+                .iter()
+                .map(|(i, _)| *i)
+                .collect();
+            crate::log(&format!(
+                "Warning: OCR worker did not finish within {} ms and was stopped; {} \
+                 screenshot(s) have no OCR result: {:?} (a resume re-queues them)",
+                drain_timeout_ms,
+                pending.len(),
+                pending
+            ));
+            ocr_pending = Some(pending);
+        }
+    }
+
     // Persist the final status so this session is correctly classified on disk
     // (no longer "running"; resumable only if it stopped short of `total`).
+    // Screenshots the stopped OCR worker never read are noted in the message.
     let (meta_status, meta_message) = match &outcome {
         AutomationOutcome::Completed { .. } => ("completed", None),
         AutomationOutcome::Aborted { .. } => ("aborted", None),
         AutomationOutcome::Error { message, .. } => ("error", Some(message.clone())),
     };
-    if let Some(session_dir) = csv_path.parent() {
-        crate::automation::session_meta::write_meta(
-            session_dir,
-            &crate::automation::session_meta::RunMeta {
-                total: max_iterations,
-                completed,
-                status: meta_status.to_string(),
-                message: meta_message,
-                dismissed: false,
-            },
-        );
-    }
-    set_last_outcome(outcome);
-
-    // Drop the sender to signal OCR worker to finish
-    drop(ctx.work_sender);
+    let meta_message = match ocr_pending.filter(|pending| !pending.is_empty()) {
+        Some(pending) => {
+            let note = format!("OCR unfinished for iterations {:?}", pending);
+            Some(meta_message.map_or(note.clone(), |message| format!("{}; {}", message, note)))
+        }
+        None => meta_message,
+    };
+    crate::automation::session_meta::write_meta(
+        &session_dir,
+        &crate::automation::session_meta::RunMeta {
+            total: max_iterations,
+            completed,
+            status: meta_status.to_string(),
+            message: meta_message,
+            dismissed: false,
+            capture_mode: manifest.config.capture_mode,
+        },
+    );
 
-    // Wait for OCR worker to finish processing remaining items
-    crate::log("Waiting for OCR worker to finish...");
-    if let Err(e) = ocr_handle.join() {
-        crate::log(&format!("OCR worker thread panicked: {:?}", e));
-    }
+    // The worker has exited or been stopped; it sends nothing from here on
+    ocr_events.close();
 
     crate::log("All processing complete");
 
+    // Last event of the run; the embedder's receiver then sees the channel close
+    events.publish(AutomationEvent::Finished(outcome));
+    events.close();
+
     // Deactivate per-session logging
     crate::set_session_log(None);
 }
 
+/// Waits up to `timeout` for the OCR worker to drain its queue. On timeout the
+/// worker is stopped: once this returns false it writes no further rows, so
+/// results.csv is complete as it stands (the worker thread itself is left to
+/// exit after the screenshot it is reading).
+fn drain_ocr_worker(
+    handle: JoinHandle<()>,
+    stop: &OcrWorkerStop,
+    timeout: Option<Duration>,
+) -> bool {
+    match join_with_timeout(handle, timeout) {
+        Some(Ok(())) => true,
+        Some(Err(e)) => {
+            crate::log(&format!("OCR worker thread panicked: {:?}", e));
+            true
+        }
+        None => {
+            stop.stop();
+            false
+        }
+    }
+}
+
+/// Joins `handle`, giving up after `timeout` (`None` waits indefinitely).
+///
+/// Returns `None` on timeout; the handle is dropped, which detaches the thread.
+fn join_with_timeout<T>(
+    handle: JoinHandle<T>,
+    timeout: Option<Duration>,
+) -> Option<thread::Result<T>> {
+    if let Some(timeout) = timeout {
+        let deadline = Instant::now() + timeout;
+        while !handle.is_finished() {
+            if Instant::now() >= deadline {
+                return None;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+    Some(handle.join())
+}
+
 /// Re-export request_abort for convenience.
 pub use crate::automation::state::request_abort;
 
@@ -545,4 +967,140 @@ mod tests {
 
         clear_live_scores();
     }
+
+    #[test]
+    fn publish_forwards_events_until_receiver_disconnects() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let events = RunEvents::new(Some(sender));
+
+        let completed = |iteration| AutomationEvent::IterationCompleted {
+            iteration,
+            completed: iteration - 1,
+            total: 10,
+        };
+        events.publish(completed(4));
+        match receiver.try_recv() {
+            Ok(AutomationEvent::IterationCompleted { iteration, completed, total }) => {
+                assert_eq!((iteration, completed, total), (4, 3, 10));
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        drop(receiver);
+        events.publish(completed(5));
+        assert!(events.0.lock().unwrap().is_none());
+    }
+
+    #[test]
+    fn closed_worker_handle_sends_nothing_after_finished() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let events = RunEvents::new(Some(sender));
+        let worker = events.handle();
+
+        worker.close();
+        events.publish(AutomationEvent::Finished(AutomationOutcome::Completed {
+            completed: 1,
+            total: 1,
+        }));
+        events.close();
+        // A worker that outlived its run
+        worker.publish(AutomationEvent::IterationStarted { iteration: 2, total: 2 });
+
+        let received: Vec<AutomationEvent> = receiver.iter().collect();
+        assert_eq!(received.len(), 1);
+        assert!(matches!(received[0], AutomationEvent::Finished(_)));
+    }
+
+    #[test]
+    fn screenshots_missing_results_skips_processed() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in [
+            "001_20260101_120000.png",
+            "002_20260101_120100.png",
+            "003_20260101_120200.png",
+            "notes.txt",
+        ] {
+            std::fs::write(dir.path().join(name), b"").unwrap();
+        }
+
+        let processed: HashSet<u32> = [1].into_iter().collect();
+        let missing = screenshots_missing_results(dir.path(), &processed);
+        let iterations: Vec<u32> = missing.iter().map(|(i, _)| *i).collect();
+        assert_eq!(iterations, vec![2, 3]);
+        assert!(missing[0].1.ends_with("002_20260101_120100.png"));
+    }
+
+    #[test]
+    fn rotate_screenshots_keeps_newest_and_pending() {
+        let dir = tempfile::tempdir().unwrap();
+        for i in 1..=5 {
+            std::fs::write(dir.path().join(format!("{:03}_20260101_1200{:02}.png", i, i)), b"")
+                .unwrap();
+        }
+
+        // Keep 2: iterations 1-3 are over the cap, but 2 is still queued for OCR.
+        let deleted = rotate_screenshots(dir.path(), 2, |iteration| iteration == 2);
+        let deleted: Vec<u32> = deleted
+            .iter()
+            .map(|p| p.file_name().unwrap().to_str().unwrap()[..3].parse().unwrap())
+            .collect();
+        assert_eq!(deleted, vec![1, 3]);
+
+        let left: Vec<u32> = session_screenshots(dir.path()).iter().map(|(i, _)| *i).collect();
+        assert_eq!(left, vec![2, 4, 5]);
+    }
+
+    #[test]
+    fn join_with_timeout_detaches_a_stuck_thread() {
+        let finished = thread::spawn(|| 7);
+        let joined = join_with_timeout(finished, Some(Duration::from_secs(5)));
+        assert_eq!(joined.map(|r| r.unwrap()), Some(7));
+
+        // Blocks until `release` is dropped, standing in for a wedged worker
+        let (release, blocker) = std::sync::mpsc::channel::<()>();
+        let stuck = thread::spawn(move || {
+            let _ = blocker.recv();
+        });
+        assert!(join_with_timeout(stuck, Some(Duration::from_millis(100))).is_none());
+        drop(release);
+    }
+
+    #[test]
+    fn drain_timeout_stops_a_worker_that_is_still_running() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("results.csv");
+        let stop = OcrWorkerStop::default();
+
+        // Stands in for a worker with a long queue: appends a row every 10 ms
+        // through the stop gate until stopped
+        let (exited, worker_exit) = std::sync::mpsc::channel();
+        let handle = {
+            let (stop, csv_path) = (stop.clone(), csv_path.clone());
+            thread::spawn(move || {
+                while stop
+                    .unless_stopped(|| {
+                        let mut file = fs::OpenOptions::new()
+                            .create(true)
+                            .append(true)
+                            .open(&csv_path)
+                            .unwrap();
+                        std::io::Write::write_all(&mut file, b"row\n").unwrap();
+                    })
+                    .is_some()
+                {
+                    thread::sleep(Duration::from_millis(10));
+                }
+                exited.send(()).unwrap();
+            })
+        };
+
+        assert!(!drain_ocr_worker(handle, &stop, Some(Duration::from_millis(100))));
+        let rows_at_stop = fs::read_to_string(&csv_path).unwrap().lines().count();
+        assert!(rows_at_stop > 0);
+
+        // No row is written once the drain gave up, and the worker exits
+        worker_exit.recv_timeout(Duration::from_secs(5)).unwrap();
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(fs::read_to_string(&csv_path).unwrap().lines().count(), rows_at_stop);
+    }
 }
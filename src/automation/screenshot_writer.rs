@@ -0,0 +1,158 @@
+//! Background PNG writer for automation screenshots (`async_screenshot_save`).
+//!
+//! The state machine hands each capture to a writer thread over a bounded
+//! channel and moves straight on to clicking End; PNG encoding and disk I/O
+//! happen on the writer. A screenshot is queued for OCR only once its file is
+//! on disk, so the OCR worker never opens a half-written PNG. The first save
+//! error is kept in a shared slot the state machine checks every iteration,
+//! and `finish` drains the queue before the run's outcome is reported.
+
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use image::{ImageBuffer, Rgba};
+use std::path::PathBuf;
+use std::sync::mpsc::{sync_channel, Sender, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+
+use crate::automation::queue::OcrWorkItem;
+use crate::capture::timing::record_save;
+
+/// Screenshots that may wait for the writer before `submit` blocks. Each is a
+/// full-window RGBA buffer (~8 MB at 1080p), so this also bounds memory.
+const WRITE_QUEUE_CAPACITY: usize = 4;
+
+/// One screenshot waiting to be written.
+struct SaveJob {
+    img: ImageBuffer<Rgba<u8>, Vec<u8>>,
+    path: PathBuf,
+    item: OcrWorkItem,
+}
+
+/// Handle to the writer thread of one automation run.
+pub struct ScreenshotWriter {
+    sender: SyncSender<SaveJob>,
+    handle: JoinHandle<()>,
+    /// First save error, set by the writer thread
+    error: Arc<Mutex<Option<String>>>,
+}
+
+impl ScreenshotWriter {
+    /// Starts the writer thread. Written screenshots are queued on
+    /// `ocr_sender` when given (None for capture-only runs).
+    pub fn spawn(ocr_sender: Option<Sender<OcrWorkItem>>) -> Self {
+        let (sender, receiver) = sync_channel::<SaveJob>(WRITE_QUEUE_CAPACITY);
+        let error = Arc::new(Mutex::new(None));
+        let error_slot = Arc::clone(&error);
+
+        let handle = thread::spawn(move || {
+            for job in receiver {
+                let save_start = Instant::now();
+                if let Err(e) = job.img.save(&job.path) {
+                    let message = format!(
+                        "Failed to save screenshot {}: {}",
+                        crate::paths::relative_display(&job.path),
+                        e
+                    );
+                    crate::log(&message);
+                    error_slot.lock().unwrap().get_or_insert(message);
+                    continue;
+                }
+                record_save(save_start.elapsed());
+                crate::log(&format!(
+                    "Iteration {}: Screenshot saved to {}",
+                    job.item.iteration,
+                    crate::paths::relative_display(&job.path)
+                ));
+
+                if let Some(ocr_sender) = &ocr_sender
+                    && let Err(e) = ocr_sender.send(job.item)
+                {
+                    crate::log(&format!("Warning: Failed to queue OCR work item: {}", e));
+                }
+            }
+        });
+
+        Self { sender, handle, error }
+    }
+
+    /// Queues `img` to be written to `path`; blocks only while the queue is
+    /// full.
+    pub fn submit(
+        &self,
+        img: ImageBuffer<Rgba<u8>, Vec<u8>>,
+        path: PathBuf,
+        iteration: u32,
+    ) -> Result<()> {
+        let item = OcrWorkItem {
+            screenshot_path: path.clone(),
+            iteration,
+            captured_at: Local::now(),
+        };
+        self.sender
+            .send(SaveJob { img, path, item })
+            .map_err(|_| anyhow!("Screenshot writer thread has stopped"))
+    }
+
+    /// The first save error so far, if any.
+    pub fn error(&self) -> Option<String> {
+        self.error.lock().unwrap().clone()
+    }
+
+    /// Waits until every queued screenshot is written. Returns the first save
+    /// error, if there was one.
+    pub fn finish(self) -> Result<()> {
+        drop(self.sender);
+        self.handle
+            .join()
+            .map_err(|_| anyhow!("Screenshot writer thread panicked"))?;
+        match self.error.lock().unwrap().take() {
+            Some(message) => Err(anyhow!(message)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automation::queue::create_work_queue;
+
+    fn test_image() -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+        ImageBuffer::from_pixel(4, 4, Rgba([10, 20, 30, 255]))
+    }
+
+    #[test]
+    fn writes_screenshots_then_queues_ocr() {
+        let dir = tempfile::tempdir().unwrap();
+        let (ocr_sender, ocr_receiver) = create_work_queue();
+        let writer = ScreenshotWriter::spawn(Some(ocr_sender));
+
+        for iteration in 1..=3 {
+            let path = dir.path().join(format!("{:03}.png", iteration));
+            writer.submit(test_image(), path, iteration).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let queued: Vec<OcrWorkItem> = ocr_receiver.iter().collect();
+        assert_eq!(queued.iter().map(|i| i.iteration).collect::<Vec<_>>(), [1, 2, 3]);
+        assert!(queued.iter().all(|i| i.screenshot_path.exists()));
+    }
+
+    #[test]
+    fn save_error_is_reported_and_not_queued() {
+        let dir = tempfile::tempdir().unwrap();
+        let (ocr_sender, ocr_receiver) = create_work_queue();
+        let writer = ScreenshotWriter::spawn(Some(ocr_sender));
+
+        let missing_dir = dir.path().join("missing").join("001.png");
+        writer.submit(test_image(), missing_dir, 1).unwrap();
+        writer.submit(test_image(), dir.path().join("002.png"), 2).unwrap();
+
+        let err = writer.finish().unwrap_err();
+        assert!(err.to_string().contains("001.png"), "{}", err);
+        let queued: Vec<u32> = ocr_receiver.iter().map(|i| i.iteration).collect();
+        assert_eq!(queued, [2]);
+    }
+}
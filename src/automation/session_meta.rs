@@ -4,13 +4,29 @@
 //! (e.g. `output/20260606_141500/`). It records the originally requested run
 //! count (`total`), which is otherwise only held in GUI memory, so an
 //! interrupted run can be resumed even after the app restarts.
+//!
+//! It also writes `manifest.json`, a record of how the run was made (app
+//! version, full config, window size), so old sessions stay interpretable
+//! after the config changes. The manifest is written when the run starts and
+//! rewritten once when it ends, only to add the capture timings. With
+//! `archive_config`, a fresh run also gets a loadable copy of that config and
+//! its reference images.
 
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
+use crate::automation::config::{AutomationConfig, CaptureMode};
+use crate::capture::timing::CaptureTimingStats;
+
 /// File name written inside each session folder.
 const META_FILENAME: &str = "run-meta.json";
 
+/// Manifest written when a session is first started (and again at its end).
+const MANIFEST_FILENAME: &str = "manifest.json";
+
+/// Subfolder of a session holding the archived reference images.
+const REFERENCES_DIRNAME: &str = "references";
+
 /// Persisted metadata describing one automation run.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RunMeta {
@@ -28,6 +44,107 @@ pub struct RunMeta {
     /// the folder and its data are kept. Defaults to false for older metadata.
     #[serde(default)]
     pub dismissed: bool,
+    /// How the session's screenshots were captured. A resume keeps it, so
+    /// every screenshot in the folder has the same layout whatever the config
+    /// says now; metadata from before it was recorded means `FullWindow`.
+    #[serde(default)]
+    pub capture_mode: CaptureMode,
+}
+
+/// Machine-readable record of the settings a run was started with.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunManifest {
+    /// `CARGO_PKG_VERSION` of the build that made the run.
+    pub app_version: String,
+    /// Local start time (RFC 3339).
+    pub started_at: String,
+    /// Total number of runs requested.
+    pub iterations: u32,
+    /// 1-based iteration this segment started at (> 1 for resume/extend).
+    pub start_iteration: u32,
+    /// Whether OCR ran (false for capture-only runs).
+    pub run_ocr: bool,
+    /// Game window client area `[width, height]`, if it could be read.
+    pub client_size: Option<[u32; 2]>,
+    /// The complete automation config in effect.
+    pub config: AutomationConfig,
+    /// Capture phase totals for the segment, filled in (and the manifest
+    /// rewritten) when it ends.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capture_timings: Option<CaptureTimingStats>,
+}
+
+/// Writes the run manifest into `session_dir`. A fresh run writes
+/// `manifest.json`; a resume/extend keeps that file and writes
+/// `manifest_resume_NNN.json` (NNN = first iteration of the segment) so every
+/// segment's settings are kept. Best-effort like `write_meta`.
+pub fn write_manifest(session_dir: &Path, manifest: &RunManifest) {
+    let path = if manifest.start_iteration > 1 {
+        session_dir.join(format!("manifest_resume_{:03}.json", manifest.start_iteration))
+    } else {
+        session_dir.join(MANIFEST_FILENAME)
+    };
+    match serde_json::to_string_pretty(manifest) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                crate::log(&format!("Failed to write {}: {}", path.display(), e));
+            }
+        }
+        Err(e) => crate::log(&format!("Failed to serialize run manifest: {}", e)),
+    }
+}
+
+/// Archives the run's settings into `session_dir`: the Start/Skip/End and
+/// blocked-screen reference images, located with `resolve_reference`
+/// (normally `paths::resolve_data_file`), into `references/`, and `config` as
+/// `config.json` with those references pointing at the copies
+/// (`references/<file>`) and without the `$schema` reference, which would not
+/// resolve there. Missing references are skipped and keep their path.
+/// Best-effort like `write_meta`.
+pub fn archive_config(
+    session_dir: &Path,
+    config: &AutomationConfig,
+    resolve_reference: impl Fn(&str) -> PathBuf,
+) {
+    let mut archived = config.without_schema_ref();
+    let references_dir = session_dir.join(REFERENCES_DIRNAME);
+    match std::fs::create_dir_all(&references_dir) {
+        Ok(()) => {
+            let references = [
+                &mut archived.start_button_reference,
+                &mut archived.skip_button_reference,
+                &mut archived.end_button_reference,
+                &mut archived.blocked_screen_reference,
+            ];
+            for reference in references {
+                let source = resolve_reference(reference);
+                let Some(file_name) = source.file_name().and_then(|name| name.to_str()) else {
+                    continue;
+                };
+                if !source.exists() {
+                    crate::log(&format!("Config archive: {} not found, skipped", reference));
+                    continue;
+                }
+                match std::fs::copy(&source, references_dir.join(file_name)) {
+                    Ok(_) => *reference = format!("{}/{}", REFERENCES_DIRNAME, file_name),
+                    Err(e) => {
+                        crate::log(&format!("Failed to archive {}: {}", source.display(), e))
+                    }
+                }
+            }
+        }
+        Err(e) => crate::log(&format!("Failed to create {}: {}", references_dir.display(), e)),
+    }
+
+    let config_path = session_dir.join("config.json");
+    match serde_json::to_string_pretty(&archived) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&config_path, json) {
+                crate::log(&format!("Failed to write {}: {}", config_path.display(), e));
+            }
+        }
+        Err(e) => crate::log(&format!("Failed to serialize config: {}", e)),
+    }
 }
 
 /// A session folder that was interrupted before all runs finished.
@@ -59,10 +176,18 @@ pub fn read_meta(session_dir: &Path) -> Option<RunMeta> {
     serde_json::from_str(&json).ok()
 }
 
-/// Counts captured screenshots in `session_dir/screenshots` (files ending
-/// `.png`). This is the crash-proof source of truth for completed runs:
-/// screenshots are saved synchronously in the `Capturing` state before any
-/// asynchronous OCR, so they never lag behind actual progress.
+/// `capture_mode` recorded for `session_dir`'s screenshots, or `FullWindow`
+/// when its run-meta.json is missing or predates the field.
+pub fn session_capture_mode(session_dir: &Path) -> CaptureMode {
+    read_meta(session_dir).map(|meta| meta.capture_mode).unwrap_or_default()
+}
+
+/// Counts captured screenshots in `session_dir/screenshots` as the highest
+/// iteration among the `NNN_<timestamp>.png` files. This is the crash-proof
+/// source of truth for completed runs: screenshots are saved synchronously in
+/// the `Capturing` state before any asynchronous OCR, so they never lag behind
+/// actual progress. The newest screenshot is never rotated away
+/// (`max_screenshots`), so the count survives deleted older files.
 pub fn count_captured(session_dir: &Path) -> u32 {
     let dir = session_dir.join("screenshots");
     let entries = match std::fs::read_dir(&dir) {
@@ -71,13 +196,15 @@ pub fn count_captured(session_dir: &Path) -> u32 {
     };
     entries
         .filter_map(|e| e.ok())
-        .filter(|e| {
-            e.path()
-                .extension()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.extension()
                 .map(|x| x.eq_ignore_ascii_case("png"))
                 .unwrap_or(false)
         })
-        .count() as u32
+        .filter_map(|p| p.file_stem()?.to_str()?.split('_').next()?.parse::<u32>().ok())
+        .max()
+        .unwrap_or(0)
 }
 
 /// Scans `output_dir` for interrupted runs that can be resumed.
@@ -138,3 +265,62 @@ pub fn dismiss_session(session_dir: &Path) -> bool {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archive_config_copies_config_and_existing_references() {
+        let exe_dir = tempfile::tempdir().unwrap();
+        let session_dir = tempfile::tempdir().unwrap();
+        let config = AutomationConfig::default();
+        let start_ref = exe_dir.path().join(&config.start_button_reference);
+        let blocked_ref = exe_dir.path().join(&config.blocked_screen_reference);
+        for reference in [&start_ref, &blocked_ref] {
+            std::fs::create_dir_all(reference.parent().unwrap()).unwrap();
+            std::fs::write(reference, b"png").unwrap();
+        }
+
+        archive_config(session_dir.path(), &config, |rel| exe_dir.path().join(rel));
+
+        let json = std::fs::read_to_string(session_dir.path().join("config.json")).unwrap();
+        let archived: AutomationConfig = serde_json::from_str(&json).unwrap();
+        let start_name = start_ref.file_name().unwrap().to_str().unwrap();
+        assert_eq!(archived.start_button_reference, format!("references/{}", start_name));
+        let blocked_name = blocked_ref.file_name().unwrap().to_str().unwrap();
+        assert_eq!(archived.blocked_screen_reference, format!("references/{}", blocked_name));
+        // Not found, so not copied: the path is left as configured
+        assert_eq!(archived.skip_button_reference, config.skip_button_reference);
+        assert!(!json.contains("$schema"));
+        let mut references: Vec<_> =
+            std::fs::read_dir(session_dir.path().join(REFERENCES_DIRNAME))
+                .unwrap()
+                .map(|e| e.unwrap().file_name())
+                .collect();
+        references.sort();
+        assert_eq!(references, [blocked_name, start_name]);
+    }
+
+    #[test]
+    fn session_capture_mode_reads_run_meta() {
+        let session_dir = tempfile::tempdir().unwrap();
+        assert_eq!(session_capture_mode(session_dir.path()), CaptureMode::FullWindow);
+
+        // Metadata written before capture_mode was recorded
+        let old = r#"{"total": 10, "completed": 4, "status": "aborted"}"#;
+        std::fs::write(session_dir.path().join(META_FILENAME), old).unwrap();
+        assert_eq!(session_capture_mode(session_dir.path()), CaptureMode::FullWindow);
+
+        let meta = RunMeta {
+            total: 10,
+            completed: 4,
+            status: "aborted".to_string(),
+            message: None,
+            dismissed: false,
+            capture_mode: CaptureMode::ScoreRegionsOnly,
+        };
+        write_meta(session_dir.path(), &meta);
+        assert_eq!(session_capture_mode(session_dir.path()), CaptureMode::ScoreRegionsOnly);
+    }
+}
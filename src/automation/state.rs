@@ -1,35 +1,52 @@
 //! Automation state machine for rehearsal data collection.
 //!
-//! The state machine sequences through: Start → Wait → Skip → Capture → Loop
+//! The state machine sequences through: Warm-up → Start → Wait → Skip → Capture → Loop
 //! Each state transition checks for abort signals and window validity.
 
 use anyhow::{anyhow, Result};
 use chrono::Local;
+use image::{ImageBuffer, Rgba};
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::Sender;
 use std::time::{Duration, Instant};
 
 use windows::Win32::Foundation::HWND;
-use windows::Win32::UI::WindowsAndMessaging::{IsWindow, SetForegroundWindow};
+use windows::Win32::UI::WindowsAndMessaging::{
+    IsIconic, IsWindow, SetForegroundWindow, ShowWindow, SW_RESTORE,
+};
 
-use crate::automation::config::AutomationConfig;
+use crate::automation::config::{
+    AutomationConfig, ButtonConfig, CaptureMode, ForegroundMode, NavigationKey, RelativeRect,
+};
 use crate::automation::detection::{
-    load_reference_histogram, wait_for_loading, wait_for_result,
-    wait_for_start_page, ClickRetryInfo, ReferenceImage,
+    is_rehearsal_page, load_reference_histogram, wait_for_loading, wait_for_result,
+    wait_for_start_page, ClickRetryInfo, ReferenceImage, ResultFingerprint,
 };
-use crate::automation::input::click_at_relative;
+use crate::automation::input::{click_button, press_navigation_key};
 use crate::automation::queue::OcrWorkItem;
-use crate::capture::capture_gakumas_to_buffer;
+use crate::automation::runner::{processed_iterations, rotate_screenshots};
+use crate::automation::screenshot_writer::ScreenshotWriter;
+use crate::calibration::preview::annotate_corner;
+use crate::capture::timing::record_save;
+use crate::capture::{capture_game_frame, capture_stable_frame, get_client_area_info};
+use crate::error::GakumasError;
+use crate::ocr::score_row_confidence;
 
 /// Global abort flag - set by abort hotkey handler.
 pub static ABORT_REQUESTED: AtomicBool = AtomicBool::new(false);
 
+/// Soft-stop flag: finish the current iteration (back on the rehearsal page),
+/// then complete instead of starting the next one.
+pub static STOP_AFTER_CURRENT: AtomicBool = AtomicBool::new(false);
+
 /// Automation state machine states.
 #[derive(Debug, Clone, PartialEq)]
 pub enum AutomationState {
     /// Waiting to start (initial state)
     Idle,
+    /// Checking references load and the rehearsal page is detected (no clicks)
+    WarmUp,
     /// Waiting for rehearsal start page to appear
     WaitingForStartPage,
     /// Clicking the Start button
@@ -58,6 +75,7 @@ impl std::fmt::Display for AutomationState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             AutomationState::Idle => write!(f, "Idle"),
+            AutomationState::WarmUp => write!(f, "Warming up"),
             AutomationState::WaitingForStartPage => write!(f, "Waiting for start page"),
             AutomationState::ClickingStart => write!(f, "Clicking Start"),
             AutomationState::WaitingForLoading => write!(f, "Waiting for loading"),
@@ -78,6 +96,7 @@ impl AutomationState {
     pub fn description_ja(&self) -> String {
         match self {
             AutomationState::Idle => "待機中".to_string(),
+            AutomationState::WarmUp => "検出を確認中".to_string(),
             AutomationState::WaitingForStartPage => "開始画面を待機中".to_string(),
             AutomationState::ClickingStart => "開始ボタンをクリック中".to_string(),
             AutomationState::WaitingForLoading => "ローディング中".to_string(),
@@ -109,8 +128,8 @@ pub struct AutomationContext {
     pub start_iteration: u32,
     /// Maximum number of iterations
     pub max_iterations: u32,
-    /// Channel sender for OCR work items
-    pub work_sender: Sender<OcrWorkItem>,
+    /// Channel sender for OCR work items (None for capture-only runs)
+    pub work_sender: Option<Sender<OcrWorkItem>>,
     /// Time when automation started
     pub start_time: Instant,
     /// Directory for saving screenshots
@@ -121,6 +140,14 @@ pub struct AutomationContext {
     skip_button_ref: Option<ReferenceImage>,
     /// Pre-loaded End button reference for post-click verification
     end_button_ref: Option<ReferenceImage>,
+    /// Client area (width, height) when the run started, for resize warnings
+    initial_client_size: Option<(i32, i32)>,
+    /// Client area seen at the last per-iteration check
+    last_client_size: Option<(i32, i32)>,
+    /// Score-row fingerprint of the previous iteration's result screen
+    last_result_fingerprint: Option<ResultFingerprint>,
+    /// Background writer for screenshots (`async_screenshot_save`)
+    screenshot_writer: Option<ScreenshotWriter>,
 }
 
 impl AutomationContext {
@@ -132,14 +159,16 @@ impl AutomationContext {
         config: AutomationConfig,
         max_iterations: u32,
         start_iteration: u32,
-        work_sender: Sender<OcrWorkItem>,
+        work_sender: Option<Sender<OcrWorkItem>>,
         screenshot_dir: PathBuf,
     ) -> Self {
-        let exe_dir = crate::paths::get_exe_dir();
-
-        let start_button_ref = load_ref_image(&exe_dir, &config.start_button_reference, "Start");
-        let skip_button_ref = load_ref_image(&exe_dir, &config.skip_button_reference, "Skip");
-        let end_button_ref = load_ref_image(&exe_dir, &config.end_button_reference, "End");
+        let start_button_ref = load_ref_image(&config.start_button_reference, "Start");
+        let skip_button_ref = load_ref_image(&config.skip_button_reference, "Skip");
+        let end_button_ref = load_ref_image(&config.end_button_reference, "End");
+        let client_size = read_client_size(hwnd);
+        let screenshot_writer = config
+            .async_screenshot_save
+            .then(|| ScreenshotWriter::spawn(work_sender.clone()));
 
         Self {
             state: AutomationState::Idle,
@@ -155,7 +184,217 @@ impl AutomationContext {
             start_button_ref,
             skip_button_ref,
             end_button_ref,
+            initial_client_size: client_size,
+            last_client_size: client_size,
+            last_result_fingerprint: None,
+            screenshot_writer,
+        }
+    }
+
+    /// Waits for screenshots still being written (`async_screenshot_save`).
+    /// A save error turns the run into an error unless it already is one.
+    pub fn finish_screenshot_writer(&mut self) {
+        let Some(writer) = self.screenshot_writer.take() else {
+            return;
+        };
+        crate::log("Waiting for screenshot writer to finish...");
+        if let Err(e) = writer.finish()
+            && !matches!(self.state, AutomationState::Error(_))
+        {
+            self.state = AutomationState::Error(e.to_string());
+        }
+    }
+
+    /// Re-reads the game window's client size and logs a warning when it has
+    /// changed since the previous check. Clicks are relative and keep working,
+    /// but the reference images were captured at the old size, so detection
+    /// and OCR crops can drift. Called once per iteration.
+    fn check_client_size(&mut self) {
+        let Some(size) = read_client_size(self.hwnd) else {
+            return;
+        };
+        if let Some(last) = self.last_client_size
+            && size != last
+        {
+            crate::log(&format!(
+                "WARNING: Game window client size changed from {}x{} to {}x{} \
+                 (iteration {}, run started at {}). Reference images and OCR \
+                 regions may no longer match; restore the window size or \
+                 recapture the references if detection or OCR starts failing.",
+                last.0,
+                last.1,
+                size.0,
+                size.1,
+                self.current_iteration,
+                self.initial_client_size
+                    .map(|(w, h)| format!("{}x{}", w, h))
+                    .unwrap_or_else(|| "unknown".to_string()),
+            ));
+        }
+        self.last_client_size = Some(size);
+    }
+
+    /// Fingerprints the captured result screen and compares it with the
+    /// previous iteration's, logging a warning when they match within
+    /// `duplicate_max_distance` bits. Returns whether it was a duplicate.
+    fn is_duplicate_result(&mut self, img: &ImageBuffer<Rgba<u8>, Vec<u8>>) -> bool {
+        // `img` is the saved screenshot, cropped in ScoreRegionsOnly mode
+        let fingerprint = ResultFingerprint::of(img, &self.config.screenshot_regions()[0]);
+        let previous = self.last_result_fingerprint.replace(fingerprint);
+        let Some(previous) = previous else {
+            return false;
+        };
+        let distance = fingerprint.distance(&previous);
+        if distance > self.config.duplicate_max_distance {
+            return false;
+        }
+        crate::log(&format!(
+            "WARNING: Iteration {} result screen matches the previous iteration's \
+             (fingerprint distance {} <= {}); the game may be stuck on one screen.",
+            self.current_iteration, distance, self.config.duplicate_max_distance
+        ));
+        true
+    }
+
+    /// Captures the result screen again while some stage's score row reads
+    /// below `recapture_confidence` (e.g. a frame caught mid-animation), up to
+    /// `max_recaptures` times, and returns the last capture. Skipped for runs
+    /// without OCR and when the threshold is 0.
+    fn recapture_while_unreadable(
+        &self,
+        mut img: ImageBuffer<Rgba<u8>, Vec<u8>>,
+        region: Option<&RelativeRect>,
+    ) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>> {
+        let threshold = self.config.recapture_confidence;
+        if threshold <= 0.0 || self.work_sender.is_none() {
+            return Ok(img);
+        }
+        let score_regions = self.config.screenshot_regions()[0];
+        for attempt in 1..=self.config.max_recaptures {
+            let confidence = match score_row_confidence(&img, &self.config, &score_regions) {
+                Ok(confidence) => confidence,
+                Err(e) => {
+                    // The OCR worker reports real OCR failures; keep the capture
+                    crate::log(&format!("Warning: Confidence pre-check failed: {}", e));
+                    return Ok(img);
+                }
+            };
+            if confidence.iter().all(|&c| c >= threshold) {
+                return Ok(img);
+            }
+            crate::log(&format!(
+                "Iteration {}: score row confidence {:.1?} below {:.1}, recapturing ({}/{})",
+                self.current_iteration, confidence, threshold, attempt, self.config.max_recaptures
+            ));
+            img = capture_stable_frame(self.hwnd, self.config.stable_frames, region)?;
+        }
+        Ok(img)
+    }
+
+    /// Checks the game window isn't minimized, restoring it when
+    /// `restore_minimized` is set. Returns false (state set to `Error`) when
+    /// the run cannot continue.
+    fn ensure_not_minimized(&mut self) -> bool {
+        if !unsafe { IsIconic(self.hwnd) }.as_bool() {
+            return true;
+        }
+        if !self.config.restore_minimized {
+            crate::log("Game window is minimized, stopping automation (restore_minimized is off)");
+            self.state = AutomationState::Error("Game window minimized".to_string());
+            return false;
+        }
+
+        crate::log("Game window is minimized, restoring it");
+        unsafe {
+            let _ = ShowWindow(self.hwnd, SW_RESTORE);
+        }
+        // Give the game time to redraw before anything is captured
+        std::thread::sleep(Duration::from_millis(500));
+        if unsafe { IsIconic(self.hwnd) }.as_bool() {
+            crate::log("Game window is still minimized after restore, stopping automation");
+            self.state = AutomationState::Error("Game window minimized".to_string());
+            return false;
+        }
+        true
+    }
+
+    /// Deletes the oldest screenshots beyond `max_screenshots`. With OCR on,
+    /// screenshots without a row in results.csv yet are still queued (or
+    /// buffered, see `csv_sync_interval`) and are left alone.
+    fn rotate_screenshots(&self) {
+        let processed = self.work_sender.as_ref().map(|_| {
+            let session_dir = self.screenshot_dir.parent().unwrap_or(&self.screenshot_dir);
+            processed_iterations(&session_dir.join("results.csv"))
+        });
+        let pending = |iteration: u32| processed.as_ref().is_some_and(|done| !done.contains(&iteration));
+        let keep = self.config.max_screenshots as usize;
+        for path in rotate_screenshots(&self.screenshot_dir, keep, pending) {
+            crate::log(&format!(
+                "Deleted old screenshot {} (max_screenshots = {})",
+                crate::paths::relative_display(&path),
+                keep
+            ));
+        }
+    }
+
+    /// Saves the current window as `timeout_<state>_<iteration>.png` in the
+    /// session folder after a detection wait times out
+    /// (`GakumasError::DetectionTimeout`, not a capture timeout), so the screen
+    /// it gave up on can be inspected later.
+    ///
+    /// Best effort: capture or save failures are only logged, and the caller
+    /// still reports the original timeout error. Written next to screenshots/
+    /// rather than inside it so resume's screenshot count is unaffected.
+    fn dump_timeout_screenshot(&self, state: &str, error: &anyhow::Error) {
+        let timed_out =
+            matches!(error.downcast_ref(), Some(GakumasError::DetectionTimeout { .. }));
+        if !self.config.dump_on_timeout || !timed_out {
+            return;
+        }
+        let session_dir = self.screenshot_dir.parent().unwrap_or(&self.screenshot_dir);
+        let path = session_dir.join(format!("timeout_{}_{:03}.png", state, self.current_iteration));
+        let saved = capture_game_frame(self.hwnd)
+            .and_then(|img| img.save(&path).map_err(|e| anyhow!("{}", e)));
+        match saved {
+            Ok(()) => crate::log(&format!(
+                "Timeout screenshot saved to {}",
+                crate::paths::relative_display(&path)
+            )),
+            Err(e) => crate::log(&format!("Warning: Failed to save timeout screenshot: {}", e)),
+        }
+    }
+
+    /// Checks, before anything is clicked, that all three button references
+    /// loaded and that the rehearsal page is on screen right now.
+    fn warm_up(&self) -> Result<()> {
+        let references = [
+            ("Start", &self.start_button_ref, &self.config.start_button_reference),
+            ("Skip", &self.skip_button_ref, &self.config.skip_button_reference),
+            ("End", &self.end_button_ref, &self.config.end_button_reference),
+        ];
+        let missing: Vec<String> = references
+            .iter()
+            .filter(|(_, loaded, _)| loaded.is_none())
+            .map(|(name, _, path)| format!("{} ({})", name, path))
+            .collect();
+        if !missing.is_empty() {
+            return Err(anyhow!(
+                "Button reference images missing or unreadable: {}. \
+                 Capture them from the calibration panel before running.",
+                missing.join(", ")
+            ));
+        }
+
+        let on_rehearsal_page = is_rehearsal_page(self.hwnd, &self.config)
+            .map_err(|e| anyhow!("Rehearsal page check failed: {}", e))?;
+        if !on_rehearsal_page {
+            return Err(anyhow!(
+                "Rehearsal page not detected. Open the rehearsal page (Start button \
+                 visible) before starting, or recapture the Start reference if it is \
+                 already open."
+            ));
         }
+        Ok(())
     }
 
     /// Advances the state machine by one step.
@@ -176,6 +415,22 @@ impl AutomationContext {
             return Ok(false);
         }
 
+        // A minimized window captures as black or stale frames
+        if !self.ensure_not_minimized() {
+            return Ok(false);
+        }
+
+        // Runtime safety cap (0 = unlimited)
+        let cap_ms = self.config.max_total_runtime_ms;
+        if cap_ms > 0 && self.start_time.elapsed().as_millis() >= cap_ms as u128 {
+            crate::log(&format!(
+                "Runtime cap of {}ms exceeded after {} completed iterations, stopping automation",
+                cap_ms, self.completed_iterations
+            ));
+            self.state = AutomationState::Error("runtime cap exceeded".to_string());
+            return Ok(false);
+        }
+
         match &self.state {
             AutomationState::Idle => {
                 self.current_iteration = self.start_iteration;
@@ -183,26 +438,54 @@ impl AutomationContext {
                     "Starting automation: {} iterations",
                     self.max_iterations
                 ));
-                self.state = AutomationState::WaitingForStartPage;
+                if self.config.foreground_mode == ForegroundMode::Once {
+                    // The only time this run takes focus; later input pauses
+                    // whenever the user has switched away.
+                    crate::log("Bringing game window to the foreground (foreground_mode = Once)");
+                    unsafe {
+                        let _ = SetForegroundWindow(self.hwnd);
+                    }
+                    std::thread::sleep(Duration::from_millis(100));
+                }
+                self.state = AutomationState::WarmUp;
                 Ok(true)
             }
 
+            AutomationState::WarmUp => {
+                crate::log("Warm-up: checking references and rehearsal page detection...");
+                match self.warm_up() {
+                    Ok(()) => {
+                        crate::log("Warm-up passed");
+                        self.state = AutomationState::WaitingForStartPage;
+                        Ok(true)
+                    }
+                    Err(e) => {
+                        crate::log(&format!("Warm-up failed: {}", e));
+                        self.state = AutomationState::Error(e.to_string());
+                        Ok(false)
+                    }
+                }
+            }
+
             AutomationState::WaitingForStartPage => {
                 crate::log(&format!(
                     "Iteration {}/{}: Waiting for rehearsal page...",
                     self.current_iteration, self.max_iterations
                 ));
+                self.check_client_size();
 
                 // Only retry End button click after the first iteration of this run
                 // (the first iteration — fresh or resumed — hasn't clicked End yet)
                 let click_retry = if self.current_iteration > self.start_iteration {
                     self.end_button_ref.as_ref().map(|ref_img| ClickRetryInfo {
                         hwnd: self.hwnd,
-                        button_x: self.config.end_button.x,
-                        button_y: self.config.end_button.y,
+                        button: &self.config.end_button,
+                        key: self.config.end_key,
                         button_region: &self.config.end_button_region,
                         ref_img,
                         histogram_threshold: self.config.histogram_threshold,
+                        metric: self.config.detection_metric,
+                        foreground: self.config.foreground_mode,
                         max_retries: self.config.max_click_retries,
                     })
                 } else {
@@ -220,6 +503,7 @@ impl AutomationContext {
                             crate::log("Abort requested during start page wait");
                             self.state = AutomationState::Aborted;
                         } else {
+                            self.dump_timeout_screenshot("start_page", &e);
                             self.state =
                                 AutomationState::Error(format!("Start page wait failed: {}", e));
                         }
@@ -234,11 +518,7 @@ impl AutomationContext {
                     self.current_iteration, self.max_iterations
                 ));
 
-                if let Err(e) = click_with_focus(
-                    self.hwnd,
-                    self.config.start_button.x,
-                    self.config.start_button.y,
-                ) {
+                if let Err(e) = click_with_focus(self.hwnd, &self.config.start_button, self.config.foreground_mode) {
                     self.state = AutomationState::Error(format!("Failed to click Start: {}", e));
                     return Ok(false);
                 }
@@ -255,11 +535,13 @@ impl AutomationContext {
 
                 let click_retry = self.start_button_ref.as_ref().map(|ref_img| ClickRetryInfo {
                     hwnd: self.hwnd,
-                    button_x: self.config.start_button.x,
-                    button_y: self.config.start_button.y,
+                    button: &self.config.start_button,
+                    key: None,
                     button_region: &self.config.start_button_region,
                     ref_img,
                     histogram_threshold: self.config.histogram_threshold,
+                    metric: self.config.detection_metric,
+                    foreground: self.config.foreground_mode,
                     max_retries: self.config.max_click_retries,
                 });
 
@@ -274,6 +556,7 @@ impl AutomationContext {
                             crate::log("Abort requested during loading wait");
                             self.state = AutomationState::Aborted;
                         } else {
+                            self.dump_timeout_screenshot("loading", &e);
                             self.state =
                                 AutomationState::Error(format!("Loading wait failed: {}", e));
                         }
@@ -288,11 +571,7 @@ impl AutomationContext {
                     self.current_iteration, self.max_iterations
                 ));
 
-                if let Err(e) = click_with_focus(
-                    self.hwnd,
-                    self.config.skip_button.x,
-                    self.config.skip_button.y,
-                ) {
+                if let Err(e) = click_with_focus(self.hwnd, &self.config.skip_button, self.config.foreground_mode) {
                     self.state = AutomationState::Error(format!("Failed to click Skip: {}", e));
                     return Ok(false);
                 }
@@ -309,11 +588,13 @@ impl AutomationContext {
 
                 let click_retry = self.skip_button_ref.as_ref().map(|ref_img| ClickRetryInfo {
                     hwnd: self.hwnd,
-                    button_x: self.config.skip_button.x,
-                    button_y: self.config.skip_button.y,
+                    button: &self.config.skip_button,
+                    key: None,
                     button_region: &self.config.skip_button_region,
                     ref_img,
                     histogram_threshold: self.config.histogram_threshold,
+                    metric: self.config.detection_metric,
+                    foreground: self.config.foreground_mode,
                     max_retries: self.config.max_click_retries,
                 });
 
@@ -328,6 +609,7 @@ impl AutomationContext {
                             crate::log("Abort requested during result wait");
                             self.state = AutomationState::Aborted;
                         } else {
+                            self.dump_timeout_screenshot("result", &e);
                             self.state =
                                 AutomationState::Error(format!("Result wait failed: {}", e));
                         }
@@ -342,8 +624,22 @@ impl AutomationContext {
                     self.current_iteration, self.max_iterations
                 ));
 
-                // Capture screenshot
-                let img = match capture_gakumas_to_buffer(self.hwnd) {
+                // A background save of an earlier screenshot failed
+                if let Some(e) = self.screenshot_writer.as_ref().and_then(|w| w.error()) {
+                    self.state = AutomationState::Error(e);
+                    return Ok(false);
+                }
+
+                // Capture screenshot (median of several frames when configured).
+                // ScoreRegionsOnly captures just the score area; OCR reads it
+                // through `screenshot_regions`.
+                let score_area = self.config.score_area();
+                let region = (self.config.capture_mode == CaptureMode::ScoreRegionsOnly)
+                    .then_some(&score_area);
+                let stable_frames = self.config.stable_frames;
+                let captured = capture_stable_frame(self.hwnd, stable_frames, region)
+                    .and_then(|img| self.recapture_while_unreadable(img, region));
+                let mut img = match captured {
                     Ok(img) => img,
                     Err(e) => {
                         self.state =
@@ -352,30 +648,84 @@ impl AutomationContext {
                     }
                 };
 
-                // Generate filename with timestamp
-                let timestamp = Local::now().format("%Y%m%d_%H%M%S");
-                let filename = format!("{:03}_{}.png", self.current_iteration, timestamp);
+                // A result identical to the previous one means the game is stuck
+                // on the same screen rather than having played a new rehearsal.
+                if self.is_duplicate_result(&img) && self.config.abort_on_duplicate {
+                    self.state = AutomationState::Error(format!(
+                        "Iteration {} captured the same result screen as the previous one",
+                        self.current_iteration
+                    ));
+                    return Ok(false);
+                }
+
+                // Generate filename with timestamp (screenshot_name_template)
+                let now = Local::now();
+                let timestamp = now.format("%Y%m%d_%H%M%S").to_string();
+                let filename = self.config.screenshot_file_name(
+                    Some(self.current_iteration),
+                    img.dimensions(),
+                    &timestamp,
+                );
+
+                // The corner label is outside the score area, so only full
+                // captures get one
+                if self.config.annotate_screenshots && region.is_none() {
+                    let label = format!(
+                        "#{:03} {}",
+                        self.current_iteration,
+                        now.format("%Y-%m-%d %H:%M:%S")
+                    );
+                    annotate_corner(&mut img, &label);
+                }
+
                 let screenshot_path = self.screenshot_dir.join(&filename);
 
-                // Save screenshot
-                if let Err(e) = img.save(&screenshot_path) {
-                    self.state =
-                        AutomationState::Error(format!("Failed to save screenshot: {}", e));
-                    return Ok(false);
+                if let Some(writer) = &self.screenshot_writer {
+                    // Written (then queued for OCR) on the writer thread
+                    if let Err(e) =
+                        writer.submit(img, screenshot_path.clone(), self.current_iteration)
+                    {
+                        self.state = AutomationState::Error(e.to_string());
+                        return Ok(false);
+                    }
+                    crate::log(&format!(
+                        "Iteration {}/{}: Screenshot queued for saving to {}",
+                        self.current_iteration,
+                        self.max_iterations,
+                        crate::paths::relative_display(&screenshot_path)
+                    ));
+                } else {
+                    // Save screenshot
+                    let save_start = Instant::now();
+                    if let Err(e) = img.save(&screenshot_path) {
+                        self.state =
+                            AutomationState::Error(format!("Failed to save screenshot: {}", e));
+                        return Ok(false);
+                    }
+                    record_save(save_start.elapsed());
+
+                    crate::log(&format!(
+                        "Iteration {}/{}: Screenshot saved to {}",
+                        self.current_iteration,
+                        self.max_iterations,
+                        crate::paths::relative_display(&screenshot_path)
+                    ));
                 }
 
-                crate::log(&format!(
-                    "Iteration {}/{}: Screenshot saved to {}",
-                    self.current_iteration,
-                    self.max_iterations,
-                    crate::paths::relative_display(&screenshot_path)
-                ));
+                // Queue for OCR processing (skipped for capture-only runs and
+                // background saves, which queue once the file is written)
+                let ocr_sender =
+                    self.work_sender.as_ref().filter(|_| self.screenshot_writer.is_none());
+                if let Some(sender) = ocr_sender {
+                    let work_item = OcrWorkItem::new(screenshot_path, self.current_iteration);
+                    if let Err(e) = sender.send(work_item) {
+                        crate::log(&format!("Warning: Failed to queue OCR work item: {}", e));
+                        // Don't fail automation for this - OCR is secondary
+                    }
+                }
 
-                // Queue for OCR processing
-                let work_item = OcrWorkItem::new(screenshot_path, self.current_iteration);
-                if let Err(e) = self.work_sender.send(work_item) {
-                    crate::log(&format!("Warning: Failed to queue OCR work item: {}", e));
-                    // Don't fail automation for this - OCR is secondary
+                if self.config.max_screenshots > 0 {
+                    self.rotate_screenshots();
                 }
 
                 // This run produced a result; count it as completed.
@@ -386,16 +736,20 @@ impl AutomationContext {
             }
 
             AutomationState::ClickingEnd => {
-                crate::log(&format!(
-                    "Iteration {}/{}: Clicking End button",
-                    self.current_iteration, self.max_iterations
-                ));
-
-                if let Err(e) = click_with_focus(
-                    self.hwnd,
-                    self.config.end_button.x,
-                    self.config.end_button.y,
-                ) {
+                let result = if let Some(key) = self.config.end_key {
+                    crate::log(&format!(
+                        "Iteration {}/{}: Pressing {:?} to end",
+                        self.current_iteration, self.max_iterations, key
+                    ));
+                    key_with_focus(self.hwnd, key, self.config.foreground_mode)
+                } else {
+                    crate::log(&format!(
+                        "Iteration {}/{}: Clicking End button",
+                        self.current_iteration, self.max_iterations
+                    ));
+                    click_with_focus(self.hwnd, &self.config.end_button, self.config.foreground_mode)
+                };
+                if let Err(e) = result {
                     self.state = AutomationState::Error(format!("Failed to click End: {}", e));
                     return Ok(false);
                 }
@@ -413,7 +767,25 @@ impl AutomationContext {
                     ));
                     self.state = AutomationState::Complete;
                     Ok(false)
+                } else if STOP_AFTER_CURRENT.load(Ordering::SeqCst) {
+                    crate::log(&format!(
+                        "Stopping after iteration {}/{} as requested ({:.1}s)",
+                        self.current_iteration,
+                        self.max_iterations,
+                        self.start_time.elapsed().as_secs_f32()
+                    ));
+                    self.state = AutomationState::Complete;
+                    Ok(false)
                 } else {
+                    let delay_ms = self.config.inter_iteration_delay_ms;
+                    if delay_ms > 0 {
+                        crate::log(&format!("Waiting {}ms before next iteration", delay_ms));
+                        // Sleep in slices so an abort is honored promptly.
+                        let until = Instant::now() + Duration::from_millis(delay_ms);
+                        while Instant::now() < until && !ABORT_REQUESTED.load(Ordering::SeqCst) {
+                            std::thread::sleep(Duration::from_millis(100).min(until - Instant::now()));
+                        }
+                    }
                     self.current_iteration += 1;
                     // Wait for start page before clicking Start again
                     self.state = AutomationState::WaitingForStartPage;
@@ -440,14 +812,17 @@ impl AutomationContext {
     }
 }
 
+/// Client area (width, height) of `hwnd`, or None if it cannot be read.
+fn read_client_size(hwnd: HWND) -> Option<(i32, i32)> {
+    get_client_area_info(hwnd)
+        .ok()
+        .map(|(rect, _)| (rect.right - rect.left, rect.bottom - rect.top))
+}
+
 /// Tries to load a reference image for post-click verification.
 /// Returns None with a log message if the image doesn't exist or fails to load.
-fn load_ref_image(
-    exe_dir: &std::path::Path,
-    relative_path: &str,
-    button_name: &str,
-) -> Option<ReferenceImage> {
-    let path = exe_dir.join(relative_path);
+fn load_ref_image(relative_path: &str, button_name: &str) -> Option<ReferenceImage> {
+    let path = crate::paths::resolve_data_file(relative_path);
     if !path.exists() {
         return None;
     }
@@ -474,26 +849,39 @@ fn is_window_valid(hwnd: HWND) -> bool {
     unsafe { IsWindow(hwnd).as_bool() }
 }
 
-/// Clicks at a relative position after re-focusing the window.
+/// Clicks a configured button after re-focusing the window
+/// (`ForegroundMode::EachClick`).
 ///
 /// Re-focusing is important because the user might click elsewhere during automation.
-fn click_with_focus(hwnd: HWND, rel_x: f32, rel_y: f32) -> Result<()> {
+fn click_with_focus(hwnd: HWND, button: &ButtonConfig, foreground: ForegroundMode) -> Result<()> {
     if !is_window_valid(hwnd) {
         return Err(anyhow!("Game window no longer exists"));
     }
 
     // Bring window to foreground
-    unsafe {
-        let _ = SetForegroundWindow(hwnd);
+    if foreground == ForegroundMode::EachClick {
+        unsafe {
+            let _ = SetForegroundWindow(hwnd);
+        }
+        std::thread::sleep(Duration::from_millis(50));
     }
-    std::thread::sleep(Duration::from_millis(50));
 
-    click_at_relative(hwnd, rel_x, rel_y)
+    click_button(hwnd, button, foreground)
 }
 
-/// Resets the abort flag. Call before starting automation.
+/// Presses a navigation key in the game window; `press_navigation_key`
+/// brings the window to the foreground itself when `foreground` asks for it.
+fn key_with_focus(hwnd: HWND, key: NavigationKey, foreground: ForegroundMode) -> Result<()> {
+    if !is_window_valid(hwnd) {
+        return Err(anyhow!("Game window no longer exists"));
+    }
+    press_navigation_key(hwnd, key, foreground)
+}
+
+/// Resets the abort and stop-after-current flags. Call before starting automation.
 pub fn reset_abort_flag() {
     ABORT_REQUESTED.store(false, Ordering::SeqCst);
+    STOP_AFTER_CURRENT.store(false, Ordering::SeqCst);
 }
 
 /// Requests abort of running automation.
@@ -501,6 +889,17 @@ pub fn request_abort() {
     ABORT_REQUESTED.store(true, Ordering::SeqCst);
 }
 
+/// Requests a soft stop: the running iteration finishes normally and the run
+/// then completes, leaving the game on the rehearsal page.
+pub fn request_stop_after_current() {
+    STOP_AFTER_CURRENT.store(true, Ordering::SeqCst);
+}
+
+/// Whether a soft stop has been requested for the running automation.
+pub fn is_stop_after_current_requested() -> bool {
+    STOP_AFTER_CURRENT.load(Ordering::SeqCst)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -508,6 +907,7 @@ mod tests {
     #[test]
     fn test_state_display() {
         assert_eq!(format!("{}", AutomationState::Idle), "Idle");
+        assert_eq!(format!("{}", AutomationState::WarmUp), "Warming up");
         assert_eq!(
             format!("{}", AutomationState::WaitingForLoading),
             "Waiting for loading"
@@ -0,0 +1,184 @@
+//! Timer capture: screenshots of the game window at a fixed interval, with no
+//! clicking and no state machine.
+//!
+//! For logging while the game is advanced by hand. Each started capture gets
+//! its own session folder (`<timestamp>_timer`) laid out like an automation
+//! run's (screenshots/, results.csv), so the review window and chart
+//! generation work on it unchanged. Captures go through `capture_stable_frame`
+//! and, when OCR is on, the same OCR worker an automation run uses (without
+//! its events: timer scores never reach the live view or a run's channel).
+
+use anyhow::{anyhow, Context, Result};
+use chrono::Local;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::automation::config::{get_config, AutomationConfig, CaptureMode};
+use crate::automation::csv_writer::{init_csv, results_header};
+use crate::automation::ocr_worker::{run_ocr_worker, OcrWorkerStop};
+use crate::automation::queue::{create_work_queue, OcrWorkItem};
+use crate::capture::{capture_stable_frame, find_gakumas_window_with_retry};
+
+/// Handle to a running timer capture. Dropping it stops the capture thread
+/// before its next capture; queued screenshots are still OCR'd by the worker,
+/// which exits once the queue is drained. Neither thread is joined, so the UI
+/// never blocks on them.
+pub struct TimedCapture {
+    /// Dropped to stop: the capture thread waits on the other end
+    _stop: Sender<()>,
+    session_dir: PathBuf,
+    captured: Arc<AtomicU32>,
+    /// Most recent capture or save error, cleared by the next success
+    error: Arc<Mutex<Option<String>>>,
+}
+
+impl TimedCapture {
+    /// Creates a new session folder and starts capturing every
+    /// `timed_capture_interval_ms`, OCR'ing each capture when `timed_capture_ocr`
+    /// is set.
+    ///
+    /// # Errors
+    /// Returns an error if an automation run is in progress, the game window
+    /// cannot be found, or the session folder cannot be created.
+    pub fn start() -> Result<Self> {
+        if crate::automation::is_automation_running() {
+            return Err(anyhow!("Automation is running"));
+        }
+        let config = AutomationConfig::clone(&get_config());
+        let hwnd = find_gakumas_window_with_retry(config.window_find_timeout_ms)
+            .context("Failed to find game window")?;
+
+        let output_dir = crate::paths::get_session_output_dir();
+        crate::paths::ensure_writable_dir(&output_dir).with_context(|| {
+            format!("Output directory {} is not writable", output_dir.display())
+        })?;
+        let timestamp = Local::now().format("%Y%m%d_%H%M%S");
+        let session_dir = output_dir.join(format!("{}_timer", timestamp));
+        let screenshot_dir = session_dir.join("screenshots");
+        let csv_path = session_dir.join("results.csv");
+        fs::create_dir_all(&screenshot_dir).context("Failed to create screenshot directory")?;
+        if config.timed_capture_ocr {
+            init_csv(&csv_path, results_header(&config))?;
+        }
+
+        crate::log(&format!(
+            "Timer capture started: every {} ms{} into {}",
+            config.timed_capture_interval_ms,
+            if config.timed_capture_ocr { " with OCR" } else { "" },
+            crate::paths::relative_display(&session_dir)
+        ));
+
+        let ocr_sender = config.timed_capture_ocr.then(|| {
+            let (sender, receiver) = create_work_queue();
+            let regions = config.screenshot_regions();
+            let worker_config = config.clone();
+            // No events: the timer is not an automation run, so its scores
+            // stay out of the live view and any run's event channel
+            thread::spawn(move || {
+                run_ocr_worker(
+                    receiver,
+                    csv_path,
+                    regions,
+                    worker_config,
+                    None,
+                    false,
+                    OcrWorkerStop::default(),
+                );
+            });
+            sender
+        });
+
+        let (stop, stopped) = channel::<()>();
+        let captured = Arc::new(AtomicU32::new(0));
+        let error = Arc::new(Mutex::new(None));
+        {
+            let captured = captured.clone();
+            let error = error.clone();
+            // SAFETY: HWND is just a pointer wrapper, and Windows handles are
+            // valid across threads. We reconstruct it in the spawned thread.
+            let hwnd_raw = hwnd.0 as usize;
+            thread::spawn(move || {
+                let hwnd = windows::Win32::Foundation::HWND(hwnd_raw as *mut std::ffi::c_void);
+                let interval = Duration::from_millis(config.timed_capture_interval_ms);
+                let mut next_capture = Instant::now();
+                loop {
+                    // Deadline-based so slow captures don't stretch the interval
+                    let wait = next_capture.saturating_duration_since(Instant::now());
+                    match stopped.recv_timeout(wait) {
+                        Err(RecvTimeoutError::Timeout) => {}
+                        _ => break,
+                    }
+                    next_capture += interval;
+
+                    let iteration = captured.load(Ordering::SeqCst) + 1;
+                    match capture_once(hwnd, &config, &screenshot_dir, iteration) {
+                        Ok(path) => {
+                            captured.store(iteration, Ordering::SeqCst);
+                            *error.lock().unwrap() = None;
+                            if let Some(sender) = &ocr_sender
+                                && sender.send(OcrWorkItem::new(path, iteration)).is_err()
+                            {
+                                crate::log("Timer capture: OCR worker has stopped");
+                            }
+                        }
+                        Err(e) => {
+                            crate::log(&format!("Timer capture {} failed: {:#}", iteration, e));
+                            *error.lock().unwrap() = Some(format!("{:#}", e));
+                        }
+                    }
+                }
+                crate::log(&format!(
+                    "Timer capture stopped after {} capture(s)",
+                    captured.load(Ordering::SeqCst)
+                ));
+            });
+        }
+
+        Ok(Self { _stop: stop, session_dir, captured, error })
+    }
+
+    /// Session folder this capture writes into.
+    pub fn session_dir(&self) -> &Path {
+        &self.session_dir
+    }
+
+    /// Screenshots saved so far.
+    pub fn captured(&self) -> u32 {
+        self.captured.load(Ordering::SeqCst)
+    }
+
+    /// Why the latest capture failed, if it did.
+    pub fn error(&self) -> Option<String> {
+        self.error.lock().unwrap().clone()
+    }
+}
+
+/// Captures and saves one screenshot, named like an automation capture
+/// (`screenshot_file_name`). Returns the saved path.
+fn capture_once(
+    hwnd: windows::Win32::Foundation::HWND,
+    config: &AutomationConfig,
+    screenshot_dir: &Path,
+    iteration: u32,
+) -> Result<PathBuf> {
+    let score_area = config.score_area();
+    let region = (config.capture_mode == CaptureMode::ScoreRegionsOnly).then_some(&score_area);
+    let img = capture_stable_frame(hwnd, config.stable_frames, region)?;
+
+    let timestamp = Local::now().format("%Y%m%d_%H%M%S").to_string();
+    let file_name = config.screenshot_file_name(Some(iteration), img.dimensions(), &timestamp);
+    let path = crate::paths::unique_file_path(screenshot_dir.join(file_name));
+    img.save(&path)
+        .with_context(|| format!("Failed to save {}", crate::paths::relative_display(&path)))?;
+    crate::log(&format!(
+        "Timer capture {}: saved {}",
+        iteration,
+        crate::paths::relative_display(&path)
+    ));
+    Ok(path)
+}
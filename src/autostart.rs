@@ -0,0 +1,73 @@
+//! Launch-at-login support via the per-user `Run` registry key.
+//!
+//! Enabling writes `HKCU\Software\Microsoft\Windows\CurrentVersion\Run\<name>`
+//! pointing at the current executable; disabling deletes that value. Only the
+//! current user is affected, so no elevation is needed.
+
+use anyhow::{Context, Result};
+use windows::core::{w, PCWSTR};
+use windows::Win32::Foundation::ERROR_FILE_NOT_FOUND;
+use windows::Win32::System::Registry::{
+    RegCloseKey, RegCreateKeyExW, RegDeleteKeyValueW, RegGetValueW, RegSetValueExW, HKEY,
+    HKEY_CURRENT_USER, KEY_SET_VALUE, REG_OPTION_NON_VOLATILE, REG_SZ, RRF_RT_REG_SZ,
+};
+
+/// Subkey of HKCU holding per-user startup commands.
+const RUN_KEY: PCWSTR = w!("Software\\Microsoft\\Windows\\CurrentVersion\\Run");
+
+/// Value name this app registers under `RUN_KEY`.
+const VALUE_NAME: PCWSTR = w!("GakumasRehearsalAutomation");
+
+/// Registers the running executable to start at login. Idempotent: an
+/// existing entry is overwritten, which also refreshes a moved exe's path.
+pub fn enable() -> Result<()> {
+    let exe = std::env::current_exe().context("Failed to locate the executable")?;
+    let command = format!("\"{}\"", exe.display());
+    // REG_SZ data is the NUL-terminated UTF-16 string, as bytes.
+    let wide: Vec<u16> = command.encode_utf16().chain(std::iter::once(0)).collect();
+    let bytes: Vec<u8> = wide.iter().flat_map(|c| c.to_le_bytes()).collect();
+
+    unsafe {
+        let mut key = HKEY::default();
+        RegCreateKeyExW(
+            HKEY_CURRENT_USER,
+            RUN_KEY,
+            0,
+            PCWSTR::null(),
+            REG_OPTION_NON_VOLATILE,
+            KEY_SET_VALUE,
+            None,
+            &mut key,
+            None,
+        )
+        .ok()
+        .context("Failed to open the Run registry key")?;
+        let written = RegSetValueExW(key, VALUE_NAME, 0, REG_SZ, Some(&bytes));
+        let _ = RegCloseKey(key);
+        written.ok().context("Failed to write the Run registry value")?;
+    }
+
+    crate::log(&format!("Autostart enabled: {}", command));
+    Ok(())
+}
+
+/// Whether the login entry currently exists. Read from the registry rather
+/// than the config, so an entry removed elsewhere (Task Manager's startup tab,
+/// another copy of the app) shows as it really is.
+pub fn is_enabled() -> bool {
+    let result = unsafe {
+        RegGetValueW(HKEY_CURRENT_USER, RUN_KEY, VALUE_NAME, RRF_RT_REG_SZ, None, None, None)
+    };
+    result.is_ok()
+}
+
+/// Removes the login entry. Returns `Ok(false)` when there was none.
+pub fn disable() -> Result<bool> {
+    let result = unsafe { RegDeleteKeyValueW(HKEY_CURRENT_USER, RUN_KEY, VALUE_NAME) };
+    if result == ERROR_FILE_NOT_FOUND {
+        return Ok(false);
+    }
+    result.ok().context("Failed to delete the Run registry value")?;
+    crate::log("Autostart disabled: Run registry value removed");
+    Ok(true)
+}
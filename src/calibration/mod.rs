@@ -5,11 +5,15 @@
 
 pub mod coords;
 pub mod preview;
+pub mod references;
 pub mod state;
 pub mod wizard;
 
+pub use references::start_reference_wizard;
+pub use state::CalibrationStep;
 pub use wizard::{
     handle_calibration_hotkey, is_calibrating, show_preview_once, start_calibration,
+    start_calibration_item,
     HOTKEY_CAL_ENTER, HOTKEY_CAL_ESCAPE, HOTKEY_CAL_F1, HOTKEY_CAL_F2, HOTKEY_CAL_F3,
     HOTKEY_CAL_N, HOTKEY_CAL_Y,
 };
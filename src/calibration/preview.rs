@@ -1,22 +1,25 @@
 //! Preview rendering for calibration visualization.
 //!
 //! Draws rectangles, crosshairs, and labels on screenshots to show
-//! configured button positions and brightness detection region.
-//!
-//! Note: Score regions are no longer used. The OCR module processes
-//! the full image with pattern matching, eliminating the need for
-//! region-based calibration.
+//! configured button positions, the detection regions and the OCR regions.
+//! Every item carries a short text label (`Start`, `S1C2`, `Total3`, ...) so a
+//! busy preview can be checked box by box.
 
 use anyhow::Result;
 use image::{ImageBuffer, Rgba};
 use std::process::Command;
 
-use crate::automation::AutomationConfig;
+use crate::automation::{AutomationConfig, RelativeRect};
+use crate::ocr::extract::clamp_characters_per_stage;
+use crate::ocr::preprocess::{binarize, crop_region, upscale};
 
 /// Color constants for preview rendering.
 pub const COLOR_BUTTON: Rgba<u8> = Rgba([255, 0, 0, 255]); // Red
 pub const COLOR_BRIGHTNESS: Rgba<u8> = Rgba([255, 255, 0, 255]); // Yellow
 pub const COLOR_HIGHLIGHT: Rgba<u8> = Rgba([255, 128, 0, 255]); // Orange
+pub const COLOR_SCORE: Rgba<u8> = Rgba([0, 255, 0, 255]); // Green
+pub const COLOR_TOTAL: Rgba<u8> = Rgba([0, 255, 255, 255]); // Cyan
+pub const COLOR_BONUS: Rgba<u8> = Rgba([255, 0, 255, 255]); // Magenta
 
 /// What item to highlight in the preview.
 #[derive(Clone, Debug)]
@@ -38,65 +41,91 @@ pub fn render_preview(
     let (width, height) = img.dimensions();
 
     // Draw buttons as crosshairs
-    draw_crosshair(
-        &mut img,
-        (config.start_button.x * width as f32) as u32,
-        (config.start_button.y * height as f32) as u32,
-        COLOR_BUTTON,
-        15,
-    );
-    draw_crosshair(
-        &mut img,
-        (config.skip_button.x * width as f32) as u32,
-        (config.skip_button.y * height as f32) as u32,
-        COLOR_BUTTON,
-        15,
-    );
-    draw_crosshair(
-        &mut img,
-        (config.end_button.x * width as f32) as u32,
-        (config.end_button.y * height as f32) as u32,
-        COLOR_BUTTON,
-        15,
-    );
+    let buttons = [
+        ("Start", &config.start_button),
+        ("Skip", &config.skip_button),
+        ("End", &config.end_button),
+    ];
+    for (name, button) in buttons {
+        let x = (button.x * width as f32) as u32;
+        let y = (button.y * height as f32) as u32;
+        draw_crosshair(&mut img, x, y, COLOR_BUTTON, 15);
+        draw_label(&mut img, x + 18, y.saturating_sub(15), name, COLOR_BUTTON);
+    }
 
-    // Draw start button region (for page detection)
-    let r = &config.start_button_region;
-    draw_rect(
-        &mut img,
-        (r.x * width as f32) as u32,
-        (r.y * height as f32) as u32,
-        (r.width * width as f32) as u32,
-        (r.height * height as f32) as u32,
-        COLOR_BRIGHTNESS,
-        2,
-    );
+    // Draw button regions (start/end: page detection, skip: brightness detection)
+    let button_regions = [
+        ("Start area", &config.start_button_region),
+        ("Skip area", &config.skip_button_region),
+        ("End area", &config.end_button_region),
+    ];
+    for (name, r) in button_regions {
+        draw_labeled_region(&mut img, r, name, COLOR_BRIGHTNESS);
+    }
 
-    // Draw skip button region (for brightness detection)
-    let r = &config.skip_button_region;
-    draw_rect(
-        &mut img,
-        (r.x * width as f32) as u32,
-        (r.y * height as f32) as u32,
-        (r.width * width as f32) as u32,
-        (r.height * height as f32) as u32,
-        COLOR_BRIGHTNESS,
-        2,
-    );
+    // Draw OCR regions: one score row per stage (labeled per character
+    // column), plus each stage's total and bonus crops
+    let characters = clamp_characters_per_stage(config.characters_per_stage) as u32;
+    for stage in 0..3 {
+        let r = &config.score_regions[stage];
+        draw_labeled_region(&mut img, r, "", COLOR_SCORE);
+        let (x, y) = ((r.x * width as f32) as u32, (r.y * height as f32) as u32);
+        let column_w = (r.width * width as f32) as u32 / characters;
+        for character in 0..characters {
+            let label = format!("S{}C{}", stage + 1, character + 1);
+            draw_label(&mut img, x + character * column_w, y, &label, COLOR_SCORE);
+        }
 
-    // Draw end button region (for result page detection)
-    let r = &config.end_button_region;
+        let label = format!("Total{}", stage + 1);
+        draw_labeled_region(&mut img, &config.total_regions[stage], &label, COLOR_TOTAL);
+        let label = format!("Bonus{}", stage + 1);
+        draw_labeled_region(&mut img, &config.bonus_regions[stage], &label, COLOR_BONUS);
+    }
+
+    img
+}
+
+/// Draws a relative region's border in `color` with `label` at its top-left.
+/// An empty label draws the border only.
+fn draw_labeled_region(
+    img: &mut ImageBuffer<Rgba<u8>, Vec<u8>>,
+    r: &RelativeRect,
+    label: &str,
+    color: Rgba<u8>,
+) {
+    let (width, height) = img.dimensions();
+    let x = (r.x * width as f32) as u32;
+    let y = (r.y * height as f32) as u32;
     draw_rect(
-        &mut img,
-        (r.x * width as f32) as u32,
-        (r.y * height as f32) as u32,
+        img,
+        x,
+        y,
         (r.width * width as f32) as u32,
         (r.height * height as f32) as u32,
-        COLOR_BRIGHTNESS,
+        color,
         2,
     );
+    if !label.is_empty() {
+        draw_label(img, x, y, label, color);
+    }
+}
 
-    img
+/// Draws `text` in `color` on a black box whose bottom edge sits on `y` (just
+/// above a region's top border), or just below `y` when there is no room above.
+pub fn draw_label(
+    img: &mut ImageBuffer<Rgba<u8>, Vec<u8>>,
+    x: u32,
+    y: u32,
+    text: &str,
+    color: Rgba<u8>,
+) {
+    let scale = (img.height() / 540).max(2);
+    let pad = scale;
+    let w = text_width(text, scale) + 2 * pad;
+    let h = 5 * scale + 2 * pad;
+    let top = if y >= h { y - h } else { y + 2 };
+    fill_rect(img, x, top, w, h, Rgba([0, 0, 0, 255]));
+    draw_text(img, x + pad, top + pad, text, color, scale);
 }
 
 /// Renders preview with a single highlighted region (for per-step preview).
@@ -178,6 +207,40 @@ pub fn render_preview_with_highlight(
     img
 }
 
+/// Gap between stacked strips in `render_thresholded_scores`, in pixels.
+const STRIP_GAP: u32 = 4;
+
+/// Crops each score region and binarizes it exactly as the OCR does
+/// (`ocr_upscale_factor`, `ocr_preprocess_mode`, `ocr_threshold`), stacking
+/// the three strips top to bottom on a gray background. Clean black digits on
+/// white mean the region and threshold will read well.
+pub fn render_thresholded_scores(
+    screenshot: &ImageBuffer<Rgba<u8>, Vec<u8>>,
+    config: &AutomationConfig,
+) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+    let strips: Vec<_> = config
+        .score_regions
+        .iter()
+        .map(|region| {
+            let crop = upscale(crop_region(screenshot, region), config.ocr_upscale_factor);
+            binarize(&crop, config.ocr_preprocess_mode, config.ocr_threshold)
+        })
+        .collect();
+
+    let width = strips.iter().map(|s| s.width()).max().unwrap_or(0).max(1);
+    let height = strips.iter().map(|s| s.height() + STRIP_GAP).sum::<u32>().max(1);
+    let mut img = ImageBuffer::from_pixel(width, height, Rgba([128, 128, 128, 255]));
+    let mut top = 0;
+    for strip in &strips {
+        for (x, y, pixel) in strip.enumerate_pixels() {
+            let v = pixel[0];
+            img.put_pixel(x, top + y, Rgba([v, v, v, 255]));
+        }
+        top += strip.height() + STRIP_GAP;
+    }
+    img
+}
+
 /// Saves preview image and opens with system default viewer.
 pub fn show_preview(img: &ImageBuffer<Rgba<u8>, Vec<u8>>, filename: &str) -> Result<()> {
     // Save to file
@@ -248,6 +311,115 @@ pub fn draw_rect(
     }
 }
 
+/// Fills a solid rectangle, clipped to the image bounds.
+pub fn fill_rect(
+    img: &mut ImageBuffer<Rgba<u8>, Vec<u8>>,
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+    color: Rgba<u8>,
+) {
+    let (img_w, img_h) = img.dimensions();
+    for py in y..(y + h).min(img_h) {
+        for px in x..(x + w).min(img_w) {
+            img.put_pixel(px, py, color);
+        }
+    }
+}
+
+/// 3x5 bitmap glyphs for the characters used in capture annotations and
+/// preview labels. Each row is 3 bits, most significant bit on the left.
+/// Letters are drawn as capitals whatever their case.
+fn glyph(c: char) -> [u8; 5] {
+    match c.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '#' => [0b101, 0b111, 0b101, 0b111, 0b101],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        _ => [0; 5], // space and anything unsupported
+    }
+}
+
+/// Width in pixels of `text` drawn by `draw_text` at `scale` (3px glyph +
+/// 1px gap per character, times scale).
+pub fn text_width(text: &str, scale: u32) -> u32 {
+    (text.chars().count() as u32 * 4).saturating_sub(1) * scale
+}
+
+/// Draws `text` with the built-in 3x5 bitmap font; top-left at (x, y).
+/// Only digits, letters and `-:#/` have glyphs; other characters render as
+/// spaces.
+pub fn draw_text(
+    img: &mut ImageBuffer<Rgba<u8>, Vec<u8>>,
+    x: u32,
+    y: u32,
+    text: &str,
+    color: Rgba<u8>,
+    scale: u32,
+) {
+    for (i, c) in text.chars().enumerate() {
+        let gx = x + i as u32 * 4 * scale;
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for col in 0..3u32 {
+                if bits & (0b100 >> col) != 0 {
+                    fill_rect(img, gx + col * scale, y + row as u32 * scale, scale, scale, color);
+                }
+            }
+        }
+    }
+}
+
+/// Draws a small label (white text on a black box) in the top-left corner.
+///
+/// Used to stamp iteration/timestamp onto saved automation screenshots. The
+/// top-left corner is clear of every OCR region, so the stamp never reaches
+/// the digits the OCR worker later reads back from the saved file.
+pub fn annotate_corner(img: &mut ImageBuffer<Rgba<u8>, Vec<u8>>, text: &str) {
+    let scale = (img.height() / 360).max(2);
+    let pad = 2 * scale;
+    let w = text_width(text, scale) + 2 * pad;
+    let h = 5 * scale + 2 * pad;
+    fill_rect(img, 0, 0, w, h, Rgba([0, 0, 0, 255]));
+    draw_text(img, pad, pad, text, Rgba([255, 255, 255, 255]), scale);
+}
+
 /// Draws a crosshair at a point.
 pub fn draw_crosshair(
     img: &mut ImageBuffer<Rgba<u8>, Vec<u8>>,
@@ -314,4 +486,63 @@ mod tests {
         // Check arm is red
         assert_eq!(*img.get_pixel(60, 50), COLOR_BUTTON);
     }
+
+    #[test]
+    fn test_draw_text_glyph() {
+        let white = Rgba([255, 255, 255, 255]);
+        let mut img = ImageBuffer::from_pixel(20, 10, Rgba([0, 0, 0, 255]));
+        draw_text(&mut img, 0, 0, "1", white, 1);
+
+        // "1" = 010 / 110 / 010 / 010 / 111
+        assert_eq!(*img.get_pixel(1, 0), white);
+        assert_eq!(*img.get_pixel(0, 0), Rgba([0, 0, 0, 255]));
+        assert_eq!(*img.get_pixel(0, 1), white);
+        assert_eq!(*img.get_pixel(2, 4), white);
+        assert_eq!(text_width("12", 1), 7);
+    }
+
+    #[test]
+    fn test_annotate_corner_stays_in_top_left() {
+        let mut img = ImageBuffer::from_pixel(721, 1281, Rgba([10, 10, 10, 255]));
+        annotate_corner(&mut img, "#001 2026-01-01 12:00:00");
+
+        assert_eq!(*img.get_pixel(0, 0), Rgba([0, 0, 0, 255]));
+        // The first score region starts at y ~0.137 of the height; untouched.
+        let y = (0.137 * 1281.0) as u32;
+        assert_eq!(*img.get_pixel(5, y), Rgba([10, 10, 10, 255]));
+    }
+
+    #[test]
+    fn test_render_preview_labels_regions() {
+        let black = Rgba([0, 0, 0, 255]);
+        let screenshot = ImageBuffer::from_pixel(720, 1280, Rgba([90, 90, 90, 255]));
+        let config = AutomationConfig::default();
+        let img = render_preview(&screenshot, &config);
+
+        // A label box (black, with green glyph pixels) sits just above the
+        // first score region's top-left corner.
+        let r = &config.score_regions[0];
+        let (x, y) = ((r.x * 720.0) as u32, (r.y * 1280.0) as u32);
+        assert_eq!(*img.get_pixel(x, y - 1), black);
+        let mut label_box =
+            (x..x + text_width("S1C1", 2)).flat_map(|px| (y - 14..y).map(move |py| (px, py)));
+        assert!(label_box.any(|(px, py)| *img.get_pixel(px, py) == COLOR_SCORE));
+    }
+
+    #[test]
+    fn test_render_thresholded_scores_stacks_binarized_rows() {
+        let mut screenshot = ImageBuffer::from_pixel(720, 1280, Rgba([40, 40, 40, 255]));
+        let config = AutomationConfig::default();
+        // A bright "digit" inside the first score row.
+        let r = &config.score_regions[0];
+        let (x, y) = ((r.x * 720.0) as u32 + 10, (r.y * 1280.0) as u32 + 2);
+        fill_rect(&mut screenshot, x, y, 6, 6, Rgba([255, 255, 255, 255]));
+
+        let img = render_thresholded_scores(&screenshot, &config);
+        let strip_h = (r.height * 1280.0) as u32;
+        // Text is black, background white, the gap below the strip gray.
+        assert_eq!(*img.get_pixel(12, 4), Rgba([0, 0, 0, 255]));
+        assert_eq!(*img.get_pixel(2, 0), Rgba([255, 255, 255, 255]));
+        assert_eq!(*img.get_pixel(2, strip_h), Rgba([128, 128, 128, 255]));
+    }
 }
@@ -0,0 +1,299 @@
+//! Guided capture of all three histogram reference images.
+//!
+//! Walks the user through the screens a rehearsal passes through, capturing
+//! one reference per screen with the existing `save_*_reference` functions:
+//! 1. Rehearsal page (Start button) -> `start_button_reference`
+//! 2. Loading complete (Skip button enabled) -> `skip_button_reference`
+//! 3. Result page (End button) -> `end_button_reference`
+//!
+//! Uses the calibration wizard's hotkeys, so it cannot run alongside it. Each
+//! capture is written next to its target first and only replaces the configured
+//! reference once confirmed from the preview.
+
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use windows::Win32::Foundation::HWND;
+
+use crate::automation::{
+    get_config, save_end_button_reference, save_skip_button_reference,
+    save_start_button_reference, AutomationConfig,
+};
+use crate::calibration::preview::show_preview;
+use crate::calibration::wizard::{
+    register_calibration_hotkeys, unregister_calibration_hotkeys, HOTKEY_CAL_ENTER,
+    HOTKEY_CAL_ESCAPE, HOTKEY_CAL_F1, HOTKEY_CAL_N, HOTKEY_CAL_Y,
+};
+use crate::capture::find_gakumas_window;
+use crate::log;
+
+/// Global reference wizard state protected by mutex.
+static REFERENCE_WIZARD: Mutex<Option<ReferenceContext>> = Mutex::new(None);
+
+/// Screens the wizard captures a reference on, in rehearsal order.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ReferenceStep {
+    Start,
+    Skip,
+    End,
+    Complete,
+}
+
+impl ReferenceStep {
+    fn next(self) -> Self {
+        match self {
+            Self::Start => Self::Skip,
+            Self::Skip => Self::End,
+            Self::End | Self::Complete => Self::Complete,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Start => "Start",
+            Self::Skip => "Skip",
+            Self::End => "End",
+            Self::Complete => "Complete",
+        }
+    }
+
+    /// Configured reference path for this step, relative to the exe directory.
+    fn reference_path(self, config: &AutomationConfig) -> Option<&str> {
+        match self {
+            Self::Start => Some(&config.start_button_reference),
+            Self::Skip => Some(&config.skip_button_reference),
+            Self::End => Some(&config.end_button_reference),
+            Self::Complete => None,
+        }
+    }
+}
+
+/// Runtime context for an active reference wizard.
+/// Note: HWND is stored as isize for Send+Sync safety with Mutex.
+struct ReferenceContext {
+    /// Handle to the window the hotkeys are registered on.
+    app_hwnd: isize,
+    /// Handle to the game window being captured.
+    game_hwnd: isize,
+    /// Screen the next capture is for.
+    current_step: ReferenceStep,
+    /// Capture waiting for Y/N: (unconfirmed file, configured reference path).
+    pending: Option<(PathBuf, PathBuf)>,
+    /// References replaced so far, for the closing summary.
+    saved: Vec<&'static str>,
+}
+
+/// Returns true if the reference wizard is currently active.
+pub fn is_capturing_references() -> bool {
+    REFERENCE_WIZARD.lock().unwrap().is_some()
+}
+
+/// Starts the guided capture of the Start, Skip and End reference images.
+pub fn start_reference_wizard(app_hwnd: HWND) -> Result<()> {
+    if is_capturing_references() {
+        log("Reference capture already in progress.");
+        return Ok(());
+    }
+    if super::is_calibrating() {
+        return Err(anyhow!("Finish or abort calibration before capturing references"));
+    }
+
+    let game_hwnd = find_gakumas_window()?;
+    log(&format!("Found game window: {:?}", game_hwnd));
+
+    register_calibration_hotkeys(app_hwnd)?;
+
+    *REFERENCE_WIZARD.lock().unwrap() = Some(ReferenceContext {
+        app_hwnd: app_hwnd.0 as isize,
+        game_hwnd: game_hwnd.0 as isize,
+        current_step: ReferenceStep::Start,
+        pending: None,
+        saved: Vec::new(),
+    });
+
+    log("");
+    log("=======================================================");
+    log("           REFERENCE CAPTURE STARTED");
+    log("=======================================================");
+    log("");
+    log("Captures the Start, Skip and End button references in the order a");
+    log("rehearsal shows them. Regions come from the current config.");
+    log("");
+    log("Hotkeys:");
+    log("  F1     - Capture the reference for the current screen");
+    log("  Y      - Keep the capture and move on");
+    log("  N      - Discard the capture and retry");
+    log("  Enter  - Skip this reference (keep the existing image)");
+    log("  Escape - Abort (unconfirmed captures are discarded)");
+    log("");
+    print_step_instructions(ReferenceStep::Start);
+
+    Ok(())
+}
+
+/// Handles a calibration hotkey press while the reference wizard is active.
+pub fn handle_reference_hotkey(hotkey_id: i32) -> Result<()> {
+    let mut guard = REFERENCE_WIZARD.lock().unwrap();
+    let ctx = match guard.as_mut() {
+        Some(c) => c,
+        None => return Ok(()),
+    };
+
+    match hotkey_id {
+        HOTKEY_CAL_ESCAPE => {
+            log("");
+            log("Reference capture aborted by user.");
+            drop(guard);
+            stop_reference_wizard();
+            return Ok(());
+        }
+        HOTKEY_CAL_F1 if ctx.pending.is_none() => capture_current(ctx)?,
+        HOTKEY_CAL_Y => {
+            if let Some((pending, target)) = ctx.pending.take() {
+                std::fs::rename(&pending, &target).map_err(|e| {
+                    anyhow!("Failed to replace {}: {}", target.display(), e)
+                })?;
+                log(&format!(
+                    "Saved {} button reference to {}",
+                    ctx.current_step.name(),
+                    crate::paths::relative_display(&target)
+                ));
+                ctx.saved.push(ctx.current_step.name());
+                advance(ctx);
+            }
+        }
+        HOTKEY_CAL_N => {
+            if let Some((pending, _)) = ctx.pending.take() {
+                let _ = std::fs::remove_file(pending);
+                log("Capture discarded. Press F1 to capture again.");
+                log("");
+            }
+        }
+        HOTKEY_CAL_ENTER if ctx.pending.is_none() => {
+            log(&format!(
+                "Skipping {} reference (keeping existing image)...",
+                ctx.current_step.name()
+            ));
+            log("");
+            advance(ctx);
+        }
+        _ => {}
+    }
+
+    if ctx.current_step == ReferenceStep::Complete {
+        log("");
+        log("=======================================================");
+        log("           REFERENCE CAPTURE COMPLETE!");
+        log("=======================================================");
+        if ctx.saved.is_empty() {
+            log("No references were replaced.");
+        } else {
+            log(&format!("Replaced references: {}", ctx.saved.join(", ")));
+        }
+        drop(guard);
+        stop_reference_wizard();
+    }
+
+    Ok(())
+}
+
+/// F1: saves the current step's region next to its reference path and opens
+/// it for confirmation.
+fn capture_current(ctx: &mut ReferenceContext) -> Result<()> {
+    let config = get_config();
+    let Some(rel_path) = ctx.current_step.reference_path(&config) else {
+        return Ok(());
+    };
+    let target = crate::paths::get_data_dir().join(rel_path);
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let pending = pending_path(&target);
+
+    let game_hwnd = HWND(ctx.game_hwnd as *mut std::ffi::c_void);
+    match ctx.current_step {
+        ReferenceStep::Start => save_start_button_reference(game_hwnd, &config, &pending)?,
+        ReferenceStep::Skip => save_skip_button_reference(game_hwnd, &config, &pending)?,
+        ReferenceStep::End => save_end_button_reference(game_hwnd, &config, &pending)?,
+        ReferenceStep::Complete => return Ok(()),
+    }
+
+    let captured = image::open(&pending)
+        .map_err(|e| anyhow!("Failed to reload {}: {}", pending.display(), e))?
+        .to_rgba8();
+    show_preview(&captured, "reference_preview.png")?;
+    log("Preview opened: reference_preview.png");
+    log("Does the image show the whole button? Y to keep, N to retry.");
+
+    ctx.pending = Some((pending, target));
+    Ok(())
+}
+
+/// Moves to the next screen and prints its instructions.
+fn advance(ctx: &mut ReferenceContext) {
+    ctx.current_step = ctx.current_step.next();
+    if ctx.current_step != ReferenceStep::Complete {
+        print_step_instructions(ctx.current_step);
+    }
+}
+
+/// Unconfirmed capture for `target`: `references/start.png` ->
+/// `references/start.pending.png`.
+fn pending_path(target: &Path) -> PathBuf {
+    let stem = target.file_stem().unwrap_or_default().to_string_lossy();
+    target.with_file_name(format!("{}.pending.png", stem))
+}
+
+fn print_step_instructions(step: ReferenceStep) {
+    match step {
+        ReferenceStep::Start => {
+            log("Reference 1/3: Start button (開始する)");
+            log("Open the rehearsal page so the Start button is visible.");
+        }
+        ReferenceStep::Skip => {
+            log("Reference 2/3: Skip button (スキップ)");
+            log("Start the rehearsal and wait until loading finishes and Skip is bright.");
+        }
+        ReferenceStep::End => {
+            log("Reference 3/3: End button (終了)");
+            log("Skip to the result page so the End button is visible.");
+        }
+        ReferenceStep::Complete => return,
+    }
+    log("Press F1 to capture.");
+}
+
+/// Stops the reference wizard, discarding an unconfirmed capture.
+pub fn stop_reference_wizard() {
+    let mut guard = REFERENCE_WIZARD.lock().unwrap();
+    if let Some(ctx) = guard.take() {
+        if let Some((pending, _)) = ctx.pending {
+            let _ = std::fs::remove_file(pending);
+        }
+        unregister_calibration_hotkeys(HWND(ctx.app_hwnd as *mut std::ffi::c_void));
+        log("Reference capture ended.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pending_path, ReferenceStep};
+    use std::path::Path;
+
+    #[test]
+    fn steps_follow_rehearsal_order() {
+        let mut step = ReferenceStep::Start;
+        let mut order = Vec::new();
+        while step != ReferenceStep::Complete {
+            order.push(step.name());
+            step = step.next();
+        }
+        assert_eq!(order, ["Start", "Skip", "End"]);
+    }
+
+    #[test]
+    fn pending_capture_sits_next_to_target() {
+        let pending = pending_path(Path::new("references/skip_button.png"));
+        assert_eq!(pending, Path::new("references/skip_button.pending.png"));
+    }
+}
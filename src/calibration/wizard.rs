@@ -10,7 +10,7 @@
 //! Note: Score regions are no longer needed. The OCR module uses full-image
 //! processing with pattern matching, making region calibration unnecessary.
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use std::sync::Mutex;
 use windows::Win32::Foundation::HWND;
 use windows::Win32::UI::Input::KeyboardAndMouse::{
@@ -18,11 +18,15 @@ use windows::Win32::UI::Input::KeyboardAndMouse::{
     VK_RETURN, VK_Y,
 };
 
-use crate::automation::{get_config, ButtonConfig, RelativeRect};
+use crate::automation::{get_config, AutomationConfig, ButtonConfig, RelativeRect};
 use crate::calibration::coords::{get_cursor_position, screen_to_relative};
-use crate::calibration::preview::{render_preview, render_preview_with_highlight, show_preview, HighlightedItem};
+use crate::calibration::preview::{
+    render_preview, render_preview_with_highlight, render_thresholded_scores, show_preview,
+    HighlightedItem,
+};
+use crate::calibration::references::{handle_reference_hotkey, is_capturing_references};
 use crate::calibration::state::{CalibrationItems, CalibrationStep};
-use crate::capture::{capture_gakumas_to_buffer, find_gakumas_window};
+use crate::capture::{capture_gakumas_to_buffer, capture_game_to_buffer, find_gakumas_window};
 use crate::log;
 
 // Calibration hotkey IDs (must not conflict with main hotkeys)
@@ -52,6 +56,9 @@ struct CalibrationContext {
     pending_top_left: Option<(f32, f32)>,
     /// Whether we're waiting for user confirmation (Y/N).
     awaiting_confirmation: bool,
+    /// Recalibrating one item only: confirming or skipping it completes the
+    /// wizard instead of moving on to the next item.
+    single_item: bool,
 }
 
 /// Converts an HWND to isize for storage.
@@ -71,11 +78,31 @@ pub fn is_calibrating() -> bool {
 
 /// Starts the calibration wizard.
 pub fn start_calibration(app_hwnd: HWND) -> Result<()> {
+    begin_calibration(app_hwnd, CalibrationStep::StartButton, false)
+}
+
+/// Starts the wizard for a single item (e.g. just the Skip button region).
+///
+/// `step` may be any step of the item; a region always starts from its
+/// top-left corner. Once the item is confirmed the captured value is merged
+/// into the current config and saved, leaving every other item untouched.
+pub fn start_calibration_item(app_hwnd: HWND, step: CalibrationStep) -> Result<()> {
+    if matches!(step, CalibrationStep::Complete) {
+        return Err(anyhow!("Complete is not a calibration item"));
+    }
+    begin_calibration(app_hwnd, rewind_to_step_start(&step), true)
+}
+
+/// Registers the hotkeys and installs a context starting at `first_step`.
+fn begin_calibration(app_hwnd: HWND, first_step: CalibrationStep, single_item: bool) -> Result<()> {
     // Check if already calibrating
     if is_calibrating() {
         log("Calibration already in progress.");
         return Ok(());
     }
+    if is_capturing_references() {
+        return Err(anyhow!("Finish or abort reference capture before calibrating"));
+    }
 
     // Find game window
     let game_hwnd = find_gakumas_window()?;
@@ -88,10 +115,11 @@ pub fn start_calibration(app_hwnd: HWND) -> Result<()> {
     let context = CalibrationContext {
         app_hwnd: hwnd_to_isize(app_hwnd),
         game_hwnd: hwnd_to_isize(game_hwnd),
-        current_step: CalibrationStep::StartButton,
+        current_step: first_step.clone(),
         items: CalibrationItems::default(),
         pending_top_left: None,
         awaiting_confirmation: false,
+        single_item,
     };
 
     *CALIBRATION.lock().unwrap() = Some(context);
@@ -102,8 +130,13 @@ pub fn start_calibration(app_hwnd: HWND) -> Result<()> {
     log("           CALIBRATION MODE STARTED");
     log("=======================================================");
     log("");
-    log("This wizard collects button positions for automation.");
-    log("Score regions are NOT needed - OCR uses full-image processing.");
+    if single_item {
+        log(&format!("Recalibrating a single item: {}", first_step.description()));
+        log("Other items keep their current values.");
+    } else {
+        log("This wizard collects button positions for automation.");
+        log("Score regions are NOT needed - OCR uses full-image processing.");
+    }
     log("");
     log("Hotkeys:");
     log("  F1     - Record point (for buttons)");
@@ -121,7 +154,14 @@ pub fn start_calibration(app_hwnd: HWND) -> Result<()> {
 }
 
 /// Handles a calibration hotkey press.
+///
+/// The reference wizard borrows the same hotkeys; while it runs, presses are
+/// forwarded to it.
 pub fn handle_calibration_hotkey(hotkey_id: i32) -> Result<()> {
+    if is_capturing_references() {
+        return handle_reference_hotkey(hotkey_id);
+    }
+
     let mut guard = CALIBRATION.lock().unwrap();
     let ctx = match guard.as_mut() {
         Some(c) => c,
@@ -215,12 +255,13 @@ fn handle_point_capture(ctx: &mut CalibrationContext) -> Result<()> {
 
     log(&format!("Recorded position: ({:.3}, {:.3})", rel_x, rel_y));
 
-    // Store the position
-    let button = ButtonConfig { x: rel_x, y: rel_y };
+    // Store the position, keeping the button's configured click style
+    let config = get_config();
+    let at = |base: &ButtonConfig| ButtonConfig { x: rel_x, y: rel_y, ..base.clone() };
     match ctx.current_step {
-        CalibrationStep::StartButton => ctx.items.start_button = Some(button),
-        CalibrationStep::SkipButton => ctx.items.skip_button = Some(button),
-        CalibrationStep::EndButton => ctx.items.end_button = Some(button),
+        CalibrationStep::StartButton => ctx.items.start_button = Some(at(&config.start_button)),
+        CalibrationStep::SkipButton => ctx.items.skip_button = Some(at(&config.skip_button)),
+        CalibrationStep::EndButton => ctx.items.end_button = Some(at(&config.end_button)),
         _ => {}
     }
 
@@ -357,7 +398,7 @@ fn show_step_preview(ctx: &CalibrationContext) -> Result<()> {
 
     // Build partial config for preview
     let base_config = get_config();
-    let mut preview_config = base_config.clone();
+    let mut preview_config = AutomationConfig::clone(&base_config);
 
     // Update with captured values
     if let Some(ref btn) = ctx.items.start_button {
@@ -408,6 +449,11 @@ fn advance_to_next_step(ctx: &mut CalibrationContext) -> Result<()> {
     log("Confirmed.");
     log("");
 
+    if ctx.single_item {
+        ctx.current_step = CalibrationStep::Complete;
+        return Ok(());
+    }
+
     ctx.current_step = match ctx.current_step {
         CalibrationStep::StartButton => CalibrationStep::StartButtonRegionTopLeft,
         CalibrationStep::StartButtonRegionBottomRight => CalibrationStep::SkipButton,
@@ -446,6 +492,12 @@ fn rewind_to_step_start(step: &CalibrationStep) -> CalibrationStep {
 
 /// Skips the current step without recording (keeps existing config value).
 fn skip_current_step(ctx: &mut CalibrationContext) -> Result<()> {
+    if ctx.single_item {
+        ctx.pending_top_left = None;
+        ctx.current_step = CalibrationStep::Complete;
+        return Ok(());
+    }
+
     // Skip to the next "start" step (not intermediate steps like BottomRight)
     ctx.current_step = match ctx.current_step {
         CalibrationStep::StartButton => CalibrationStep::StartButtonRegionTopLeft,
@@ -530,7 +582,7 @@ fn finish_calibration(items: CalibrationItems, game_hwnd: HWND) -> Result<()> {
 
     // Build final config
     let base_config = get_config();
-    let mut final_config = base_config.clone();
+    let mut final_config = AutomationConfig::clone(&base_config);
 
     // Apply captured values
     if let Some(btn) = items.start_button {
@@ -553,10 +605,7 @@ fn finish_calibration(items: CalibrationItems, game_hwnd: HWND) -> Result<()> {
     }
 
     // Save config
-    let config_path = crate::paths::get_exe_dir().join("config.json");
-
-    let json = serde_json::to_string_pretty(&final_config)?;
-    std::fs::write(&config_path, &json)?;
+    let config_path = crate::automation::save_config(&final_config)?;
     log(&format!("Config saved to: {}", crate::paths::relative_display(&config_path)));
 
     // Show final preview
@@ -568,8 +617,10 @@ fn finish_calibration(items: CalibrationItems, game_hwnd: HWND) -> Result<()> {
         }
     }
 
+    crate::automation::reload_config();
+
     log("");
-    log("Calibration finished. You may need to restart the app to use new config.");
+    log("Calibration finished. The new config is active for the next run.");
 
     stop_calibration()?;
     Ok(())
@@ -587,7 +638,7 @@ pub fn stop_calibration() -> Result<()> {
 }
 
 /// Registers calibration-specific hotkeys.
-fn register_calibration_hotkeys(hwnd: HWND) -> Result<()> {
+pub(super) fn register_calibration_hotkeys(hwnd: HWND) -> Result<()> {
     unsafe {
         RegisterHotKey(hwnd, HOTKEY_CAL_F1, MOD_NOREPEAT, VK_F1.0 as u32)?;
         RegisterHotKey(hwnd, HOTKEY_CAL_F2, MOD_NOREPEAT, VK_F2.0 as u32)?;
@@ -601,7 +652,7 @@ fn register_calibration_hotkeys(hwnd: HWND) -> Result<()> {
 }
 
 /// Unregisters calibration-specific hotkeys.
-fn unregister_calibration_hotkeys(hwnd: HWND) {
+pub(super) fn unregister_calibration_hotkeys(hwnd: HWND) {
     unsafe {
         let _ = UnregisterHotKey(hwnd, HOTKEY_CAL_F1);
         let _ = UnregisterHotKey(hwnd, HOTKEY_CAL_F2);
@@ -613,16 +664,21 @@ fn unregister_calibration_hotkeys(hwnd: HWND) {
     }
 }
 
-/// Shows a one-shot preview of all configured regions.
+/// Shows a one-shot preview of all configured regions, plus the score rows
+/// as thresholded for OCR.
 pub fn show_preview_once() -> Result<()> {
-    let game_hwnd = find_gakumas_window()?;
     let config = get_config();
 
     log("Capturing screenshot for preview...");
-    let screenshot = capture_gakumas_to_buffer(game_hwnd)?;
-    let preview = render_preview(&screenshot, config);
+    let screenshot = capture_game_to_buffer()?;
+    let preview = render_preview(&screenshot, &config);
     show_preview(&preview, "regions_preview.png")?;
     log("Preview opened: regions_preview.png");
 
+    // What the OCR actually sees in each score row after thresholding
+    let thresholded = render_thresholded_scores(&screenshot, &config);
+    show_preview(&thresholded, "regions_preview_thresholded.png")?;
+    log("Preview opened: regions_preview_thresholded.png");
+
     Ok(())
 }
@@ -0,0 +1,99 @@
+//! Windows Runtime initialization for the capture APIs.
+//!
+//! `main` normally initializes the main thread multithreaded, which also gives
+//! every other thread the process's implicit MTA, so capture threads need no
+//! setup of their own. If another component (a shell extension, an injected
+//! overlay DLL, ...) already made the main thread single-threaded,
+//! `RoInitialize(RO_INIT_MULTITHREADED)` fails with `RPC_E_CHANGED_MODE`; the
+//! main thread then joins that STA instead, and since there is no implicit MTA,
+//! each capture thread initializes itself (`ensure_thread_initialized`).
+
+use anyhow::{anyhow, Result};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use windows::core::HRESULT;
+use windows::Win32::Foundation::RPC_E_CHANGED_MODE;
+use windows::Win32::System::WinRT::{RoInitialize, RO_INIT_MULTITHREADED, RO_INIT_SINGLETHREADED};
+
+/// Set when the main thread fell back to a single-threaded apartment.
+static MAIN_THREAD_STA: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Whether `ensure_thread_initialized` already ran on this thread.
+    static THREAD_INITIALIZED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Initializes the Windows Runtime on the main thread: multithreaded, or
+/// single-threaded when the thread is already an STA.
+///
+/// # Errors
+/// Returns an error explaining what to try when neither works.
+pub fn init_main_thread() -> Result<()> {
+    let err = match unsafe { RoInitialize(RO_INIT_MULTITHREADED) } {
+        Ok(()) => return Ok(()),
+        Err(e) => e,
+    };
+    if err.code() != RPC_E_CHANGED_MODE {
+        return Err(anyhow!(init_error_message(err.code(), &err.message())));
+    }
+
+    crate::log(
+        "RoInitialize: the main thread was already initialized single-threaded by another \
+         component (RPC_E_CHANGED_MODE); falling back to RO_INIT_SINGLETHREADED",
+    );
+    match unsafe { RoInitialize(RO_INIT_SINGLETHREADED) } {
+        Ok(()) => {
+            MAIN_THREAD_STA.store(true, Ordering::SeqCst);
+            crate::log(
+                "RoInitialize: running single-threaded; capture threads join the MTA themselves",
+            );
+            Ok(())
+        }
+        Err(e) => Err(anyhow!(init_error_message(e.code(), &e.message()))),
+    }
+}
+
+/// Joins the calling thread to the MTA if the main thread fell back to an STA
+/// (otherwise the implicit MTA already covers it). Runs once per thread; a
+/// thread that already has an apartment keeps it.
+pub fn ensure_thread_initialized() {
+    if !MAIN_THREAD_STA.load(Ordering::SeqCst) || THREAD_INITIALIZED.get() {
+        return;
+    }
+    THREAD_INITIALIZED.set(true);
+    if let Err(e) = unsafe { RoInitialize(RO_INIT_MULTITHREADED) }
+        && e.code() != RPC_E_CHANGED_MODE
+    {
+        crate::log(&format!(
+            "RoInitialize on capture thread failed ({:#010x}): {}",
+            e.code().0,
+            e.message()
+        ));
+    }
+}
+
+/// Startup error for a failed `RoInitialize`, with what the user can do.
+fn init_error_message(code: HRESULT, message: &str) -> String {
+    format!(
+        "Failed to initialize the Windows Runtime ({:#010x}: {}). Another program may have \
+         set up COM in this process in an incompatible way; close screen overlays, recorders \
+         and accessibility tools that hook into other windows, then restart the app \
+         (or reboot if it keeps failing).",
+        code.0,
+        message.trim()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn init_error_message_names_hresult_and_remedy() {
+        let message = init_error_message(RPC_E_CHANGED_MODE, "Cannot change thread mode\r\n");
+        assert!(message.contains("0x80010106"), "{}", message);
+        assert!(message.contains("Cannot change thread mode)."), "{}", message);
+        assert!(message.contains("restart"), "{}", message);
+    }
+}
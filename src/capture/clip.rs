@@ -0,0 +1,225 @@
+//! Short animated clips of the game window, saved as GIF.
+//!
+//! Unlike a screenshot, which opens a fresh capture session per frame, a clip
+//! keeps one session running and samples its latest frame at a fixed rate.
+//! Frames are downscaled before encoding: full-size GIF frames of a 1080p
+//! window are several megabytes each and slow to quantize. Independent of
+//! automation; triggered from the GUI hotkey only.
+
+use anyhow::{anyhow, Result};
+use chrono::Local;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::imageops::FilterType;
+use image::{Delay, Frame, ImageBuffer, Rgba};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use windows::Foundation::TypedEventHandler;
+use windows::Graphics::Capture::{Direct3D11CaptureFrame, Direct3D11CaptureFramePool};
+use windows::Win32::Foundation::HWND;
+use windows::Win32::Graphics::Direct3D11::{ID3D11Device, ID3D11DeviceContext};
+
+use super::screenshot::{
+    configure_session, create_capture_item, create_d3d11_device, create_direct3d_device,
+    frame_pixel_format, frame_to_rgba, next_frame, FrameSignal,
+};
+use super::timing::CaptureTimings;
+use super::window::get_client_area_info;
+
+/// Clip length used by the hotkey.
+pub const CLIP_DURATION_MS: u64 = 3000;
+
+/// Frame rate used by the hotkey.
+pub const CLIP_FPS: u32 = 10;
+
+/// Longest edge of a clip frame; larger captures are scaled down to fit.
+const CLIP_MAX_EDGE: u32 = 640;
+
+/// Records `duration_ms` of the game window at `fps` frames per second and
+/// saves it as an animated GIF in the screenshots folder.
+///
+/// Windows Graphics Capture only delivers a frame when the window content
+/// changes, so a still screen repeats the previous frame rather than stalling
+/// the clip. Returns the path to the saved file.
+pub fn capture_clip(hwnd: HWND, duration_ms: u64, fps: u32) -> Result<PathBuf> {
+    if fps == 0 || duration_ms == 0 {
+        return Err(anyhow!("Clip needs a positive duration and frame rate"));
+    }
+    let frame_count = clip_frame_count(duration_ms, fps);
+    crate::log(&format!(
+        "Recording {}ms clip at {}fps ({} frames)...",
+        duration_ms, fps, frame_count
+    ));
+
+    let frames = record_frames(hwnd, frame_count, fps)?;
+
+    let timestamp = Local::now().format("%Y%m%d_%H%M%S");
+    let path = crate::paths::get_screenshots_dir().join(format!("gakumas_clip_{}.gif", timestamp));
+    crate::log("Encoding clip...");
+    encode_gif(frames, fps, &path)?;
+    crate::log(&format!("Saved clip to {}", crate::paths::relative_display(&path)));
+
+    Ok(path)
+}
+
+/// Number of frames in a clip, at least one.
+fn clip_frame_count(duration_ms: u64, fps: u32) -> usize {
+    ((duration_ms * fps as u64).div_ceil(1000)).max(1) as usize
+}
+
+/// Size a `width` x `height` frame is scaled to so neither edge exceeds
+/// `CLIP_MAX_EDGE`, keeping the aspect ratio.
+fn clip_frame_size(width: u32, height: u32) -> (u32, u32) {
+    let longest = width.max(height);
+    if longest <= CLIP_MAX_EDGE {
+        return (width, height);
+    }
+    let scale = |edge: u32| ((edge as u64 * CLIP_MAX_EDGE as u64 / longest as u64) as u32).max(1);
+    (scale(width), scale(height))
+}
+
+/// Samples `frame_count` frames from one capture session, one every
+/// `1000 / fps` milliseconds.
+fn record_frames(
+    hwnd: HWND,
+    frame_count: usize,
+    fps: u32,
+) -> Result<Vec<ImageBuffer<Rgba<u8>, Vec<u8>>>> {
+    let (client_rect, client_offset) = get_client_area_info(hwnd)?;
+    let crop = (
+        client_offset.x as u32,
+        client_offset.y as u32,
+        (client_rect.right - client_rect.left) as u32,
+        (client_rect.bottom - client_rect.top) as u32,
+    );
+
+    let (device, context) = create_d3d11_device()?;
+    let item = create_capture_item(hwnd)?;
+    let size = item.Size()?;
+    let d3d_device = create_direct3d_device(&device)?;
+    let frame_pool = Direct3D11CaptureFramePool::CreateFreeThreaded(
+        &d3d_device,
+        frame_pixel_format(),
+        1,
+        size,
+    )?;
+    let session = frame_pool.CreateCaptureSession(&item)?;
+    configure_session(&session)?;
+
+    let frame_arrived = Arc::new(FrameSignal::default());
+    let frame_arrived_clone = frame_arrived.clone();
+    frame_pool.FrameArrived(&TypedEventHandler::new(
+        move |_pool: &Option<Direct3D11CaptureFramePool>, _| {
+            frame_arrived_clone.notify();
+            Ok(())
+        },
+    ))?;
+    session.StartCapture()?;
+
+    let result = (|| -> Result<Vec<ImageBuffer<Rgba<u8>, Vec<u8>>>> {
+        let interval = Duration::from_secs_f64(1.0 / fps as f64);
+        let first = next_frame(&frame_pool, &d3d_device, size, &frame_arrived)?;
+        let start = Instant::now();
+        let mut frames = vec![frame_to_image(&device, &context, &first, crop)?];
+        first.Close()?;
+
+        while frames.len() < frame_count {
+            let due = start + interval * frames.len() as u32;
+            if let Some(wait) = due.checked_duration_since(Instant::now()) {
+                std::thread::sleep(wait);
+            }
+
+            // Take a new frame if one arrived; otherwise the screen is unchanged
+            let latest = if frame_arrived.take() {
+                frame_pool.TryGetNextFrame().ok()
+            } else {
+                None
+            };
+            let img = match latest {
+                Some(frame) => {
+                    let img = frame_to_image(&device, &context, &frame, crop)?;
+                    frame.Close()?;
+                    img
+                }
+                None => frames.last().expect("first frame pushed above").clone(),
+            };
+            frames.push(img);
+        }
+        Ok(frames)
+    })();
+
+    let _ = session.Close();
+    let _ = frame_pool.Close();
+    result
+}
+
+/// Copies a captured frame to the CPU, crops it to the client area and
+/// scales it down to clip size.
+fn frame_to_image(
+    device: &ID3D11Device,
+    context: &ID3D11DeviceContext,
+    frame: &Direct3D11CaptureFrame,
+    crop: (u32, u32, u32, u32),
+) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>> {
+    let mut timings = CaptureTimings::default();
+    let img = frame_to_rgba(device, context, frame, |_, _| crop, &mut timings)?;
+
+    let (width, height) = clip_frame_size(img.width(), img.height());
+    if (width, height) == img.dimensions() {
+        return Ok(img);
+    }
+    Ok(image::imageops::resize(&img, width, height, FilterType::Triangle))
+}
+
+/// Writes `frames` to `path` as a looping GIF shown at `fps`.
+fn encode_gif(frames: Vec<ImageBuffer<Rgba<u8>, Vec<u8>>>, fps: u32, path: &Path) -> Result<()> {
+    let file = File::create(path)
+        .map_err(|e| anyhow!("Failed to create {}: {}", path.display(), e))?;
+    let mut encoder = GifEncoder::new_with_speed(BufWriter::new(file), 10);
+    encoder.set_repeat(Repeat::Infinite)?;
+    let delay = Delay::from_numer_denom_ms(1000, fps);
+    encoder.encode_frames(frames.into_iter().map(|img| Frame::from_parts(img, 0, 0, delay)))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::codecs::gif::GifDecoder;
+    use image::AnimationDecoder;
+    use std::io::BufReader;
+
+    #[test]
+    fn test_clip_frame_count_rounds_up() {
+        assert_eq!(clip_frame_count(3000, 10), 30);
+        assert_eq!(clip_frame_count(250, 10), 3);
+        assert_eq!(clip_frame_count(1, 1), 1);
+    }
+
+    #[test]
+    fn test_clip_frame_size_fits_longest_edge() {
+        assert_eq!(clip_frame_size(1920, 1080), (640, 360));
+        assert_eq!(clip_frame_size(1080, 1920), (360, 640));
+        assert_eq!(clip_frame_size(400, 300), (400, 300));
+    }
+
+    #[test]
+    fn test_encode_gif_writes_every_frame() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("clip.gif");
+        let frames = (0..3u8)
+            .map(|i| ImageBuffer::from_pixel(8, 4, Rgba([i * 80, 0, 0, 255])))
+            .collect();
+
+        encode_gif(frames, 10, &path).unwrap();
+
+        let decoder = GifDecoder::new(BufReader::new(File::open(&path).unwrap())).unwrap();
+        let decoded = decoder.into_frames().collect_frames().unwrap();
+        assert_eq!(decoded.len(), 3);
+        assert_eq!(decoded[0].buffer().dimensions(), (8, 4));
+        assert_eq!(Duration::from(decoded[0].delay()), Duration::from_millis(100));
+    }
+}
@@ -3,14 +3,25 @@
 //! This module provides:
 //! - Window discovery (`find_gakumas_window`)
 //! - Client area information (`get_client_area_info`)
-//! - Screenshot capture (`capture_gakumas`)
+//! - Screenshot capture (`capture_gakumas`, `capture_game_to_buffer`)
+//! - Animated GIF clips from one running session (`clip`)
 //! - Region capture (`capture_region`)
+//! - Monitor-region capture (`capture_monitor_region`), selected by `capture_source`
+//! - Windows Runtime initialization with an STA fallback (`apartment`)
+//! - Per-phase capture timings aggregated over a run (`timing`)
 
+pub mod apartment;
+pub mod clip;
+pub mod monitor;
 pub mod region;
 pub mod screenshot;
+pub mod timing;
 pub mod window;
 
+pub use monitor::capture_game_frame;
 pub use region::capture_region;
-pub use screenshot::{capture_gakumas, capture_gakumas_to_buffer, capture_gakumas_to_buffer as capture_window_to_image};
-pub use window::find_gakumas_window;
+pub use screenshot::{
+    capture_gakumas, capture_gakumas_to_buffer, capture_game_to_buffer, capture_stable_frame,
+};
+pub use window::{find_gakumas_window, find_gakumas_window_with_retry};
 pub use window::get_client_area_info;
@@ -0,0 +1,142 @@
+//! Monitor-region capture, a fallback for setups where Windows Graphics Capture
+//! of the game window is blocked but capturing a whole display still works.
+//!
+//! The configured `capture_monitor_rect` is cropped out of the monitor frame and
+//! stands in for the game's client area, so every relative region in the config
+//! keeps its meaning.
+
+use anyhow::{anyhow, Context, Result};
+use image::{ImageBuffer, Rgba};
+
+use windows::Graphics::Capture::GraphicsCaptureItem;
+use windows::Win32::Foundation::{BOOL, HWND, LPARAM, RECT, TRUE};
+use windows::Win32::Graphics::Gdi::{EnumDisplayMonitors, HDC, HMONITOR};
+use windows::Win32::System::WinRT::Graphics::Capture::IGraphicsCaptureItemInterop;
+
+use crate::automation::config::{get_config, CaptureSource, RelativeRect};
+
+use super::screenshot::{
+    capture_gakumas_region_to_buffer, capture_gakumas_to_buffer, capture_item_once,
+};
+
+/// Captures a frame of the game according to `capture_source`: the window's
+/// client area, or the configured monitor region.
+pub fn capture_game_frame(hwnd: HWND) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>> {
+    let config = get_config();
+    match config.capture_source {
+        CaptureSource::Window => Ok(capture_gakumas_to_buffer(hwnd)?),
+        CaptureSource::Monitor => {
+            capture_monitor_region(config.capture_monitor_index, &config.capture_monitor_rect)
+        }
+    }
+}
+
+/// `capture_game_frame` limited to `region` (relative coordinates). Window
+/// captures convert only the region out of the frame and, like full frames,
+/// are retried and timed; monitor captures are cropped afterwards.
+pub fn capture_game_region(
+    hwnd: HWND,
+    region: &RelativeRect,
+) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>> {
+    let config = get_config();
+    match config.capture_source {
+        CaptureSource::Window => Ok(capture_gakumas_region_to_buffer(hwnd, Some(region))?),
+        CaptureSource::Monitor => {
+            let frame =
+                capture_monitor_region(config.capture_monitor_index, &config.capture_monitor_rect)?;
+            let (x, y, w, h) = relative_to_pixels(region, frame.width(), frame.height());
+            Ok(image::imageops::crop_imm(&frame, x, y, w, h).to_image())
+        }
+    }
+}
+
+/// Lists the handles of all attached monitors, in display enumeration order.
+pub fn list_monitors() -> Vec<HMONITOR> {
+    unsafe extern "system" fn enum_callback(
+        monitor: HMONITOR,
+        _hdc: HDC,
+        _rect: *mut RECT,
+        lparam: LPARAM,
+    ) -> BOOL {
+        unsafe {
+            let monitors = &mut *(lparam.0 as *mut Vec<HMONITOR>);
+            monitors.push(monitor);
+        }
+        TRUE
+    }
+
+    let mut monitors: Vec<HMONITOR> = Vec::new();
+    unsafe {
+        let _ = EnumDisplayMonitors(
+            HDC::default(),
+            None,
+            Some(enum_callback),
+            LPARAM(&mut monitors as *mut _ as isize),
+        );
+    }
+    monitors
+}
+
+/// Captures the part of monitor `monitor_index` covered by `rect` (relative to
+/// the monitor's full resolution) and returns it in RGBA format.
+pub fn capture_monitor_region(
+    monitor_index: usize,
+    rect: &RelativeRect,
+) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>> {
+    let monitors = list_monitors();
+    let monitor = *monitors.get(monitor_index).ok_or_else(|| {
+        anyhow!(
+            "Monitor {} not found ({} monitor(s) attached)",
+            monitor_index,
+            monitors.len()
+        )
+    })?;
+
+    let item = create_capture_item_for_monitor(monitor)?;
+    let (img, _) =
+        capture_item_once(&item, |width, height| relative_to_pixels(rect, width, height))?;
+    Ok(img)
+}
+
+/// Converts a relative rect to a pixel rect `(x, y, width, height)` clamped to
+/// a `width` x `height` frame; the result is always at least 1x1.
+pub(super) fn relative_to_pixels(rect: &RelativeRect, width: u32, height: u32) -> (u32, u32, u32, u32) {
+    let x = ((rect.x * width as f32) as u32).min(width.saturating_sub(1));
+    let y = ((rect.y * height as f32) as u32).min(height.saturating_sub(1));
+    let w = ((rect.width * width as f32) as u32).min(width - x).max(1);
+    let h = ((rect.height * height as f32) as u32).min(height - y).max(1);
+    (x, y, w, h)
+}
+
+/// Creates a GraphicsCaptureItem for the specified monitor.
+fn create_capture_item_for_monitor(monitor: HMONITOR) -> Result<GraphicsCaptureItem> {
+    super::apartment::ensure_thread_initialized();
+    let class_name = windows::core::h!("Windows.Graphics.Capture.GraphicsCaptureItem");
+    let interop: IGraphicsCaptureItemInterop = unsafe {
+        windows::Win32::System::WinRT::RoGetActivationFactory(class_name)
+            .context("Failed to get IGraphicsCaptureItemInterop")?
+    };
+
+    unsafe {
+        interop
+            .CreateForMonitor(monitor)
+            .context("Failed to create capture item for monitor")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_to_pixels_clamps_to_frame() {
+        let full = RelativeRect { x: 0.0, y: 0.0, width: 1.0, height: 1.0 };
+        assert_eq!(relative_to_pixels(&full, 1920, 1080), (0, 0, 1920, 1080));
+
+        let right_half = RelativeRect { x: 0.5, y: 0.25, width: 0.5, height: 0.5 };
+        assert_eq!(relative_to_pixels(&right_half, 1920, 1080), (960, 270, 960, 540));
+
+        let overhang = RelativeRect { x: 0.9, y: 0.9, width: 0.5, height: 0.5 };
+        assert_eq!(relative_to_pixels(&overhang, 100, 100), (90, 90, 10, 10));
+    }
+}
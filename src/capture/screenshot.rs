@@ -5,12 +5,18 @@ use chrono::Local;
 use image::{ImageBuffer, Rgba};
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
 
 use windows::core::Interface;
 use windows::Foundation::TypedEventHandler;
-use windows::Graphics::Capture::{Direct3D11CaptureFramePool, GraphicsCaptureItem};
+use windows::Graphics::Capture::{
+    Direct3D11CaptureFrame, Direct3D11CaptureFramePool, GraphicsCaptureItem,
+    GraphicsCaptureSession,
+};
+use windows::Graphics::DirectX::Direct3D11::IDirect3DDevice;
 use windows::Graphics::DirectX::DirectXPixelFormat;
+use windows::Graphics::SizeInt32;
 use windows::Win32::Foundation::HWND;
 use windows::Win32::Graphics::Direct3D::D3D_DRIVER_TYPE_HARDWARE;
 use windows::Win32::Graphics::Direct3D11::{
@@ -18,205 +24,41 @@ use windows::Win32::Graphics::Direct3D11::{
     D3D11_CPU_ACCESS_READ, D3D11_CREATE_DEVICE_BGRA_SUPPORT, D3D11_MAP_READ, D3D11_SDK_VERSION,
     D3D11_TEXTURE2D_DESC, D3D11_USAGE_STAGING,
 };
+use windows::Win32::Graphics::Dxgi::Common::{
+    DXGI_FORMAT, DXGI_FORMAT_B8G8R8A8_UNORM, DXGI_FORMAT_R16G16B16A16_FLOAT,
+};
 use windows::Win32::System::WinRT::Direct3D11::CreateDirect3D11DeviceFromDXGIDevice;
 use windows::Win32::System::WinRT::Graphics::Capture::IGraphicsCaptureItemInterop;
 
+use super::monitor::{capture_game_frame, capture_game_region, relative_to_pixels};
+use super::timing::{record_capture, CaptureTimings};
 use super::window::{find_gakumas_window, get_client_area_info};
+use crate::automation::{CapturePixelFormat, RelativeRect};
+use crate::error::GakumasError;
+
+/// One captured RGBA frame and how long each phase of its capture took.
+type TimedFrame = (ImageBuffer<Rgba<u8>, Vec<u8>>, CaptureTimings);
 
 /// Captures a screenshot of the gakumas.exe game window.
 ///
 /// This function:
-/// 1. Finds the game window
-/// 2. Creates a D3D11 device for GPU-accelerated capture
-/// 3. Uses Windows Graphics Capture API to capture the window
-/// 4. Crops to the client area (excluding title bar and borders)
-/// 5. Converts from BGRA to RGBA format
-/// 6. Saves as a PNG file with timestamp
+/// 1. Captures the game with `capture_game_to_buffer`, so `capture_source`,
+///    `capture_retries` and the pixel format settings apply as in automation
+/// 2. Saves it as a PNG file named from `screenshot_name_template`
 ///
 /// Returns the path to the saved screenshot file.
 pub fn capture_gakumas() -> Result<PathBuf> {
     crate::log("Starting capture...");
 
-    let hwnd = find_gakumas_window()?;
-    crate::log(&format!("Window handle: {:?}", hwnd));
-
-    let (client_rect, client_offset) = get_client_area_info(hwnd)?;
-    let client_width = client_rect.right - client_rect.left;
-    let client_height = client_rect.bottom - client_rect.top;
-    crate::log(&format!(
-        "Client area: {}x{} at offset ({}, {})",
-        client_width, client_height, client_offset.x, client_offset.y
-    ));
-
-    // Create D3D11 device
-    crate::log("Creating D3D11 device...");
-    let (device, context) = create_d3d11_device()?;
-    crate::log("D3D11 device created");
-
-    // Create capture item from window
-    crate::log("Creating capture item...");
-    let item = create_capture_item(hwnd)?;
-    crate::log("Capture item created");
-    let size = item.Size()?;
-    crate::log(&format!("Capture size: {}x{}", size.Width, size.Height));
-
-    // Create frame pool
-    crate::log("Creating Direct3D device wrapper...");
-    let d3d_device = create_direct3d_device(&device)?;
-    crate::log("Creating frame pool...");
-    let frame_pool = Direct3D11CaptureFramePool::CreateFreeThreaded(
-        &d3d_device,
-        DirectXPixelFormat::B8G8R8A8UIntNormalized,
-        1,
-        size,
-    )?;
-
-    // Create capture session
-    crate::log("Creating capture session...");
-    let session = frame_pool.CreateCaptureSession(&item)?;
-    crate::log("Capture session created");
-
-    // Disable cursor capture to exclude cursor from screenshots
-    session.SetIsCursorCaptureEnabled(false)?;
-
-    // Set up frame arrival handling
-    let frame_arrived = Arc::new(AtomicBool::new(false));
-    let frame_arrived_clone = frame_arrived.clone();
-
-    frame_pool.FrameArrived(&TypedEventHandler::new(
-        move |_pool: &Option<Direct3D11CaptureFramePool>, _| {
-            frame_arrived_clone.store(true, Ordering::SeqCst);
-            Ok(())
-        },
-    ))?;
-
-    // Start capture
-    crate::log("Starting capture session...");
-    session.StartCapture()?;
-    crate::log("Capture started, waiting for frame...");
-
-    // Wait for frame
-    let start = std::time::Instant::now();
-    while !frame_arrived.load(Ordering::SeqCst) {
-        if start.elapsed().as_secs() > 5 {
-            return Err(anyhow!("Timeout waiting for frame"));
-        }
-        std::thread::sleep(std::time::Duration::from_millis(10));
-    }
-    crate::log("Frame arrived");
-
-    // Get the frame
-    crate::log("Getting frame...");
-    let frame = frame_pool.TryGetNextFrame()?;
-    crate::log("Got frame");
-    let surface = frame.Surface()?;
-
-    // Get the D3D11 texture from the surface
-    let access: windows::Win32::System::WinRT::Direct3D11::IDirect3DDxgiInterfaceAccess =
-        surface.cast()?;
-    let texture: ID3D11Texture2D = unsafe { access.GetInterface()? };
-
-    // Get texture description
-    let mut desc = D3D11_TEXTURE2D_DESC::default();
-    unsafe { texture.GetDesc(&mut desc) };
-
-    // Create staging texture for CPU read
-    let staging_desc = D3D11_TEXTURE2D_DESC {
-        Width: desc.Width,
-        Height: desc.Height,
-        MipLevels: 1,
-        ArraySize: 1,
-        Format: desc.Format,
-        SampleDesc: desc.SampleDesc,
-        Usage: D3D11_USAGE_STAGING,
-        BindFlags: Default::default(),
-        CPUAccessFlags: D3D11_CPU_ACCESS_READ.0 as u32,
-        MiscFlags: Default::default(),
-    };
-
-    let staging_texture = unsafe {
-        let mut staging: Option<ID3D11Texture2D> = None;
-        device.CreateTexture2D(&staging_desc, None, Some(&mut staging))?;
-        staging.ok_or_else(|| anyhow!("Failed to create staging texture"))?
-    };
-
-    // Copy to staging texture
-    unsafe {
-        context.CopyResource(
-            &staging_texture.cast::<ID3D11Resource>()?,
-            &texture.cast::<ID3D11Resource>()?,
-        );
-    }
-
-    // Map the staging texture
-    let mapped = unsafe {
-        let mut mapped = Default::default();
-        context.Map(
-            &staging_texture.cast::<ID3D11Resource>()?,
-            0,
-            D3D11_MAP_READ,
-            0,
-            Some(&mut mapped),
-        )?;
-        mapped
-    };
-
-    // Calculate crop parameters
-    let crop_x = client_offset.x as u32;
-    let crop_y = client_offset.y as u32;
-    let crop_width = client_width as u32;
-    let crop_height = client_height as u32;
-
-    crate::log(&format!(
-        "Cropping from ({}, {}) size {}x{}",
-        crop_x, crop_y, crop_width, crop_height
-    ));
-
-    // Create image from mapped data (cropped to client area)
-    let mut img: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::new(crop_width, crop_height);
-
-    let src_data = unsafe {
-        std::slice::from_raw_parts(
-            mapped.pData as *const u8,
-            (mapped.RowPitch * desc.Height) as usize,
-        )
-    };
-    let row_pitch = mapped.RowPitch as usize;
-
-    for y in 0..crop_height {
-        let src_y = (crop_y + y) as usize;
-        if src_y >= desc.Height as usize {
-            break;
-        }
-        for x in 0..crop_width {
-            let src_x = (crop_x + x) as usize;
-            if src_x >= desc.Width as usize {
-                break;
-            }
-            let offset = src_y * row_pitch + src_x * 4;
-            // BGRA -> RGBA
-            let b = src_data[offset];
-            let g = src_data[offset + 1];
-            let r = src_data[offset + 2];
-            let a = src_data[offset + 3];
-            img.put_pixel(x, y, Rgba([r, g, b, a]));
-        }
-    }
-
-    // Unmap
-    unsafe {
-        context.Unmap(&staging_texture.cast::<ID3D11Resource>()?, 0);
-    }
-
-    // Stop capture
-    session.Close()?;
-    frame_pool.Close()?;
+    let img = capture_game_to_buffer()?;
+    crate::log(&format!("Captured {}x{} frame", img.width(), img.height()));
 
     // Save to file
     crate::log("Saving image...");
-    let timestamp = Local::now().format("%Y%m%d_%H%M%S");
-    let filename = format!("gakumas_{}.png", timestamp);
-    let path = crate::paths::get_screenshots_dir().join(&filename);
+    let timestamp = Local::now().format("%Y%m%d_%H%M%S").to_string();
+    let config = crate::automation::get_config();
+    let filename = config.screenshot_file_name(None, img.dimensions(), &timestamp);
+    let path = crate::paths::unique_file_path(crate::paths::get_screenshots_dir().join(&filename));
 
     img.save(&path)?;
     crate::log(&format!("Saved to {}", crate::paths::relative_display(&path)));
@@ -228,7 +70,7 @@ pub fn capture_gakumas() -> Result<PathBuf> {
 ///
 /// The device is used for GPU-accelerated graphics operations.
 /// The context is used to issue rendering commands.
-fn create_d3d11_device() -> Result<(ID3D11Device, ID3D11DeviceContext)> {
+pub(super) fn create_d3d11_device() -> Result<(ID3D11Device, ID3D11DeviceContext)> {
     let mut device: Option<ID3D11Device> = None;
     let mut context: Option<ID3D11DeviceContext> = None;
 
@@ -255,9 +97,7 @@ fn create_d3d11_device() -> Result<(ID3D11Device, ID3D11DeviceContext)> {
 /// Creates a WinRT Direct3D device wrapper from a D3D11 device.
 ///
 /// This wrapper is required by the Windows Graphics Capture API.
-fn create_direct3d_device(
-    device: &ID3D11Device,
-) -> Result<windows::Graphics::DirectX::Direct3D11::IDirect3DDevice> {
+pub(super) fn create_direct3d_device(device: &ID3D11Device) -> Result<IDirect3DDevice> {
     let dxgi_device: windows::Win32::Graphics::Dxgi::IDXGIDevice = device.cast()?;
     let inspectable = unsafe { CreateDirect3D11DeviceFromDXGIDevice(&dxgi_device)? };
     inspectable
@@ -265,64 +105,340 @@ fn create_direct3d_device(
         .context("Failed to cast to IDirect3DDevice")
 }
 
+/// Frame pool pixel format for the configured `capture_pixel_format`.
+pub(super) fn frame_pixel_format() -> DirectXPixelFormat {
+    match crate::automation::get_config().capture_pixel_format {
+        CapturePixelFormat::Bgra8 => DirectXPixelFormat::B8G8R8A8UIntNormalized,
+        CapturePixelFormat::Rgba16Float => DirectXPixelFormat::R16G16B16A16Float,
+    }
+}
+
+/// Set once the outcome of turning off the capture border has been logged,
+/// so it is reported on the first capture rather than on every one.
+static BORDER_RESULT_LOGGED: AtomicBool = AtomicBool::new(false);
+
+/// Applies the capture-session options shared by every capture path.
+///
+/// The cursor is excluded unless `capture_cursor` is set, so a pointer resting
+/// over the game never lands in a screenshot. With `suppress_capture_border`
+/// the yellow capture border is turned off where the OS allows it; older
+/// Windows builds reject the call, which is logged once and otherwise
+/// harmless.
+pub(super) fn configure_session(session: &GraphicsCaptureSession) -> Result<()> {
+    let config = crate::automation::get_config();
+    session.SetIsCursorCaptureEnabled(config.capture_cursor)?;
+    if config.suppress_capture_border {
+        let result = session.SetIsBorderRequired(false);
+        if !BORDER_RESULT_LOGGED.swap(true, Ordering::SeqCst) {
+            match result {
+                Ok(()) => crate::log("Capture border suppressed"),
+                Err(e) => crate::log(&format!(
+                    "Capture border could not be turned off ({}); this Windows version \
+                     draws a yellow border around the captured window",
+                    e
+                )),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// How long `next_frame` waits for a frame before giving up with
+/// `GakumasError::CaptureTimeout`.
+const FRAME_WAIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often a polling `FrameSignal::wait` rechecks the flag.
+const FRAME_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// "A frame arrived" flag shared between a frame pool's `FrameArrived`
+/// handler and the capture thread.
+///
+/// With `event_driven_frame_wait` the capture thread blocks on a condition
+/// variable and wakes as soon as the handler fires; otherwise it polls the
+/// flag every `FRAME_POLL_INTERVAL`, which can add up to that much latency.
+#[derive(Default)]
+pub(super) struct FrameSignal {
+    arrived: Mutex<bool>,
+    condvar: Condvar,
+}
+
+impl FrameSignal {
+    /// Marks a frame as arrived and wakes any waiter. Called from the
+    /// `FrameArrived` handler.
+    pub(super) fn notify(&self) {
+        *self.arrived.lock().unwrap() = true;
+        self.condvar.notify_all();
+    }
+
+    /// Clears the flag, returning whether a frame had arrived.
+    pub(super) fn take(&self) -> bool {
+        std::mem::take(&mut *self.arrived.lock().unwrap())
+    }
+
+    /// Waits up to `timeout` for a frame, without clearing the flag. Returns
+    /// false on timeout.
+    fn wait(&self, timeout: Duration, event_driven: bool) -> bool {
+        if event_driven {
+            let arrived = self.arrived.lock().unwrap();
+            let (arrived, _) = self
+                .condvar
+                .wait_timeout_while(arrived, timeout, |arrived| !*arrived)
+                .unwrap();
+            return *arrived;
+        }
+
+        let start = Instant::now();
+        while !*self.arrived.lock().unwrap() {
+            if start.elapsed() >= timeout {
+                return false;
+            }
+            std::thread::sleep(FRAME_POLL_INTERVAL);
+        }
+        true
+    }
+}
+
+/// Waits for the next frame from `frame_pool`, which was created with `size`.
+///
+/// If the window was resized between `item.Size()` and the frame arriving, the
+/// frame's `ContentSize` no longer matches the pool and a staging copy of it
+/// would be garbled. In that case the pool is recreated at the new size and
+/// the next frame is awaited once more; a second mismatch is an error.
+pub(super) fn next_frame(
+    frame_pool: &Direct3D11CaptureFramePool,
+    d3d_device: &IDirect3DDevice,
+    size: SizeInt32,
+    frame_arrived: &FrameSignal,
+) -> Result<Direct3D11CaptureFrame> {
+    let event_driven = crate::automation::get_config().event_driven_frame_wait;
+    let mut pool_size = size;
+    for attempt in 0..2 {
+        if !frame_arrived.wait(FRAME_WAIT_TIMEOUT, event_driven) {
+            return Err(GakumasError::CaptureTimeout.into());
+        }
+
+        let frame = frame_pool.TryGetNextFrame()?;
+        let content_size = frame.ContentSize()?;
+        if content_size == pool_size {
+            return Ok(frame);
+        }
+        if attempt == 1 {
+            break;
+        }
+
+        crate::log(&format!(
+            "Capture size changed ({}x{} -> {}x{}); recreating frame pool",
+            pool_size.Width, pool_size.Height, content_size.Width, content_size.Height
+        ));
+        frame.Close()?;
+        frame_arrived.take();
+        frame_pool.Recreate(
+            d3d_device,
+            frame_pixel_format(),
+            1,
+            content_size,
+        )?;
+        pool_size = content_size;
+    }
+    Err(anyhow!("Capture size kept changing; frame pool recreate did not help"))
+}
+
 /// Captures a screenshot of the specified game window and returns it as an ImageBuffer.
 ///
 /// This is similar to `capture_gakumas` but:
 /// - Takes an HWND parameter instead of finding the window
 /// - Returns the image data instead of saving to file
 /// - Does not log as verbosely
-pub fn capture_gakumas_to_buffer(hwnd: HWND) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>> {
+///
+/// Windows Graphics Capture occasionally never delivers the first frame right
+/// after the window gains focus. A frame timeout is therefore retried with a
+/// brand-new session up to `capture_retries` times; other errors are returned
+/// at once.
+///
+/// The successful attempt's phase timings are added to the run totals (see
+/// `capture::timing`). Failures are `GakumasError::CaptureTimeout` when no
+/// frame arrived on the last attempt, else `GakumasError::Capture`.
+pub fn capture_gakumas_to_buffer(
+    hwnd: HWND,
+) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>, GakumasError> {
+    capture_gakumas_region_to_buffer(hwnd, None)
+}
+
+/// `capture_gakumas_to_buffer` limited to `region` of the client area (relative
+/// coordinates). The frame is still copied whole, but only the region is
+/// converted out of the mapped texture.
+pub fn capture_gakumas_region_to_buffer(
+    hwnd: HWND,
+    region: Option<&RelativeRect>,
+) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>, GakumasError> {
+    let retries = crate::automation::get_config().capture_retries;
+    let mut attempt = 0;
+    loop {
+        match capture_gakumas_to_buffer_once(hwnd, region) {
+            Ok((img, timings)) => {
+                record_capture(&timings);
+                if attempt > 0 {
+                    crate::log(&format!("Capture succeeded on retry {}/{}", attempt, retries));
+                }
+                return Ok(img);
+            }
+            Err(e) if attempt < retries && is_capture_timeout(&e) => {
+                attempt += 1;
+                crate::log(&format!(
+                    "Capture failed ({}); retrying with a fresh session ({}/{})",
+                    e, attempt, retries
+                ));
+            }
+            Err(e) => {
+                if attempt > 0 {
+                    crate::log(&format!("Capture retry {}/{} failed: {}", attempt, retries, e));
+                }
+                return Err(into_capture_error(e));
+            }
+        }
+    }
+}
+
+/// Finds the game window and captures it with `capture_game_frame` (the
+/// window, or the monitor region under `capture_source = Monitor`), for
+/// callers that just want "the game" and don't hold an HWND.
+pub fn capture_game_to_buffer() -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>, GakumasError> {
+    let hwnd = find_gakumas_window()?;
+    capture_game_frame(hwnd).map_err(into_capture_error)
+}
+
+/// The `GakumasError` inside `e`, or `e` wrapped as `GakumasError::Capture`.
+fn into_capture_error(e: anyhow::Error) -> GakumasError {
+    match e.downcast::<GakumasError>() {
+        Ok(typed) => typed,
+        Err(e) => GakumasError::Capture(e),
+    }
+}
+
+/// One attempt at capturing `region` of the client area, neither retried nor
+/// added to the run's capture timings (see `capture_region`).
+pub(super) fn capture_gakumas_region_once(
+    hwnd: HWND,
+    region: &RelativeRect,
+) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>> {
+    capture_gakumas_to_buffer_once(hwnd, Some(region)).map(|(img, _)| img)
+}
+
+/// True if an attempt failed because no frame arrived (worth a fresh session).
+fn is_capture_timeout(e: &anyhow::Error) -> bool {
+    matches!(e.downcast_ref(), Some(GakumasError::CaptureTimeout))
+}
+
+/// One capture attempt with its own device, frame pool and session, and how
+/// long each phase of it took.
+fn capture_gakumas_to_buffer_once(
+    hwnd: HWND,
+    region: Option<&RelativeRect>,
+) -> Result<TimedFrame> {
+    let phase_start = std::time::Instant::now();
     let (client_rect, client_offset) = get_client_area_info(hwnd)?;
-    let client_width = client_rect.right - client_rect.left;
-    let client_height = client_rect.bottom - client_rect.top;
+    let client_width = (client_rect.right - client_rect.left) as u32;
+    let client_height = (client_rect.bottom - client_rect.top) as u32;
 
-    // Create D3D11 device
-    let (device, context) = create_d3d11_device()?;
+    // Crop to the client area, or `region` within it
+    let (region_x, region_y, crop_width, crop_height) = match region {
+        Some(region) => relative_to_pixels(region, client_width, client_height),
+        None => (0, 0, client_width, client_height),
+    };
+    let crop = (
+        client_offset.x as u32 + region_x,
+        client_offset.y as u32 + region_y,
+        crop_width,
+        crop_height,
+    );
 
-    // Create capture item from window
     let item = create_capture_item(hwnd)?;
+    let item_setup = phase_start.elapsed();
+    let (img, mut timings) = capture_item_once(&item, |_, _| crop)?;
+    timings.setup += item_setup;
+    Ok((img, timings))
+}
+
+/// Captures one frame of `item` with its own device, frame pool and session
+/// and converts part of it to RGBA (see `frame_to_rgba`), returning how long
+/// each phase took. The session and pool are closed on failure too, so a
+/// retry starts from scratch. Shared by window and monitor captures.
+pub(super) fn capture_item_once(
+    item: &GraphicsCaptureItem,
+    crop: impl FnOnce(u32, u32) -> (u32, u32, u32, u32),
+) -> Result<TimedFrame> {
+    let mut timings = CaptureTimings::default();
+    let phase_start = std::time::Instant::now();
+
+    // Create D3D11 device
+    let (device, context) = create_d3d11_device()?;
     let size = item.Size()?;
 
     // Create frame pool
     let d3d_device = create_direct3d_device(&device)?;
     let frame_pool = Direct3D11CaptureFramePool::CreateFreeThreaded(
         &d3d_device,
-        DirectXPixelFormat::B8G8R8A8UIntNormalized,
+        frame_pixel_format(),
         1,
         size,
     )?;
 
     // Create capture session
-    let session = frame_pool.CreateCaptureSession(&item)?;
+    let session = frame_pool.CreateCaptureSession(item)?;
 
-    // Disable cursor capture to exclude cursor from screenshots
-    session.SetIsCursorCaptureEnabled(false)?;
+    // Exclude the cursor (unless `capture_cursor`) and the capture border
+    configure_session(&session)?;
 
     // Set up frame arrival handling
-    let frame_arrived = Arc::new(AtomicBool::new(false));
+    let frame_arrived = Arc::new(FrameSignal::default());
     let frame_arrived_clone = frame_arrived.clone();
 
     frame_pool.FrameArrived(&TypedEventHandler::new(
         move |_pool: &Option<Direct3D11CaptureFramePool>, _| {
-            frame_arrived_clone.store(true, Ordering::SeqCst);
+            frame_arrived_clone.notify();
             Ok(())
         },
     ))?;
+    timings.setup = phase_start.elapsed();
 
     // Start capture
+    let phase_start = std::time::Instant::now();
     session.StartCapture()?;
 
-    // Wait for frame
-    let start = std::time::Instant::now();
-    while !frame_arrived.load(Ordering::SeqCst) {
-        if start.elapsed().as_secs() > 5 {
-            return Err(anyhow!("Timeout waiting for frame"));
+    // Wait for the frame (recreating the pool once if the size changed) and
+    // convert it; on failure close the session and pool now
+    let img = next_frame(&frame_pool, &d3d_device, size, &frame_arrived).and_then(|frame| {
+        timings.frame_wait = phase_start.elapsed();
+        frame_to_rgba(&device, &context, &frame, crop, &mut timings)
+    });
+    let img = match img {
+        Ok(img) => img,
+        Err(e) => {
+            let _ = session.Close();
+            let _ = frame_pool.Close();
+            return Err(e);
         }
-        std::thread::sleep(std::time::Duration::from_millis(10));
-    }
+    };
 
-    // Get the frame
-    let frame = frame_pool.TryGetNextFrame()?;
+    // Stop capture
+    session.Close()?;
+    frame_pool.Close()?;
+
+    Ok((img, timings))
+}
+
+/// Copies `frame` into a CPU-readable staging texture and converts the
+/// `crop(frame_width, frame_height)` pixel rect `(x, y, width, height)` of it
+/// to RGBA (see `mapped_to_rgba`). Adds the copy/map and conversion times to
+/// `timings`.
+pub(super) fn frame_to_rgba(
+    device: &ID3D11Device,
+    context: &ID3D11DeviceContext,
+    frame: &Direct3D11CaptureFrame,
+    crop: impl FnOnce(u32, u32) -> (u32, u32, u32, u32),
+    timings: &mut CaptureTimings,
+) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>> {
+    let phase_start = std::time::Instant::now();
     let surface = frame.Surface()?;
 
     // Get the D3D11 texture from the surface
@@ -353,81 +469,49 @@ pub fn capture_gakumas_to_buffer(hwnd: HWND) -> Result<ImageBuffer<Rgba<u8>, Vec
         device.CreateTexture2D(&staging_desc, None, Some(&mut staging))?;
         staging.ok_or_else(|| anyhow!("Failed to create staging texture"))?
     };
+    let staging_resource = staging_texture.cast::<ID3D11Resource>()?;
 
-    // Copy to staging texture
-    unsafe {
-        context.CopyResource(
-            &staging_texture.cast::<ID3D11Resource>()?,
-            &texture.cast::<ID3D11Resource>()?,
-        );
-    }
-
-    // Map the staging texture
+    // Copy to staging texture and map it
     let mapped = unsafe {
+        context.CopyResource(&staging_resource, &texture.cast::<ID3D11Resource>()?);
         let mut mapped = Default::default();
-        context.Map(
-            &staging_texture.cast::<ID3D11Resource>()?,
-            0,
-            D3D11_MAP_READ,
-            0,
-            Some(&mut mapped),
-        )?;
+        context.Map(&staging_resource, 0, D3D11_MAP_READ, 0, Some(&mut mapped))?;
         mapped
     };
+    timings.copy_map += phase_start.elapsed();
 
-    // Calculate crop parameters
-    let crop_x = client_offset.x as u32;
-    let crop_y = client_offset.y as u32;
-    let crop_width = client_width as u32;
-    let crop_height = client_height as u32;
-
-    // Create image from mapped data (cropped to client area)
-    let mut img: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::new(crop_width, crop_height);
-
+    // Create image from mapped data (cropped to the requested rect)
+    let phase_start = std::time::Instant::now();
     let src_data = unsafe {
         std::slice::from_raw_parts(
             mapped.pData as *const u8,
             (mapped.RowPitch * desc.Height) as usize,
         )
     };
-    let row_pitch = mapped.RowPitch as usize;
-
-    for y in 0..crop_height {
-        let src_y = (crop_y + y) as usize;
-        if src_y >= desc.Height as usize {
-            break;
-        }
-        for x in 0..crop_width {
-            let src_x = (crop_x + x) as usize;
-            if src_x >= desc.Width as usize {
-                break;
-            }
-            let offset = src_y * row_pitch + src_x * 4;
-            // BGRA -> RGBA
-            let b = src_data[offset];
-            let g = src_data[offset + 1];
-            let r = src_data[offset + 2];
-            let a = src_data[offset + 3];
-            img.put_pixel(x, y, Rgba([r, g, b, a]));
-        }
-    }
+    let mapped_frame = MappedFrame {
+        data: src_data,
+        row_pitch: mapped.RowPitch as usize,
+        width: desc.Width,
+        height: desc.Height,
+    };
+    let img = mapped_to_rgba(
+        &mapped_frame,
+        desc.Format,
+        crop(desc.Width, desc.Height),
+        crate::automation::get_config().force_opaque,
+    );
+    timings.convert += phase_start.elapsed();
 
     // Unmap
-    unsafe {
-        context.Unmap(&staging_texture.cast::<ID3D11Resource>()?, 0);
-    }
-
-    // Stop capture
-    session.Close()?;
-    frame_pool.Close()?;
-
-    Ok(img)
+    unsafe { context.Unmap(&staging_resource, 0) };
+    img
 }
 
 /// Creates a GraphicsCaptureItem for the specified window.
 ///
 /// The capture item represents the window that will be captured.
-fn create_capture_item(hwnd: HWND) -> Result<GraphicsCaptureItem> {
+pub(super) fn create_capture_item(hwnd: HWND) -> Result<GraphicsCaptureItem> {
+    super::apartment::ensure_thread_initialized();
     let class_name = windows::core::h!("Windows.Graphics.Capture.GraphicsCaptureItem");
     crate::log("Getting activation factory...");
     let interop: IGraphicsCaptureItemInterop = unsafe {
@@ -443,3 +527,391 @@ fn create_capture_item(hwnd: HWND) -> Result<GraphicsCaptureItem> {
             .context("Failed to create capture item for window")
     }
 }
+
+/// Delay between frames grabbed by `capture_stable_frame`, roughly two frames
+/// at 60fps so consecutive captures land on different animation phases.
+const STABLE_FRAME_INTERVAL_MS: u64 = 33;
+
+/// Captures `n` consecutive frames and returns their per-pixel median.
+///
+/// The result screen has shimmer/particle animations that briefly cover the
+/// score digits; a pixel disturbed in only a minority of frames takes its
+/// steady value in the median. `n <= 1` is a plain single capture. If the
+/// window is resized mid-burst the frames cannot be combined, so the latest
+/// frame is returned as-is. Frames come from `capture_game_frame`, so the
+/// configured `capture_source` applies.
+///
+/// With `region`, only that part of the frame is captured
+/// (`capture_game_region`), which skips converting the rest of the window on
+/// every frame.
+pub fn capture_stable_frame(
+    hwnd: HWND,
+    n: u32,
+    region: Option<&RelativeRect>,
+) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>> {
+    let capture_once = |hwnd| match region {
+        Some(region) => capture_game_region(hwnd, region),
+        None => capture_game_frame(hwnd),
+    };
+    if n <= 1 {
+        return capture_once(hwnd);
+    }
+
+    let mut frames = Vec::with_capacity(n as usize);
+    for i in 0..n {
+        if i > 0 {
+            std::thread::sleep(std::time::Duration::from_millis(STABLE_FRAME_INTERVAL_MS));
+        }
+        frames.push(capture_once(hwnd)?);
+    }
+
+    match median_frames(&frames) {
+        Some(img) => Ok(img),
+        None => {
+            crate::log("Warning: Frame sizes changed during capture; using the last frame");
+            Ok(frames.pop().expect("n > 1 frames captured"))
+        }
+    }
+}
+
+/// A CPU-mapped BGRA texture: `height` rows of `row_pitch` bytes each, of
+/// which the first `width * 4` are pixels.
+pub(super) struct MappedFrame<'a> {
+    pub data: &'a [u8],
+    pub row_pitch: usize,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Copies the `(x, y, width, height)` crop of a mapped BGRA frame into an
+/// RGBA image. Crop pixels that fall outside the frame stay transparent black.
+///
+/// Windows Graphics Capture does not promise a meaningful alpha channel:
+/// opaque window content often arrives with alpha 0, and copying that byte
+/// through produces PNGs some viewers draw as fully transparent. With
+/// `force_opaque` (the config option, on by default) every pixel gets alpha
+/// 255; without it the captured alpha is kept as-is.
+///
+/// Works a row at a time on slices of the source and of the image's backing
+/// buffer, so the inner loop is a plain byte swizzle without per-pixel bounds
+/// checks or `put_pixel` calls.
+pub(super) fn bgra_to_rgba(
+    frame: &MappedFrame,
+    crop: (u32, u32, u32, u32),
+    force_opaque: bool,
+) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+    let (crop_x, crop_y, width, height) = crop;
+    if width == 0 || height == 0 {
+        return ImageBuffer::new(width, height);
+    }
+    let mut raw = vec![0u8; width as usize * height as usize * 4];
+
+    // Rows and columns of the crop that lie inside the frame
+    let rows = frame.height.saturating_sub(crop_y).min(height) as usize;
+    let cols = frame.width.saturating_sub(crop_x).min(width) as usize;
+    if rows == 0 || cols == 0 {
+        // Entirely outside the frame, where row offsets would overrun `data`
+        return ImageBuffer::from_raw(width, height, raw).expect("buffer sized for the crop");
+    }
+
+    let dst_stride = width as usize * 4;
+    for (y, dst_row) in raw.chunks_exact_mut(dst_stride).take(rows).enumerate() {
+        let start = (crop_y as usize + y) * frame.row_pitch + crop_x as usize * 4;
+        let src_row = &frame.data[start..start + cols * 4];
+        for (dst, src) in dst_row[..cols * 4].chunks_exact_mut(4).zip(src_row.chunks_exact(4)) {
+            // BGRA -> RGBA
+            dst[0] = src[2];
+            dst[1] = src[1];
+            dst[2] = src[0];
+            dst[3] = if force_opaque { 255 } else { src[3] };
+        }
+    }
+
+    ImageBuffer::from_raw(width, height, raw).expect("buffer sized for the crop")
+}
+
+/// Converts the `(x, y, width, height)` crop of a mapped frame to 8-bit RGBA
+/// according to the texture's actual `format`, rather than assuming BGRA.
+///
+/// 8-bit BGRA goes through `bgra_to_rgba`; 16-bit float RGBA through
+/// `rgba16f_to_rgba`. Any other format is an error, since reading it as
+/// 4-byte BGRA would produce a garbled image without complaint.
+pub(super) fn mapped_to_rgba(
+    frame: &MappedFrame,
+    format: DXGI_FORMAT,
+    crop: (u32, u32, u32, u32),
+    force_opaque: bool,
+) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>> {
+    match format {
+        DXGI_FORMAT_B8G8R8A8_UNORM => Ok(bgra_to_rgba(frame, crop, force_opaque)),
+        DXGI_FORMAT_R16G16B16A16_FLOAT => Ok(rgba16f_to_rgba(frame, crop, force_opaque)),
+        other => Err(anyhow!(
+            "Unsupported capture pixel format (DXGI_FORMAT {}); expected B8G8R8A8_UNORM or \
+             R16G16B16A16_FLOAT",
+            other.0
+        )),
+    }
+}
+
+/// Copies the crop of a mapped 16-bit float RGBA frame (8 bytes per pixel,
+/// linear scRGB) into an 8-bit sRGB image. Values above SDR white (1.0) are
+/// clipped; crop pixels outside the frame stay transparent black, and alpha
+/// follows `force_opaque` like `bgra_to_rgba`.
+pub(super) fn rgba16f_to_rgba(
+    frame: &MappedFrame,
+    crop: (u32, u32, u32, u32),
+    force_opaque: bool,
+) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+    let (crop_x, crop_y, width, height) = crop;
+    if width == 0 || height == 0 {
+        return ImageBuffer::new(width, height);
+    }
+    let mut raw = vec![0u8; width as usize * height as usize * 4];
+
+    let rows = frame.height.saturating_sub(crop_y).min(height) as usize;
+    let cols = frame.width.saturating_sub(crop_x).min(width) as usize;
+    if rows == 0 || cols == 0 {
+        return ImageBuffer::from_raw(width, height, raw).expect("buffer sized for the crop");
+    }
+
+    let dst_stride = width as usize * 4;
+    for (y, dst_row) in raw.chunks_exact_mut(dst_stride).take(rows).enumerate() {
+        let start = (crop_y as usize + y) * frame.row_pitch + crop_x as usize * 8;
+        let src_row = &frame.data[start..start + cols * 8];
+        for (dst, src) in dst_row[..cols * 4].chunks_exact_mut(4).zip(src_row.chunks_exact(8)) {
+            let channel = |i: usize| f16_to_f32(u16::from_le_bytes([src[2 * i], src[2 * i + 1]]));
+            dst[0] = linear_to_srgb8(channel(0));
+            dst[1] = linear_to_srgb8(channel(1));
+            dst[2] = linear_to_srgb8(channel(2));
+            dst[3] = if force_opaque {
+                255
+            } else {
+                (channel(3).clamp(0.0, 1.0) * 255.0).round() as u8
+            };
+        }
+    }
+
+    ImageBuffer::from_raw(width, height, raw).expect("buffer sized for the crop")
+}
+
+/// IEEE 754 half-precision bits to `f32`.
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1F) as i32;
+    let fraction = (bits & 0x3FF) as f32;
+    match exponent {
+        0 => sign * fraction * 2f32.powi(-24),
+        0x1F if fraction == 0.0 => sign * f32::INFINITY,
+        0x1F => f32::NAN,
+        _ => sign * (1.0 + fraction / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
+
+/// Linear light (1.0 = SDR white) to an 8-bit sRGB value, clipped to 0..=255.
+fn linear_to_srgb8(linear: f32) -> u8 {
+    let c = linear.clamp(0.0, 1.0);
+    let encoded = if c <= 0.003_130_8 {
+        12.92 * c
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (encoded * 255.0).round() as u8
+}
+
+/// Per-pixel, per-channel median of equally sized frames.
+///
+/// Returns `None` when `frames` is empty or the frames differ in size. For an
+/// even count the two middle values are averaged.
+pub fn median_frames(
+    frames: &[ImageBuffer<Rgba<u8>, Vec<u8>>],
+) -> Option<ImageBuffer<Rgba<u8>, Vec<u8>>> {
+    let first = frames.first()?;
+    let (w, h) = first.dimensions();
+    if frames.iter().any(|f| f.dimensions() != (w, h)) {
+        return None;
+    }
+
+    let n = frames.len();
+    let mut values = vec![0u8; n];
+    let mut raw = vec![0u8; first.as_raw().len()];
+    for (i, out) in raw.iter_mut().enumerate() {
+        for (v, frame) in values.iter_mut().zip(frames) {
+            *v = frame.as_raw()[i];
+        }
+        values.sort_unstable();
+        *out = if n % 2 == 1 {
+            values[n / 2]
+        } else {
+            ((values[n / 2 - 1] as u16 + values[n / 2] as u16) / 2) as u8
+        };
+    }
+    ImageBuffer::from_raw(w, h, raw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_signal_wakes_waiter() {
+        for event_driven in [true, false] {
+            let signal = Arc::new(FrameSignal::default());
+            let notifier = Arc::clone(&signal);
+            let handle = std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(20));
+                notifier.notify();
+            });
+            assert!(signal.wait(Duration::from_secs(5), event_driven));
+            handle.join().unwrap();
+            // Waiting leaves the flag set for the caller to clear
+            assert!(signal.take());
+            assert!(!signal.take());
+        }
+    }
+
+    #[test]
+    fn test_frame_signal_times_out() {
+        for event_driven in [true, false] {
+            let signal = FrameSignal::default();
+            let start = Instant::now();
+            assert!(!signal.wait(Duration::from_millis(30), event_driven));
+            assert!(start.elapsed() >= Duration::from_millis(30));
+        }
+    }
+
+    /// 3x2 BGRA frame with 4 bytes of row padding; pixel (x, y) is
+    /// B = 10x + y, G = 100, R = 200, A = 0.
+    fn mapped_test_frame() -> Vec<u8> {
+        let mut data = Vec::new();
+        for y in 0..2u8 {
+            for x in 0..3u8 {
+                data.extend_from_slice(&[10 * x + y, 100, 200, 0]);
+            }
+            data.extend_from_slice(&[0xEE; 4]);
+        }
+        data
+    }
+
+    #[test]
+    fn test_bgra_to_rgba_crops_and_swaps_channels() {
+        let data = mapped_test_frame();
+        let frame = MappedFrame { data: &data, row_pitch: 16, width: 3, height: 2 };
+
+        let img = bgra_to_rgba(&frame, (1, 0, 2, 2), true);
+        assert_eq!(img.dimensions(), (2, 2));
+        assert_eq!(*img.get_pixel(0, 0), Rgba([200, 100, 10, 255]));
+        assert_eq!(*img.get_pixel(1, 1), Rgba([200, 100, 21, 255]));
+
+        // Captured alpha is kept when not forcing opaque
+        let img = bgra_to_rgba(&frame, (0, 0, 3, 2), false);
+        assert_eq!(*img.get_pixel(2, 0), Rgba([200, 100, 20, 0]));
+    }
+
+    #[test]
+    fn test_bgra_to_rgba_outside_frame_stays_transparent() {
+        let data = mapped_test_frame();
+        let frame = MappedFrame { data: &data, row_pitch: 16, width: 3, height: 2 };
+
+        let img = bgra_to_rgba(&frame, (2, 1, 2, 2), true);
+        assert_eq!(*img.get_pixel(0, 0), Rgba([200, 100, 21, 255]));
+        assert_eq!(*img.get_pixel(1, 0), Rgba([0, 0, 0, 0]));
+        assert_eq!(*img.get_pixel(0, 1), Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn test_bgra_to_rgba_empty_crop() {
+        let data = mapped_test_frame();
+        let frame = MappedFrame { data: &data, row_pitch: 16, width: 3, height: 2 };
+        assert_eq!(bgra_to_rgba(&frame, (0, 0, 0, 2), true).dimensions(), (0, 2));
+        // Entirely outside the frame: all transparent black
+        let img = bgra_to_rgba(&frame, (5, 5, 2, 2), true);
+        assert!(img.pixels().all(|p| *p == Rgba([0, 0, 0, 0])));
+    }
+
+    #[test]
+    fn test_bgra_to_rgba_crop_right_of_frame() {
+        // Rows are inside the frame but no column is; the second row's offset
+        // lies past the end of the data.
+        let data = mapped_test_frame();
+        let frame = MappedFrame { data: &data, row_pitch: 16, width: 3, height: 2 };
+        let img = bgra_to_rgba(&frame, (5, 0, 2, 2), true);
+        assert_eq!(img.dimensions(), (2, 2));
+        assert!(img.pixels().all(|p| *p == Rgba([0, 0, 0, 0])));
+    }
+
+    #[test]
+    fn test_f16_to_f32() {
+        assert_eq!(f16_to_f32(0x0000), 0.0);
+        assert_eq!(f16_to_f32(0x3C00), 1.0);
+        assert_eq!(f16_to_f32(0x3800), 0.5);
+        assert_eq!(f16_to_f32(0xC000), -2.0);
+        assert_eq!(f16_to_f32(0x0001), 2f32.powi(-24));
+        assert_eq!(f16_to_f32(0x7C00), f32::INFINITY);
+    }
+
+    #[test]
+    fn test_rgba16f_to_rgba_encodes_srgb_and_clips_hdr() {
+        // Linear 0.5 at half alpha, then HDR 2.0 (above SDR white) and -1.0
+        let pixels: [[u16; 4]; 2] = [
+            [0x3800, 0x3800, 0x0000, 0x3800],
+            [0x4000, 0x3C00, 0xBC00, 0x3C00],
+        ];
+        let data: Vec<u8> = pixels.iter().flatten().flat_map(|c| c.to_le_bytes()).collect();
+        let frame = MappedFrame { data: &data, row_pitch: 16, width: 2, height: 1 };
+
+        let img = rgba16f_to_rgba(&frame, (0, 0, 2, 1), false);
+        assert_eq!(*img.get_pixel(0, 0), Rgba([188, 188, 0, 128]));
+        assert_eq!(*img.get_pixel(1, 0), Rgba([255, 255, 0, 255]));
+
+        let img = rgba16f_to_rgba(&frame, (0, 0, 2, 1), true);
+        assert_eq!(img.get_pixel(0, 0)[3], 255);
+    }
+
+    #[test]
+    fn test_rgba16f_to_rgba_crop_outside_frame() {
+        let data = [0u8; 16];
+        let frame = MappedFrame { data: &data, row_pitch: 16, width: 2, height: 1 };
+        let img = rgba16f_to_rgba(&frame, (3, 0, 2, 1), true);
+        assert_eq!(img.dimensions(), (2, 1));
+        assert!(img.pixels().all(|p| *p == Rgba([0, 0, 0, 0])));
+        assert_eq!(rgba16f_to_rgba(&frame, (0, 4, 2, 1), true).dimensions(), (2, 1));
+    }
+
+    #[test]
+    fn test_mapped_to_rgba_dispatches_on_format() {
+        let data = mapped_test_frame();
+        let frame = MappedFrame { data: &data, row_pitch: 16, width: 3, height: 2 };
+
+        let img = mapped_to_rgba(&frame, DXGI_FORMAT_B8G8R8A8_UNORM, (0, 0, 3, 2), true).unwrap();
+        assert_eq!(img, bgra_to_rgba(&frame, (0, 0, 3, 2), true));
+
+        let err = mapped_to_rgba(&frame, DXGI_FORMAT(2), (0, 0, 3, 2), true).unwrap_err();
+        assert!(err.to_string().contains("Unsupported capture pixel format"), "{}", err);
+    }
+
+    #[test]
+    fn test_median_frames_drops_transient_pixel() {
+        let steady: ImageBuffer<Rgba<u8>, Vec<u8>> =
+            ImageBuffer::from_pixel(4, 4, Rgba([50, 60, 70, 255]));
+        let mut sparkle = steady.clone();
+        sparkle.put_pixel(1, 1, Rgba([255, 255, 255, 255]));
+
+        let median = median_frames(&[steady.clone(), sparkle, steady.clone()]).unwrap();
+        assert_eq!(median, steady, "A one-frame sparkle should be removed");
+    }
+
+    #[test]
+    fn test_median_frames_even_count_and_mismatch() {
+        let a: ImageBuffer<Rgba<u8>, Vec<u8>> =
+            ImageBuffer::from_pixel(2, 2, Rgba([10, 10, 10, 255]));
+        let b: ImageBuffer<Rgba<u8>, Vec<u8>> =
+            ImageBuffer::from_pixel(2, 2, Rgba([20, 20, 20, 255]));
+        let median = median_frames(&[a.clone(), b]).unwrap();
+        assert_eq!(median.get_pixel(0, 0)[0], 15);
+
+        let small: ImageBuffer<Rgba<u8>, Vec<u8>> =
+            ImageBuffer::from_pixel(1, 1, Rgba([0, 0, 0, 255]));
+        assert!(median_frames(&[a, small]).is_none());
+        assert!(median_frames(&[]).is_none());
+    }
+}
@@ -0,0 +1,138 @@
+//! Per-phase timing of window captures, aggregated over an automation run.
+//!
+//! `capture_gakumas_to_buffer` records how long each phase of a capture took
+//! and the automation records its screenshot saves; the runner takes the
+//! totals when a run ends, logs them and writes them to the session manifest.
+
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Totals since the last `take_capture_timing_stats`.
+static CAPTURE_TIMING_STATS: Mutex<CaptureTimingStats> = Mutex::new(CaptureTimingStats::new());
+
+/// Time spent in each phase of one window capture.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CaptureTimings {
+    /// D3D11 device, capture item, frame pool and session creation
+    pub setup: Duration,
+    /// From `StartCapture` until the frame arrived
+    pub frame_wait: Duration,
+    /// Staging texture creation, GPU copy and map
+    pub copy_map: Duration,
+    /// BGRA to RGBA conversion and client-area crop
+    pub convert: Duration,
+}
+
+/// Capture phase totals over a run, in milliseconds.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct CaptureTimingStats {
+    /// Successful window captures timed
+    pub captures: u32,
+    /// Total of `CaptureTimings::setup`
+    pub setup_ms: f64,
+    /// Total of `CaptureTimings::frame_wait`
+    pub frame_wait_ms: f64,
+    /// Total of `CaptureTimings::copy_map`
+    pub copy_map_ms: f64,
+    /// Total of `CaptureTimings::convert`
+    pub convert_ms: f64,
+    /// Screenshot PNG writes timed
+    pub saves: u32,
+    /// Total time spent writing screenshots
+    pub save_ms: f64,
+}
+
+impl CaptureTimingStats {
+    const fn new() -> Self {
+        Self {
+            captures: 0,
+            setup_ms: 0.0,
+            frame_wait_ms: 0.0,
+            copy_map_ms: 0.0,
+            convert_ms: 0.0,
+            saves: 0,
+            save_ms: 0.0,
+        }
+    }
+
+    fn add_capture(&mut self, t: &CaptureTimings) {
+        self.captures += 1;
+        self.setup_ms += millis(t.setup);
+        self.frame_wait_ms += millis(t.frame_wait);
+        self.copy_map_ms += millis(t.copy_map);
+        self.convert_ms += millis(t.convert);
+    }
+
+    fn add_save(&mut self, elapsed: Duration) {
+        self.saves += 1;
+        self.save_ms += millis(elapsed);
+    }
+
+    /// One-line per-capture averages for the log, e.g.
+    /// `120 captures, avg setup 35.1ms, frame wait 61.0ms, ...`.
+    pub fn summary(&self) -> String {
+        let per_capture = |total: f64| total / self.captures.max(1) as f64;
+        format!(
+            "{} captures, avg setup {:.1}ms, frame wait {:.1}ms, copy/map {:.1}ms, \
+             convert {:.1}ms; {} saves, avg save {:.1}ms",
+            self.captures,
+            per_capture(self.setup_ms),
+            per_capture(self.frame_wait_ms),
+            per_capture(self.copy_map_ms),
+            per_capture(self.convert_ms),
+            self.saves,
+            self.save_ms / self.saves.max(1) as f64
+        )
+    }
+}
+
+fn millis(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+/// Adds one successful capture's phase timings to the running totals.
+pub fn record_capture(timings: &CaptureTimings) {
+    CAPTURE_TIMING_STATS.lock().unwrap().add_capture(timings);
+}
+
+/// Adds one screenshot save to the running totals.
+pub fn record_save(elapsed: Duration) {
+    CAPTURE_TIMING_STATS.lock().unwrap().add_save(elapsed);
+}
+
+/// Returns the totals recorded so far and starts over from zero.
+pub fn take_capture_timing_stats() -> CaptureTimingStats {
+    std::mem::take(&mut *CAPTURE_TIMING_STATS.lock().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_sum_phases_and_average_in_summary() {
+        let mut stats = CaptureTimingStats::default();
+        for wait in [40, 80] {
+            stats.add_capture(&CaptureTimings {
+                setup: Duration::from_millis(10),
+                frame_wait: Duration::from_millis(wait),
+                copy_map: Duration::from_millis(4),
+                convert: Duration::from_millis(6),
+            });
+        }
+        stats.add_save(Duration::from_millis(30));
+
+        assert_eq!(stats.captures, 2);
+        assert_eq!(stats.frame_wait_ms, 120.0);
+        let summary = stats.summary();
+        assert!(summary.starts_with("2 captures, avg setup 10.0ms, frame wait 60.0ms"), "{}", summary);
+        assert!(summary.ends_with("1 saves, avg save 30.0ms"), "{}", summary);
+    }
+
+    #[test]
+    fn test_empty_stats_summary_does_not_divide_by_zero() {
+        let summary = CaptureTimingStats::default().summary();
+        assert!(summary.contains("avg setup 0.0ms"), "{}", summary);
+    }
+}
@@ -3,6 +3,8 @@
 use anyhow::{anyhow, Result};
 use std::ffi::OsString;
 use std::os::windows::ffi::OsStringExt;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
 
 use windows::Win32::Foundation::{BOOL, HWND, LPARAM, POINT, RECT, TRUE};
 use windows::Win32::Graphics::Gdi::ClientToScreen;
@@ -14,14 +16,54 @@ use windows::Win32::UI::WindowsAndMessaging::{
     GetWindowThreadProcessId, IsWindowVisible,
 };
 
+use crate::automation::state::ABORT_REQUESTED;
+use crate::error::GakumasError;
+
 /// The exact process name to match (case-insensitive).
 const GAKUMAS_PROCESS_NAME: &str = "gakumas.exe";
 
 /// Finds the main window of gakumas.exe by enumerating all visible windows
 /// and matching the process executable name.
 ///
-/// Returns the window handle (HWND) if found, or an error if the game is not running.
-pub fn find_gakumas_window() -> Result<HWND> {
+/// Returns the window handle (HWND) if found, or `GakumasError::WindowNotFound`
+/// if the game is not running.
+pub fn find_gakumas_window() -> Result<HWND, GakumasError> {
+    find_window(true)
+}
+
+/// Interval between polls in `find_gakumas_window_with_retry`.
+const WINDOW_POLL_INTERVAL_MS: u64 = 500;
+
+/// Like `find_gakumas_window`, but keeps polling every ~500ms until the game
+/// window appears or `timeout_ms` elapses (0 = a single attempt), so automation
+/// can be started while the game is still launching. Only the first attempt
+/// lists every visible window; later polls log one line each. Gives up early
+/// if an abort is requested.
+pub fn find_gakumas_window_with_retry(timeout_ms: u64) -> Result<HWND, GakumasError> {
+    let start = Instant::now();
+    let timeout = Duration::from_millis(timeout_ms);
+    let mut attempt = 1u32;
+    let mut result = find_window(true);
+    while result.is_err() && start.elapsed() < timeout {
+        if ABORT_REQUESTED.load(Ordering::SeqCst) {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(WINDOW_POLL_INTERVAL_MS));
+        attempt += 1;
+        crate::log(&format!(
+            "Waiting for gakumas.exe window (attempt {}, {:.1}s of {:.1}s)...",
+            attempt,
+            start.elapsed().as_secs_f32(),
+            timeout.as_secs_f32()
+        ));
+        result = find_window(false);
+    }
+    result
+}
+
+/// Enumerates visible windows looking for gakumas.exe; `verbose` logs every
+/// window seen (useful once, noise when polling).
+fn find_window(verbose: bool) -> Result<HWND, GakumasError> {
     struct EnumData {
         hwnd: Option<HWND>,
         process_name: Option<String>,
@@ -124,12 +166,14 @@ pub fn find_gakumas_window() -> Result<HWND> {
         }
     }
 
-    crate::log("Searching for gakumas.exe window...");
-    crate::log("Listing visible windows:");
+    if verbose {
+        crate::log("Searching for gakumas.exe window...");
+        crate::log("Listing visible windows:");
+    }
     let mut data = EnumData {
         hwnd: None,
         process_name: None,
-        debug: true,
+        debug: verbose,
     };
     unsafe {
         // Don't use ? here - EnumWindows returns FALSE when callback stops it early,
@@ -141,8 +185,7 @@ pub fn find_gakumas_window() -> Result<HWND> {
         crate::log(&format!("Found process: \"{}\"", name));
     }
 
-    data.hwnd
-        .ok_or_else(|| anyhow!("Could not find gakumas.exe window. Is the game running?"))
+    data.hwnd.ok_or(GakumasError::WindowNotFound)
 }
 
 /// Gets the client area rectangle and its offset relative to the window origin.
@@ -0,0 +1,174 @@
+//! Command-line argument parsing for scripted/headless use.
+//!
+//! With no arguments the app starts normally (GUI, or the tray app in developer
+//! mode). The flags below let a script run a fixed number of iterations and
+//! exit, or regenerate charts for an existing session folder.
+
+use anyhow::{anyhow, Result};
+use std::path::PathBuf;
+
+/// Usage text printed for `--help` and on parse errors.
+pub const USAGE: &str = "\
+Usage: gakumas-rehearsal-automation [OPTIONS]
+
+Options:
+  -n, --iterations <N>   Number of rehearsal runs (pre-fills the GUI count)
+  --no-gui               Run automation headless, exit when it finishes
+  --analyze <DIR>        Generate charts/statistics for a session folder and exit
+  --review <DIR>         Type in the scores of a session's flagged rows and exit
+  --compare <A> <B>      Compare session B with session A (writes comparison.json/png to B)
+  --calibrate            Start the calibration wizard (tray mode)
+  -h, --help             Show this help";
+
+/// Parsed command-line options.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CliArgs {
+    /// `--iterations N`
+    pub iterations: Option<u32>,
+    /// `--analyze <session_dir>`
+    pub analyze: Option<PathBuf>,
+    /// `--review <session_dir>`
+    pub review: Option<PathBuf>,
+    /// `--compare <session_a> <session_b>`
+    pub compare: Option<(PathBuf, PathBuf)>,
+    /// `--calibrate`
+    pub calibrate: bool,
+    /// `--no-gui`
+    pub no_gui: bool,
+    /// `-h` / `--help`
+    pub help: bool,
+}
+
+impl CliArgs {
+    /// Parses arguments (excluding the program name).
+    pub fn parse<I, S>(args: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut out = Self::default();
+        let mut args = args.into_iter().map(Into::into);
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--iterations" | "-n" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| anyhow!("--iterations requires a value"))?;
+                    let n: u32 = value
+                        .parse()
+                        .map_err(|_| anyhow!("Invalid --iterations value: {}", value))?;
+                    if n == 0 {
+                        return Err(anyhow!("--iterations must be at least 1"));
+                    }
+                    out.iterations = Some(n);
+                }
+                "--analyze" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| anyhow!("--analyze requires a session folder"))?;
+                    out.analyze = Some(PathBuf::from(value));
+                }
+                "--review" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| anyhow!("--review requires a session folder"))?;
+                    out.review = Some(PathBuf::from(value));
+                }
+                "--compare" => {
+                    let (Some(a), Some(b)) = (args.next(), args.next()) else {
+                        return Err(anyhow!("--compare requires two session folders"));
+                    };
+                    out.compare = Some((PathBuf::from(a), PathBuf::from(b)));
+                }
+                "--calibrate" => out.calibrate = true,
+                "--no-gui" => out.no_gui = true,
+                "-h" | "--help" => out.help = true,
+                other => return Err(anyhow!("Unknown argument: {}", other)),
+            }
+        }
+
+        let modes = [
+            out.analyze.is_some(),
+            out.review.is_some(),
+            out.compare.is_some(),
+            out.calibrate,
+            out.no_gui,
+        ]
+        .iter()
+        .filter(|&&m| m)
+        .count();
+        if modes > 1 {
+            return Err(anyhow!(
+                "--analyze, --review, --compare, --calibrate and --no-gui cannot be combined"
+            ));
+        }
+        // Only a run (GUI or --no-gui) has a run count
+        let run_mode = modes == 0 || out.no_gui;
+        if out.iterations.is_some() && !run_mode {
+            return Err(anyhow!(
+                "--iterations only applies to the GUI and --no-gui, not --analyze, --review, --compare or --calibrate"
+            ));
+        }
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_empty() {
+        let args = CliArgs::parse(Vec::<String>::new()).unwrap();
+        assert_eq!(args, CliArgs::default());
+    }
+
+    #[test]
+    fn test_parse_headless_run() {
+        let args = CliArgs::parse(["--no-gui", "--iterations", "50"]).unwrap();
+        assert!(args.no_gui);
+        assert_eq!(args.iterations, Some(50));
+    }
+
+    #[test]
+    fn test_parse_analyze() {
+        let args = CliArgs::parse(["--analyze", "output/20260101_120000"]).unwrap();
+        assert_eq!(args.analyze, Some(PathBuf::from("output/20260101_120000")));
+    }
+
+    #[test]
+    fn test_parse_review() {
+        let args = CliArgs::parse(["--review", "output/20260101_120000"]).unwrap();
+        assert_eq!(args.review, Some(PathBuf::from("output/20260101_120000")));
+        assert!(CliArgs::parse(["--review"]).is_err());
+        assert!(CliArgs::parse(["--review", "a", "--analyze", "b"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_compare() {
+        let args = CliArgs::parse(["--compare", "output/a", "output/b"]).unwrap();
+        assert_eq!(args.compare, Some((PathBuf::from("output/a"), PathBuf::from("output/b"))));
+        assert!(CliArgs::parse(["--compare", "output/a"]).is_err());
+        assert!(CliArgs::parse(["--compare", "a", "b", "--no-gui"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(CliArgs::parse(["--iterations"]).is_err());
+        assert!(CliArgs::parse(["--iterations", "abc"]).is_err());
+        assert!(CliArgs::parse(["--iterations", "0"]).is_err());
+        assert!(CliArgs::parse(["--bogus"]).is_err());
+        assert!(CliArgs::parse(["--no-gui", "--calibrate"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_iterations_only_with_a_run() {
+        assert_eq!(CliArgs::parse(["-n", "5"]).unwrap().iterations, Some(5));
+        assert!(CliArgs::parse(["--analyze", "a", "--iterations", "5"]).is_err());
+        assert!(CliArgs::parse(["-n", "5", "--review", "a"]).is_err());
+        assert!(CliArgs::parse(["--compare", "a", "b", "-n", "5"]).is_err());
+        assert!(CliArgs::parse(["--calibrate", "--iterations", "5"]).is_err());
+    }
+}
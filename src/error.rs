@@ -0,0 +1,92 @@
+//! Typed errors for the main capture and OCR failure modes.
+//!
+//! The crate otherwise reports errors through `anyhow`. The entry points whose
+//! callers react differently per failure (`find_gakumas_window`,
+//! `capture_gakumas_to_buffer`, `ocr_screenshot`, and the detection waits for
+//! a timeout or a blocked screen) return `GakumasError`
+//! instead; it still converts into `anyhow::Error` with `?`, and
+//! [`user_message`] finds it again inside an `anyhow::Error` for GUI text.
+
+/// A failure of window discovery, capture, detection or OCR, or a game that
+/// is not ready to play.
+#[derive(Debug, thiserror::Error)]
+pub enum GakumasError {
+    /// No visible window belongs to gakumas.exe
+    #[error("Could not find gakumas.exe window. Is the game running?")]
+    WindowNotFound,
+    /// The capture session started but never delivered a frame
+    #[error("Timeout waiting for frame")]
+    CaptureTimeout,
+    /// Any other capture failure (device, session, texture copy, ...)
+    #[error("{0:#}")]
+    Capture(anyhow::Error),
+    /// Tesseract or score parsing failed
+    #[error("OCR failed: {0:#}")]
+    Ocr(anyhow::Error),
+    /// A detection wait gave up before `what` appeared on screen
+    #[error("Timeout waiting for {what} after {timeout_ms}ms")]
+    DetectionTimeout { what: &'static str, timeout_ms: u64 },
+    /// The game showed `blocked_screen_reference` (maintenance or update
+    /// notice) instead of the rehearsal page
+    #[error("Game not ready (maintenance/update?)")]
+    GameNotReady,
+}
+
+impl GakumasError {
+    /// Japanese explanation shown in the GUI.
+    pub fn user_message(&self) -> String {
+        match self {
+            Self::WindowNotFound => "ゲームが見つかりません。学マスを起動してください".to_string(),
+            Self::CaptureTimeout => {
+                "ゲーム画面を取得できませんでした（タイムアウト）。ゲームが最小化されていないか確認してください"
+                    .to_string()
+            }
+            Self::Capture(e) => format!("キャプチャに失敗しました: {:#}", e),
+            Self::Ocr(e) => format!("スコアを読み取れませんでした: {:#}", e),
+            Self::DetectionTimeout { what, timeout_ms } => {
+                format!("画面の検出がタイムアウトしました（{}、{}ms）", what, timeout_ms)
+            }
+            Self::GameNotReady => {
+                "メンテナンスまたはアップデートの画面が表示されています".to_string()
+            }
+        }
+    }
+}
+
+/// GUI text for `e`: the `GakumasError` explanation if one is in its chain,
+/// else the error's own message.
+pub fn user_message(e: &anyhow::Error) -> String {
+    match e.downcast_ref::<GakumasError>() {
+        Some(err) => err.user_message(),
+        None => e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn user_message_finds_variant_behind_context() {
+        let result: anyhow::Result<()> =
+            Err(GakumasError::WindowNotFound).context("Failed to find game window");
+        let e = result.unwrap_err();
+        assert_eq!(user_message(&e), "ゲームが見つかりません。学マスを起動してください");
+        assert!(matches!(e.downcast_ref(), Some(GakumasError::WindowNotFound)));
+    }
+
+    #[test]
+    fn detection_timeout_keeps_log_text() {
+        let e: anyhow::Error =
+            GakumasError::DetectionTimeout { what: "result page", timeout_ms: 30_000 }.into();
+        assert_eq!(e.to_string(), "Timeout waiting for result page after 30000ms");
+        assert!(matches!(e.downcast_ref(), Some(GakumasError::DetectionTimeout { .. })));
+    }
+
+    #[test]
+    fn user_message_falls_back_to_error_text() {
+        let e = anyhow::anyhow!("Automation is already running");
+        assert_eq!(user_message(&e), "Automation is already running");
+    }
+}
@@ -2,11 +2,12 @@
 //!
 //! Provides a graphical interface using egui/eframe for user interaction.
 
+pub mod monitor;
 pub mod render;
 pub mod state;
 
-use std::sync::atomic::{AtomicI32, Ordering};
-use std::sync::{Arc, OnceLock};
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::Instant;
 
 use eframe::egui::{self, TextureHandle, Vec2};
@@ -20,24 +21,48 @@ use crate::automation::runner::{
     AutomationOutcome,
 };
 use crate::automation::results_edit::{
-    load_review_rows, save_review_rows, ReviewRow, RECOVERY_MANUAL, RECOVERY_VERIFIED,
+    load_review_rows, save_review_rows, ReviewRow, RECOVERY_FLAGGED, RECOVERY_MANUAL,
+    RECOVERY_VERIFIED,
 };
-use crate::automation::state::request_abort;
+use crate::automation::state::{request_abort, request_stop_after_current};
+use crate::calibration::CalibrationStep;
 
-use render::ReviewActions;
-use state::{AutomationStatus, GuiState, ReviewState};
+use render::{ReferenceKind, ReviewActions};
+use state::{AutomationStatus, GuiState, ReviewState, TimedCaptureStatus};
 
 /// Menu item IDs for tray menu
 const MENU_SHOW_WINDOW: &str = "show_window";
+const MENU_SCREENSHOT: &str = "screenshot";
+const MENU_TOGGLE_AUTOMATION: &str = "toggle_automation";
+const MENU_CALIBRATE: &str = "calibrate";
 const MENU_EXIT: &str = "exit";
 
-/// Hotkey IDs
-const HOTKEY_SCREENSHOT: i32 = 101;
-const HOTKEY_ABORT: i32 = 102;
+/// Hotkey IDs (clear of the calibration wizard's 100..=106, which are
+/// registered on the same hotkey window while calibrating)
+const HOTKEY_SCREENSHOT: i32 = 201;
+const HOTKEY_ABORT: i32 = 202;
+const HOTKEY_DEBUG_OCR: i32 = 203;
+const HOTKEY_CLIP: i32 = 204;
 
 /// Global hotkey event signal (set by hotkey thread, read by GUI thread)
 static HOTKEY_TRIGGERED: AtomicI32 = AtomicI32::new(0);
 
+/// Set by the tray's キャリブレーション item; the hotkey thread starts the
+/// wizard, since its hotkeys must be registered on a window that thread owns.
+static CALIBRATION_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Set while a GIF clip is being recorded, so a repeated hotkey press doesn't
+/// start a second capture session alongside it.
+static CLIP_RECORDING: AtomicBool = AtomicBool::new(false);
+
+/// Set by the 項目別キャリブレーション buttons; like `CALIBRATION_REQUESTED`,
+/// the hotkey thread starts the wizard for just this item.
+static CALIBRATION_ITEM_REQUESTED: Mutex<Option<CalibrationStep>> = Mutex::new(None);
+
+/// Set by 参照画像 → まとめて撮影; the hotkey thread starts the guided
+/// reference capture, which borrows the calibration hotkeys.
+static REFERENCE_WIZARD_REQUESTED: AtomicBool = AtomicBool::new(false);
+
 /// egui context shared with the hotkey thread. eframe only runs `update()` when
 /// the window is focused/repainting, so a hotkey pressed while the window is in
 /// the background would sit queued until the window came to front. The hotkey
@@ -60,6 +85,21 @@ const LIVE_PLOT_PANEL_WIDTH: f32 = 760.0;
 /// Width of the left guide-image side panel.
 const GUIDE_PANEL_WIDTH: f32 = 300.0;
 
+/// Repaint cadence while automation runs (progress, ETA, state text). When not
+/// running the GUI repaints only on input, tray/hotkey wake-ups and the
+/// detection monitor.
+const RUNNING_REPAINT_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Hotkey thread sleep between empty `PeekMessage` polls. Starts at the
+/// minimum after any message or request and doubles up to the maximum while
+/// nothing happens; calibration keeps it at the minimum.
+const HOTKEY_POLL_MIN: std::time::Duration = std::time::Duration::from_millis(50);
+const HOTKEY_POLL_MAX: std::time::Duration = std::time::Duration::from_millis(400);
+
+/// How long the process must stay idle (no automation) before its average CPU
+/// usage over that stretch is logged.
+const IDLE_CPU_LOG_INTERVAL: std::time::Duration = std::time::Duration::from_secs(300);
+
 /// Persisted GUI preferences, stored as `gui_settings.json` next to the executable
 /// (consistent with the app's other portable config files). Currently just the
 /// live-distribution toggle. Defaults to on.
@@ -74,9 +114,9 @@ impl Default for GuiSettings {
     }
 }
 
-/// Path to the persisted GUI settings file (next to the executable).
+/// Path to the persisted GUI settings file (in the data directory).
 fn gui_settings_path() -> std::path::PathBuf {
-    crate::paths::get_exe_dir().join("gui_settings.json")
+    crate::paths::resolve_data_file("gui_settings.json")
 }
 
 /// Loads GUI settings, returning defaults if the file is missing or unreadable.
@@ -122,6 +162,9 @@ pub struct GuiApp {
     /// is unchanged. Set after a review save so manual corrections / verifications
     /// (which can change values or flags without changing the row count) are reflected.
     live_chart_dirty: bool,
+    /// `chart_config.json`, loaded at startup, whose palette the live figure uses
+    /// so it matches the generated charts.
+    live_chart_config: crate::analysis::ChartConfig,
     /// Whether the window is currently expanded to make room for the live plot
     /// side panel. Used to resize once on show/hide rather than every frame.
     live_chart_expanded: bool,
@@ -131,15 +174,32 @@ pub struct GuiApp {
     /// Tray icon (kept alive for the duration of the app).
     #[allow(dead_code)]
     tray_icon: Option<TrayIcon>,
-    /// Menu event receiver for tray menu (uses crossbeam-channel from tray-icon).
-    menu_event_receiver: Option<tray_icon::menu::MenuEventReceiver>,
+    /// Menu event receiver for tray menu (fed by the handler installed in
+    /// `setup_tray_icon`).
+    menu_event_receiver: Option<std::sync::mpsc::Receiver<MenuEvent>>,
     /// Flag to request exit from tray menu.
     exit_requested: bool,
+    /// Running 検出モニター thread, present while the monitor is enabled and idle.
+    detection_monitor: Option<monitor::DetectionMonitor>,
+    /// Running 定期キャプチャ, present from its start until stopped or a run starts.
+    timed_capture: Option<crate::automation::TimedCapture>,
+    /// A テスト実行 is in progress; its results are opened when it finishes.
+    test_run_pending: bool,
+    /// Running OCRテスト capture, polled each frame until it finishes.
+    ocr_test: Option<std::thread::JoinHandle<Option<[[u32; 3]; 3]>>>,
+    /// start_minimized without a tray icon: minimize on the first frame
+    /// instead of hiding, so the taskbar can bring the window back.
+    minimize_on_start: bool,
 }
 
 impl GuiApp {
-    /// Create a new GUI application instance.
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+    /// Create a new GUI application instance around the tray icon made by
+    /// `setup_tray_icon`.
+    pub fn new(
+        cc: &eframe::CreationContext<'_>,
+        tray_icon: Option<TrayIcon>,
+        menu_event_receiver: Option<std::sync::mpsc::Receiver<MenuEvent>>,
+    ) -> Self {
         // Configure fonts to support Japanese
         Self::setup_fonts(&cc.egui_ctx);
 
@@ -148,9 +208,6 @@ impl GuiApp {
         // window is not focused).
         let _ = EGUI_CTX.set(cc.egui_ctx.clone());
 
-        // Set up tray icon
-        let (tray_icon, menu_event_receiver) = Self::setup_tray_icon();
-
         // Restore the persisted live-distribution preference (default on).
         let settings = load_gui_settings();
         let mut state = GuiState::default();
@@ -166,6 +223,9 @@ impl GuiApp {
             live_chart_total: 0,
             live_chart_excluded: 0,
             live_chart_dirty: false,
+            live_chart_config: crate::analysis::ChartConfig::load(
+                &crate::paths::resolve_data_file("chart_config.json"),
+            ),
             // Seed to match the persisted preference so the initial viewport size
             // (chosen in run_gui) is not resized on the first frame.
             live_chart_expanded: settings.show_live_chart,
@@ -173,6 +233,11 @@ impl GuiApp {
             tray_icon,
             menu_event_receiver,
             exit_requested: false,
+            detection_monitor: None,
+            timed_capture: None,
+            test_run_pending: false,
+            ocr_test: None,
+            minimize_on_start: false,
         };
         // Populate the resume picker with interrupted sessions found on disk.
         app.scan_resumable_sessions();
@@ -194,17 +259,21 @@ impl GuiApp {
     }
 
     /// Set up the system tray icon with menu.
-    fn setup_tray_icon() -> (Option<TrayIcon>, Option<tray_icon::menu::MenuEventReceiver>) {
+    fn setup_tray_icon() -> (Option<TrayIcon>, Option<std::sync::mpsc::Receiver<MenuEvent>>) {
         // Create menu
         let menu = Menu::new();
-        let show_item = MenuItem::with_id(MENU_SHOW_WINDOW, "ウィンドウを表示", true, None);
-        let exit_item = MenuItem::with_id(MENU_EXIT, "終了", true, None);
+        let items = [
+            MenuItem::with_id(MENU_SHOW_WINDOW, "ウィンドウを表示", true, None),
+            MenuItem::with_id(MENU_SCREENSHOT, "スクリーンショット", true, None),
+            MenuItem::with_id(MENU_TOGGLE_AUTOMATION, "自動化開始/停止", true, None),
+            MenuItem::with_id(MENU_CALIBRATE, "キャリブレーション", true, None),
+            MenuItem::with_id(MENU_EXIT, "終了", true, None),
+        ];
 
-        if let Err(e) = menu.append(&show_item) {
-            crate::log(&format!("Failed to add show menu item: {}", e));
-        }
-        if let Err(e) = menu.append(&exit_item) {
-            crate::log(&format!("Failed to add exit menu item: {}", e));
+        for item in &items {
+            if let Err(e) = menu.append(item) {
+                crate::log(&format!("Failed to add tray menu item '{}': {}", item.text(), e));
+            }
         }
 
         // Create tray icon with default Windows icon
@@ -223,10 +292,18 @@ impl GuiApp {
             }
         };
 
-        // Get the menu event receiver
-        let menu_event_receiver = Some(MenuEvent::receiver().clone());
+        // Forward menu events through our own channel so each one also wakes the
+        // event loop: while the window is hidden to the tray, update() would
+        // otherwise not run to see them.
+        let (sender, receiver) = std::sync::mpsc::channel();
+        MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+            let _ = sender.send(event);
+            if let Some(ctx) = EGUI_CTX.get() {
+                ctx.request_repaint();
+            }
+        }));
 
-        (tray_icon, menu_event_receiver)
+        (tray_icon, Some(receiver))
     }
 
     /// Setup fonts with Japanese support.
@@ -340,7 +417,7 @@ impl GuiApp {
         let excluded = rows.len() - included.len();
         let stats = crate::analysis::statistics::DataSetStats::from_score_rows(&included);
 
-        match crate::analysis::charts::render_live_box_plot_rgba(&stats) {
+        match crate::analysis::charts::render_live_box_plot_rgba(&stats, &self.live_chart_config) {
             Ok((w, h, rgba)) => {
                 let color =
                     egui::ColorImage::from_rgba_unmultiplied([w as usize, h as usize], &rgba);
@@ -368,7 +445,7 @@ impl GuiApp {
                     // Automation finished - resolve the real outcome (success vs
                     // timeout/error vs abort) reported by the runner.
                     let session_path = crate::automation::runner::get_current_session_path()
-                        .unwrap_or_else(|| crate::paths::get_output_dir());
+                        .unwrap_or_else(crate::paths::get_session_output_dir);
                     self.state.latest_session_path = Some(session_path.clone());
 
                     self.state.status =
@@ -380,10 +457,14 @@ impl GuiApp {
                     // The just-finished session should immediately appear in (or
                     // drop out of) the resume picker.
                     self.scan_resumable_sessions();
+                    if std::mem::take(&mut self.test_run_pending) {
+                        self.show_test_run_results(&session_path);
+                    }
                 } else {
                     // Still running - update progress
                     let current = crate::automation::runner::get_current_iteration();
                     let state_desc = crate::automation::runner::get_current_state_description();
+                    self.state.eta.observe(*start_time, current, Instant::now());
                     self.state.status = AutomationStatus::Running {
                         current,
                         total: *total,
@@ -433,9 +514,12 @@ impl GuiApp {
 
         // Generate charts whenever there is captured data to analyze, even on a
         // partial (timeout/abort) run, so the user still gets stats for what ran.
-        if completed > 0 {
+        // Capture-only runs have no OCR results, so there is nothing to chart.
+        if !crate::automation::runner::is_ocr_enabled() {
+            crate::log("GUI: Capture-only run, skipping chart generation");
+        } else if completed > 0 {
             crate::log("GUI: Auto-generating charts...");
-            match crate::analysis::generate_analysis_for_session(&session_path) {
+            match crate::analysis::generate_analysis_for_session(&session_path, None) {
                 Ok((chart_paths, json_path)) => {
                     crate::log(&format!(
                         "GUI: Charts generated: {} files, stats: {}",
@@ -474,29 +558,75 @@ impl GuiApp {
         let iterations = self.state.iterations;
 
         // Start automation (runner creates session folder internally)
-        match start_automation(Some(iterations)) {
+        match start_automation(Some(iterations), self.state.run_ocr, None) {
             Ok(()) => {
-                // Get session path from runner
-                self.state.latest_session_path = crate::automation::runner::get_current_session_path();
-
-                self.state.status = AutomationStatus::Running {
-                    current: 0,
-                    total: iterations,
-                    state_description: "開始中...".to_string(),
-                    start_time: Instant::now(),
-                };
-                self.state.automation_start_time = Some(Instant::now());
-                crate::log(&format!("GUI: Started automation with {} iterations", iterations));
+                self.enter_running(iterations);
+                crate::log(&format!(
+                    "GUI: Started automation with {} iterations{}",
+                    iterations,
+                    if self.state.run_ocr { "" } else { " (capture only)" }
+                ));
             }
-            Err(e) => {
-                self.state.status = AutomationStatus::Error {
-                    completed: 0,
-                    total: iterations,
-                    message: e.to_string(),
-                    session_path: None,
-                };
-                crate::log(&format!("GUI: Failed to start automation: {}", e));
+            Err(e) => self.enter_start_error(iterations, &e),
+        }
+    }
+
+    /// Handle テスト実行 (1回) click: one iteration with OCR debug crops, whose
+    /// screenshot and scores are opened once it finishes.
+    fn handle_test_run(&mut self) {
+        match crate::automation::runner::start_test_run() {
+            Ok(()) => {
+                self.enter_running(1);
+                self.test_run_pending = true;
+                crate::log("GUI: Started test run (1 iteration)");
             }
+            Err(e) => self.enter_start_error(1, &e),
+        }
+    }
+
+    /// Switch to Running for a just-started fresh run of `total` iterations.
+    fn enter_running(&mut self, total: u32) {
+        // Get session path from runner
+        self.state.latest_session_path = crate::automation::runner::get_current_session_path();
+
+        self.state.status = AutomationStatus::Running {
+            current: 0,
+            total,
+            state_description: "開始中...".to_string(),
+            start_time: Instant::now(),
+        };
+        self.state.automation_start_time = Some(Instant::now());
+    }
+
+    /// Show why a fresh run of `total` iterations failed to start.
+    fn enter_start_error(&mut self, total: u32, e: &anyhow::Error) {
+        self.state.status = AutomationStatus::Error {
+            completed: 0,
+            total,
+            message: crate::error::user_message(e),
+            session_path: None,
+        };
+        crate::log(&format!("GUI: Failed to start automation: {}", e));
+    }
+
+    /// After a test run: open its screenshot, log its scores and show them in
+    /// the review window (all rows, since a clean row is hidden by default).
+    fn show_test_run_results(&mut self, session_path: &std::path::Path) {
+        let row = load_review_rows(session_path).ok().and_then(|rows| rows.into_iter().next());
+        let Some(row) = row else {
+            crate::log("GUI: Test run produced no OCR result; see session.log");
+            return;
+        };
+        crate::log(&format!(
+            "GUI: Test run scores: {:?} (recovery: {}, totals: {:?})",
+            row.scores, row.recovery, row.stage_totals
+        ));
+        if let Err(e) = std::process::Command::new("explorer").arg(&row.screenshot).spawn() {
+            crate::log(&format!("GUI: Failed to open screenshot: {}", e));
+        }
+        self.handle_open_review();
+        if let Some(review) = &mut self.state.review {
+            review.show_all = true;
         }
     }
 
@@ -506,6 +636,12 @@ impl GuiApp {
         crate::log("GUI: Requested automation abort");
     }
 
+    /// Handle "この周回で停止" click: finish the current iteration, then complete.
+    fn handle_stop_after_current(&mut self) {
+        request_stop_after_current();
+        crate::log("GUI: Requested stop after the current iteration");
+    }
+
     /// Handle "続行" (continue) button click — resumes the in-memory interrupted run.
     fn handle_continue(&mut self) {
         if let Some((completed, total, session_path)) = self.state.status.resumable() {
@@ -526,7 +662,7 @@ impl GuiApp {
                     self.state.status = AutomationStatus::Error {
                         completed,
                         total,
-                        message: e.to_string(),
+                        message: crate::error::user_message(&e),
                         session_path: Some(session_path),
                     };
                     crate::log(&format!("GUI: Failed to resume automation: {}", e));
@@ -576,7 +712,7 @@ impl GuiApp {
 
     /// Rescan the output directory for interrupted sessions that can be resumed.
     fn scan_resumable_sessions(&mut self) {
-        let dir = crate::paths::get_output_dir();
+        let dir = crate::paths::get_session_output_dir();
         self.state.resumable_sessions =
             crate::automation::session_meta::list_resumable(&dir);
         // Keep selection valid; default to the newest when none chosen.
@@ -650,10 +786,95 @@ impl GuiApp {
         }
     }
 
+    /// Handle "設定を再読み込み": swap in a freshly loaded config.json. Only the
+    /// next run picks it up; a run in progress keeps the config it started with.
+    fn handle_reload_config(&mut self) {
+        crate::automation::reload_config();
+        // Drop the editor buffer so the 設定 panel shows the reloaded values.
+        self.state.config_edit = None;
+        crate::log("GUI: Config reloaded");
+    }
+
+    /// Handle 設定 → 保存: write the editor buffer to config.json and reload it.
+    /// Invalid fields are still saved as-is; `reload_config` validates them and
+    /// falls back to defaults, logging each one.
+    fn handle_save_config(&mut self) {
+        let Some(config) = &self.state.config_edit else {
+            return;
+        };
+        let autostart_changed = config.autostart != crate::autostart::is_enabled();
+        let autostart = config.autostart;
+        match crate::automation::save_config(config) {
+            Ok(path) => {
+                crate::log(&format!(
+                    "GUI: Config saved to {}",
+                    crate::paths::relative_display(&path)
+                ));
+                if autostart_changed {
+                    apply_autostart(autostart);
+                }
+                self.handle_reload_config();
+            }
+            Err(e) => crate::log(&format!("GUI: Failed to save config: {}", e)),
+        }
+    }
+
+    /// Handle 設定 → 初期値に戻す: replace the editor buffer with defaults.
+    /// Nothing is written until the user presses 保存.
+    fn handle_reset_config(&mut self) {
+        let developer_mode = crate::automation::get_config().developer_mode;
+        self.state.config_edit = Some(crate::automation::AutomationConfig {
+            // Not shown in the editor; keep the user's mode rather than resetting it.
+            developer_mode,
+            ..Default::default()
+        });
+        crate::log("GUI: Config editor reset to defaults (unsaved)");
+    }
+
+    /// Handle 参照画像 → 撮影: save the button region of the live game window to
+    /// the reference path configured for it, so histogram detection can use it.
+    fn handle_capture_reference(&self, kind: ReferenceKind) {
+        let hwnd = match crate::capture::find_gakumas_window() {
+            Ok(hwnd) => hwnd,
+            Err(e) => {
+                crate::log(&format!("GUI: Could not find game window: {}", e));
+                return;
+            }
+        };
+
+        let config = crate::automation::get_config();
+        let (name, rel_path) = match kind {
+            ReferenceKind::Start => ("Start button", config.start_button_reference.as_str()),
+            ReferenceKind::Skip => ("Skip button", config.skip_button_reference.as_str()),
+            ReferenceKind::End => ("End button", config.end_button_reference.as_str()),
+            ReferenceKind::Blocked => ("blocked screen", config.blocked_screen_reference.as_str()),
+        };
+        let path = crate::paths::get_data_dir().join(rel_path);
+        if let Some(parent) = path.parent()
+            && let Err(e) = std::fs::create_dir_all(parent)
+        {
+            crate::log(&format!("GUI: Failed to create {}: {}", parent.display(), e));
+            return;
+        }
+
+        crate::log(&format!("GUI: Capturing {} reference...", name));
+        let result = match kind {
+            ReferenceKind::Start => crate::automation::save_start_button_reference(hwnd, &config, &path),
+            ReferenceKind::Skip => crate::automation::save_skip_button_reference(hwnd, &config, &path),
+            ReferenceKind::End => crate::automation::save_end_button_reference(hwnd, &config, &path),
+            ReferenceKind::Blocked => crate::automation::save_blocked_screen_reference(hwnd, &path),
+        };
+        match result {
+            Ok(()) => crate::log(&format!("GUI: {} reference captured", name)),
+            Err(e) => crate::log(&format!("GUI: Failed to capture {} reference: {}", name, e)),
+        }
+    }
+
     /// Handle generate charts button click.
     fn handle_generate_charts(&self) {
         crate::log("GUI: Generating charts...");
-        match crate::analysis::generate_analysis() {
+        let range = self.state.analysis_range();
+        match crate::analysis::generate_analysis(range.as_ref()) {
             Ok((chart_paths, json_path)) => {
                 crate::log(&format!(
                     "GUI: Charts generated: {} files, stats: {}",
@@ -678,7 +899,7 @@ impl GuiApp {
                 let mut repaired = 0u32;
                 for r in &rows {
                     match r.recovery.as_str() {
-                        "flagged" => flagged += 1,
+                        RECOVERY_FLAGGED => flagged += 1,
                         "repaired" => repaired += 1,
                         _ => {}
                     }
@@ -717,6 +938,7 @@ impl GuiApp {
         match load_review_rows(&path) {
             Ok(rows) => {
                 let edits = Self::edits_from_rows(&rows);
+                let capture_mode = crate::automation::session_meta::session_capture_mode(&path);
                 self.state.review = Some(ReviewState {
                     session_path: path,
                     rows,
@@ -732,6 +954,7 @@ impl GuiApp {
                     preview: None,
                     expanded: None,
                     open: true,
+                    capture_mode,
                 });
                 crate::log("GUI: Opened OCR result review window");
             }
@@ -806,7 +1029,9 @@ impl GuiApp {
             }
         }
         let session_path = review.session_path.clone();
-        let saved = match save_review_rows(&session_path, &review.rows) {
+        let config = crate::automation::get_config();
+        let header = crate::automation::csv_writer::results_header(&config);
+        let saved = match save_review_rows(&session_path, &review.rows, header) {
             Ok(()) => {
                 review.dirty = false;
                 review.edits = Self::edits_from_rows(&review.rows);
@@ -835,9 +1060,10 @@ impl GuiApp {
             self.live_chart_dirty = true;
             // Charts derive only from the scores, so regenerate them only when a
             // score actually changed; a verify-only save leaves them identical.
+            // Always the full session: these are the outputs the edits replace.
             if changed > 0 {
                 crate::log("GUI: Regenerating charts after review edits...");
-                match crate::analysis::generate_analysis_for_session(&session_path) {
+                match crate::analysis::generate_analysis_for_session(&session_path, None) {
                     Ok((chart_paths, json_path)) => crate::log(&format!(
                         "GUI: Charts regenerated: {} files, stats: {}",
                         chart_paths.len(),
@@ -924,6 +1150,55 @@ impl GuiApp {
         }
     }
 
+    /// Start/stop the 検出モニター thread to match the checkbox (paused during
+    /// runs) and copy its latest measurement into the state for rendering.
+    fn update_detection_monitor(&mut self, ctx: &egui::Context) {
+        let wanted = self.state.monitor_enabled && !self.state.status.is_running();
+        if wanted && self.detection_monitor.is_none() {
+            self.detection_monitor = Some(monitor::DetectionMonitor::start(ctx.clone()));
+        } else if !wanted {
+            self.detection_monitor = None;
+        }
+        self.state.monitor = self.detection_monitor.as_ref().map(|m| m.snapshot());
+    }
+
+    /// Start the 定期キャプチャ, or stop it if running. Its session becomes the
+    /// latest session so charts and review apply to it.
+    fn handle_toggle_timed_capture(&mut self) {
+        if let Some(capture) = self.timed_capture.take() {
+            crate::log("GUI: Timer capture stop requested");
+            self.state.latest_session_path = Some(capture.session_dir().to_path_buf());
+            return;
+        }
+        match crate::automation::TimedCapture::start() {
+            Ok(capture) => {
+                self.state.latest_session_path = Some(capture.session_dir().to_path_buf());
+                self.timed_capture = Some(capture);
+            }
+            Err(e) => {
+                crate::log(&format!("GUI: Failed to start timer capture: {:#}", e));
+                self.state.timed_capture.error = Some(crate::error::user_message(&e));
+            }
+        }
+    }
+
+    /// Stop the 定期キャプチャ once a run starts (both would capture the same
+    /// window) and copy its progress into the state for rendering.
+    fn update_timed_capture(&mut self) {
+        if self.state.status.is_running() && self.timed_capture.take().is_some() {
+            crate::log("GUI: Timer capture stopped for automation run");
+        }
+        if let Some(capture) = &self.timed_capture {
+            self.state.timed_capture = TimedCaptureStatus {
+                running: true,
+                captured: capture.captured(),
+                error: capture.error(),
+            };
+        } else {
+            self.state.timed_capture.running = false;
+        }
+    }
+
     /// Handle open folder button click.
     fn handle_open_folder(&self) {
         if let Some(path) = &self.state.latest_session_path {
@@ -940,11 +1215,27 @@ impl GuiApp {
 
 impl eframe::App for GuiApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if std::mem::take(&mut self.minimize_on_start) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+        }
+
         // Handle tray menu events
         self.handle_tray_events(ctx);
 
         // Handle global hotkey events
         self.handle_hotkey_events();
+        self.poll_ocr_test();
+
+        // With minimize_to_tray, the close button hides the window instead of
+        // exiting; only the tray's 終了 actually closes the app.
+        if ctx.input(|i| i.viewport().close_requested())
+            && !self.exit_requested
+            && crate::automation::get_config().minimize_to_tray
+        {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+            crate::log("GUI: Window hidden to tray");
+        }
 
         // Check if exit was requested from tray menu
         if self.exit_requested {
@@ -961,6 +1252,9 @@ impl eframe::App for GuiApp {
         // Rebuild the live distribution figure when new iteration data has arrived.
         self.update_live_chart(ctx);
 
+        self.update_detection_monitor(ctx);
+        self.update_timed_capture();
+
         // Persist the live-distribution preference whenever the user changes it, so it
         // is remembered across restarts.
         if self.state.show_live_chart != self.saved_show_live_chart {
@@ -984,9 +1278,10 @@ impl eframe::App for GuiApp {
             self.live_chart_expanded = show_live_panel;
         }
 
-        // Request repaint while automation is running (for progress updates)
+        // Periodic repaints only while automation is running (for progress
+        // updates); Idle and finished states redraw on events alone.
         if self.state.status.is_running() {
-            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+            ctx.request_repaint_after(RUNNING_REPAINT_INTERVAL);
         }
 
         // Header spanning the full width. No separator line (it looked awkward above
@@ -998,7 +1293,7 @@ impl eframe::App for GuiApp {
             ui.heading("学マス リハーサル統計自動化ツール");
             ui.label(
                 egui::RichText::new(
-                    "💡 ショートカット: Ctrl+Shift+S でスクリーンショット／ Ctrl+Shift+Q で自動実行を中止",
+                    "💡 ショートカット: Ctrl+Shift+S でスクリーンショット／ Ctrl+Shift+Q で自動実行を中止／ Ctrl+Shift+O でOCRテスト／ Ctrl+Shift+G で3秒のGIFを録画",
                 )
                 .small()
                 .weak(),
@@ -1057,7 +1352,9 @@ impl eframe::App for GuiApp {
                 .show(ui, |ui| {
                     let actions = render::render_control_panel(ui, &mut self.state);
                     if actions.start { self.handle_start(); }
+                    if actions.test_run { self.handle_test_run(); }
                     if actions.stop { self.handle_stop(); }
+                    if actions.stop_after_current { self.handle_stop_after_current(); }
                     if actions.continue_run { self.handle_continue(); }
                     if actions.generate_charts { self.handle_generate_charts(); }
                     if actions.open_folder { self.handle_open_folder(); }
@@ -1067,6 +1364,31 @@ impl eframe::App for GuiApp {
                     if actions.dismiss_selected { self.handle_dismiss_selected(); }
                     if actions.extend { self.handle_extend(); }
                     if actions.open_review { self.handle_open_review(); }
+                    if actions.reload_config { self.handle_reload_config(); }
+                    if actions.save_config { self.handle_save_config(); }
+                    if actions.reset_config { self.handle_reset_config(); }
+                    if let Some(kind) = actions.capture_reference { self.handle_capture_reference(kind); }
+                    if actions.reference_wizard {
+                        if is_automation_running() {
+                            crate::log("GUI: Reference capture unavailable while automation is running");
+                        } else {
+                            crate::log("GUI: Guided reference capture requested");
+                            REFERENCE_WIZARD_REQUESTED.store(true, Ordering::SeqCst);
+                        }
+                    }
+                    if actions.toggle_timed_capture { self.handle_toggle_timed_capture(); }
+                    if actions.ocr_test {
+                        crate::log("GUI: Capture and OCR requested");
+                        self.start_ocr_test();
+                    }
+                    if let Some(step) = actions.recalibrate_item {
+                        if is_automation_running() {
+                            crate::log("GUI: Calibration unavailable while automation is running");
+                        } else {
+                            crate::log(&format!("GUI: Recalibration requested: {}", step.description()));
+                            *CALIBRATION_ITEM_REQUESTED.lock().unwrap() = Some(step);
+                        }
+                    }
                 });
         });
 
@@ -1078,23 +1400,86 @@ impl eframe::App for GuiApp {
 impl GuiApp {
     /// Handle tray icon menu events.
     fn handle_tray_events(&mut self, ctx: &egui::Context) {
-        if let Some(receiver) = &self.menu_event_receiver {
-            // Non-blocking check for menu events
-            while let Ok(event) = receiver.try_recv() {
-                match event.id.0.as_str() {
-                    MENU_SHOW_WINDOW => {
-                        // Bring window to front
-                        ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
-                        crate::log("Tray: Show window requested");
-                    }
-                    MENU_EXIT => {
-                        crate::log("Tray: Exit requested");
-                        self.exit_requested = true;
+        // Non-blocking check for menu events; drained first because handlers
+        // need `&mut self`
+        let events: Vec<MenuEvent> = match &self.menu_event_receiver {
+            Some(receiver) => receiver.try_iter().collect(),
+            None => return,
+        };
+        for event in events {
+            match event.id.0.as_str() {
+                MENU_SHOW_WINDOW => {
+                    // Bring window to front (un-hiding it if closed to the tray)
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+                    crate::log("Tray: Show window requested");
+                }
+                MENU_SCREENSHOT => {
+                    crate::log("Tray: Screenshot requested");
+                    take_screenshot();
+                }
+                MENU_TOGGLE_AUTOMATION => self.handle_tray_toggle_automation(),
+                MENU_CALIBRATE => {
+                    if is_automation_running() {
+                        crate::log("Tray: Calibration unavailable while automation is running");
+                    } else {
+                        crate::log("Tray: Calibration requested");
+                        CALIBRATION_REQUESTED.store(true, Ordering::SeqCst);
                     }
-                    _ => {}
                 }
+                MENU_EXIT => {
+                    crate::log("Tray: Exit requested");
+                    self.exit_requested = true;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Tray "自動化開始/停止": stops a running automation, or starts one with
+    /// the run count set in the panel when idle (same as ◼ 停止 / ▶ 開始).
+    fn handle_tray_toggle_automation(&mut self) {
+        match self.state.status {
+            AutomationStatus::Running { .. } => {
+                crate::log("Tray: Stop automation requested");
+                self.handle_stop();
+            }
+            AutomationStatus::Idle => {
+                crate::log("Tray: Start automation requested");
+                self.handle_start();
+            }
+            _ => crate::log(
+                "Tray: Start ignored; return the panel to idle before starting a new run",
+            ),
+        }
+    }
+
+    /// Starts an OCRテスト on a worker thread (capture and OCR take long enough
+    /// to stall the GUI); `poll_ocr_test` collects it.
+    fn start_ocr_test(&mut self) {
+        if self.ocr_test.is_some() {
+            crate::log("OCR test: Already running");
+            return;
+        }
+        self.ocr_test = Some(std::thread::spawn(|| {
+            let scores = debug_capture_and_ocr();
+            if let Some(ctx) = EGUI_CTX.get() {
+                ctx.request_repaint();
             }
+            scores
+        }));
+        self.state.ocr_test_running = true;
+    }
+
+    /// Joins a finished OCRテスト worker. Its readout is already in the log.
+    fn poll_ocr_test(&mut self) {
+        if !self.ocr_test.as_ref().is_some_and(|handle| handle.is_finished()) {
+            return;
+        }
+        if let Some(Err(_)) = self.ocr_test.take().map(|handle| handle.join()) {
+            crate::log("OCR test: Worker panicked");
         }
+        self.state.ocr_test_running = false;
     }
 
     /// Handle global hotkey events.
@@ -1107,10 +1492,7 @@ impl GuiApp {
         match hotkey_id {
             HOTKEY_SCREENSHOT => {
                 crate::log("Hotkey: Screenshot (Ctrl+Shift+S)");
-                match crate::capture::capture_gakumas() {
-                    Ok(path) => crate::log(&format!("Screenshot saved: {}", path.display())),
-                    Err(e) => crate::log(&format!("Screenshot failed: {}", e)),
-                }
+                take_screenshot();
             }
             HOTKEY_ABORT => {
                 if is_automation_running() {
@@ -1120,17 +1502,136 @@ impl GuiApp {
                     crate::log("Hotkey: Abort pressed but no automation running");
                 }
             }
+            HOTKEY_DEBUG_OCR => {
+                crate::log("Hotkey: Capture and OCR (Ctrl+Shift+O)");
+                self.start_ocr_test();
+            }
+            HOTKEY_CLIP => {
+                crate::log("Hotkey: Record clip (Ctrl+Shift+G)");
+                record_clip();
+            }
             _ => {}
         }
     }
 }
 
+/// Writes or removes the login `Run` registry entry to match `enabled`.
+fn apply_autostart(enabled: bool) {
+    let result = if enabled {
+        crate::autostart::enable()
+    } else {
+        crate::autostart::disable().map(|removed| {
+            if !removed {
+                crate::log("GUI: Autostart was not registered; nothing to remove");
+            }
+        })
+    };
+    if let Err(e) = result {
+        crate::log(&format!(
+            "GUI: Failed to {} autostart: {:#}",
+            if enabled { "enable" } else { "disable" },
+            e
+        ));
+    }
+}
+
+/// Saves a screenshot of the game window (hotkey and tray menu).
+fn take_screenshot() {
+    match crate::capture::capture_gakumas() {
+        Ok(path) => crate::log(&format!("Screenshot saved: {}", path.display())),
+        Err(e) => crate::log(&format!("Screenshot failed: {}", e)),
+    }
+}
+
+/// Records a short GIF of the game on a background thread, so the GUI keeps
+/// repainting for the few seconds the capture takes.
+fn record_clip() {
+    if CLIP_RECORDING.swap(true, Ordering::SeqCst) {
+        crate::log("Clip: Already recording");
+        return;
+    }
+    let hwnd = match crate::capture::find_gakumas_window() {
+        Ok(hwnd) => hwnd.0 as isize,
+        Err(e) => {
+            crate::log(&format!("Clip: Could not find game window: {}", e));
+            CLIP_RECORDING.store(false, Ordering::SeqCst);
+            return;
+        }
+    };
+    std::thread::spawn(move || {
+        let hwnd = windows::Win32::Foundation::HWND(hwnd as *mut std::ffi::c_void);
+        match crate::capture::clip::capture_clip(
+            hwnd,
+            crate::capture::clip::CLIP_DURATION_MS,
+            crate::capture::clip::CLIP_FPS,
+        ) {
+            Ok(path) => crate::log(&format!("Clip saved: {}", path.display())),
+            Err(e) => crate::log(&format!("Clip failed: {}", e)),
+        }
+        CLIP_RECORDING.store(false, Ordering::SeqCst);
+    });
+}
+
+/// One-shot OCR check (button and hotkey): captures the game as configured by
+/// `capture_source`, runs `ocr_screenshot` with the current regions and logs
+/// the parsed scores, so region/threshold tuning doesn't need a full run.
+///
+/// `score_regions` always holds a value (invalid entries are reset to the
+/// defaults on load), so a failed read is what signals regions that don't fit
+/// the screen; the log then points at calibration.
+fn debug_capture_and_ocr() -> Option<[[u32; 3]; 3]> {
+    let hwnd = match crate::capture::find_gakumas_window() {
+        Ok(hwnd) => hwnd,
+        Err(e) => {
+            crate::log(&format!("OCR test: Could not find game window: {}", e));
+            return None;
+        }
+    };
+    let img = match crate::capture::capture_game_frame(hwnd) {
+        Ok(img) => img,
+        Err(e) => {
+            crate::log(&format!("OCR test: Capture failed: {}", e));
+            return None;
+        }
+    };
+
+    let config = crate::automation::get_config();
+    match crate::ocr::ocr_screenshot(
+        &img,
+        &config.score_regions,
+        &config.total_regions,
+        &config.bonus_regions,
+    ) {
+        Ok(readout) => {
+            crate::log(&format!("OCR test: scores = {:?}", readout.scores));
+            for (stage, scores) in readout.scores.iter().enumerate() {
+                crate::log(&format!(
+                    "OCR test: Stage {}: {:?} (total={:?}, bonus={:?})",
+                    stage + 1,
+                    scores,
+                    readout.totals[stage],
+                    readout.bonuses[stage]
+                ));
+            }
+            Some(readout.scores)
+        }
+        Err(e) => {
+            crate::log(&format!("OCR test: OCR failed: {}", e));
+            crate::log(
+                "OCR test: Make sure the result screen is showing; if it is, calibrate the \
+                 score regions first (tray → キャリブレーション)",
+            );
+            None
+        }
+    }
+}
+
 /// Newest session folder under the output directory, or `None` if there are no
 /// sessions. Folder names are `YYYYMMDD_HHMMSS`, so the lexicographically-largest
 /// name is the most recent. Only directories containing a `results.csv` qualify,
 /// so an empty/aborted-before-OCR folder is skipped.
 fn newest_session_dir() -> Option<std::path::PathBuf> {
-    let dir = crate::paths::get_output_dir();
+    let dir = crate::paths::get_session_output_dir();
     let mut best: Option<(String, std::path::PathBuf)> = None;
     for entry in std::fs::read_dir(&dir).ok()?.flatten() {
         let path = entry.path();
@@ -1150,7 +1651,9 @@ fn newest_session_dir() -> Option<std::path::PathBuf> {
 
 /// Run the GUI application.
 /// This function blocks until the window is closed.
-pub fn run_gui() -> eframe::Result<()> {
+///
+/// `initial_iterations` (from `--iterations`) pre-fills the run-count input.
+pub fn run_gui(initial_iterations: Option<u32>) -> eframe::Result<()> {
     crate::log("GUI: Creating native options...");
 
     // Start hotkey handler thread
@@ -1167,8 +1670,20 @@ pub fn run_gui() -> eframe::Result<()> {
     } else {
         WINDOW_SIZE_COLLAPSED
     };
+    // With start_minimized the window opens hidden and the tray's ウィンドウを表示
+    // brings it up. The tray icon is made first so that, when it cannot be, the
+    // window is only minimized rather than hidden with no way back.
+    let (tray_icon, menu_event_receiver) = GuiApp::setup_tray_icon();
+    let start_minimized = crate::automation::get_config().start_minimized;
+    let start_hidden = start_minimized && tray_icon.is_some();
+    if start_hidden {
+        crate::log("GUI: Starting hidden in the tray (start_minimized)");
+    } else if start_minimized {
+        crate::log("GUI: No tray icon; starting minimized instead of hidden");
+    }
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
+            .with_visible(!start_hidden)
             .with_inner_size(initial_size)
             .with_min_inner_size(Vec2::new(560.0, 450.0))
             .with_title("Gakumas Rehearsal Automation")
@@ -1182,9 +1697,14 @@ pub fn run_gui() -> eframe::Result<()> {
     let result = eframe::run_native(
         "Gakumas Rehearsal Automation",
         options,
-        Box::new(|cc| {
+        Box::new(move |cc| {
             crate::log("GUI: Creating GuiApp instance...");
-            Ok(Box::new(GuiApp::new(cc)))
+            let mut app = GuiApp::new(cc, tray_icon, menu_event_receiver);
+            app.minimize_on_start = start_minimized && !start_hidden;
+            if let Some(n) = initial_iterations {
+                app.state.iterations = n;
+            }
+            Ok(Box::new(app))
         }),
     );
 
@@ -1266,13 +1786,41 @@ fn run_hotkey_thread(running: Arc<std::sync::atomic::AtomicBool>) {
             crate::log("Hotkey: Ctrl+Shift+Q registered (abort)");
         }
 
+        // Ctrl+Shift+O for a one-shot capture + OCR
+        if let Err(e) = RegisterHotKey(hwnd, HOTKEY_DEBUG_OCR, MOD_CONTROL | MOD_SHIFT | MOD_NOREPEAT, 0x4F) {
+            crate::log(&format!("Hotkey thread: Failed to register OCR test hotkey: {}", e));
+        } else {
+            crate::log("Hotkey: Ctrl+Shift+O registered (OCR test)");
+        }
+
+        // Ctrl+Shift+G for a short GIF clip
+        if let Err(e) = RegisterHotKey(hwnd, HOTKEY_CLIP, MOD_CONTROL | MOD_SHIFT | MOD_NOREPEAT, 0x47) {
+            crate::log(&format!("Hotkey thread: Failed to register clip hotkey: {}", e));
+        } else {
+            crate::log("Hotkey: Ctrl+Shift+G registered (clip)");
+        }
+
         // Message loop
         let mut msg = MSG::default();
+        let mut poll_sleep = HOTKEY_POLL_MIN;
+        let mut idle_cpu = IdleCpuSampler::new();
         while running.load(Ordering::SeqCst) {
             // Use PeekMessage with timeout to allow checking running flag
             if PeekMessageW(&mut msg, HWND::default(), 0, 0, PM_REMOVE).as_bool() {
+                poll_sleep = HOTKEY_POLL_MIN;
                 if msg.message == WM_HOTKEY {
                     let hotkey_id = msg.wParam.0 as i32;
+                    // Calibration wizard keys are handled here, on the thread
+                    // that registered them.
+                    if (crate::calibration::HOTKEY_CAL_F1..=crate::calibration::HOTKEY_CAL_ENTER)
+                        .contains(&hotkey_id)
+                    {
+                        if let Err(e) = crate::calibration::handle_calibration_hotkey(hotkey_id) {
+                            crate::log(&format!("Calibration error: {}", e));
+                        }
+                        let _ = DispatchMessageW(&msg);
+                        continue;
+                    }
                     HOTKEY_TRIGGERED.store(hotkey_id, Ordering::SeqCst);
                     // Wake the GUI event loop so the hotkey is handled now, even
                     // when the window is in the background (otherwise update()
@@ -1283,17 +1831,109 @@ fn run_hotkey_thread(running: Arc<std::sync::atomic::AtomicBool>) {
                 }
                 let _ = DispatchMessageW(&msg);
             } else {
-                std::thread::sleep(std::time::Duration::from_millis(50));
+                let mut requested = false;
+                if CALIBRATION_REQUESTED.swap(false, Ordering::SeqCst) {
+                    requested = true;
+                    if let Err(e) = crate::calibration::start_calibration(hwnd) {
+                        crate::log(&format!("Failed to start calibration: {}", e));
+                    }
+                }
+                let item = CALIBRATION_ITEM_REQUESTED.lock().unwrap().take();
+                if let Some(step) = item {
+                    requested = true;
+                    if let Err(e) = crate::calibration::start_calibration_item(hwnd, step) {
+                        crate::log(&format!("Failed to start calibration: {}", e));
+                    }
+                }
+                if REFERENCE_WIZARD_REQUESTED.swap(false, Ordering::SeqCst) {
+                    requested = true;
+                    if let Err(e) = crate::calibration::start_reference_wizard(hwnd) {
+                        crate::log(&format!("Failed to start reference capture: {}", e));
+                    }
+                }
+
+                let automation_running = is_automation_running();
+                idle_cpu.tick(automation_running);
+                poll_sleep = if requested
+                    || automation_running
+                    || crate::calibration::is_calibrating()
+                    || crate::calibration::references::is_capturing_references()
+                {
+                    HOTKEY_POLL_MIN
+                } else {
+                    (poll_sleep * 2).min(HOTKEY_POLL_MAX)
+                };
+                std::thread::sleep(poll_sleep);
             }
         }
 
         // Cleanup
+        let _ = crate::calibration::wizard::stop_calibration();
+        crate::calibration::references::stop_reference_wizard();
         let _ = UnregisterHotKey(hwnd, HOTKEY_SCREENSHOT);
         let _ = UnregisterHotKey(hwnd, HOTKEY_ABORT);
+        let _ = UnregisterHotKey(hwnd, HOTKEY_DEBUG_OCR);
+        let _ = UnregisterHotKey(hwnd, HOTKEY_CLIP);
         crate::log("Hotkey thread: Cleaned up");
     }
 }
 
+/// Logs the process's average CPU usage over each idle stretch of
+/// `IDLE_CPU_LOG_INTERVAL`, so idle overhead can be compared between builds.
+/// A stretch restarts whenever automation is running.
+struct IdleCpuSampler {
+    window_start: Instant,
+    cpu_at_start: Option<std::time::Duration>,
+}
+
+impl IdleCpuSampler {
+    fn new() -> Self {
+        Self {
+            window_start: Instant::now(),
+            cpu_at_start: process_cpu_time(),
+        }
+    }
+
+    fn tick(&mut self, automation_running: bool) {
+        if automation_running {
+            *self = Self::new();
+            return;
+        }
+        let wall = self.window_start.elapsed();
+        if wall < IDLE_CPU_LOG_INTERVAL {
+            return;
+        }
+        if let (Some(start), Some(now)) = (self.cpu_at_start, process_cpu_time()) {
+            let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+            let percent =
+                now.saturating_sub(start).as_secs_f64() / wall.as_secs_f64() / cores as f64 * 100.0;
+            crate::log(&format!(
+                "GUI: Idle CPU usage {:.2}% over the last {}s ({} logical cores)",
+                percent,
+                wall.as_secs(),
+                cores
+            ));
+        }
+        *self = Self::new();
+    }
+}
+
+/// Total user + kernel CPU time consumed by this process so far.
+fn process_cpu_time() -> Option<std::time::Duration> {
+    use windows::Win32::Foundation::FILETIME;
+    use windows::Win32::System::Threading::{GetCurrentProcess, GetProcessTimes};
+
+    let mut creation = FILETIME::default();
+    let mut exit = FILETIME::default();
+    let mut kernel = FILETIME::default();
+    let mut user = FILETIME::default();
+    unsafe { GetProcessTimes(GetCurrentProcess(), &mut creation, &mut exit, &mut kernel, &mut user) }
+        .ok()?;
+    // FILETIME counts 100ns ticks
+    let ticks = |t: FILETIME| ((t.dwHighDateTime as u64) << 32) | t.dwLowDateTime as u64;
+    Some(std::time::Duration::from_nanos((ticks(kernel) + ticks(user)) * 100))
+}
+
 /// Window procedure for hotkey message-only window.
 unsafe extern "system" fn hotkey_window_proc(
     hwnd: windows::Win32::Foundation::HWND,
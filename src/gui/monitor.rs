@@ -0,0 +1,102 @@
+//! Live detection readout for the 検出モニター panel.
+//!
+//! While enabled, a background thread measures the Start/Skip/End button
+//! regions of the game window once per second and publishes the brightness
+//! and histogram similarity, so thresholds can be tuned by watching the
+//! numbers instead of the log.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::automation::{load_reference_histogram, measure_region, RegionReading};
+
+/// Interval between measurements.
+const MONITOR_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Latest measurement of the three button regions, in Start/Skip/End order.
+#[derive(Clone, Debug, Default)]
+pub struct MonitorSnapshot {
+    pub readings: [Option<RegionReading>; 3],
+    /// Why the last measurement failed (e.g. game window not found).
+    pub error: Option<String>,
+}
+
+/// Handle to the measuring thread. Dropping it signals the thread to stop; it
+/// exits after its current sleep rather than being joined, so the UI never
+/// blocks on it.
+pub struct DetectionMonitor {
+    stop: Arc<AtomicBool>,
+    latest: Arc<Mutex<MonitorSnapshot>>,
+}
+
+impl DetectionMonitor {
+    /// Starts measuring on a background thread; `ctx` is repainted after each
+    /// measurement so the readout updates without user input.
+    pub fn start(ctx: eframe::egui::Context) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let latest = Arc::new(Mutex::new(MonitorSnapshot::default()));
+
+        {
+            let stop = stop.clone();
+            let latest = latest.clone();
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::SeqCst) {
+                    let snapshot = measure_all();
+                    if let Ok(mut guard) = latest.lock() {
+                        *guard = snapshot;
+                    }
+                    ctx.request_repaint();
+                    std::thread::sleep(MONITOR_INTERVAL);
+                }
+            });
+        }
+
+        crate::log("GUI: Detection monitor started");
+        Self { stop, latest }
+    }
+
+    /// Copy of the most recent measurement.
+    pub fn snapshot(&self) -> MonitorSnapshot {
+        self.latest.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+}
+
+impl Drop for DetectionMonitor {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        crate::log("GUI: Detection monitor stopped");
+    }
+}
+
+/// Measures all three regions once. References are re-read every time so a
+/// freshly captured 参照画像 or a reloaded config shows up immediately.
+fn measure_all() -> MonitorSnapshot {
+    let hwnd = match crate::capture::find_gakumas_window() {
+        Ok(hwnd) => hwnd,
+        Err(e) => {
+            return MonitorSnapshot {
+                error: Some(e.user_message()),
+                ..Default::default()
+            }
+        }
+    };
+
+    let config = crate::automation::get_config();
+    let skip_subrect = config.skip_button_active_subrect.as_ref();
+    let targets = [
+        (&config.start_button_region, None, &config.start_button_reference),
+        (&config.skip_button_region, skip_subrect, &config.skip_button_reference),
+        (&config.end_button_region, None, &config.end_button_reference),
+    ];
+
+    let mut snapshot = MonitorSnapshot::default();
+    for (slot, (region, subrect, reference)) in snapshot.readings.iter_mut().zip(targets) {
+        let ref_img = load_reference_histogram(&crate::paths::resolve_data_file(reference)).ok();
+        match measure_region(hwnd, region, subrect, ref_img.as_ref(), config.detection_metric) {
+            Ok(reading) => *slot = Some(reading),
+            Err(e) => snapshot.error = Some(e.to_string()),
+        }
+    }
+    snapshot
+}
@@ -6,6 +6,14 @@ use eframe::egui::{self, Color32, RichText, TextureHandle, Vec2};
 
 use super::state::{AutomationStatus, GuiState, ReviewState};
 use crate::analysis::statistics::{ColumnStats, DataSetStats};
+use crate::automation::config::MAX_CLICK_HOLD_MS;
+use crate::automation::{
+    AutomationConfig, ButtonConfig, CaptureMode, CapturePixelFormat, CaptureSource, DetectionMetric, ForegroundMode, NavigationKey, OcrBackend,
+    OcrPreprocessMode,
+    RelativeRect,
+};
+use crate::automation::state::is_stop_after_current_requested;
+use crate::calibration::CalibrationStep;
 
 /// One-tap run-count presets shown beneath every run-count input. Edit this
 /// single array to change the buttons everywhere they appear.
@@ -53,13 +61,15 @@ fn stat_cell(ui: &mut egui::Ui, text: &str, strong: bool) {
 pub fn render_live_stats_table(ui: &mut egui::Ui, stats: &DataSetStats) {
     // Each metric is a name plus a field accessor (non-capturing closures coerce to
     // function pointers, so they share one type and live in a single array).
-    let metrics: [(&str, fn(&ColumnStats) -> f64); 6] = [
+    type Metric = (&'static str, fn(&ColumnStats) -> f64);
+    let metrics: [Metric; 7] = [
         ("Avg", |c| c.mean),
         ("Med", |c| c.median),
         ("Max", |c| c.max as f64),
         ("Min", |c| c.min as f64),
         ("Q1", |c| c.quartile_1),
         ("Q3", |c| c.quartile_3),
+        ("Range", |c| c.range as f64),
     ];
 
     // Equal-width columns (label + 9 data) that together fill the panel width.
@@ -87,6 +97,13 @@ pub fn render_live_stats_table(ui: &mut egui::Ui, stats: &DataSetStats) {
                 }
                 ui.end_row();
             }
+
+            // CV is a ratio, so it gets a percentage instead of the "k" format.
+            stat_cell(ui, "CV", true);
+            for col in &stats.columns {
+                stat_cell(ui, &format!("{:.1}%", col.cv * 100.0), false);
+            }
+            ui.end_row();
         });
 }
 
@@ -172,12 +189,28 @@ pub fn render_guide_image(
     }
 }
 
+/// Which button-region reference image a 参照画像 button captures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReferenceKind {
+    /// 開始する on the rehearsal start page.
+    Start,
+    /// スキップ during the rehearsal.
+    Skip,
+    /// 終了 on the result page.
+    End,
+    /// The whole maintenance/update screen (`blocked_screen_reference`).
+    Blocked,
+}
+
 /// Click signals collected from the state-driven control panel in one frame.
 /// Each field is true if the corresponding button was clicked this frame.
 #[derive(Default)]
 pub struct PanelActions {
     pub start: bool,
+    /// Run one iteration with OCR debug output, then open its results.
+    pub test_run: bool,
     pub stop: bool,
+    pub stop_after_current: bool,
     pub continue_run: bool,
     pub generate_charts: bool,
     pub open_folder: bool,
@@ -191,6 +224,22 @@ pub struct PanelActions {
     pub extend: bool,
     /// Open the OCR result review/edit window for the latest session.
     pub open_review: bool,
+    /// Re-read config.json so the next run uses the edited values.
+    pub reload_config: bool,
+    /// Write the config editor's buffer to config.json and make it active.
+    pub save_config: bool,
+    /// Replace the config editor's buffer with the built-in defaults (unsaved).
+    pub reset_config: bool,
+    /// Capture the given button region from the game window as its reference image.
+    pub capture_reference: Option<ReferenceKind>,
+    /// Start the guided capture of all three reference images.
+    pub reference_wizard: bool,
+    /// Capture the game once and log the OCR'd scores (tuning aid).
+    pub ocr_test: bool,
+    /// Rerun the calibration wizard for this one item only.
+    pub recalibrate_item: Option<CalibrationStep>,
+    /// Start the 定期キャプチャ timer, or stop it if running.
+    pub toggle_timed_capture: bool,
 }
 
 /// Signals collected from the review/edit window in one frame.
@@ -246,11 +295,43 @@ fn render_idle(ui: &mut egui::Ui, state: &mut GuiState, actions: &mut PanelActio
     // asked not to move it — so it cannot be toggled from the running panel.
     ui.checkbox(&mut state.show_live_chart, "ライブ分布を表示")
         .on_hover_text("実行中に9つのスコア分布（箱ひげ図）をリアルタイム表示します");
+    ui.checkbox(&mut state.run_ocr, "OCR・グラフ生成を行う")
+        .on_hover_text("オフにするとスクリーンショットの保存のみを行います");
 
     ui.add_space(12.0);
     if ui.button(RichText::new("▶ 開始").size(18.0)).clicked() {
         actions.start = true;
     }
+    ui.add_space(6.0);
+    if ui
+        .button("🧪 テスト実行 (1回)")
+        .on_hover_text("1回だけOCR付きで実行し、OCRのデバッグ画像を保存します。終了後にスクリーンショットと読み取ったスコアを開きます")
+        .clicked()
+    {
+        actions.test_run = true;
+    }
+    ui.add_space(6.0);
+    if ui
+        .button("🔄 設定を再読み込み")
+        .on_hover_text("config.json を読み直します（次回の実行から反映されます）")
+        .clicked()
+    {
+        actions.reload_config = true;
+    }
+    ui.add_space(6.0);
+    if ui
+        .add_enabled(!state.ocr_test_running, egui::Button::new("🔍 OCRテスト"))
+        .on_hover_text("結果画面を1回撮影してOCRし、読み取ったスコアをログに出力します（Ctrl+Shift+O）")
+        .clicked()
+    {
+        actions.ocr_test = true;
+    }
+    ui.add_space(6.0);
+    render_config_editor(ui, state, actions);
+    render_reference_capture(ui, actions);
+    render_item_calibration(ui, actions);
+    render_detection_monitor(ui, state);
+    render_timed_capture(ui, state, actions);
 
     // Shortcut to the most recent session's results, so charts/folder stay
     // reachable after returning to Idle (e.g. via the terminal-state 戻る button)
@@ -261,6 +342,7 @@ fn render_idle(ui: &mut egui::Ui, state: &mut GuiState, actions: &mut PanelActio
         ui.add_space(4.0);
         ui.label(RichText::new("前回の結果").strong());
         ui.add_space(6.0);
+        render_analysis_range(ui, state);
         if ui.button("📊 グラフを生成").clicked() {
             actions.generate_charts = true;
         }
@@ -287,7 +369,7 @@ fn render_idle(ui: &mut egui::Ui, state: &mut GuiState, actions: &mut PanelActio
 }
 
 /// Running: read-only count derived from the live run, warning, progress,
-/// elapsed, and Stop. No editable input and no Start, so the count shown here
+/// elapsed/ETA, and Stop. No editable input and no Start, so the count shown here
 /// can never contradict the run in progress.
 fn render_running(
     ui: &mut egui::Ui,
@@ -325,12 +407,31 @@ fn render_running(
         ui.horizontal(|ui| {
             ui.label("経過時間:");
             ui.label(elapsed);
+            ui.add_space(12.0);
+            ui.label("残り時間(推定):");
+            ui.label(state.eta.remaining_text(current, total));
         });
     }
 
     ui.add_space(12.0);
-    if ui.button(RichText::new("◼ 停止").size(18.0)).clicked() {
-        actions.stop = true;
+    ui.horizontal(|ui| {
+        if ui.button(RichText::new("◼ 停止").size(18.0)).clicked() {
+            actions.stop = true;
+        }
+        let stop_pending = is_stop_after_current_requested();
+        if ui
+            .add_enabled(!stop_pending, egui::Button::new(RichText::new("⏸ この周回で停止").size(18.0)))
+            .on_hover_text("現在の周回を最後まで実行してから停止します（ゲームはリハーサル画面に戻ります）")
+            .clicked()
+        {
+            actions.stop_after_current = true;
+        }
+    });
+    if is_stop_after_current_requested() {
+        ui.label(
+            RichText::new("現在の周回の終了後に停止します")
+                .color(Color32::from_rgb(200, 150, 0)),
+        );
     }
     // The live score-distribution figure (when enabled) is shown large in a separate
     // right-hand side panel, not here, so it is actually readable.
@@ -401,6 +502,7 @@ fn render_finished(
     ui.add_space(16.0);
     ui.heading("アクション");
     ui.add_space(8.0);
+    render_analysis_range(ui, state);
     if ui.button("📊 グラフを生成").clicked() {
         actions.generate_charts = true;
     }
@@ -454,6 +556,26 @@ fn render_finished(
     }
 }
 
+/// Iteration range the 📊 グラフを生成 button analyzes (default: all).
+fn render_analysis_range(ui: &mut egui::Ui, state: &mut GuiState) {
+    ui.horizontal(|ui| {
+        ui.label("分析する周回:");
+        ui.add(
+            egui::DragValue::new(&mut state.analysis_first_iteration)
+                .range(1..=99_999)
+                .speed(1.0),
+        );
+        ui.label("〜");
+        ui.add(
+            egui::DragValue::new(&mut state.analysis_last_iteration)
+                .range(0..=99_999)
+                .speed(1.0),
+        )
+        .on_hover_text("0 = 最後まで。範囲を指定した結果はセッション内の iterations_<開始>-<終了> フォルダに保存されます");
+    });
+    ui.add_space(4.0);
+}
+
 /// Lists which result files exist in a finished session's folder.
 fn render_generated_files(ui: &mut egui::Ui, session_path: &std::path::Path) {
     ui.add_space(8.0);
@@ -717,8 +839,11 @@ fn draw_stage_crop(
             return;
         }
     };
-    let cfg = crate::automation::get_config();
-    let crop = crate::automation::review_crop_rect(cfg, stage);
+    let cfg = AutomationConfig {
+        capture_mode: review.capture_mode,
+        ..AutomationConfig::clone(&crate::automation::get_config())
+    };
+    let crop = crate::automation::review_crop_rect(&cfg, stage);
     if crop.width <= 0.0 || crop.height <= 0.0 {
         return;
     }
@@ -789,3 +914,555 @@ fn render_resume_section(ui: &mut egui::Ui, state: &mut GuiState, actions: &mut
             }
         });
 }
+
+/// Text color for a config field that `AutomationConfig::validate` would reject.
+const INVALID_FIELD_COLOR: Color32 = Color32::from_rgb(220, 40, 40);
+
+/// Grid label for one config field, red when the current value is invalid.
+fn config_label(ui: &mut egui::Ui, label: &str, invalid: bool) {
+    let text = RichText::new(label);
+    ui.label(if invalid { text.color(INVALID_FIELD_COLOR) } else { text });
+}
+
+/// Collapsible 定期キャプチャ section: start/stop of timer capture, which saves
+/// (and optionally OCRs) a screenshot at a fixed interval without clicking.
+fn render_timed_capture(ui: &mut egui::Ui, state: &GuiState, actions: &mut PanelActions) {
+    egui::CollapsingHeader::new("⏱ 定期キャプチャ")
+        .id_salt("timed_capture_collapsing")
+        .show(ui, |ui| {
+            let cfg = crate::automation::get_config();
+            ui.label(
+                RichText::new(format!(
+                    "{}ミリ秒ごとに撮影します{}（クリック操作なし）",
+                    cfg.timed_capture_interval_ms,
+                    if cfg.timed_capture_ocr { "・OCRあり" } else { "" }
+                ))
+                .small(),
+            );
+            ui.add_space(4.0);
+            let status = &state.timed_capture;
+            let label = if status.running {
+                "⏹ 定期キャプチャを停止"
+            } else {
+                "▶ 定期キャプチャを開始"
+            };
+            if ui
+                .button(label)
+                .on_hover_text("ゲームを手動で進めながら結果を記録します。間隔とOCRは設定で変更できます")
+                .clicked()
+            {
+                actions.toggle_timed_capture = true;
+            }
+            if status.running || status.captured > 0 {
+                ui.label(format!("撮影枚数: {}", status.captured));
+            }
+            if let Some(err) = &status.error {
+                ui.label(RichText::new(format!("⚠ {}", err)).color(INVALID_FIELD_COLOR).small());
+            }
+        });
+}
+
+/// Collapsible 参照画像 section: one button per histogram reference image. Each
+/// captures the configured button region from the game window, which must be
+/// showing the matching screen at the time.
+fn render_reference_capture(ui: &mut egui::Ui, actions: &mut PanelActions) {
+    egui::CollapsingHeader::new("🖼 参照画像")
+        .id_salt("reference_capture_collapsing")
+        .show(ui, |ui| {
+            ui.label(
+                RichText::new("ゲーム画面に対象のボタンが表示されている状態で押してください")
+                    .small(),
+            );
+            ui.add_space(4.0);
+            let buttons = [
+                (ReferenceKind::Start, "開始ボタンを撮影", "リハーサル開始画面（「開始する」）"),
+                (ReferenceKind::Skip, "スキップボタンを撮影", "リハーサル中（「スキップ」）"),
+                (ReferenceKind::End, "終了ボタンを撮影", "結果画面（「終了」）"),
+                (
+                    ReferenceKind::Blocked,
+                    "メンテナンス画面を撮影",
+                    "メンテナンス・アップデートのお知らせ画面（ウィンドウ全体）",
+                ),
+            ];
+            for (kind, label, hover) in buttons {
+                if ui.button(label).on_hover_text(hover).clicked() {
+                    actions.capture_reference = Some(kind);
+                }
+            }
+            ui.add_space(4.0);
+            if ui
+                .button("3枚まとめて撮影 (ガイド付き)")
+                .on_hover_text("開始・スキップ・終了の順に画面を進めながら F1 で撮影し、Y で確定します")
+                .clicked()
+            {
+                actions.reference_wizard = true;
+            }
+        });
+}
+
+/// Collapsible 項目別キャリブレーション section: one button per calibration
+/// item, so a single drifted position or region can be redone without the
+/// full wizard.
+fn render_item_calibration(ui: &mut egui::Ui, actions: &mut PanelActions) {
+    egui::CollapsingHeader::new("🎯 項目別キャリブレーション")
+        .id_salt("item_calibration_collapsing")
+        .show(ui, |ui| {
+            ui.label(
+                RichText::new("選んだ項目だけを再設定し、他の設定はそのまま残します").small(),
+            );
+            ui.add_space(4.0);
+            let items = [
+                (CalibrationStep::StartButton, "開始ボタンの位置"),
+                (CalibrationStep::StartButtonRegionTopLeft, "開始ボタンの領域"),
+                (CalibrationStep::SkipButton, "スキップボタンの位置"),
+                (CalibrationStep::SkipButtonRegionTopLeft, "スキップボタンの領域"),
+                (CalibrationStep::EndButton, "終了ボタンの位置"),
+                (CalibrationStep::EndButtonRegionTopLeft, "終了ボタンの領域"),
+            ];
+            for (step, label) in items {
+                if ui.button(label).on_hover_text(step.description()).clicked() {
+                    actions.recalibrate_item = Some(step);
+                }
+            }
+        });
+}
+
+/// Collapsible 検出モニター section: live brightness and histogram similarity
+/// of the three button regions, each bar green once it passes its threshold.
+fn render_detection_monitor(ui: &mut egui::Ui, state: &mut GuiState) {
+    egui::CollapsingHeader::new("📈 検出モニター")
+        .id_salt("detection_monitor_collapsing")
+        .show(ui, |ui| {
+            ui.checkbox(&mut state.monitor_enabled, "1秒ごとに測定する")
+                .on_hover_text("ゲーム画面のボタン領域の明るさと参照画像との類似度を表示します");
+            let Some(snapshot) = &state.monitor else {
+                return;
+            };
+
+            let cfg = crate::automation::get_config();
+            let pass = Color32::from_rgb(80, 170, 90);
+            let fail = Color32::from_gray(120);
+            let bar = |ui: &mut egui::Ui, fraction: f32, text: String, ok: bool| {
+                ui.add(
+                    egui::ProgressBar::new(fraction.clamp(0.0, 1.0))
+                        .desired_width(120.0)
+                        .fill(if ok { pass } else { fail })
+                        .text(text),
+                );
+            };
+
+            ui.add_space(4.0);
+            egui::Grid::new("detection_monitor_grid").num_columns(3).show(ui, |ui| {
+                ui.label("");
+                ui.label(format!("明るさ (>{:.1})", cfg.brightness_threshold));
+                ui.label(format!("類似度 (≥{:.3})", cfg.histogram_threshold));
+                ui.end_row();
+
+                for (name, reading) in ["開始", "スキップ", "終了"].iter().zip(&snapshot.readings) {
+                    ui.label(*name);
+                    match reading {
+                        Some(r) => {
+                            bar(
+                                ui,
+                                r.brightness / 255.0,
+                                format!("{:.1}", r.brightness),
+                                r.brightness > cfg.brightness_threshold,
+                            );
+                            match r.similarity {
+                                Some(s) => bar(ui, s, format!("{:.3}", s), s >= cfg.histogram_threshold),
+                                None => {
+                                    ui.label(RichText::new("参照画像なし").small());
+                                }
+                            }
+                        }
+                        None => {
+                            ui.label("—");
+                            ui.label("—");
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+
+            if let Some(err) = &snapshot.error {
+                ui.label(RichText::new(format!("⚠ {}", err)).color(INVALID_FIELD_COLOR).small());
+            }
+        });
+}
+
+/// One `label | DragValue` grid row for a numeric config field.
+fn config_drag<T: egui::emath::Numeric>(
+    ui: &mut egui::Ui,
+    label: &str,
+    value: &mut T,
+    range: std::ops::RangeInclusive<T>,
+    speed: f64,
+    invalid: bool,
+) {
+    config_label(ui, label, invalid);
+    ui.add(egui::DragValue::new(value).range(range).speed(speed));
+    ui.end_row();
+}
+
+/// One grid row for a button: relative position plus click hold time and
+/// double-click toggle.
+fn config_point(ui: &mut egui::Ui, label: &str, p: &mut ButtonConfig, invalid: bool) {
+    config_label(ui, label, invalid);
+    ui.horizontal(|ui| {
+        for (prefix, v) in [("x ", &mut p.x), ("y ", &mut p.y)] {
+            ui.add(
+                egui::DragValue::new(v)
+                    .range(0.0..=1.0)
+                    .speed(0.001)
+                    .fixed_decimals(3)
+                    .prefix(prefix),
+            );
+        }
+        ui.add(
+            egui::DragValue::new(&mut p.click_hold_ms)
+                .range(1..=MAX_CLICK_HOLD_MS)
+                .speed(1.0)
+                .prefix("押下 ")
+                .suffix("ms"),
+        );
+        ui.checkbox(&mut p.double_click, "ダブル");
+    });
+    ui.end_row();
+}
+
+/// One grid row for a relative rectangle (detection / OCR region).
+fn config_rect(ui: &mut egui::Ui, label: &str, r: &mut RelativeRect, invalid: bool) {
+    config_label(ui, label, invalid);
+    ui.horizontal(|ui| {
+        for (prefix, v) in [
+            ("x ", &mut r.x),
+            ("y ", &mut r.y),
+            ("w ", &mut r.width),
+            ("h ", &mut r.height),
+        ] {
+            ui.add(
+                egui::DragValue::new(v)
+                    .range(0.0..=1.0)
+                    .speed(0.001)
+                    .fixed_decimals(3)
+                    .prefix(prefix),
+            );
+        }
+    });
+    ui.end_row();
+}
+
+/// Collapsible "設定" editor over a working copy of the config
+/// (`state.config_edit`, loaded on first expand). Out-of-range fields are shown
+/// in red using `AutomationConfig::validate`; 保存 writes config.json and makes
+/// it active for the next run, 初期値に戻す replaces the buffer with defaults.
+fn render_config_editor(ui: &mut egui::Ui, state: &mut GuiState, actions: &mut PanelActions) {
+    egui::CollapsingHeader::new("⚙ 設定")
+        .id_salt("config_editor_collapsing")
+        .show(ui, |ui| {
+            let cfg = state.config_edit.get_or_insert_with(|| AutomationConfig {
+                // The registry is the truth for autostart; the saved flag may be stale
+                autostart: crate::autostart::is_enabled(),
+                ..AutomationConfig::clone(&crate::automation::get_config())
+            });
+            let invalid = cfg.invalid_fields();
+            let bad = |name: &str| invalid.iter().any(|f| f == name);
+
+            if !invalid.is_empty() {
+                ui.label(
+                    RichText::new(format!("⚠ 範囲外の値があります: {}", invalid.join(", ")))
+                        .color(INVALID_FIELD_COLOR)
+                        .small(),
+                );
+                ui.add_space(4.0);
+            }
+
+            ui.label(RichText::new("検出").strong());
+            egui::Grid::new("config_detection_grid").num_columns(2).show(ui, |ui| {
+                config_drag(ui, "明るさしきい値", &mut cfg.brightness_threshold, 0.0..=255.0, 0.5, bad("brightness_threshold"));
+                config_drag(ui, "ヒストグラム類似度", &mut cfg.histogram_threshold, 0.0..=1.0, 0.005, bad("histogram_threshold"));
+                config_label(ui, "類似度の指標", false);
+                egui::ComboBox::from_id_salt("config_detection_metric")
+                    .selected_text(format!("{:?}", cfg.detection_metric))
+                    .show_ui(ui, |ui| {
+                        for metric in [DetectionMetric::Bhattacharyya, DetectionMetric::Correlation, DetectionMetric::Both] {
+                            ui.selectable_value(&mut cfg.detection_metric, metric, format!("{:?}", metric));
+                        }
+                    })
+                    .response
+                    .on_hover_text("Both は両方の指標がしきい値を超えた場合のみ一致とみなします (誤検出を減らします)");
+                ui.end_row();
+                config_drag(ui, "検出確定回数", &mut cfg.detection_confirm_count, 1..=20, 0.1, bad("detection_confirm_count"));
+                config_drag(ui, "クリック再試行回数", &mut cfg.max_click_retries, 0..=20, 0.1, false);
+                config_label(ui, "メンテナンス画面を検出", false);
+                ui.checkbox(&mut cfg.blocked_screen_detection, "")
+                    .on_hover_text("開始画面の待機中にメンテナンス・アップデート画面を検出したら即座に中止します（参照画像: blocked_screen_reference）");
+                ui.end_row();
+            });
+
+            ui.add_space(6.0);
+            ui.label(RichText::new("OCR").strong());
+            egui::Grid::new("config_ocr_grid").num_columns(2).show(ui, |ui| {
+                config_label(ui, "二値化方式", false);
+                egui::ComboBox::from_id_salt("config_preprocess_mode")
+                    .selected_text(format!("{:?}", cfg.ocr_preprocess_mode))
+                    .show_ui(ui, |ui| {
+                        for mode in [
+                            OcrPreprocessMode::Global,
+                            OcrPreprocessMode::Otsu,
+                            OcrPreprocessMode::AdaptiveMean,
+                            OcrPreprocessMode::Edge,
+                        ] {
+                            ui.selectable_value(&mut cfg.ocr_preprocess_mode, mode, format!("{:?}", mode));
+                        }
+                    });
+                ui.end_row();
+                config_label(ui, "OCR実行方式", false);
+                egui::ComboBox::from_id_salt("config_ocr_backend")
+                    .selected_text(format!("{:?}", cfg.ocr_backend))
+                    .show_ui(ui, |ui| {
+                        for backend in [OcrBackend::Process, OcrBackend::InProcess] {
+                            ui.selectable_value(&mut cfg.ocr_backend, backend, format!("{:?}", backend));
+                        }
+                    })
+                    .response
+                    .on_hover_text("InProcess は tesseract-inprocess 機能付きでビルドした場合のみ有効です");
+                ui.end_row();
+                config_drag(ui, "スコア二値化しきい値", &mut cfg.ocr_threshold, 0..=255, 1.0, false);
+                config_drag(ui, "合計二値化しきい値", &mut cfg.total_threshold, 0..=255, 1.0, false);
+                config_drag(ui, "ボーナス青最小値", &mut cfg.bonus_blue_min, 0..=255, 1.0, false);
+                config_drag(ui, "ボーナス青赤差", &mut cfg.bonus_br_margin, 0..=255, 1.0, false);
+                let bounds_bad = bad("min_valid_score");
+                config_drag(ui, "最小スコア", &mut cfg.min_valid_score, 0..=9_999_999, 10.0, bounds_bad);
+                config_drag(ui, "最大スコア", &mut cfg.max_valid_score, 0..=9_999_999, 1000.0, bounds_bad);
+                config_drag(ui, "最小OCR信頼度", &mut cfg.ocr_min_confidence, 0.0..=100.0, 0.5, bad("ocr_min_confidence"));
+                config_drag(ui, "ページ分割モード (PSM)", &mut cfg.ocr_psm, 3..=13, 0.05, bad("ocr_psm"));
+                config_drag(ui, "1ステージの人数", &mut cfg.characters_per_stage, 1..=3, 0.05, bad("characters_per_stage"));
+                config_drag(ui, "拡大倍率", &mut cfg.ocr_upscale_factor, 1..=4, 0.05, bad("ocr_upscale_factor"));
+                config_drag(ui, "合成フレーム数", &mut cfg.stable_frames, 1..=9, 0.05, bad("stable_frames"));
+                config_drag(ui, "再撮影する信頼度 (0=無効)", &mut cfg.recapture_confidence, 0.0..=100.0, 0.5, bad("recapture_confidence"));
+                config_drag(ui, "最大再撮影回数", &mut cfg.max_recaptures, 0..=5, 0.05, bad("max_recaptures"));
+            });
+
+            ui.add_space(6.0);
+            ui.label(RichText::new("キャプチャ").strong());
+            egui::Grid::new("config_capture_grid").num_columns(2).show(ui, |ui| {
+                config_label(ui, "取得元", false);
+                egui::ComboBox::from_id_salt("config_capture_source")
+                    .selected_text(match cfg.capture_source {
+                        CaptureSource::Window => "ウィンドウ",
+                        CaptureSource::Monitor => "モニター",
+                    })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut cfg.capture_source, CaptureSource::Window, "ウィンドウ");
+                        ui.selectable_value(&mut cfg.capture_source, CaptureSource::Monitor, "モニター");
+                    });
+                ui.end_row();
+                config_drag(ui, "キャプチャ再試行回数", &mut cfg.capture_retries, 0..=5, 0.05, false);
+                config_label(ui, "保存範囲", false);
+                egui::ComboBox::from_id_salt("config_capture_mode")
+                    .selected_text(match cfg.capture_mode {
+                        CaptureMode::FullWindow => "画面全体",
+                        CaptureMode::ScoreRegionsOnly => "スコア領域のみ",
+                    })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut cfg.capture_mode, CaptureMode::FullWindow, "画面全体");
+                        ui.selectable_value(&mut cfg.capture_mode, CaptureMode::ScoreRegionsOnly, "スコア領域のみ");
+                    })
+                    .response
+                    .on_hover_text("スコア・合計・ボーナス領域を囲む範囲だけを保存し、ディスク使用量を減らします");
+                ui.end_row();
+                config_label(ui, "カーソルを含める", false);
+                ui.checkbox(&mut cfg.capture_cursor, "");
+                ui.end_row();
+                config_label(ui, "キャプチャ枠を消す", false);
+                ui.checkbox(&mut cfg.suppress_capture_border, "")
+                    .on_hover_text("キャプチャ中のウィンドウに表示される黄色い枠を消します（古いWindowsでは非対応）");
+                ui.end_row();
+                config_label(ui, "不透明で保存", false);
+                ui.checkbox(&mut cfg.force_opaque, "")
+                    .on_hover_text("キャプチャのアルファ値を255にします（オフで取得したアルファ値を保持）");
+                ui.end_row();
+                config_label(ui, "ピクセル形式", false);
+                egui::ComboBox::from_id_salt("config_capture_pixel_format")
+                    .selected_text(match cfg.capture_pixel_format {
+                        CapturePixelFormat::Bgra8 => "8ビット (BGRA)",
+                        CapturePixelFormat::Rgba16Float => "16ビット浮動小数点 (HDR)",
+                    })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut cfg.capture_pixel_format, CapturePixelFormat::Bgra8, "8ビット (BGRA)");
+                        ui.selectable_value(&mut cfg.capture_pixel_format, CapturePixelFormat::Rgba16Float, "16ビット浮動小数点 (HDR)");
+                    })
+                    .response
+                    .on_hover_text("HDRディスプレイで画像が白っぽくなる場合は16ビットを試してください");
+                ui.end_row();
+                config_label(ui, "フレーム到着を待機", false);
+                ui.checkbox(&mut cfg.event_driven_frame_wait, "")
+                    .on_hover_text("フレーム到着の通知で即座に取得します（オフで10msごとにポーリング）");
+                ui.end_row();
+                config_drag(ui, "保存する最大枚数 (0=無制限)", &mut cfg.max_screenshots, 0..=100_000, 1.0, false);
+                config_label(ui, "バックグラウンドで保存", false);
+                ui.checkbox(&mut cfg.async_screenshot_save, "")
+                    .on_hover_text("スクリーンショットの書き込みを別スレッドで行い、保存を待たずに次の操作へ進みます");
+                ui.end_row();
+                config_drag(ui, "同一結果の判定差", &mut cfg.duplicate_max_distance, 0..=256, 0.2, false);
+                config_label(ui, "定期キャプチャでOCR", false);
+                ui.checkbox(&mut cfg.timed_capture_ocr, "")
+                    .on_hover_text("定期キャプチャの各画像をOCRし、results.csv に記録します");
+                ui.end_row();
+                config_label(ui, "同一結果で中断", false);
+                ui.checkbox(&mut cfg.abort_on_duplicate, "");
+                ui.end_row();
+                config_label(ui, "最小化を自動で戻す", false);
+                ui.checkbox(&mut cfg.restore_minimized, "")
+                    .on_hover_text("オフにするとゲームが最小化されたときに自動化をエラーで停止します");
+                ui.end_row();
+                if cfg.capture_source == CaptureSource::Monitor {
+                    config_drag(ui, "モニター番号", &mut cfg.capture_monitor_index, 0..=8, 0.05, false);
+                    config_rect(ui, "モニター内の領域", &mut cfg.capture_monitor_rect, bad("capture_monitor_rect"));
+                }
+            });
+
+            ui.add_space(6.0);
+            ui.label(RichText::new("タイミング (ms)").strong());
+            egui::Grid::new("config_timing_grid").num_columns(2).show(ui, |ui| {
+                config_drag(ui, "読み込みタイムアウト", &mut cfg.loading_timeout_ms, 0..=600_000, 100.0, bad("loading_timeout_ms"));
+                config_drag(ui, "結果画面タイムアウト", &mut cfg.result_timeout_ms, 0..=600_000, 100.0, bad("result_timeout_ms"));
+                config_drag(ui, "撮影前の待機", &mut cfg.capture_delay_ms, 0..=60_000, 10.0, false);
+                config_drag(ui, "周回間の待機", &mut cfg.inter_iteration_delay_ms, 0..=60_000, 10.0, false);
+                config_drag(ui, "ゲーム起動の待機", &mut cfg.window_find_timeout_ms, 0..=600_000, 500.0, false);
+                config_drag(ui, "定期キャプチャの間隔", &mut cfg.timed_capture_interval_ms, 100..=3_600_000, 100.0, bad("timed_capture_interval_ms"));
+                config_drag(ui, "最大実行時間 (0=無制限)", &mut cfg.max_total_runtime_ms, 0..=u64::MAX, 60_000.0, false);
+                config_drag(ui, "OCR完了待ちの上限 (0=無制限)", &mut cfg.ocr_drain_timeout_ms, 0..=u64::MAX, 1_000.0, false);
+            });
+
+            ui.add_space(6.0);
+            ui.label(RichText::new("ボタン位置").strong());
+            egui::Grid::new("config_buttons_grid").num_columns(2).show(ui, |ui| {
+                config_point(ui, "開始", &mut cfg.start_button, bad("start_button"));
+                config_point(ui, "スキップ", &mut cfg.skip_button, bad("skip_button"));
+                config_point(ui, "終了", &mut cfg.end_button, bad("end_button"));
+                config_label(ui, "終了の操作", false);
+                let key_label = |key: Option<NavigationKey>| match key {
+                    None => "クリック".to_string(),
+                    Some(key) => format!("{:?}キー", key),
+                };
+                egui::ComboBox::from_id_salt("config_end_key")
+                    .selected_text(key_label(cfg.end_key))
+                    .show_ui(ui, |ui| {
+                        for key in [
+                            None,
+                            Some(NavigationKey::Enter),
+                            Some(NavigationKey::Escape),
+                            Some(NavigationKey::Space),
+                        ] {
+                            ui.selectable_value(&mut cfg.end_key, key, key_label(key));
+                        }
+                    })
+                    .response
+                    .on_hover_text("結果画面を終了ボタンのクリックではなくキー入力で閉じます");
+                ui.end_row();
+                config_label(ui, "ゲームを前面に出す", false);
+                let foreground_label = |mode: ForegroundMode| match mode {
+                    ForegroundMode::EachClick => "クリックごと",
+                    ForegroundMode::Once => "開始時のみ",
+                    ForegroundMode::Never => "しない",
+                };
+                egui::ComboBox::from_id_salt("config_foreground_mode")
+                    .selected_text(foreground_label(cfg.foreground_mode))
+                    .show_ui(ui, |ui| {
+                        for mode in [ForegroundMode::EachClick, ForegroundMode::Once, ForegroundMode::Never] {
+                            ui.selectable_value(&mut cfg.foreground_mode, mode, foreground_label(mode));
+                        }
+                    })
+                    .response
+                    .on_hover_text("開始時のみ: 実行中も他のアプリを使えます。ゲームが背面にある間は操作を一時停止し、ゲームを前面に戻すと再開します\nしない: 開始時も前面に出しません（ゲームを自分で前面に置いてください）");
+                ui.end_row();
+            });
+
+            ui.add_space(6.0);
+            ui.label(RichText::new("領域").strong());
+            egui::Grid::new("config_regions_grid").num_columns(2).show(ui, |ui| {
+                config_rect(ui, "開始ボタン領域", &mut cfg.start_button_region, bad("start_button_region"));
+                config_rect(ui, "スキップボタン領域", &mut cfg.skip_button_region, bad("skip_button_region"));
+                config_label(ui, "スキップ明るさ範囲を限定", false);
+                let mut use_subrect = cfg.skip_button_active_subrect.is_some();
+                ui.checkbox(&mut use_subrect, "")
+                    .on_hover_text("スキップボタン領域内の一部 (領域に対する相対座標) だけで明るさを測ります");
+                ui.end_row();
+                if use_subrect {
+                    let full = RelativeRect { x: 0.0, y: 0.0, width: 1.0, height: 1.0 };
+                    let subrect = cfg.skip_button_active_subrect.get_or_insert(full);
+                    config_rect(ui, "明るさ測定範囲", subrect, bad("skip_button_active_subrect"));
+                } else {
+                    cfg.skip_button_active_subrect = None;
+                }
+                config_rect(ui, "終了ボタン領域", &mut cfg.end_button_region, bad("end_button_region"));
+                for i in 0..3 {
+                    let name = format!("score_regions[{}]", i);
+                    config_rect(ui, &format!("スコア S{}", i + 1), &mut cfg.score_regions[i], bad(&name));
+                }
+                for i in 0..3 {
+                    let name = format!("total_regions[{}]", i);
+                    config_rect(ui, &format!("合計 S{}", i + 1), &mut cfg.total_regions[i], bad(&name));
+                }
+                for i in 0..3 {
+                    let name = format!("bonus_regions[{}]", i);
+                    config_rect(ui, &format!("ボーナス S{}", i + 1), &mut cfg.bonus_regions[i], bad(&name));
+                }
+            });
+
+            ui.add_space(6.0);
+            ui.label(RichText::new("アプリ").strong());
+            egui::Grid::new("config_app_grid").num_columns(2).show(ui, |ui| {
+                config_label(ui, "閉じるとトレイに格納", false);
+                ui.checkbox(&mut cfg.minimize_to_tray, "");
+                ui.end_row();
+                config_label(ui, "トレイに格納して起動", false);
+                ui.checkbox(&mut cfg.start_minimized, "");
+                ui.end_row();
+                config_label(ui, "Windows 起動時に開始", false);
+                ui.checkbox(&mut cfg.autostart, "")
+                    .on_hover_text("保存時にレジストリ (HKCU\\…\\Run) へ登録／削除します");
+                ui.end_row();
+                config_label(ui, "設定と参照画像を保存", false);
+                ui.checkbox(&mut cfg.archive_config, "")
+                    .on_hover_text("実行開始時に config.json と参照画像をセッションフォルダにコピーします");
+                ui.end_row();
+                config_label(ui, "出力フォルダ", false);
+                ui.add(egui::TextEdit::singleline(&mut cfg.output_dir).hint_text("output"))
+                    .on_hover_text("セッションの保存先（絶対パス、またはデータフォルダからの相対パス）。空欄で既定の output フォルダ");
+                ui.end_row();
+                config_label(ui, "CSVヘッダー", bad("csv_header"));
+                ui.add(egui::TextEdit::singleline(&mut cfg.csv_header).hint_text("iteration,timestamp,screenshot,s1c1,…"))
+                    .on_hover_text("results.csv の列名を置き換えます（列の順序と数は既定と同じ）。空欄で既定のヘッダー");
+                ui.end_row();
+                config_label(ui, "ファイル名テンプレート", bad("screenshot_name_template"));
+                ui.add(egui::TextEdit::singleline(&mut cfg.screenshot_name_template).hint_text("{iter}_{ts}"))
+                    .on_hover_text("スクリーンショットのファイル名（.png を除く）。{iter} {ts} {profile} {w} {h} が使えます。{iter}_ で始めてください。空欄で既定の名前");
+                ui.end_row();
+                config_label(ui, "プロファイル名", false);
+                ui.add(egui::TextEdit::singleline(&mut cfg.profile_name).hint_text("default"))
+                    .on_hover_text("ファイル名テンプレートの {profile} に入る名前");
+                ui.end_row();
+            });
+
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui
+                    .button("💾 保存")
+                    .on_hover_text("config.json に保存し、次回の実行から反映します")
+                    .clicked()
+                {
+                    actions.save_config = true;
+                }
+                if ui
+                    .button("↺ 初期値に戻す")
+                    .on_hover_text("すべての項目を初期値に戻します（保存するまで反映されません）")
+                    .clicked()
+                {
+                    actions.reset_config = true;
+                }
+            });
+        });
+}
@@ -5,8 +5,14 @@
 use crate::automation::results_edit::ReviewRow;
 use crate::automation::session_meta::ResumableSession;
 use eframe::egui::TextureHandle;
+use std::collections::VecDeque;
 use std::path::PathBuf;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+/// Number of recent iteration durations averaged for the ETA. Game load times
+/// vary run to run, so a short window keeps the estimate steady without
+/// lagging far behind a genuine slowdown.
+const ETA_WINDOW: usize = 10;
 
 /// State for the OCR result review/edit window (see EXECPLAN_OCR_REVIEW_EDIT_GUI).
 ///
@@ -41,6 +47,8 @@ pub struct ReviewState {
     pub expanded: Option<u32>,
     /// Whether the review window is shown.
     pub open: bool,
+    /// How the session's screenshots were captured, for placing the crops.
+    pub capture_mode: crate::automation::CaptureMode,
 }
 
 impl std::fmt::Debug for ReviewState {
@@ -178,6 +186,96 @@ impl AutomationStatus {
     }
 }
 
+/// Progress of the 定期キャプチャ timer mode, copied from the running
+/// `TimedCapture` every frame.
+#[derive(Clone, Debug, Default)]
+pub struct TimedCaptureStatus {
+    pub running: bool,
+    /// Screenshots saved by the current (or last) timer capture.
+    pub captured: u32,
+    /// Why starting, or the latest capture, failed.
+    pub error: Option<String>,
+}
+
+/// Estimates time remaining from the durations of recently completed iterations.
+///
+/// Fed by polling the runner's current iteration: each time the counter
+/// advances, the time since the previous advance is recorded as one iteration's
+/// duration. Keyed on the run's `start_time` so a new or resumed run starts with
+/// an empty history.
+#[derive(Debug, Default)]
+pub struct EtaEstimator {
+    run_start: Option<Instant>,
+    last_mark: Option<(u32, Instant)>,
+    samples: VecDeque<Duration>,
+}
+
+impl EtaEstimator {
+    /// Records the iteration in progress as observed at `now`.
+    pub fn observe(&mut self, run_start: Instant, current: u32, now: Instant) {
+        if self.run_start != Some(run_start) {
+            *self = Self {
+                run_start: Some(run_start),
+                ..Self::default()
+            };
+        }
+        match self.last_mark {
+            Some((prev, at)) if current > prev => {
+                // Several iterations may have finished between two polls; split
+                // the interval evenly rather than recording one long sample.
+                let steps = current - prev;
+                let per_step = now.duration_since(at) / steps;
+                for _ in 0..steps.min(ETA_WINDOW as u32) {
+                    if self.samples.len() == ETA_WINDOW {
+                        self.samples.pop_front();
+                    }
+                    self.samples.push_back(per_step);
+                }
+                self.last_mark = Some((current, now));
+            }
+            Some(_) => {}
+            // The first iteration mark only starts timing once a run is
+            // actually underway (current 0 means still preparing).
+            None if current >= 1 => self.last_mark = Some((current, now)),
+            None => {}
+        }
+    }
+
+    /// Estimated time until `total` iterations are done, or None until at least
+    /// one full iteration has been timed.
+    pub fn remaining(&self, current: u32, total: u32, now: Instant) -> Option<Duration> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let average = self.samples.iter().sum::<Duration>() / self.samples.len() as u32;
+        // The iteration in progress still counts as remaining, minus the time
+        // already spent on it.
+        let left = total.saturating_sub(current) + 1;
+        let in_progress = self
+            .last_mark
+            .map(|(_, at)| now.duration_since(at))
+            .unwrap_or_default();
+        Some((average * left).saturating_sub(in_progress.min(average)))
+    }
+
+    /// Display text for the ETA: `HH:MM:SS` (or `MM:SS` under an hour), or "—"
+    /// while no estimate is available yet.
+    pub fn remaining_text(&self, current: u32, total: u32) -> String {
+        match self.remaining(current, total, Instant::now()) {
+            Some(d) => {
+                let secs = d.as_secs();
+                let (h, m, s) = (secs / 3600, (secs / 60) % 60, secs % 60);
+                if h > 0 {
+                    format!("{}:{:02}:{:02}", h, m, s)
+                } else {
+                    format!("{:02}:{:02}", m, s)
+                }
+            }
+            None => "—".to_string(),
+        }
+    }
+}
+
 /// GUI application state.
 #[derive(Debug)]
 pub struct GuiState {
@@ -207,6 +305,28 @@ pub struct GuiState {
     /// The texture itself lives on `GuiApp` (a `TextureHandle` is not `Debug`);
     /// this is just the user's show/hide preference.
     pub show_live_chart: bool,
+    /// Working copy edited by the 設定 panel. Loaded from the active config
+    /// the first time the panel is expanded; None until then (and after a
+    /// reload, so the editor re-reads the new values).
+    pub config_edit: Option<crate::automation::AutomationConfig>,
+    /// Whether a fresh run performs OCR. When off, only screenshots are saved
+    /// (no results.csv rows, no auto-generated charts).
+    pub run_ocr: bool,
+    /// Moving-average ETA for the run in progress.
+    pub eta: EtaEstimator,
+    /// Whether the 検出モニター is switched on. It is paused while a run is in
+    /// progress (the automation does its own measuring).
+    pub monitor_enabled: bool,
+    /// Latest 検出モニター measurement; None while the monitor is off.
+    pub monitor: Option<super::monitor::MonitorSnapshot>,
+    /// 定期キャプチャ progress. Timer capture stops when a run starts.
+    pub timed_capture: TimedCaptureStatus,
+    /// First iteration included when charts are generated from the panel.
+    pub analysis_first_iteration: u32,
+    /// Last iteration included when charts are generated (0 = through the end).
+    pub analysis_last_iteration: u32,
+    /// An OCRテスト is running on its worker thread; its button is disabled.
+    pub ocr_test_running: bool,
 }
 
 impl Default for GuiState {
@@ -222,6 +342,84 @@ impl Default for GuiState {
             review: None,
             attention_counts: None,
             show_live_chart: false,
+            config_edit: None,
+            run_ocr: true,
+            eta: EtaEstimator::default(),
+            monitor_enabled: false,
+            monitor: None,
+            timed_capture: TimedCaptureStatus::default(),
+            analysis_first_iteration: 1,
+            analysis_last_iteration: 0,
+            ocr_test_running: false,
         }
     }
 }
+
+impl GuiState {
+    /// Iteration range for 📊 グラフを生成, or None for the full session.
+    pub fn analysis_range(&self) -> Option<std::ops::RangeInclusive<u32>> {
+        let first = self.analysis_first_iteration.max(1);
+        let last = match self.analysis_last_iteration {
+            0 => u32::MAX,
+            n => n,
+        };
+        (first > 1 || last < u32::MAX).then_some(first..=last)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(n: u64) -> Duration {
+        Duration::from_secs(n)
+    }
+
+    #[test]
+    fn test_eta_first_iteration_has_no_estimate() {
+        let start = Instant::now();
+        let mut eta = EtaEstimator::default();
+
+        // Still preparing, then the first iteration starts: nothing timed yet
+        eta.observe(start, 0, start);
+        assert_eq!(eta.remaining(0, 5, start), None);
+        eta.observe(start, 1, start + secs(2));
+        assert_eq!(eta.remaining(1, 5, start + secs(5)), None);
+        assert_eq!(eta.remaining_text(1, 5), "—");
+
+        // The first completed iteration gives the first estimate
+        eta.observe(start, 2, start + secs(12));
+        assert_eq!(eta.remaining(2, 5, start + secs(12)), Some(secs(40)));
+    }
+
+    #[test]
+    fn test_eta_stalled_iteration_stops_at_one_average() {
+        let start = Instant::now();
+        let mut eta = EtaEstimator::default();
+        eta.observe(start, 1, start);
+        eta.observe(start, 2, start + secs(10));
+
+        // Polling the same iteration records no new samples
+        eta.observe(start, 2, start + secs(15));
+        assert_eq!(eta.remaining(2, 5, start + secs(15)), Some(secs(35)));
+
+        // A stalled iteration counts down by at most one average duration
+        assert_eq!(eta.remaining(2, 5, start + secs(20)), Some(secs(30)));
+        assert_eq!(eta.remaining(2, 5, start + secs(600)), Some(secs(30)));
+    }
+
+    #[test]
+    fn test_eta_splits_skipped_iterations_and_resets_per_run() {
+        let start = Instant::now();
+        let mut eta = EtaEstimator::default();
+        eta.observe(start, 1, start);
+        // Two iterations finished between polls: two 5s samples, not one 10s
+        eta.observe(start, 3, start + secs(10));
+        assert_eq!(eta.remaining(3, 5, start + secs(10)), Some(secs(15)));
+
+        // A new run start discards the previous run's history
+        let restart = start + secs(60);
+        eta.observe(restart, 1, restart);
+        assert_eq!(eta.remaining(1, 5, restart), None);
+    }
+}
@@ -8,8 +8,11 @@
 
 mod analysis;
 mod automation;
+mod autostart;
 mod calibration;
 mod capture;
+mod cli;
+mod error;
 mod gui;
 mod ocr;
 mod paths;
@@ -18,8 +21,9 @@ use anyhow::{anyhow, Result};
 use chrono::Local;
 use std::fs::OpenOptions;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
+use std::time::Duration;
 
 use windows::core::w;
 use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, POINT, WPARAM};
@@ -132,11 +136,12 @@ fn main() -> Result<()> {
         }
     }));
 
-    unsafe {
-        windows::Win32::System::WinRT::RoInitialize(
-            windows::Win32::System::WinRT::RO_INIT_MULTITHREADED,
-        )?
-    };
+    if let Err(e) = capture::apartment::init_main_thread() {
+        log(&format!("{:#}", e));
+        attach_parent_console();
+        eprintln!("{:#}", e);
+        return Err(e);
+    }
 
     // Ensure output directories exist
     paths::ensure_directories()?;
@@ -149,17 +154,52 @@ fn main() -> Result<()> {
 
     // Load configuration
     automation::init_config();
+    automation::config::write_config_schema();
+
+    // Parse command-line flags (all optional; no flags = normal startup)
+    let args = match cli::CliArgs::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            attach_parent_console();
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if args.help
+        || args.analyze.is_some()
+        || args.review.is_some()
+        || args.compare.is_some()
+        || args.no_gui
+    {
+        attach_parent_console();
+    }
+    if args.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+    if let Some(session_dir) = &args.analyze {
+        return run_cli_analyze(session_dir);
+    }
+    if let Some(session_dir) = &args.review {
+        return run_cli_review(session_dir);
+    }
+    if let Some((session_a, session_b)) = &args.compare {
+        return run_cli_compare(session_a, session_b);
+    }
+    if args.no_gui {
+        return run_headless(args.iterations);
+    }
 
     // Check if developer mode is enabled
     let config = automation::get_config();
-    if config.developer_mode {
+    if config.developer_mode || args.calibrate {
         // Run as system tray application (developer mode)
         log("Developer mode enabled - running tray application");
-        run_tray_app()
+        run_tray_app(args.calibrate)
     } else {
         // Run GUI application (normal mode)
         log("Starting GUI application...");
-        match gui::run_gui() {
+        match gui::run_gui(args.iterations) {
             Ok(()) => {
                 log("GUI application exited normally");
                 Ok(())
@@ -172,8 +212,196 @@ fn main() -> Result<()> {
     }
 }
 
+/// Attaches to the console of the launching shell, if any, so CLI output is
+/// visible despite the `windows` subsystem (which has no console of its own).
+fn attach_parent_console() {
+    use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+    unsafe {
+        let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+/// `--analyze <dir>`: generates charts and statistics for one session folder.
+fn run_cli_analyze(session_dir: &Path) -> Result<()> {
+    if !session_dir.is_dir() {
+        return Err(anyhow!("Session folder not found: {}", session_dir.display()));
+    }
+    log(&format!("Generating analysis for {}", session_dir.display()));
+    let (chart_paths, json_path) = analysis::generate_analysis_for_session(session_dir, None)?;
+    log(&format!(
+        "Analysis complete: {} charts, statistics: {}",
+        chart_paths.len(),
+        json_path.display()
+    ));
+    Ok(())
+}
+
+/// `--compare <a> <b>`: compares session B with session A, writing
+/// comparison.json and comparison.png to B's folder.
+fn run_cli_compare(session_a: &Path, session_b: &Path) -> Result<()> {
+    let (json_path, chart_path) =
+        analysis::compare::compare_sessions(session_a, session_b, session_b)?;
+    log(&format!(
+        "Comparison complete: {}, chart: {}",
+        json_path.display(),
+        chart_path.display()
+    ));
+    Ok(())
+}
+
+/// `--review <dir>`: prompts for the nine scores of each flagged row of a
+/// session, opening its score crop for reference, and rewrites the CSVs with
+/// the typed rows marked `manual` (the CLI counterpart of the review window).
+fn run_cli_review(session_dir: &Path) -> Result<()> {
+    use automation::results_edit::{
+        load_review_rows, parse_manual_scores, save_review_rows, RECOVERY_FLAGGED,
+    };
+
+    let mut rows = load_review_rows(session_dir)?;
+    let flagged: Vec<usize> = (0..rows.len()).filter(|&i| rows[i].recovery == RECOVERY_FLAGGED).collect();
+    if flagged.is_empty() {
+        println!("No flagged rows in {}", session_dir.display());
+        return Ok(());
+    }
+
+    let config = automation::AutomationConfig {
+        capture_mode: automation::session_meta::session_capture_mode(session_dir),
+        ..automation::AutomationConfig::clone(&automation::get_config())
+    };
+    let crop_rect = automation::review_crop_rect(&config, 0)
+        .union(&automation::review_crop_rect(&config, 2));
+    println!(
+        "{} flagged row(s). Type the 9 scores (s1c1..s3c3, space or comma separated); \
+         leave blank to keep a row as it is, or type q to stop.",
+        flagged.len()
+    );
+
+    let stdin = std::io::stdin();
+    let mut changed = 0;
+    'rows: for (n, &i) in flagged.iter().enumerate() {
+        let row = &mut rows[i];
+        println!();
+        println!("[{}/{}] Iteration {}", n + 1, flagged.len(), row.iteration);
+        for (stage, scores) in row.scores.iter().enumerate() {
+            println!("  S{}: {} {} {}", stage + 1, scores[0], scores[1], scores[2]);
+        }
+        match image::open(&row.screenshot) {
+            Ok(img) => {
+                let crop = ocr::preprocess::crop_region(&img.to_rgba8(), &crop_rect);
+                if let Err(e) = calibration::preview::show_preview(&crop, "review_crop.png") {
+                    println!("  (Could not open the score crop: {})", e);
+                }
+            }
+            Err(e) => println!("  (Could not load {}: {})", row.screenshot, e),
+        }
+
+        loop {
+            print!("> ");
+            std::io::stdout().flush()?;
+            let mut line = String::new();
+            if stdin.read_line(&mut line)? == 0 || line.trim().eq_ignore_ascii_case("q") {
+                break 'rows;
+            }
+            match parse_manual_scores(&line) {
+                Ok(Some(scores)) => {
+                    row.set_manual_scores(scores);
+                    changed += 1;
+                    break;
+                }
+                Ok(None) => break,
+                Err(e) => println!("  {}; try again", e),
+            }
+        }
+    }
+
+    if changed > 0 {
+        let header = automation::csv_writer::results_header(&config);
+        save_review_rows(session_dir, &rows, header)?;
+    }
+    log(&format!(
+        "Manual entry: {} row(s) marked manual in {}",
+        changed,
+        session_dir.display()
+    ));
+    Ok(())
+}
+
+/// `--no-gui`: runs automation headless and blocks until it finishes.
+///
+/// Progress is printed from the run's event stream. Returns an error
+/// (non-zero exit code) unless every requested run completed.
+fn run_headless(iterations: Option<u32>) -> Result<()> {
+    use automation::runner::AutomationOutcome;
+    use automation::state::AutomationState;
+    use automation::AutomationEvent;
+
+    let (events, receiver) = std::sync::mpsc::channel();
+    automation::start_automation(iterations, true, Some(events))?;
+
+    // The channel closes after `Finished`
+    let mut outcome = None;
+    for event in receiver {
+        match event {
+            AutomationEvent::StateChanged { state, description } => {
+                if matches!(state, AutomationState::Error(_) | AutomationState::Aborted) {
+                    println!("{}", description);
+                }
+            }
+            AutomationEvent::IterationStarted { iteration, total } => {
+                println!("Iteration {}/{}", iteration, total);
+            }
+            AutomationEvent::IterationCompleted { iteration, completed, total } => {
+                println!("Iteration {} captured ({}/{} done)", iteration, completed, total);
+            }
+            AutomationEvent::Scores { iteration, scores, flagged } => {
+                let flag = if flagged { " (flagged)" } else { "" };
+                println!("Iteration {} scores: {:?}{}", iteration, scores, flag);
+            }
+            AutomationEvent::Finished(finished) => outcome = Some(finished),
+        }
+    }
+    while automation::is_automation_running() {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+
+    let (completed, result) = match outcome {
+        Some(AutomationOutcome::Completed { completed, total }) => {
+            log(&format!("Headless run completed: {}/{} iterations", completed, total));
+            (completed, Ok(()))
+        }
+        Some(AutomationOutcome::Aborted { completed, total }) => (
+            completed,
+            Err(anyhow!("Automation aborted after {}/{} iterations", completed, total)),
+        ),
+        Some(AutomationOutcome::Error { completed, total, message }) => (
+            completed,
+            Err(anyhow!(
+                "Automation failed after {}/{} iterations: {}",
+                completed, total, message
+            )),
+        ),
+        None => (0, Err(anyhow!("Automation stopped without reporting an outcome"))),
+    };
+
+    // Same as the GUI: analyze whatever was captured, even on a partial run.
+    if completed > 0
+        && let Some(session_dir) = automation::runner::get_current_session_path()
+        && let Err(e) = run_cli_analyze(&session_dir)
+    {
+        log(&format!("Failed to generate charts: {}", e));
+    }
+
+    if let Err(e) = &result {
+        log(&format!("{}", e));
+    }
+    result
+}
+
 /// Runs the main system tray application with hotkey handling.
-fn run_tray_app() -> Result<()> {
+///
+/// With `start_calibration` set (the `--calibrate` flag), the calibration
+/// wizard starts as soon as the hotkey window exists.
+fn run_tray_app(start_calibration: bool) -> Result<()> {
     // Create hidden window for message handling
     let hwnd = create_message_window()?;
     unsafe { MAIN_HWND = hwnd };
@@ -261,6 +489,10 @@ fn run_tray_app() -> Result<()> {
     log("Hotkey: Ctrl+Shift+F12 (relative click test - MOVES CURSOR)");
     log("Right-click tray icon to exit");
 
+    if start_calibration && let Err(e) = calibration::start_calibration(hwnd) {
+        log(&format!("Failed to start calibration: {}", e));
+    }
+
     // Message loop
     let mut msg = MSG::default();
     unsafe {
@@ -342,7 +574,9 @@ unsafe extern "system" fn window_proc(
                     return LRESULT(0);
                 }
 
-                // Normal hotkeys
+                // Normal hotkeys. Keep these as thin dispatchers: capture lives in
+                // capture::screenshot and the click tests in automation::input, so
+                // fixes there apply to the tray app, the GUI and headless runs alike.
                 if hotkey_id == HOTKEY_ID {
                     log("Hotkey pressed! Capturing...");
                     match capture::capture_gakumas() {
@@ -366,8 +600,7 @@ unsafe extern "system" fn window_proc(
                     let config = automation::get_config();
                     match capture::find_gakumas_window() {
                         Ok(game_hwnd) => {
-                            let pos = &config.test_click_position;
-                            match automation::click_at_relative(game_hwnd, pos.x, pos.y) {
+                            match automation::click_button(game_hwnd, &config.test_click_position, config.foreground_mode) {
                                 Ok(()) => log("Relative click test completed"),
                                 Err(e) => log(&format!("Relative click test failed: {}", e)),
                             }
@@ -380,7 +613,7 @@ unsafe extern "system" fn window_proc(
                     match capture::find_gakumas_window() {
                         Ok(game_hwnd) => {
                             log("Capturing region for brightness test...");
-                            match automation::measure_region_brightness(game_hwnd, config) {
+                            match automation::measure_region_brightness(game_hwnd, &config) {
                                 Ok(brightness) => {
                                     log(&format!("Region brightness: {:.2}", brightness));
                                     log(&format!(
@@ -395,6 +628,20 @@ unsafe extern "system" fn window_proc(
                                 }
                                 Err(e) => log(&format!("Brightness test failed: {}", e)),
                             }
+                            let pages = [
+                                ("Rehearsal page", automation::is_rehearsal_page(game_hwnd, &config)),
+                                ("Result page", automation::is_result_page(game_hwnd, &config)),
+                            ];
+                            for (name, on_page) in pages {
+                                match on_page {
+                                    Ok(on_page) => log(&format!(
+                                        "{}: {}",
+                                        name,
+                                        if on_page { "DETECTED" } else { "not detected" }
+                                    )),
+                                    Err(e) => log(&format!("{} check failed: {}", name, e)),
+                                }
+                            }
                         }
                         Err(e) => log(&format!("Could not find game window: {}", e)),
                     }
@@ -403,7 +650,7 @@ unsafe extern "system" fn window_proc(
                     if automation::is_automation_running() {
                         log("Automation is already running");
                     } else {
-                        match automation::start_automation(None) {
+                        match automation::start_automation(None, true, None) {
                             Ok(()) => {} // Logging handled by start_automation
                             Err(e) => log(&format!("Failed to start automation: {}", e)),
                         }
@@ -569,18 +816,8 @@ fn show_context_menu(hwnd: HWND) {
 
 /// Tests OCR on the current game window screenshot
 fn test_ocr() {
-    // Find game window
-    let game_hwnd = match capture::find_gakumas_window() {
-        Ok(hwnd) => hwnd,
-        Err(e) => {
-            log(&format!("Could not find game window: {}", e));
-            return;
-        }
-    };
-
-    // Capture screenshot
     log("Capturing screenshot for OCR...");
-    let img = match capture::capture_window_to_image(game_hwnd) {
+    let img = match capture::capture_game_to_buffer() {
         Ok(img) => img,
         Err(e) => {
             log(&format!("Failed to capture screenshot: {}", e));
@@ -637,7 +874,7 @@ fn capture_start_reference() {
     let ref_path = paths::get_rehearsal_template_dir().join("start_button_ref.png");
 
     // Capture and save
-    match automation::save_start_button_reference(game_hwnd, config, &ref_path) {
+    match automation::save_start_button_reference(game_hwnd, &config, &ref_path) {
         Ok(()) => {
             log(&format!(
                 "Start button reference saved to {}",
@@ -669,7 +906,7 @@ fn capture_skip_reference() {
     let ref_path = paths::get_rehearsal_template_dir().join("skip_button_ref.png");
 
     // Capture and save
-    match automation::save_skip_button_reference(game_hwnd, config, &ref_path) {
+    match automation::save_skip_button_reference(game_hwnd, &config, &ref_path) {
         Ok(()) => {
             log(&format!(
                 "Skip button reference saved to {}",
@@ -701,7 +938,7 @@ fn capture_end_reference() {
     let ref_path = paths::get_rehearsal_template_dir().join("end_button_ref.png");
 
     // Capture and save
-    match automation::save_end_button_reference(game_hwnd, config, &ref_path) {
+    match automation::save_end_button_reference(game_hwnd, &config, &ref_path) {
         Ok(()) => {
             log(&format!(
                 "End button reference saved to {}",
@@ -717,7 +954,7 @@ fn capture_end_reference() {
 
 /// Generates statistics charts from the results CSV file.
 fn generate_charts() {
-    match analysis::generate_analysis() {
+    match analysis::generate_analysis(None) {
         Ok((chart_paths, json_path)) => {
             log("Charts generated successfully!");
             for path in &chart_paths {
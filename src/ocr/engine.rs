@@ -1,17 +1,25 @@
 use anyhow::{anyhow, Result};
 use image::{ImageBuffer, Luma};
+use std::ffi::OsString;
+use std::path::Path;
 use std::process::Command;
+use std::sync::atomic::{AtomicU32, Ordering};
 use tempfile::NamedTempFile;
 
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
 
 use super::setup::{find_tesseract_executable, find_tessdata_dir};
+use crate::automation::config::{AutomationConfig, OcrBackend};
 
 /// Windows flag to prevent console window from appearing
 #[cfg(windows)]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
+/// Numbers each process-backend run so concurrent callers (the OCR worker and
+/// the automation thread's confidence pre-check) never share an output file.
+static OUTPUT_SEQ: AtomicU32 = AtomicU32::new(0);
+
 /// Represents a line of OCR text with confidence score
 #[derive(Debug, Clone)]
 pub struct OcrLine {
@@ -29,7 +37,57 @@ pub struct OcrWord {
 
 /// Runs Tesseract on a preprocessed grayscale image.
 /// Returns structured output with lines and confidence scores.
-pub fn recognize_image(img: &ImageBuffer<Luma<u8>, Vec<u8>>) -> Result<Vec<OcrLine>> {
+///
+/// Uses `config.ocr_psm` (default 6, a single uniform block of text).
+pub fn recognize_image(
+    img: &ImageBuffer<Luma<u8>, Vec<u8>>,
+    config: &AutomationConfig,
+) -> Result<Vec<OcrLine>> {
+    let psm = config.ocr_psm.to_string();
+    let tsv_content = recognize_tsv(img, &psm, None, "tesseract_out", config.ocr_backend)?;
+    parse_tsv_output(&tsv_content)
+}
+
+/// Runs Tesseract on `img` with page-segmentation mode `psm` (and, if given, a
+/// character whitelist) and returns its TSV output.
+///
+/// Uses `backend` (the configured `ocr_backend`). `InProcess` needs the
+/// `tesseract-inprocess` feature; without it the process backend is used and
+/// a warning is logged once.
+fn recognize_tsv(
+    img: &ImageBuffer<Luma<u8>, Vec<u8>>,
+    psm: &str,
+    whitelist: Option<&str>,
+    output_name: &str,
+    backend: OcrBackend,
+) -> Result<String> {
+    if backend == OcrBackend::InProcess {
+        #[cfg(feature = "tesseract-inprocess")]
+        return super::inprocess::recognize_tsv(img, psm, whitelist);
+        #[cfg(not(feature = "tesseract-inprocess"))]
+        {
+            use std::sync::atomic::AtomicBool;
+            static FALLBACK_LOGGED: AtomicBool = AtomicBool::new(false);
+            if !FALLBACK_LOGGED.swap(true, Ordering::SeqCst) {
+                crate::log(
+                    "OCR: ocr_backend InProcess requires a build with the tesseract-inprocess \
+                     feature; using the tesseract.exe process backend",
+                );
+            }
+        }
+    }
+    recognize_tsv_process(img, psm, whitelist, output_name)
+}
+
+/// Process backend: writes `img` to a temp PNG and runs `tesseract.exe` on it
+/// with TSV output. `output_name` prefixes the temp output file, which is
+/// unique per call.
+fn recognize_tsv_process(
+    img: &ImageBuffer<Luma<u8>, Vec<u8>>,
+    psm: &str,
+    whitelist: Option<&str>,
+    output_name: &str,
+) -> Result<String> {
     let tesseract_exe = find_tesseract_executable()?;
     let tessdata_dir = find_tessdata_dir()?;
 
@@ -40,24 +98,16 @@ pub fn recognize_image(img: &ImageBuffer<Luma<u8>, Vec<u8>>) -> Result<Vec<OcrLi
     // Create a unique output base path for Tesseract
     // Tesseract will append .tsv to this path
     let temp_dir = std::env::temp_dir();
+    let seq = OUTPUT_SEQ.fetch_add(1, Ordering::Relaxed);
     let output_base = temp_dir
-        .join(format!("tesseract_out_{}", std::process::id()))
+        .join(format!("{}_{}_{}", output_name, std::process::id(), seq))
         .to_string_lossy()
         .to_string();
 
     // Run Tesseract with TSV output for structured data
     // Use -c tessedit_create_tsv=1 instead of "tsv" config file
     let mut cmd = Command::new(&tesseract_exe);
-    cmd.arg(temp_input.path())
-        .arg(&output_base)
-        .arg("--tessdata-dir")
-        .arg(&tessdata_dir)
-        .arg("-l")
-        .arg("eng")
-        .arg("--psm")
-        .arg("6") // Assume single uniform block of text
-        .arg("-c")
-        .arg("tessedit_create_tsv=1");
+    cmd.args(tesseract_tsv_args(temp_input.path(), &output_base, &tessdata_dir, psm, whitelist));
 
     // Prevent console window from appearing on Windows
     #[cfg(windows)]
@@ -91,11 +141,42 @@ pub fn recognize_image(img: &ImageBuffer<Luma<u8>, Vec<u8>>) -> Result<Vec<OcrLi
     // Clean up output file
     let _ = std::fs::remove_file(&tsv_path);
 
-    // Parse TSV output
-    parse_tsv_output(&tsv_content)
+    Ok(tsv_content)
+}
+
+/// Builds the `tesseract.exe` argument list for a TSV run of the process
+/// backend. Split out of `recognize_tsv_process` so the arguments can be
+/// checked without running Tesseract.
+fn tesseract_tsv_args(
+    input: &Path,
+    output_base: &str,
+    tessdata_dir: &Path,
+    psm: &str,
+    whitelist: Option<&str>,
+) -> Vec<OsString> {
+    let mut args: Vec<OsString> = vec![
+        input.into(),
+        output_base.into(),
+        "--tessdata-dir".into(),
+        tessdata_dir.into(),
+        "-l".into(),
+        "eng".into(),
+        "--psm".into(),
+        psm.into(),
+    ];
+    if let Some(whitelist) = whitelist {
+        args.push("-c".into());
+        args.push(format!("tessedit_char_whitelist={}", whitelist).into());
+    }
+    args.push("-c".into());
+    args.push("tessedit_create_tsv=1".into());
+    args
 }
 
-/// Parses Tesseract TSV output into structured OcrLine data
+/// Parses Tesseract TSV output into structured OcrLine data.
+///
+/// The CLI writes a header row and the API's `GetTSVText` does not; either way
+/// only word rows (level 5) are used, so both parse identically.
 fn parse_tsv_output(tsv: &str) -> Result<Vec<OcrLine>> {
     let mut lines: Vec<OcrLine> = Vec::new();
     let mut current_line_num: i32 = -1;
@@ -103,8 +184,7 @@ fn parse_tsv_output(tsv: &str) -> Result<Vec<OcrLine>> {
     let mut current_conf_sum: f32 = 0.0;
     let mut current_word_count: usize = 0;
 
-    for line in tsv.lines().skip(1) {
-        // Skip header
+    for line in tsv.lines() {
         let fields: Vec<&str> = line.split('\t').collect();
         if fields.len() < 12 {
             continue;
@@ -189,60 +269,16 @@ fn parse_tsv_output(tsv: &str) -> Result<Vec<OcrLine>> {
 
 /// Runs Tesseract on a cropped score-row image.
 ///
-/// Uses --psm 6 (block of text) for proper word segmentation when multiple
-/// numbers are present in the cropped region. No character whitelist is used;
-/// the crop itself limits noise, and downstream regex filtering handles the rest.
-pub fn recognize_image_line(img: &ImageBuffer<Luma<u8>, Vec<u8>>) -> Result<Vec<OcrLine>> {
-    let tesseract_exe = find_tesseract_executable()?;
-    let tessdata_dir = find_tessdata_dir()?;
-
-    // Save image to temporary file
-    let temp_input = NamedTempFile::with_suffix(".png")?;
-    img.save(temp_input.path())?;
-
-    let temp_dir = std::env::temp_dir();
-    let output_base = temp_dir
-        .join(format!("tesseract_line_{}", std::process::id()))
-        .to_string_lossy()
-        .to_string();
-
-    let mut cmd = Command::new(&tesseract_exe);
-    cmd.arg(temp_input.path())
-        .arg(&output_base)
-        .arg("--tessdata-dir")
-        .arg(&tessdata_dir)
-        .arg("-l")
-        .arg("eng")
-        .arg("--psm")
-        .arg("6") // Block of text — better word segmentation for multiple numbers
-        .arg("-c")
-        .arg("tessedit_create_tsv=1");
-
-    #[cfg(windows)]
-    cmd.creation_flags(CREATE_NO_WINDOW);
-
-    let output = cmd.output()?;
-
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    if !stderr.is_empty() {
-        crate::log(&format!("Tesseract stderr: {}", stderr.trim()));
-    }
-
-    if !output.status.success() {
-        return Err(anyhow!("Tesseract failed with exit code {:?}: {}", output.status.code(), stderr));
-    }
-
-    let tsv_path = format!("{}.tsv", output_base);
-    let tsv_content = match std::fs::read_to_string(&tsv_path) {
-        Ok(content) => content,
-        Err(e) => {
-            crate::log(&format!("Expected TSV path: {}", tsv_path));
-            return Err(anyhow!("Failed to read Tesseract output at {}: {}", tsv_path, e));
-        }
-    };
-
-    let _ = std::fs::remove_file(&tsv_path);
-
+/// Uses `config.ocr_psm` (default 6, block of text) for proper word
+/// segmentation when multiple numbers are present in the cropped region. No
+/// character whitelist is used; the crop itself limits noise, and downstream
+/// regex filtering handles the rest.
+pub fn recognize_image_line(
+    img: &ImageBuffer<Luma<u8>, Vec<u8>>,
+    config: &AutomationConfig,
+) -> Result<Vec<OcrLine>> {
+    let psm = config.ocr_psm.to_string();
+    let tsv_content = recognize_tsv(img, &psm, None, "tesseract_line", config.ocr_backend)?;
     parse_tsv_output(&tsv_content)
 }
 
@@ -269,57 +305,10 @@ pub fn recognize_single_number(
     img: &ImageBuffer<Luma<u8>, Vec<u8>>,
     whitelist: &str,
     anchor_plus: bool,
+    backend: OcrBackend,
 ) -> Result<Option<u32>> {
-    let tesseract_exe = find_tesseract_executable()?;
-    let tessdata_dir = find_tessdata_dir()?;
-
-    let temp_input = NamedTempFile::with_suffix(".png")?;
-    img.save(temp_input.path())?;
-
-    let temp_dir = std::env::temp_dir();
-    let output_base = temp_dir
-        .join(format!("tesseract_num_{}", std::process::id()))
-        .to_string_lossy()
-        .to_string();
-
-    let mut cmd = Command::new(&tesseract_exe);
-    cmd.arg(temp_input.path())
-        .arg(&output_base)
-        .arg("--tessdata-dir")
-        .arg(&tessdata_dir)
-        .arg("-l")
-        .arg("eng")
-        .arg("--psm")
-        .arg("7") // Single text line
-        .arg("-c")
-        .arg(format!("tessedit_char_whitelist={}", whitelist))
-        .arg("-c")
-        .arg("tessedit_create_tsv=1");
-
-    #[cfg(windows)]
-    cmd.creation_flags(CREATE_NO_WINDOW);
-
-    let output = cmd.output()?;
-
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    if !stderr.is_empty() {
-        crate::log(&format!("Tesseract stderr: {}", stderr.trim()));
-    }
-
-    if !output.status.success() {
-        return Err(anyhow!("Tesseract failed with exit code {:?}: {}", output.status.code(), stderr));
-    }
-
-    let tsv_path = format!("{}.tsv", output_base);
-    let tsv_content = match std::fs::read_to_string(&tsv_path) {
-        Ok(content) => content,
-        Err(e) => {
-            crate::log(&format!("Expected TSV path: {}", tsv_path));
-            return Err(anyhow!("Failed to read Tesseract output at {}: {}", tsv_path, e));
-        }
-    };
-
-    let _ = std::fs::remove_file(&tsv_path);
+    // Single text line
+    let tsv_content = recognize_tsv(img, "7", Some(whitelist), "tesseract_num", backend)?;
 
     let lines = parse_tsv_output(&tsv_content)?;
     let raw: String = lines
@@ -436,7 +425,31 @@ pub fn recognize_image_simple(img: &ImageBuffer<Luma<u8>, Vec<u8>>) -> Result<St
 
 #[cfg(test)]
 mod tests {
-    use super::{longest_digit_run, parse_single_number};
+    use super::{longest_digit_run, parse_single_number, parse_tsv_output, tesseract_tsv_args};
+    use crate::automation::config::AutomationConfig;
+    use std::ffi::OsString;
+    use std::path::Path;
+
+    fn psm_arg(args: &[OsString]) -> Option<&OsString> {
+        let idx = args.iter().position(|a| a == "--psm")?;
+        args.get(idx + 1)
+    }
+
+    #[test]
+    fn test_psm_arg_reflects_config() {
+        let default_psm = AutomationConfig::default().ocr_psm.to_string();
+        let args = tesseract_tsv_args(Path::new("in.png"), "out", Path::new("tessdata"), &default_psm, None);
+        assert_eq!(psm_arg(&args), Some(&OsString::from("6")));
+
+        let config = AutomationConfig {
+            ocr_psm: 11,
+            ..AutomationConfig::default()
+        };
+        let psm = config.ocr_psm.to_string();
+        let args = tesseract_tsv_args(Path::new("in.png"), "out", Path::new("tessdata"), &psm, Some("0123456789"));
+        assert_eq!(psm_arg(&args), Some(&OsString::from("11")));
+        assert!(args.iter().any(|a| a == "tessedit_char_whitelist=0123456789"));
+    }
 
     #[test]
     fn test_total_parsing() {
@@ -480,6 +493,23 @@ mod tests {
         assert_eq!(parse_single_number("+", true), None);
     }
 
+    #[test]
+    fn test_parse_tsv_with_or_without_header() {
+        // CLI output starts with a header row; the in-process API omits it.
+        let header = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\t\
+                      left\ttop\twidth\theight\tconf\ttext\n";
+        let body = "1\t1\t0\t0\t0\t0\t0\t0\t100\t20\t-1\t\n\
+                    5\t1\t1\t1\t1\t1\t0\t0\t40\t20\t90.5\t576,880\n\
+                    5\t1\t1\t1\t1\t2\t50\t0\t40\t20\t80.5\t1,193,622\n";
+        for tsv in [format!("{}{}", header, body), body.to_string()] {
+            let lines = parse_tsv_output(&tsv).unwrap();
+            assert_eq!(lines.len(), 1);
+            assert_eq!(lines[0].text, "576,880 1,193,622");
+            assert_eq!(lines[0].words.len(), 2);
+            assert_eq!(lines[0].confidence, 85.5);
+        }
+    }
+
     #[test]
     fn test_longest_digit_run() {
         assert_eq!(longest_digit_run("2,744,700"), "2744700");
@@ -2,15 +2,21 @@ use anyhow::{anyhow, Result};
 use regex::Regex;
 
 use super::engine::OcrLine;
+use crate::automation::config::AutomationConfig;
 use crate::log;
 
 /// Pattern to match score-like words:
 /// - Plain numbers: 12345
 /// - Numbers with comma separators: 12,345 or 1,234,567
 /// - Numbers with period separators: 12.345
+/// - Full-width digits and separators, alone or mixed: １２，３４５ or １２,345
+/// - A space inside a number, before a 3-digit group: 12 345 or 12, 345
 /// - Dashes: -- or — or ー or 一 or – or ― or ─ (indicating zero or missing score)
+///
+/// Digits are spelled out as `[0-9０-９]` rather than `\d`, which would also
+/// accept every other Unicode digit script.
 const SCORE_PATTERN: &str =
-    r"^((\d+[,.])*\d+|[\-\u{2014}\u{2013}\u{2015}\u{2500}\u{30FC}\u{4E00}]+)$";
+    r"^([0-9０-９]+(?:[,.，．] ?[0-9０-９]+| [0-9０-９]{3})*|[\-\u{2014}\u{2013}\u{2015}\u{2500}\u{30FC}\u{4E00}]+)$";
 
 /// Pattern to extract individual score tokens from a whole line of OCR text.
 ///
@@ -48,13 +54,24 @@ const SCORE_PATTERN: &str =
 ///
 /// This assumes scores are rendered with thousand separators, which the game
 /// always does. A genuinely comma-less number (not observed in practice) would
-/// be over-split into 3-digit chunks.
+/// be over-split into 3-digit chunks. A separator read as a space ("12 345")
+/// is turned back into a comma by `join_spaced_groups` before matching.
 const SCORE_TOKEN_PATTERN: &str =
     r"[1-9][,.]\d{3}[,.]\d{3}|\d{1,3}(?:[,.]\d{3})?|[\-\u{2014}\u{2013}\u{2015}\u{2500}\u{30FC}\u{4E00}]+";
 
-/// Minimum confidence threshold for accepting OCR lines
+/// Minimum confidence threshold for accepting OCR lines (and the default for
+/// per-stage words, see [`ScoreBounds`])
 const MIN_CONFIDENCE: f32 = 60.0;
 
+/// Number of character slots stored per stage. Formations with fewer idols use
+/// a prefix of these slots; the rest are padded with 0.
+pub const MAX_CHARACTERS_PER_STAGE: usize = 3;
+
+/// Clamps a configured per-stage character count into `1..=MAX_CHARACTERS_PER_STAGE`.
+pub fn clamp_characters_per_stage(n: usize) -> usize {
+    n.clamp(1, MAX_CHARACTERS_PER_STAGE)
+}
+
 /// Returns true if the character is a dash-like character used for missing scores.
 fn is_dash_char(c: char) -> bool {
     matches!(
@@ -68,6 +85,50 @@ fn is_dash_char(c: char) -> bool {
     )
 }
 
+/// Maps full-width digits (０-９) and separators (，．) to their ASCII forms,
+/// leaving every other character unchanged.
+fn normalize_full_width(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '０'..='９' => char::from_digit(c as u32 - '０' as u32, 10).unwrap_or(c),
+            '，' => ',',
+            '．' => '.',
+            _ => c,
+        })
+        .collect()
+}
+
+/// Rewrites thousands groups separated by a space as comma-separated ones, so
+/// `SCORE_TOKEN_PATTERN` sees one number: "12 345" -> "12,345",
+/// "1 234 567" -> "1,234,567" and "12, 345" -> "12,345".
+///
+/// A bare space only counts as a separator between a digit and exactly three
+/// digits that end the text or are followed by another space. A group that
+/// runs into a comma, as in "50 234,567" (a stray "50" beside a score), is
+/// already a whole score and is left alone.
+fn join_spaced_groups(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let three_digits =
+        |rest: &[char]| rest.len() >= 3 && rest[..3].iter().all(char::is_ascii_digit);
+    let mut out = String::with_capacity(text.len());
+    for (i, &c) in chars.iter().enumerate() {
+        if c == ' ' && i > 0 && three_digits(&chars[i + 1..]) {
+            let group_ends = chars.get(i + 4).is_none_or(|&next| next == ' ');
+            match chars[i - 1] {
+                // "12, 345": the separator is already there
+                ',' | '.' => continue,
+                prev if prev.is_ascii_digit() && group_ends => {
+                    out.push(',');
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        out.push(c);
+    }
+    out
+}
+
 /// Returns true if the text looks like a garbled/dropped dash from OCR.
 /// Matches short strings (1-3 chars) composed of common dash-like or
 /// OCR-misread characters (e.g., "I", "l", "|", "_", "~").
@@ -85,41 +146,152 @@ fn is_dash_like(text: &str) -> bool {
     })
 }
 
+/// Plausible range for a single per-character score.
+///
+/// Values outside `[min_valid_score, max_valid_score]` are discarded during
+/// per-stage extraction, as are tokens with more digits than `max_valid_score`
+/// itself has (e.g. a zero-padded misread). The defaults keep the historical
+/// `< 100` noise filter and only reject what the 7-digit token shape already
+/// rules out, so over-range-but-repairable readings (a leading "1" misread as
+/// "7") still reach `reconcile_stage`. Built from the `min_valid_score`,
+/// `max_valid_score` and `ocr_min_confidence` config fields.
+///
+/// Whole OCR words whose Tesseract confidence is below `min_confidence` are
+/// dropped before tokenizing, like the `MIN_CONFIDENCE` line filter of
+/// [`extract_scores`].
+#[derive(Clone, Copy, Debug)]
+pub struct ScoreBounds {
+    pub min_valid_score: u32,
+    pub max_valid_score: u32,
+    pub min_confidence: f32,
+}
+
+impl From<&AutomationConfig> for ScoreBounds {
+    fn from(config: &AutomationConfig) -> Self {
+        Self {
+            min_valid_score: config.min_valid_score,
+            max_valid_score: config.max_valid_score,
+            min_confidence: config.ocr_min_confidence,
+        }
+    }
+}
+
+impl Default for ScoreBounds {
+    fn default() -> Self {
+        Self::from(&AutomationConfig::default())
+    }
+}
+
+impl ScoreBounds {
+    /// Returns true if `token` (already parsed to `val`) is a plausible score.
+    fn accepts(&self, token: &str, val: u32) -> bool {
+        let digits = token.chars().filter(|c| c.is_ascii_digit()).count();
+        let max_digits = self.max_valid_score.to_string().len();
+        digits <= max_digits && (self.min_valid_score..=self.max_valid_score).contains(&val)
+    }
+}
+
+/// Extracts per-character scores from a single cropped stage region, using the
+/// default [`ScoreBounds`] and a three-character formation.
+#[cfg(test)]
+pub fn extract_single_stage(lines: &[OcrLine]) -> Result<[u32; 3]> {
+    extract_single_stage_bounded(lines, &ScoreBounds::default(), MAX_CHARACTERS_PER_STAGE)
+}
+
 /// Extracts per-character scores from a single cropped stage region.
 ///
-/// Re-tokenizes each OCR line's raw text with `SCORE_TOKEN_PATTERN` (rather than
-/// trusting Tesseract's word boundaries), filters out noise (scores < 100), and
-/// maps the tokens left-to-right. Scanning the line text recovers correct number
+/// Re-tokenizes the raw text of each line's words with `SCORE_TOKEN_PATTERN`
+/// (rather than trusting Tesseract's word boundaries, which also split a score
+/// whose separator was read as a space, see `join_spaced_groups`), skips words
+/// below `bounds.min_confidence`, filters out values outside `bounds`
+/// (noise below `min_valid_score` is skipped; a doubled read above
+/// `max_valid_score` keeps its slot as 0 so later scores stay in their
+/// columns), and maps the tokens left-to-right. Scanning the raw text recovers correct number
 /// boundaries when Tesseract glues a >= 1,000,000 score to its neighbor (see
 /// `SCORE_TOKEN_PATTERN`) and naturally skips any leading garbage Tesseract
 /// prepends (e.g. a stray `"` or `$`), since such characters simply fall outside
 /// the pattern. Since blank characters (ー) are always on the right side, missing
 /// slots are padded with 0 on the right.
 ///
+/// Only the first `characters_per_stage` (clamped to 1..=3) tokens are mapped,
+/// so a two-idol formation never picks up a stray third token; the unused
+/// trailing slots stay 0 exactly like a blank (ー) slot.
+///
 /// Returns an error if no scores are found (each stage has at least 1 character).
-pub fn extract_single_stage(lines: &[OcrLine]) -> Result<[u32; 3]> {
+pub fn extract_single_stage_bounded(
+    lines: &[OcrLine],
+    bounds: &ScoreBounds,
+    characters_per_stage: usize,
+) -> Result<[u32; 3]> {
+    let slots = clamp_characters_per_stage(characters_per_stage);
     let token_regex = Regex::new(SCORE_TOKEN_PATTERN)?;
 
     let mut scores: Vec<u32> = Vec::new();
+    let mut accepted = 0;
 
+    // Runs of confident words within a line, space-joined so a score split at
+    // a spaced separator ("12" "345") is scanned whole; a discarded word ends
+    // the run so its neighbours are never glued together.
+    let mut runs: Vec<String> = Vec::new();
     for line in lines {
-        for m in token_regex.find_iter(&line.text) {
+        let mut run = String::new();
+        for word in &line.words {
+            if word.confidence < bounds.min_confidence {
+                log(&format!(
+                    "Discarding low-confidence word '{}' ({:.1} < {:.1})",
+                    word.text, word.confidence, bounds.min_confidence
+                ));
+                runs.push(std::mem::take(&mut run));
+                continue;
+            }
+            if !run.is_empty() {
+                run.push(' ');
+            }
+            run.push_str(&word.text);
+        }
+        runs.push(run);
+    }
+
+    for run in runs.iter().filter(|run| !run.is_empty()) {
+        let text = join_spaced_groups(&normalize_full_width(run));
+        for m in token_regex.find_iter(&text) {
             let val = parse_score(m.as_str())?;
-            // Filter noise: real per-character scores are thousands+.
-            // val < 100 (including dashes, which parse to 0) is skipped.
-            if val >= 100 {
+            // Dashes parse to 0 and mark an empty slot; always skip them quietly.
+            if val == 0 {
+                continue;
+            }
+            if bounds.accepts(m.as_str(), val) {
                 scores.push(val);
+                accepted += 1;
+            } else if val < bounds.min_valid_score {
+                log(&format!(
+                    "Discarding score token '{}' ({}) as noise below {}",
+                    m.as_str(),
+                    val,
+                    bounds.min_valid_score
+                ));
+            } else {
+                // A misread score still occupies its character's slot; leave it
+                // 0 so later scores keep their columns and reconcile flags it.
+                log(&format!(
+                    "Discarding implausible score token '{}' ({}), outside {}..={}; slot left empty",
+                    m.as_str(),
+                    val,
+                    bounds.min_valid_score,
+                    bounds.max_valid_score
+                ));
+                scores.push(0);
             }
         }
     }
 
-    if scores.is_empty() {
+    if accepted == 0 {
         return Err(anyhow!("No scores found in cropped stage region"));
     }
 
     // Map left-to-right, pad missing positions with 0
     let mut result = [0u32; 3];
-    for (i, &s) in scores.iter().take(3).enumerate() {
+    for (i, &s) in scores.iter().take(slots).enumerate() {
         result[i] = s;
     }
 
@@ -132,20 +304,35 @@ pub fn extract_single_stage(lines: &[OcrLine]) -> Result<[u32; 3]> {
     Ok(result)
 }
 
-/// Extracts 9 scores from OCR output using pattern matching.
-/// Returns [[u32; 3]; 3] representing [stage][breakdown] scores.
+/// Extracts 9 scores from OCR output using pattern matching, assuming a
+/// three-character formation. See [`extract_scores_with_slots`].
+///
+/// Whole-screen fallback: the automation pipeline reads each stage region
+/// separately via [`extract_single_stage_bounded`].
+#[allow(dead_code)]
+pub fn extract_scores(lines: &[OcrLine]) -> Result<[[u32; 3]; 3]> {
+    extract_scores_with_slots(lines, MAX_CHARACTERS_PER_STAGE)
+}
+
+/// Extracts per-character scores for all three stages from OCR output using
+/// pattern matching. Returns [[u32; 3]; 3] representing [stage][breakdown]
+/// scores; with `characters_per_stage` below 3 the trailing slots are 0.
 ///
 /// Uses a multi-pass approach to handle cases where dash characters (ー)
 /// indicating missing scores are garbled or dropped by OCR:
-/// - Pass 1: Strict match (exactly 3 score words per line)
-/// - Pass 2: Accept lines with score words + dash-like short words (total >= 3)
-/// - Pass 3: Accept lines with 1-2 score words (dashes completely dropped), pad with 0
-pub fn extract_scores(lines: &[OcrLine]) -> Result<[[u32; 3]; 3]> {
+/// - Pass 1: Strict match (exactly `characters_per_stage` score words per line)
+/// - Pass 2: Accept lines with score words + dash-like short words (total >= slots)
+/// - Pass 3: Accept lines with fewer score words (dashes completely dropped), pad with 0
+pub fn extract_scores_with_slots(
+    lines: &[OcrLine],
+    characters_per_stage: usize,
+) -> Result<[[u32; 3]; 3]> {
+    let slots = clamp_characters_per_stage(characters_per_stage);
     let score_regex = Regex::new(SCORE_PATTERN)?;
     let mut scores: Vec<[u32; 3]> = Vec::new();
     let mut used_lines: Vec<usize> = Vec::new();
 
-    // Pass 1: Strict match - exactly 3 score words per line
+    // Pass 1: Strict match - exactly `slots` score words per line
     for (idx, line) in lines.iter().enumerate() {
         if line.confidence < MIN_CONFIDENCE {
             continue;
@@ -158,7 +345,7 @@ pub fn extract_scores(lines: &[OcrLine]) -> Result<[[u32; 3]; 3]> {
             .filter(|text| score_regex.is_match(text))
             .collect();
 
-        if score_words.len() != 3 {
+        if score_words.len() != slots {
             continue;
         }
 
@@ -184,7 +371,7 @@ pub fn extract_scores(lines: &[OcrLine]) -> Result<[[u32; 3]; 3]> {
         return Ok([scores[0], scores[1], scores[2]]);
     }
 
-    // Pass 2: Accept lines with score words + dash-like words (total >= 3)
+    // Pass 2: Accept lines with score words + dash-like words (total >= slots)
     log(&format!(
         "Pass 1 found {} stages, trying pass 2 (dash-like fallback)...",
         scores.len()
@@ -202,7 +389,7 @@ pub fn extract_scores(lines: &[OcrLine]) -> Result<[[u32; 3]; 3]> {
         let mut pos = 0;
 
         for word in &line.words {
-            if pos >= 3 {
+            if pos >= slots {
                 break;
             }
             let text = word.text.as_str();
@@ -216,7 +403,7 @@ pub fn extract_scores(lines: &[OcrLine]) -> Result<[[u32; 3]; 3]> {
             }
         }
 
-        if pos == 3 {
+        if pos == slots {
             log(&format!(
                 "Found score line (pass 2): {:?} (conf: {:.0}%)",
                 stage_scores, line.confidence
@@ -230,7 +417,7 @@ pub fn extract_scores(lines: &[OcrLine]) -> Result<[[u32; 3]; 3]> {
         return Ok([scores[0], scores[1], scores[2]]);
     }
 
-    // Pass 3: Accept lines with 1-2 score words (dashes completely dropped)
+    // Pass 3: Accept lines with 1..slots score words (dashes completely dropped)
     // Only look at lines after the last matched stage to reduce false positives
     let search_start = used_lines.iter().max().map(|&i| i + 1).unwrap_or(0);
 
@@ -255,8 +442,8 @@ pub fn extract_scores(lines: &[OcrLine]) -> Result<[[u32; 3]; 3]> {
             .filter(|text| score_regex.is_match(text))
             .collect();
 
-        // Accept lines with 1-2 score words - these are lines where dashes were dropped
-        if score_words.is_empty() || score_words.len() > 3 {
+        // Accept lines with fewer score words - these are lines where dashes were dropped
+        if score_words.is_empty() || score_words.len() > slots {
             continue;
         }
 
@@ -267,8 +454,9 @@ pub fn extract_scores(lines: &[OcrLine]) -> Result<[[u32; 3]; 3]> {
         // Remaining positions stay as 0 (missing dashes)
 
         log(&format!(
-            "Found score line (pass 3, {} of 3 words): {:?} (conf: {:.0}%)",
+            "Found score line (pass 3, {} of {} words): {:?} (conf: {:.0}%)",
             score_words.len(),
+            slots,
             stage_scores,
             line.confidence
         ));
@@ -288,7 +476,8 @@ pub fn extract_scores(lines: &[OcrLine]) -> Result<[[u32; 3]; 3]> {
 }
 
 /// Parses a single score string, removing commas, periods, and whitespace.
-/// Dashes are treated as zero.
+/// Full-width digits are read as their ASCII equivalents. Dashes are treated
+/// as zero.
 pub fn parse_score(text: &str) -> Result<u32> {
     // Handle dashes as zero (ASCII hyphen, em-dash, en-dash, horizontal bar,
     // box drawing horizontal, katakana prolonged sound mark, CJK unified ideograph "one")
@@ -299,8 +488,12 @@ pub fn parse_score(text: &str) -> Result<u32> {
         return Ok(0);
     }
 
-    // Remove all non-digit characters
-    let digits: String = text.chars().filter(|c| c.is_ascii_digit()).collect();
+    // Remove all non-digit characters (separators and internal spaces), after
+    // mapping full-width digits to ASCII
+    let digits: String = normalize_full_width(text)
+        .chars()
+        .filter(|c| c.is_ascii_digit())
+        .collect();
 
     if digits.is_empty() {
         return Err(anyhow!("No digits found in score: {}", text));
@@ -330,6 +523,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_score_bounds_default_follows_config() {
+        let bounds = ScoreBounds::default();
+        let config = AutomationConfig::default();
+        assert_eq!(bounds.min_valid_score, config.min_valid_score);
+        assert_eq!(bounds.max_valid_score, config.max_valid_score);
+        assert_eq!(bounds.min_confidence, config.ocr_min_confidence);
+
+        assert!(bounds.accepts("2,134,567", 2_134_567));
+        assert!(!bounds.accepts("12,345,678", 12_345_678));
+        assert!(!bounds.accepts("99", 99));
+    }
+
     #[test]
     fn test_parse_score() {
         assert_eq!(parse_score("12345").unwrap(), 12345);
@@ -347,6 +553,64 @@ mod tests {
         assert_eq!(parse_score("ーー").unwrap(), 0);
     }
 
+    #[test]
+    fn test_parse_score_full_width_and_spaces() {
+        assert_eq!(parse_score("１２３４５").unwrap(), 12345);
+        assert_eq!(parse_score("12 345").unwrap(), 12345);
+        assert_eq!(parse_score("１２,345").unwrap(), 12345);
+        assert_eq!(parse_score("１，２３４，５６７").unwrap(), 1234567);
+        assert_eq!(parse_score("1,2３4, 567").unwrap(), 1234567);
+        assert!(parse_score("ＡＢＣ").is_err());
+    }
+
+    #[test]
+    fn test_score_pattern_full_width_and_spaces() {
+        let re = Regex::new(SCORE_PATTERN).unwrap();
+        for word in ["１２３４５", "12 345", "１２,345", "１，２３４，５６７", "12, 345", "1 234 567"] {
+            assert!(re.is_match(word), "{} should match", word);
+        }
+        // Non-scores stay rejected: letters, a space before a short group,
+        // stray spaces, other digit scripts
+        for word in ["12a45", "12 34", " 12345", "12345 ", "12  345", "١٢٣٤٥"] {
+            assert!(!re.is_match(word), "{} should not match", word);
+        }
+    }
+
+    #[test]
+    fn test_join_spaced_groups() {
+        assert_eq!(join_spaced_groups("12 345"), "12,345");
+        assert_eq!(join_spaced_groups("1 234 567 12, 345"), "1,234,567 12,345");
+        // A 3-digit group running into a comma is a score of its own
+        assert_eq!(join_spaced_groups("50 234,567"), "50 234,567");
+        // Not a group: too short, too long
+        assert_eq!(join_spaced_groups("12 34 5678"), "12 34 5678");
+    }
+
+    #[test]
+    fn test_extract_single_stage_spaced_digits() {
+        // The separator read as a space splits each score into two words.
+        let lines = vec![make_line(&["12", "345", "1", "234", "567", "23,", "456"], 90.0)];
+        let result = extract_single_stage_bounded(&lines, &ScoreBounds::default(), 3).unwrap();
+        assert_eq!(result, [12345, 1234567, 23456]);
+
+        // Or stays inside one word
+        let lines = vec![make_line(&["12 345", "23 456"], 90.0)];
+        let result = extract_single_stage_bounded(&lines, &ScoreBounds::default(), 3).unwrap();
+        assert_eq!(result, [12345, 23456, 0]);
+
+        // Noise beside a comma-separated score is still dropped
+        let lines = vec![make_line(&["50", "234,567", "345,678"], 90.0)];
+        let result = extract_single_stage_bounded(&lines, &ScoreBounds::default(), 3).unwrap();
+        assert_eq!(result, [234567, 345678, 0]);
+    }
+
+    #[test]
+    fn test_extract_single_stage_full_width_digits() {
+        let lines = vec![make_line(&["１２，３４５", "23,４56", "３４．５６７"], 90.0)];
+        let result = extract_single_stage(&lines).unwrap();
+        assert_eq!(result, [12345, 23456, 34567]);
+    }
+
     #[test]
     fn test_is_dash_like() {
         assert!(is_dash_like("I"));
@@ -428,6 +692,100 @@ mod tests {
         assert_eq!(result, [283399, 1018192, 319495]);
     }
 
+    #[test]
+    fn test_extract_single_stage_bounds_drop_low_noise() {
+        let bounds = ScoreBounds {
+            min_valid_score: 1_000,
+            max_valid_score: 9_999_999,
+            ..ScoreBounds::default()
+        };
+        let lines = vec![make_line(&["12,345", "512", "23,456"], 90.0)];
+        let result = extract_single_stage_bounded(&lines, &bounds, 3).unwrap();
+        assert_eq!(result, [12345, 23456, 0]);
+    }
+
+    #[test]
+    fn test_extract_single_stage_bounds_drop_high_garbage() {
+        // A tightened cap rejects the glued million score instead of mapping
+        // it; its slot stays empty so the next score keeps its column.
+        let bounds = ScoreBounds {
+            min_valid_score: 100,
+            max_valid_score: 999_999,
+            ..ScoreBounds::default()
+        };
+        let lines = vec![make_line(&["576,8801,193,622", "213,607"], 90.0)];
+        let result = extract_single_stage_bounded(&lines, &bounds, 3).unwrap();
+        assert_eq!(result, [576880, 0, 213607]);
+    }
+
+    #[test]
+    fn test_extract_single_stage_bounds_drop_implausible_digit_count() {
+        // "001,234" is in range by value but has more digits than the cap allows.
+        let bounds = ScoreBounds {
+            min_valid_score: 100,
+            max_valid_score: 9_999,
+            ..ScoreBounds::default()
+        };
+        let lines = vec![make_line(&["001,234", "5,678"], 90.0)];
+        let result = extract_single_stage_bounded(&lines, &bounds, 3).unwrap();
+        assert_eq!(result, [0, 5678, 0]);
+    }
+
+    #[test]
+    fn test_extract_single_stage_low_confidence_word_skipped() {
+        // A stray low-confidence blob must not take a slot from the real scores.
+        let lines = vec![OcrLine {
+            text: "812 12,345 23,456".to_string(),
+            words: vec![
+                OcrWord { text: "812".to_string(), confidence: 31.0 },
+                OcrWord { text: "12,345".to_string(), confidence: 92.0 },
+                OcrWord { text: "23,456".to_string(), confidence: 88.0 },
+            ],
+            confidence: 70.3,
+        }];
+        let result = extract_single_stage(&lines).unwrap();
+        assert_eq!(result, [12345, 23456, 0]);
+
+        let bounds = ScoreBounds { min_confidence: 0.0, ..ScoreBounds::default() };
+        let result = extract_single_stage_bounded(&lines, &bounds, 3).unwrap();
+        assert_eq!(result, [812, 12345, 23456]);
+    }
+
+    #[test]
+    fn test_extract_single_stage_all_out_of_bounds_error() {
+        let bounds = ScoreBounds {
+            min_valid_score: 100,
+            max_valid_score: 999,
+            ..ScoreBounds::default()
+        };
+        let lines = vec![make_line(&["12,345", "23,456"], 90.0)];
+        assert!(extract_single_stage_bounded(&lines, &bounds, 3).is_err());
+    }
+
+    #[test]
+    fn test_extract_single_stage_two_character_formation() {
+        // A stray third token (e.g. a leaked digit) must not fill a slot that
+        // does not exist in a two-idol formation.
+        let lines = vec![make_line(&["12,345", "23,456", "4,321"], 90.0)];
+        let result = extract_single_stage_bounded(&lines, &ScoreBounds::default(), 2).unwrap();
+        assert_eq!(result, [12345, 23456, 0]);
+    }
+
+    #[test]
+    fn test_extract_single_stage_two_character_formation_no_dash() {
+        // No trailing dash is printed for the absent slot; padding is unchanged.
+        let lines = vec![make_line(&["12,345", "23,456"], 90.0)];
+        let result = extract_single_stage_bounded(&lines, &ScoreBounds::default(), 2).unwrap();
+        assert_eq!(result, [12345, 23456, 0]);
+    }
+
+    #[test]
+    fn test_clamp_characters_per_stage() {
+        assert_eq!(clamp_characters_per_stage(0), 1);
+        assert_eq!(clamp_characters_per_stage(2), 2);
+        assert_eq!(clamp_characters_per_stage(5), 3);
+    }
+
     // --- Overlap re-split (M1) tests on the four real OCR line strings. ---
     //
     // These assert the *raw split* values that the capped SCORE_TOKEN_PATTERN
@@ -546,6 +904,20 @@ mod tests {
         assert_eq!(scores[2], [122130, 105901, 96776]);
     }
 
+    #[test]
+    fn test_extract_scores_full_width_and_spaced() {
+        let lines = vec![
+            make_line(&["５０，３３９", "50,796", "70,859"], 90.0),
+            make_line(&["64 997", "１６８００９", "128,450"], 90.0),
+            make_line(&["122,130", "105,９01", "ー"], 90.0),
+        ];
+
+        let scores = extract_scores(&lines).unwrap();
+        assert_eq!(scores[0], [50339, 50796, 70859]);
+        assert_eq!(scores[1], [64997, 168009, 128450]);
+        assert_eq!(scores[2], [122130, 105901, 0]);
+    }
+
     #[test]
     fn test_extract_scores_with_japanese_dashes() {
         // Katakana prolonged sound mark ー recognized as score pattern
@@ -623,4 +995,20 @@ mod tests {
         assert_eq!(scores[1], [0, 168009, 0]);
         assert_eq!(scores[2], [96776, 0, 0]);
     }
+
+    #[test]
+    fn test_extract_scores_two_character_formation() {
+        // Two-idol formation: every score line has exactly two words and no
+        // trailing dash; pass 1 must accept them as complete stages.
+        let lines = vec![
+            make_line(&["Stage", "1"], 90.0),
+            make_line(&["12,345", "23,456"], 90.0),
+            make_line(&["34,567", "45,678"], 90.0),
+            make_line(&["56,789", "67,890"], 90.0),
+        ];
+        let scores = extract_scores_with_slots(&lines, 2).unwrap();
+        assert_eq!(scores[0], [12345, 23456, 0]);
+        assert_eq!(scores[1], [34567, 45678, 0]);
+        assert_eq!(scores[2], [56789, 67890, 0]);
+    }
 }
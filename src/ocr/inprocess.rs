@@ -0,0 +1,60 @@
+//! In-process Tesseract backend (`ocr_backend: InProcess`, built with the
+//! `tesseract-inprocess` feature).
+//!
+//! Instead of spawning `tesseract.exe` per crop, each OCR thread initializes one
+//! engine on first use and reuses it for every later call. Output is the same
+//! TSV the process backend reads back, so both produce identical `OcrLine`s.
+
+use std::cell::RefCell;
+use std::io::Cursor;
+
+use anyhow::{anyhow, Context, Result};
+use image::{ImageBuffer, ImageFormat, Luma};
+use leptess::{LepTess, Variable};
+
+use super::setup::find_tessdata_dir;
+
+thread_local! {
+    /// This thread's engine, created by the first `recognize_tsv` call.
+    static ENGINE: RefCell<Option<LepTess>> = const { RefCell::new(None) };
+}
+
+/// Runs the thread's Tesseract engine on `img` with page-segmentation mode
+/// `psm` (and, if given, a character whitelist) and returns its TSV output.
+pub fn recognize_tsv(
+    img: &ImageBuffer<Luma<u8>, Vec<u8>>,
+    psm: &str,
+    whitelist: Option<&str>,
+) -> Result<String> {
+    let mut png = Vec::new();
+    img.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .context("Failed to encode OCR image")?;
+
+    ENGINE.with(|cell| {
+        let mut slot = cell.borrow_mut();
+        if slot.is_none() {
+            let tessdata_dir = find_tessdata_dir()?;
+            let engine = LepTess::new(Some(&tessdata_dir.to_string_lossy()), "eng")
+                .map_err(|e| anyhow!("Failed to initialize Tesseract: {}", e))?;
+            crate::log("OCR: In-process Tesseract engine initialized");
+            *slot = Some(engine);
+        }
+        let engine = slot.as_mut().expect("engine initialized above");
+
+        // Variables persist on the engine, so a previous call's whitelist is
+        // cleared rather than left in place
+        engine
+            .set_variable(Variable::TesseditPagesegMode, psm)
+            .map_err(|e| anyhow!("Failed to set page segmentation mode: {}", e))?;
+        engine
+            .set_variable(Variable::TesseditCharWhitelist, whitelist.unwrap_or(""))
+            .map_err(|e| anyhow!("Failed to set character whitelist: {}", e))?;
+
+        engine
+            .set_image_from_mem(&png)
+            .map_err(|e| anyhow!("Failed to load OCR image: {}", e))?;
+        engine
+            .get_tsv_text(0)
+            .map_err(|e| anyhow!("Tesseract failed: {}", e))
+    })
+}
@@ -1,22 +1,26 @@
 pub mod setup;
 pub mod preprocess;
 pub mod engine;
+#[cfg(feature = "tesseract-inprocess")]
+mod inprocess;
 pub mod extract;
 pub mod reconcile;
 
 pub use setup::ensure_tesseract;
 pub use preprocess::threshold_bright_pixels;
 pub use engine::{recognize_image, OcrLine, OcrWord};
-pub use extract::extract_scores;
 pub use reconcile::Recovery;
 
+use std::path::PathBuf;
+
 use anyhow::Result;
-use image::{ImageBuffer, Rgba};
+use image::{ImageBuffer, Pixel, PixelWithColorType, Rgba};
 
-use crate::automation::config::RelativeRect;
-use preprocess::{blue_mask, crop_region};
+use crate::automation::config::{AutomationConfig, RelativeRect};
+use crate::error::GakumasError;
+use preprocess::{binarize, blue_mask, crop_region, upscale};
 use engine::{recognize_image_line, recognize_single_number};
-use extract::extract_single_stage;
+use extract::{extract_single_stage_bounded, ScoreBounds};
 use reconcile::{reconcile_stage, reconstruct_from_digits};
 
 /// Per-stage OCR readout: the nine per-character scores plus the isolated
@@ -40,25 +44,87 @@ pub struct StageReadout {
 /// higher ones drop a faint comma/Pt pixel that 210 reads as an extra digit.
 const TOTAL_ALT_THRESHOLDS: &[u8] = &[180, 220, 190, 200, 230, 170, 240];
 
+/// Where `ocr_screenshot_with_debug` writes its intermediate crops: one
+/// `iter{NNN}_stage{S}_<kind>_{raw,thresh}.png` pair per crop under `dir`.
+#[derive(Clone, Debug)]
+pub struct OcrDebugDump {
+    pub dir: PathBuf,
+    pub iteration: u32,
+}
+
+impl OcrDebugDump {
+    /// Save one intermediate crop. Failures are only logged: a debug dump must
+    /// never fail the OCR it is observing.
+    fn save<P>(&self, stage_idx: usize, name: &str, img: &ImageBuffer<P, Vec<u8>>)
+    where
+        P: Pixel<Subpixel = u8> + PixelWithColorType,
+    {
+        let path = self.path(stage_idx, name);
+        let result = std::fs::create_dir_all(&self.dir)
+            .map_err(anyhow::Error::from)
+            .and_then(|_| img.save(&path).map_err(anyhow::Error::from));
+        if let Err(e) = result {
+            crate::log(&format!(
+                "OCR debug: failed to save {}: {}",
+                crate::paths::relative_display(&path),
+                e
+            ));
+        }
+    }
+
+    fn path(&self, stage_idx: usize, name: &str) -> PathBuf {
+        self.dir.join(format!(
+            "iter{:03}_stage{}_{}.png",
+            self.iteration,
+            stage_idx + 1,
+            name
+        ))
+    }
+}
+
 /// High-level function: screenshot → per-stage readout using per-stage cropping.
 ///
 /// For each of the 3 stages, crops and OCRs the score row, the isolated stage
 /// total (white text, luminance threshold), and the bonus badge (light-blue
-/// text, blue-selective mask). The preprocessing thresholds are read from the
-/// global config (`ocr_threshold`, `total_threshold`, `bonus_blue_min`,
-/// `bonus_br_margin`). The total/bonus feed the checksum reconstruction (M3/M4);
-/// a failed total/bonus reads as `None` and simply disables the checksum tier.
+/// text, blue-selective mask). Crops are upscaled by `ocr_upscale_factor`
+/// first; the OCR settings are read from the global config
+/// (`ocr_preprocess_mode`, `ocr_threshold`, `total_threshold`, `bonus_blue_min`,
+/// `bonus_br_margin`, the score bounds, `ocr_psm`, `ocr_backend`). The fixed thresholds only apply in `Global` mode; the
+/// total's threshold-sweep retry always uses them. The total/bonus feed the
+/// checksum reconstruction (M3/M4); a failed total/bonus reads as `None` and
+/// simply disables the checksum tier. Failures are `GakumasError::Ocr`.
 pub fn ocr_screenshot(
     img: &ImageBuffer<Rgba<u8>, Vec<u8>>,
     score_regions: &[RelativeRect; 3],
     total_regions: &[RelativeRect; 3],
     bonus_regions: &[RelativeRect; 3],
-) -> Result<StageReadout> {
+) -> Result<StageReadout, GakumasError> {
     let config = crate::automation::config::get_config();
+    ocr_screenshot_with_debug(img, &config, score_regions, total_regions, bonus_regions, None)
+        .map_err(GakumasError::Ocr)
+}
+
+/// `ocr_screenshot` with the OCR settings taken from `config` (a run passes
+/// the config it cloned at start, so a reload mid-run does not change how the
+/// rest of it is read), additionally saving each stage's raw and binarized
+/// crops (score row, total, bonus) through `debug` when given (the `debug_ocr`
+/// option).
+pub fn ocr_screenshot_with_debug(
+    img: &ImageBuffer<Rgba<u8>, Vec<u8>>,
+    config: &AutomationConfig,
+    score_regions: &[RelativeRect; 3],
+    total_regions: &[RelativeRect; 3],
+    bonus_regions: &[RelativeRect; 3],
+    debug: Option<&OcrDebugDump>,
+) -> Result<StageReadout> {
     let threshold = config.ocr_threshold;
     let total_threshold = config.total_threshold;
     let bonus_blue_min = config.bonus_blue_min;
     let bonus_br_margin = config.bonus_br_margin;
+    let mode = config.ocr_preprocess_mode;
+    let factor = config.ocr_upscale_factor;
+    let bounds = ScoreBounds::from(config);
+    let backend = config.ocr_backend;
 
     let mut readout = StageReadout {
         scores: [[0u32; 3]; 3],
@@ -70,19 +136,40 @@ pub fn ocr_screenshot(
     for stage_idx in 0..3 {
         // Score row.
         let score_crop = crop_region(img, &score_regions[stage_idx]);
-        let score_bin = threshold_bright_pixels(&score_crop, threshold);
-        let lines = recognize_image_line(&score_bin)?;
-        readout.scores[stage_idx] = extract_single_stage(&lines)?;
+        if factor > 1 {
+            let (w, h) = score_crop.dimensions();
+            crate::log(&format!(
+                "OCR stage {}: upscaling crops x{} (score row {}x{} -> {}x{})",
+                stage_idx + 1, factor, w, h, w * factor, h * factor
+            ));
+        }
+        let score_crop = upscale(score_crop, factor);
+        let score_bin = binarize(&score_crop, mode, threshold);
+        if let Some(dump) = debug {
+            dump.save(stage_idx, "raw", &score_crop);
+            dump.save(stage_idx, "thresh", &score_bin);
+        }
+        let lines = recognize_image_line(&score_bin, config)?;
+        readout.scores[stage_idx] =
+            extract_single_stage_bounded(&lines, &bounds, config.characters_per_stage)?;
 
         // Stage total: white text, same luminance threshold style as score rows.
-        let total_crop = crop_region(img, &total_regions[stage_idx]);
-        let total_bin = threshold_bright_pixels(&total_crop, total_threshold);
-        readout.totals[stage_idx] = recognize_single_number(&total_bin, "0123456789,", false)?;
+        let total_crop = upscale(crop_region(img, &total_regions[stage_idx]), factor);
+        let total_bin = binarize(&total_crop, mode, total_threshold);
+        if let Some(dump) = debug {
+            dump.save(stage_idx, "total_raw", &total_crop);
+            dump.save(stage_idx, "total_thresh", &total_bin);
+        }
+        readout.totals[stage_idx] = recognize_single_number(&total_bin, "0123456789,", false, backend)?;
 
         // Bonus badge: light-blue text, blue-selective mask, "+"-anchored parse.
-        let bonus_crop = crop_region(img, &bonus_regions[stage_idx]);
+        let bonus_crop = upscale(crop_region(img, &bonus_regions[stage_idx]), factor);
         let bonus_bin = blue_mask(&bonus_crop, bonus_blue_min, bonus_br_margin);
-        readout.bonuses[stage_idx] = recognize_single_number(&bonus_bin, "0123456789+", true)?;
+        if let Some(dump) = debug {
+            dump.save(stage_idx, "bonus_raw", &bonus_crop);
+            dump.save(stage_idx, "bonus_thresh", &bonus_bin);
+        }
+        readout.bonuses[stage_idx] = recognize_single_number(&bonus_bin, "0123456789+", true, backend)?;
 
         // Reconstruct overlapping-million corruption via the total/bonus checksum.
         let raw = readout.scores[stage_idx];
@@ -104,7 +191,7 @@ pub fn ocr_screenshot(
                     continue;
                 }
                 let alt_bin = threshold_bright_pixels(&total_crop, alt);
-                let alt_total = recognize_single_number(&alt_bin, "0123456789,", false)?;
+                let alt_total = recognize_single_number(&alt_bin, "0123456789,", false, backend)?;
                 if alt_total.is_none() || alt_total == readout.totals[stage_idx] {
                     continue;
                 }
@@ -171,6 +258,61 @@ pub fn ocr_screenshot(
     Ok(readout)
 }
 
+/// Mean Tesseract word confidence (0-100) of each stage's score row in `img`.
+///
+/// Crops, upscales and binarizes like `ocr_screenshot`, but skips parsing and
+/// the total/bonus reads: a quick readability check of a fresh capture for
+/// `recapture_confidence`. A row with no recognized words scores 0.
+///
+/// OCRs through `config.ocr_backend` on the calling thread, so with
+/// `InProcess` the automation thread gets its own engine on first use.
+pub fn score_row_confidence(
+    img: &ImageBuffer<Rgba<u8>, Vec<u8>>,
+    config: &AutomationConfig,
+    score_regions: &[RelativeRect; 3],
+) -> Result<[f32; 3]> {
+    let mut confidence = [0.0f32; 3];
+    for (stage_idx, region) in score_regions.iter().enumerate() {
+        let crop = upscale(crop_region(img, region), config.ocr_upscale_factor);
+        let bin = binarize(&crop, config.ocr_preprocess_mode, config.ocr_threshold);
+        confidence[stage_idx] = mean_word_confidence(&recognize_image_line(&bin, config)?);
+    }
+    Ok(confidence)
+}
+
+/// Mean confidence over every word in `lines`, or 0 when there are none.
+fn mean_word_confidence(lines: &[OcrLine]) -> f32 {
+    let confidences: Vec<f32> =
+        lines.iter().flat_map(|line| &line.words).map(|word| word.confidence).collect();
+    if confidences.is_empty() {
+        return 0.0;
+    }
+    confidences.iter().sum::<f32>() / confidences.len() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(confidences: &[f32]) -> OcrLine {
+        OcrLine {
+            text: String::new(),
+            words: confidences
+                .iter()
+                .map(|&confidence| OcrWord { text: "1".to_string(), confidence })
+                .collect(),
+            confidence: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_mean_word_confidence() {
+        assert_eq!(mean_word_confidence(&[]), 0.0);
+        assert_eq!(mean_word_confidence(&[line(&[])]), 0.0);
+        assert_eq!(mean_word_confidence(&[line(&[90.0, 60.0]), line(&[30.0])]), 60.0);
+    }
+}
+
 #[cfg(test)]
 mod e2e_tests {
     //! End-to-end acceptance for the overlap-score recovery (M2/M4).
@@ -225,3 +367,159 @@ mod e2e_tests {
         assert!(failures.is_empty(), "stage-2 mismatches:\n{}", failures.join("\n"));
     }
 }
+
+#[cfg(test)]
+mod golden_tests {
+    //! Golden result screens: synthetic screenshots with known scores, laid
+    //! out on the default OCR regions and run through `ocr_screenshot`.
+    //!
+    //! Unlike `e2e_tests`, these need no LFS fixtures: each screen is rendered
+    //! deterministically from the table below (white score/total text, blue
+    //! bonus badge on a dark background), so they catch preprocessing and
+    //! extraction regressions on clean, collision-free input. The crate has no
+    //! library target, so they live here rather than under `tests/`. They skip
+    //! themselves when Tesseract can't be set up.
+    use super::*;
+    use plotters::prelude::*;
+    use plotters::style::text_anchor::{HPos, Pos, VPos};
+
+    const SCREEN_WIDTH: u32 = 1080;
+    const SCREEN_HEIGHT: u32 = 1920;
+
+    /// Known per-character scores of each golden screen, stage-major.
+    const GOLDEN_SCREENS: &[[[u32; 3]; 3]] = &[
+        [[412_360, 398_127, 254_903], [865_214, 731_056, 602_480], [1_204_377, 318_650, 97_415]],
+        [[53_208, 61_774, 48_390], [287_415, 305_962, 299_801], [742_118, 1_086_530, 513_297]],
+    ];
+
+    /// `1234567` → `"1,234,567"`, as the result screen formats numbers.
+    fn with_commas(value: u32) -> String {
+        let digits = value.to_string();
+        let mut out = String::new();
+        for (i, c) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                out.push(',');
+            }
+            out.push(c);
+        }
+        out
+    }
+
+    /// Bonus badge value: `floor(max / 5)` (see `reconcile`).
+    fn bonus_of(scores: [u32; 3]) -> u32 {
+        scores.iter().max().copied().unwrap_or(0) / 5
+    }
+
+    fn total_of(scores: [u32; 3]) -> u32 {
+        scores.iter().sum::<u32>() + bonus_of(scores)
+    }
+
+    /// Pixel center of `rect` on the golden screen.
+    fn center(rect: &RelativeRect) -> (i32, i32) {
+        (
+            ((rect.x + rect.width / 2.0) * SCREEN_WIDTH as f32) as i32,
+            ((rect.y + rect.height / 2.0) * SCREEN_HEIGHT as f32) as i32,
+        )
+    }
+
+    /// Renders a result screen showing `stages` on the given regions.
+    fn render_screen(
+        stages: &[[u32; 3]; 3],
+        score_regions: &[RelativeRect; 3],
+        total_regions: &[RelativeRect; 3],
+        bonus_regions: &[RelativeRect; 3],
+    ) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+        let mut rgb = vec![0u8; (SCREEN_WIDTH * SCREEN_HEIGHT * 3) as usize];
+        {
+            let root = BitMapBackend::with_buffer(&mut rgb, (SCREEN_WIDTH, SCREEN_HEIGHT))
+                .into_drawing_area();
+            root.fill(&RGBColor(40, 36, 56)).unwrap();
+
+            let text = |size: f64, color: RGBColor| {
+                ("sans-serif", size)
+                    .into_font()
+                    .style(FontStyle::Bold)
+                    .color(&color)
+                    .pos(Pos::new(HPos::Center, VPos::Center))
+            };
+            let score_style = text(32.0, RGBColor(255, 255, 255));
+            let total_style = text(48.0, RGBColor(255, 255, 255));
+            let bonus_style = text(30.0, RGBColor(110, 190, 255));
+
+            for (stage, scores) in stages.iter().enumerate() {
+                // Three columns across the score row, one per character
+                let row = score_regions[stage];
+                let (_, y) = center(&row);
+                for (slot, &score) in scores.iter().enumerate() {
+                    let x = ((row.x + row.width * (2 * slot + 1) as f32 / 6.0)
+                        * SCREEN_WIDTH as f32) as i32;
+                    root.draw_text(&with_commas(score), &score_style, (x, y)).unwrap();
+                }
+                root.draw_text(
+                    &with_commas(total_of(*scores)),
+                    &total_style,
+                    center(&total_regions[stage]),
+                )
+                .unwrap();
+                root.draw_text(
+                    &format!("+{}", with_commas(bonus_of(*scores))),
+                    &bonus_style,
+                    center(&bonus_regions[stage]),
+                )
+                .unwrap();
+            }
+            root.present().unwrap();
+        }
+
+        let rgba = rgb.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect();
+        ImageBuffer::from_raw(SCREEN_WIDTH, SCREEN_HEIGHT, rgba).unwrap()
+    }
+
+    #[test]
+    fn test_with_commas() {
+        assert_eq!(with_commas(97), "97");
+        assert_eq!(with_commas(412_360), "412,360");
+        assert_eq!(with_commas(1_204_377), "1,204,377");
+    }
+
+    #[test]
+    fn ocr_reads_golden_result_screens() {
+        crate::automation::config::init_config();
+        if let Err(e) = crate::ocr::ensure_tesseract() {
+            println!("Skipping golden OCR test, Tesseract unavailable: {e}");
+            return;
+        }
+        let config = crate::automation::config::get_config();
+
+        let mut failures = Vec::new();
+        for (i, stages) in GOLDEN_SCREENS.iter().enumerate() {
+            let img = render_screen(
+                stages,
+                &config.score_regions,
+                &config.total_regions,
+                &config.bonus_regions,
+            );
+            let r = ocr_screenshot(
+                &img,
+                &config.score_regions,
+                &config.total_regions,
+                &config.bonus_regions,
+            )
+            .unwrap_or_else(|e| panic!("golden screen {i}: {e}"));
+            for (stage, &want) in stages.iter().enumerate() {
+                let want_total = Some(total_of(want));
+                if r.scores[stage] != want || r.totals[stage] != want_total {
+                    failures.push(format!(
+                        "screen {i} stage {}: got scores={:?} total={:?}, want {:?} total={:?}",
+                        stage + 1,
+                        r.scores[stage],
+                        r.totals[stage],
+                        want,
+                        want_total
+                    ));
+                }
+            }
+        }
+        assert!(failures.is_empty(), "golden mismatches:\n{}", failures.join("\n"));
+    }
+}
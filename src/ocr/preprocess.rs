@@ -1,6 +1,15 @@
 use image::{ImageBuffer, Luma, Rgba};
 
-use crate::automation::config::RelativeRect;
+use crate::automation::config::{OcrPreprocessMode, RelativeRect};
+
+/// Offset (in luminance levels) a pixel must exceed its local mean by to count
+/// as text in `threshold_adaptive_mean`. Keeps flat background from speckling.
+const ADAPTIVE_MEAN_OFFSET: i32 = 10;
+
+/// Smallest scaled Sobel magnitude (0-255) that `threshold_edges` can treat as
+/// an edge, so a flat crop (whose Otsu cutoff collapses to 0) stays blank
+/// instead of turning capture noise into text.
+const EDGE_MIN_MAGNITUDE: u8 = 24;
 
 /// Converts image to binary by keeping only bright pixels.
 ///
@@ -38,6 +47,175 @@ pub fn threshold_bright_pixels(
     output
 }
 
+/// BT.601 luminance of one pixel (same weighting as `calculate_brightness`).
+fn luminance(pixel: &Rgba<u8>) -> u8 {
+    (0.299 * pixel[0] as f32 + 0.587 * pixel[1] as f32 + 0.114 * pixel[2] as f32).round() as u8
+}
+
+/// Computes Otsu's threshold over the luminance histogram of `img`.
+///
+/// Returns the level `t` that maximizes the between-class variance of the
+/// `<= t` (background) and `> t` (text) classes.
+pub fn otsu_threshold(img: &ImageBuffer<Rgba<u8>, Vec<u8>>) -> u8 {
+    let mut histogram = [0u64; 256];
+    for pixel in img.pixels() {
+        histogram[luminance(pixel) as usize] += 1;
+    }
+    otsu_from_histogram(&histogram)
+}
+
+/// Otsu's threshold over an arbitrary 256-bin histogram.
+fn otsu_from_histogram(histogram: &[u64; 256]) -> u8 {
+    let total: u64 = histogram.iter().sum();
+    if total == 0 {
+        return 0;
+    }
+    let sum_all: f64 = histogram.iter().enumerate().map(|(i, &n)| i as f64 * n as f64).sum();
+
+    let mut best_t = 0u8;
+    let mut best_var = -1.0f64;
+    let mut weight_bg = 0u64;
+    let mut sum_bg = 0.0f64;
+    for (t, &count) in histogram.iter().enumerate() {
+        weight_bg += count;
+        if weight_bg == 0 {
+            continue;
+        }
+        let weight_fg = total - weight_bg;
+        if weight_fg == 0 {
+            break;
+        }
+        sum_bg += t as f64 * count as f64;
+        let mean_bg = sum_bg / weight_bg as f64;
+        let mean_fg = (sum_all - sum_bg) / weight_fg as f64;
+        let var = weight_bg as f64 * weight_fg as f64 * (mean_bg - mean_fg).powi(2);
+        if var > best_var {
+            best_var = var;
+            best_t = t as u8;
+        }
+    }
+
+    best_t
+}
+
+/// Binarizes with Otsu's method: pixels brighter than the crop's Otsu
+/// threshold become black (text), the rest white. Same output convention as
+/// `threshold_bright_pixels`.
+pub fn threshold_otsu(img: &ImageBuffer<Rgba<u8>, Vec<u8>>) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+    let t = otsu_threshold(img);
+    ImageBuffer::from_fn(img.width(), img.height(), |x, y| {
+        if luminance(img.get_pixel(x, y)) > t {
+            Luma([0u8])
+        } else {
+            Luma([255u8])
+        }
+    })
+}
+
+/// Binarizes by comparing each pixel to the mean luminance of a square window
+/// around it (`2 * radius + 1` pixels wide, clipped at the edges).
+///
+/// A pixel is text (black) when it is brighter than its local mean by more than
+/// `ADAPTIVE_MEAN_OFFSET`, so a background gradient shifts the cutoff along
+/// with it. The window sums come from an integral image, so cost is O(pixels).
+pub fn threshold_adaptive_mean(
+    img: &ImageBuffer<Rgba<u8>, Vec<u8>>,
+    radius: u32,
+) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+    let (width, height) = img.dimensions();
+    let (w, h) = (width as usize, height as usize);
+
+    // integral[(y + 1) * (w + 1) + (x + 1)] = sum of luminance over [0..=x, 0..=y]
+    let mut integral = vec![0u64; (w + 1) * (h + 1)];
+    for y in 0..h {
+        let mut row_sum = 0u64;
+        for x in 0..w {
+            row_sum += luminance(img.get_pixel(x as u32, y as u32)) as u64;
+            integral[(y + 1) * (w + 1) + (x + 1)] = integral[y * (w + 1) + (x + 1)] + row_sum;
+        }
+    }
+
+    let r = radius as usize;
+    ImageBuffer::from_fn(width, height, |x, y| {
+        let (x, y) = (x as usize, y as usize);
+        let (x0, y0) = (x.saturating_sub(r), y.saturating_sub(r));
+        let (x1, y1) = ((x + r + 1).min(w), (y + r + 1).min(h));
+        let sum = integral[y1 * (w + 1) + x1] + integral[y0 * (w + 1) + x0]
+            - integral[y0 * (w + 1) + x1]
+            - integral[y1 * (w + 1) + x0];
+        let count = ((x1 - x0) * (y1 - y0)) as u64;
+        let mean = (sum / count) as i32;
+
+        let lum = luminance(img.get_pixel(x as u32, y as u32)) as i32;
+        if lum > mean + ADAPTIVE_MEAN_OFFSET {
+            Luma([0u8])
+        } else {
+            Luma([255u8])
+        }
+    })
+}
+
+/// Sobel gradient magnitude of the crop's luminance, scaled to 0-255
+/// (`(|gx| + |gy|) / 8`). Edge pixels are clamped to the nearest neighbour.
+fn sobel_magnitude(img: &ImageBuffer<Rgba<u8>, Vec<u8>>) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+    let (width, height) = img.dimensions();
+    let lum = |x: i64, y: i64| -> i32 {
+        let x = x.clamp(0, width as i64 - 1) as u32;
+        let y = y.clamp(0, height as i64 - 1) as u32;
+        luminance(img.get_pixel(x, y)) as i32
+    };
+
+    ImageBuffer::from_fn(width, height, |x, y| {
+        let (x, y) = (x as i64, y as i64);
+        let gx = (lum(x + 1, y - 1) + 2 * lum(x + 1, y) + lum(x + 1, y + 1))
+            - (lum(x - 1, y - 1) + 2 * lum(x - 1, y) + lum(x - 1, y + 1));
+        let gy = (lum(x - 1, y + 1) + 2 * lum(x, y + 1) + lum(x + 1, y + 1))
+            - (lum(x - 1, y - 1) + 2 * lum(x, y - 1) + lum(x + 1, y - 1));
+        Luma([((gx.abs() + gy.abs()) / 8).min(255) as u8])
+    })
+}
+
+/// Binarizes on edge strength: pixels whose Sobel gradient magnitude exceeds
+/// the Otsu cutoff of the magnitude histogram (never below
+/// `EDGE_MIN_MAGNITUDE`) become black (text), the rest white.
+///
+/// Thin anti-aliased strokes never get bright enough for a luminance cutoff,
+/// but their sides are still sharp, so their outlines survive here.
+pub fn threshold_edges(img: &ImageBuffer<Rgba<u8>, Vec<u8>>) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+    let magnitude = sobel_magnitude(img);
+    let mut histogram = [0u64; 256];
+    for pixel in magnitude.pixels() {
+        histogram[pixel[0] as usize] += 1;
+    }
+    let cutoff = otsu_from_histogram(&histogram).max(EDGE_MIN_MAGNITUDE);
+
+    ImageBuffer::from_fn(img.width(), img.height(), |x, y| {
+        if magnitude.get_pixel(x, y)[0] > cutoff {
+            Luma([0u8])
+        } else {
+            Luma([255u8])
+        }
+    })
+}
+
+/// Binarizes a white-text crop with the configured preprocessing mode.
+///
+/// `global_threshold` is only used by `OcrPreprocessMode::Global`. The adaptive
+/// window is sized from the crop height so it spans a full digit plus
+/// surrounding background.
+pub fn binarize(
+    img: &ImageBuffer<Rgba<u8>, Vec<u8>>,
+    mode: OcrPreprocessMode,
+    global_threshold: u8,
+) -> ImageBuffer<Luma<u8>, Vec<u8>> {
+    match mode {
+        OcrPreprocessMode::Global => threshold_bright_pixels(img, global_threshold),
+        OcrPreprocessMode::Otsu => threshold_otsu(img),
+        OcrPreprocessMode::AdaptiveMean => threshold_adaptive_mean(img, img.height().max(8)),
+        OcrPreprocessMode::Edge => threshold_edges(img),
+    }
+}
+
 /// Binarizes a crop with a blue-selective color mask, for the bonus badge.
 ///
 /// The bonus value is rendered in light blue (~RGB (115,201,253)) and is
@@ -73,6 +251,21 @@ pub fn blue_mask(
     output
 }
 
+/// Enlarges a crop by an integer `factor` with Lanczos resampling.
+///
+/// Applied before binarization so the threshold sees smooth glyph edges rather
+/// than blocky pixels. `factor <= 1` returns the crop unchanged.
+pub fn upscale(
+    img: ImageBuffer<Rgba<u8>, Vec<u8>>,
+    factor: u32,
+) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+    if factor <= 1 {
+        return img;
+    }
+    let (w, h) = img.dimensions();
+    image::imageops::resize(&img, w * factor, h * factor, image::imageops::FilterType::Lanczos3)
+}
+
 /// Crops a sub-region from an image using relative coordinates.
 ///
 /// Converts the relative rect (0.0–1.0) to absolute pixel coordinates,
@@ -111,6 +304,19 @@ mod tests {
         assert_eq!(cropped.get_pixel(0, 0)[1], 50);
     }
 
+    #[test]
+    fn test_upscale() {
+        let img: ImageBuffer<Rgba<u8>, Vec<u8>> =
+            ImageBuffer::from_pixel(30, 12, Rgba([200, 200, 200, 255]));
+
+        let same = upscale(img.clone(), 1);
+        assert_eq!(same, img, "Factor 1 should leave the crop untouched");
+
+        let big = upscale(img, 3);
+        assert_eq!(big.dimensions(), (90, 36));
+        assert_eq!(big.get_pixel(45, 18)[0], 200, "Flat color should survive resampling");
+    }
+
     #[test]
     fn test_crop_region_clamps() {
         let img: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::new(100, 100);
@@ -142,6 +348,76 @@ mod tests {
         assert_eq!(result.get_pixel(2, 0)[0], 255, "Partially dark pixel should become white");
     }
 
+    #[test]
+    fn test_otsu_bimodal() {
+        // Left half dark background (40), right half bright text (220), with a
+        // little noise on each side so the histogram has two real modes.
+        let img: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::from_fn(40, 10, |x, y| {
+            let v = if x < 20 { 40 + (y % 3) as u8 } else { 220 - (y % 3) as u8 };
+            Rgba([v, v, v, 255])
+        });
+
+        let t = otsu_threshold(&img);
+        assert!((42..218).contains(&t), "threshold {} should split the modes", t);
+
+        let result = threshold_otsu(&img);
+        assert_eq!(result.get_pixel(5, 5)[0], 255, "Background should become white");
+        assert_eq!(result.get_pixel(30, 5)[0], 0, "Bright text should become black");
+    }
+
+    #[test]
+    fn test_adaptive_mean_handles_gradient() {
+        // Background ramps 60 -> 200 left to right; one bright "glyph" column
+        // sits on each side. A single global cutoff would either miss the left
+        // glyph or flood the right background; the local mean catches both.
+        let img: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::from_fn(100, 10, |x, _| {
+            let bg = 60 + (x * 140 / 99) as u8;
+            let v = if x == 10 || x == 90 { bg.saturating_add(50) } else { bg };
+            Rgba([v, v, v, 255])
+        });
+
+        let result = threshold_adaptive_mean(&img, 5);
+        assert_eq!(result.get_pixel(10, 5)[0], 0, "Left glyph should be text");
+        assert_eq!(result.get_pixel(90, 5)[0], 0, "Right glyph should be text");
+        assert_eq!(result.get_pixel(50, 5)[0], 255, "Gradient background should be white");
+        assert_eq!(result.get_pixel(95, 5)[0], 255, "Bright background should be white");
+    }
+
+    #[test]
+    fn test_edges_outline_thin_stroke() {
+        // A dim, 2px-wide anti-aliased vertical stroke (peak 150) on a dark
+        // background: below the 190 global cutoff, but its sides are sharp.
+        let img: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::from_fn(40, 16, |x, _| {
+            let v = match x {
+                19 | 20 => 150,
+                18 | 21 => 90,
+                _ => 30,
+            };
+            Rgba([v, v, v, 255])
+        });
+
+        let global = threshold_bright_pixels(&img, 190);
+        assert!(global.pixels().all(|p| p[0] == 255), "Global cutoff should lose the stroke");
+
+        let result = threshold_edges(&img);
+        assert_eq!(result.dimensions(), (40, 16));
+        assert!(result.pixels().any(|p| p[0] == 0), "Edge mode should keep the stroke");
+        assert_eq!(result.get_pixel(5, 8)[0], 255, "Flat background should become white");
+        assert_eq!(result.get_pixel(35, 8)[0], 255, "Flat background should become white");
+    }
+
+    #[test]
+    fn test_edges_flat_region_is_blank() {
+        let flat: ImageBuffer<Rgba<u8>, Vec<u8>> =
+            ImageBuffer::from_pixel(20, 8, Rgba([128, 128, 128, 255]));
+        let result = binarize(&flat, OcrPreprocessMode::Edge, 190);
+        assert_eq!(result.dimensions(), (20, 8));
+        assert!(result.pixels().all(|p| p[0] == 255), "Flat crop should have no text");
+
+        let tiny: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::from_pixel(1, 1, Rgba([0, 0, 0, 255]));
+        assert_eq!(threshold_edges(&tiny).dimensions(), (1, 1));
+    }
+
     #[test]
     fn test_blue_mask() {
         let mut img: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::new(4, 1);
@@ -79,10 +79,29 @@ struct Cand {
 /// replaced 0..=9), keep combinations that satisfy the checksum exactly, and
 /// pick the one with the lowest *corruption-aware* cost. With no usable total,
 /// fall back to a conservative structural-only pass.
+///
+/// Blank (ー) slots are always on the right, so a 0 before a later score is a
+/// read `extract_single_stage_bounded` discarded as implausible; such a stage
+/// is `Flagged` whatever the checksum finds.
 pub fn reconcile_stage(
     ocr_scores: [u32; 3],
     total: Option<u32>,
     bonus: Option<u32>,
+) -> ([u32; 3], Recovery) {
+    let (scores, rec) = reconcile_read(ocr_scores, total, bonus);
+    let discarded_slot =
+        (0..2).any(|i| ocr_scores[i] == 0 && ocr_scores[i + 1..].iter().any(|&s| s > 0));
+    if discarded_slot {
+        return (scores, Recovery::Flagged);
+    }
+    (scores, rec)
+}
+
+/// `reconcile_stage` without the discarded-slot check.
+fn reconcile_read(
+    ocr_scores: [u32; 3],
+    total: Option<u32>,
+    bonus: Option<u32>,
 ) -> ([u32; 3], Recovery) {
     let total_provided = total.is_some();
 
@@ -266,6 +285,14 @@ fn bonus_driven_repair(raw: [u32; 3], bonus: Option<u32>) -> Option<[u32; 3]> {
     hit
 }
 
+/// The stage total the game shows for these per-character scores (the module's
+/// checksum identity). Takes any number of scores so CSV readers with a
+/// configurable `characters_per_stage` can use it.
+pub fn expected_stage_total(scores: &[u32]) -> u64 {
+    let sum: u64 = scores.iter().map(|&s| s as u64).sum();
+    sum + scores.iter().copied().max().unwrap_or(0) as u64 / 5
+}
+
 /// `floor(max(combo) / 5)` — the bonus the game would render for this combo.
 fn derived_bonus(combo: [u32; 3]) -> u32 {
     combo.iter().copied().max().unwrap_or(0) / 5
@@ -808,6 +835,19 @@ mod tests {
         assert_eq!(rec, Recovery::Ok);
     }
 
+    #[test]
+    fn test_reconcile_flags_discarded_middle_slot() {
+        // The middle read was discarded as implausible; even a checksum that
+        // adds up cannot vouch for a stage with a hole in it.
+        let (_, rec) = reconcile_stage([576880, 0, 213607], Some(905_863), Some(115376));
+        assert_eq!(rec, Recovery::Flagged);
+
+        // Trailing blanks are normal.
+        let (scores, rec) = reconcile_stage([576880, 213607, 0], Some(905_863), Some(115376));
+        assert_eq!(scores, [576880, 213607, 0]);
+        assert_eq!(rec, Recovery::Ok);
+    }
+
     // --- Total-only tier: recovery must work without the bonus. ---
 
     #[test]
@@ -1268,4 +1308,11 @@ mod tests {
         // All candidates capped below MAX_SCORE.
         assert!(p.iter().all(|c| c.value < MAX_SCORE));
     }
+
+    #[test]
+    fn test_expected_stage_total_matches_real_sample() {
+        // Sample 102842 stage: total 3,661,912 with bonus 234,533.
+        assert_eq!(expected_stage_total(&[1172665, 1161196, 1093518]), 3661912);
+        assert_eq!(expected_stage_total(&[]), 0);
+    }
 }
@@ -2,6 +2,13 @@ use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 
 static EXE_DIR: OnceLock<PathBuf> = OnceLock::new();
+static EXE_DIR_WRITABLE: OnceLock<bool> = OnceLock::new();
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Folder under the user's config directory (`%APPDATA%` on Windows) that
+/// holds config.json and the app's data when the exe folder can't be written,
+/// e.g. under Program Files.
+const USER_DIR_NAME: &str = "gakumas-screenshot";
 
 /// Returns the directory containing the executable.
 pub fn get_exe_dir() -> &'static PathBuf {
@@ -13,19 +20,63 @@ pub fn get_exe_dir() -> &'static PathBuf {
     })
 }
 
-/// Returns the logs directory: `<exe_dir>/logs/`
+/// Whether files can be created next to the executable. Probed once (the
+/// probe creates and removes a temporary file) and cached.
+pub fn exe_dir_writable() -> bool {
+    *EXE_DIR_WRITABLE.get_or_init(|| ensure_writable_dir(get_exe_dir()).is_ok())
+}
+
+/// Returns `<config_dir>/gakumas-screenshot/`, if the OS has a config dir.
+pub fn get_user_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(USER_DIR_NAME))
+}
+
+/// Returns the directory the app writes its data (logs, screenshots,
+/// sessions, captured references) into: the exe directory, or the user dir
+/// when the exe directory is read-only.
+pub fn get_data_dir() -> &'static PathBuf {
+    DATA_DIR.get_or_init(|| {
+        if exe_dir_writable() {
+            return get_exe_dir().clone();
+        }
+        get_user_dir().unwrap_or_else(|| get_exe_dir().clone())
+    })
+}
+
+/// Resolves a data file given relative to the exe directory (a reference
+/// image, chart_config.json, ...): the copy in the data dir if there is one,
+/// else the one shipped next to the executable, else the data dir path where
+/// it would be created.
+pub fn resolve_data_file(relative: impl AsRef<Path>) -> PathBuf {
+    resolve_in(get_data_dir(), get_exe_dir(), relative.as_ref())
+}
+
+fn resolve_in(data_dir: &Path, exe_dir: &Path, relative: &Path) -> PathBuf {
+    let data_path = data_dir.join(relative);
+    if data_path.exists() {
+        return data_path;
+    }
+    let exe_path = exe_dir.join(relative);
+    if exe_path.exists() {
+        return exe_path;
+    }
+    data_path
+}
+
+/// Returns the logs directory: `<data_dir>/logs/`
 pub fn get_logs_dir() -> PathBuf {
-    get_exe_dir().join("logs")
+    get_data_dir().join("logs")
 }
 
-/// Returns the screenshots directory: `<exe_dir>/screenshots/`
+/// Returns the screenshots directory: `<data_dir>/screenshots/`
 pub fn get_screenshots_dir() -> PathBuf {
-    get_exe_dir().join("screenshots")
+    get_data_dir().join("screenshots")
 }
 
-/// Returns the rehearsal template directory: `<exe_dir>/resources/template/rehearsal/`
+/// Returns the rehearsal template directory:
+/// `<data_dir>/resources/template/rehearsal/`
 pub fn get_rehearsal_template_dir() -> PathBuf {
-    get_exe_dir().join("resources").join("template").join("rehearsal")
+    get_data_dir().join("resources").join("template").join("rehearsal")
 }
 
 /// Returns the tesseract directory: `<exe_dir>/tesseract/`
@@ -33,17 +84,61 @@ pub fn get_tesseract_dir() -> PathBuf {
     get_exe_dir().join("tesseract")
 }
 
-/// Returns the output directory: `<exe_dir>/output/`
+/// Returns the output directory: `<data_dir>/output/`
 pub fn get_output_dir() -> PathBuf {
-    get_exe_dir().join("output")
+    get_data_dir().join("output")
+}
+
+/// Returns the folder sessions are written to: the configured `output_dir`
+/// (see `resolve_output_dir`), or `get_output_dir()` when it is unset.
+pub fn get_session_output_dir() -> PathBuf {
+    resolve_output_dir(&crate::automation::get_config().output_dir)
+}
+
+/// Resolves a configured output folder: empty means the default
+/// `<data_dir>/output/`, and a relative path is taken from the data directory.
+fn resolve_output_dir(configured: &str) -> PathBuf {
+    let configured = configured.trim();
+    if configured.is_empty() {
+        return get_output_dir();
+    }
+    // `join` keeps an absolute path as-is
+    get_data_dir().join(configured)
+}
+
+/// Creates `dir` if missing and checks that files can be written in it.
+pub fn ensure_writable_dir(dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    tempfile::NamedTempFile::new_in(dir)?;
+    Ok(())
 }
 
-/// Returns a display-friendly path string relative to the exe directory.
+/// Returns `path`, or when a file already exists there, the first free
+/// `<stem>_2.<ext>`, `<stem>_3.<ext>`, ... next to it, so saving never
+/// overwrites an earlier file.
+pub fn unique_file_path(path: PathBuf) -> PathBuf {
+    if !path.exists() {
+        return path;
+    }
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+    let extension = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    (2u32..)
+        .map(|n| path.with_file_name(format!("{}_{}{}", stem, n, extension)))
+        .find(|candidate| !candidate.exists())
+        .expect("some suffix is free")
+}
+
+/// Returns a display-friendly path string relative to the data or exe
+/// directory.
 ///
-/// Strips the exe directory prefix so logs don't leak the user's full file path.
+/// Strips the directory prefix so logs don't leak the user's full file path.
 /// Falls back to the full path if stripping fails.
 pub fn relative_display(path: &Path) -> String {
-    path.strip_prefix(get_exe_dir())
+    path.strip_prefix(get_data_dir())
+        .or_else(|_| path.strip_prefix(get_exe_dir()))
         .unwrap_or(path)
         .display()
         .to_string()
@@ -57,3 +152,57 @@ pub fn ensure_directories() -> std::io::Result<()> {
     std::fs::create_dir_all(get_output_dir())?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_output_dir() {
+        assert_eq!(resolve_output_dir(""), get_output_dir());
+        assert_eq!(resolve_output_dir("  "), get_output_dir());
+        assert_eq!(resolve_output_dir("sessions"), get_data_dir().join("sessions"));
+
+        let absolute = std::env::temp_dir().join("gakumas_sessions");
+        assert_eq!(resolve_output_dir(&absolute.to_string_lossy()), absolute);
+    }
+
+    #[test]
+    fn test_resolve_in_prefers_data_dir_then_exe_dir() {
+        let data = tempfile::tempdir().unwrap();
+        let exe = tempfile::tempdir().unwrap();
+        let rel = Path::new("ref.png");
+
+        // Neither exists: created in the data dir
+        assert_eq!(resolve_in(data.path(), exe.path(), rel), data.path().join(rel));
+        // Shipped with the install only
+        std::fs::write(exe.path().join(rel), b"").unwrap();
+        assert_eq!(resolve_in(data.path(), exe.path(), rel), exe.path().join(rel));
+        // Captured into the data dir wins
+        std::fs::write(data.path().join(rel), b"").unwrap();
+        assert_eq!(resolve_in(data.path(), exe.path(), rel), data.path().join(rel));
+    }
+
+    #[test]
+    fn test_unique_file_path_appends_suffix() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gakumas_20260101_120000.png");
+        assert_eq!(unique_file_path(path.clone()), path);
+
+        std::fs::write(&path, b"").unwrap();
+        let second = dir.path().join("gakumas_20260101_120000_2.png");
+        assert_eq!(unique_file_path(path.clone()), second);
+        std::fs::write(&second, b"").unwrap();
+        assert_eq!(unique_file_path(path), dir.path().join("gakumas_20260101_120000_3.png"));
+    }
+
+    #[test]
+    fn test_ensure_writable_dir_creates_missing() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("a").join("b");
+        ensure_writable_dir(&dir).unwrap();
+        assert!(dir.is_dir());
+        // The probe file is removed again
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+    }
+}